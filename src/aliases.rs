@@ -0,0 +1,72 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use crate::FileChangePaths;
+
+/// Maps CODEOWNERS owners (case-insensitively) onto the names reports should use, so owners
+/// finer-grained than the organization's reporting lines can be merged.
+#[derive(Clone, Default)]
+pub struct OwnerAliases(HashMap<String, String>);
+
+impl OwnerAliases {
+    pub fn new(aliases: &BTreeMap<String, String>) -> Self {
+        OwnerAliases(
+            aliases
+                .iter()
+                .map(|(owner, alias)| (owner.to_lowercase(), alias.clone()))
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The alias of `owner`, or `owner` itself when it has none.
+    pub fn resolve<'a>(&'a self, owner: &'a str) -> &'a str {
+        self.0
+            .get(&owner.to_lowercase())
+            .map(|alias| alias.as_str())
+            .unwrap_or(owner)
+    }
+}
+
+/// Reads `owner\tnew_owner` rows, after a header, from a TSV file of renamed owners.
+pub fn read_owner_renames_from_tsv(path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let mut renames = BTreeMap::new();
+    for line in BufReader::new(File::open(path)?).lines().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (owner, new_owner) = line.split_once('\t').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            )
+        })?;
+        renames.insert(owner.to_string(), new_owner.to_string());
+    }
+    Ok(renames)
+}
+
+/// Replaces the owners of every file change with their aliases. Whether the author is a
+/// codeowner is left as computed from the original owners.
+pub fn alias_commit_owners<T, I>(
+    commits: I,
+    aliases: OwnerAliases,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    commits.map(move |commit_result| {
+        let mut commit = commit_result?;
+        commit.map_owners(|owner| aliases.resolve(owner).to_string());
+        Ok(commit)
+    })
+}
@@ -1,287 +1,1245 @@
-use std::{collections::HashMap, io};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
 
-use crate::{CommitInfoWithCodeowner, FileChangeWithCodeowner};
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    get_owners_at_commit, git_tree_files, languages::LanguageTotals, CommitInfoWithCodeowner,
+    FileChangeWithCodeowner, LanguageChanges, NewcomerBreakdown, OwnerContributors, ProgressSink,
+};
+
+type ContributorStats = HashMap<(String, String), (usize, usize)>;
+
+/// What the adjusted metrics measure a change by, both for the adjusted changes and for
+/// how much of a commit each owner is credited with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AdjustedWeight {
+    Insertions,
+    /// Deleted lines, so refactors that mostly remove code get their due.
+    Deletions,
+    /// Insertions and deletions together.
+    #[default]
+    Changes,
+}
+
+impl FromStr for AdjustedWeight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "insertions" => Ok(AdjustedWeight::Insertions),
+            "deletions" => Ok(AdjustedWeight::Deletions),
+            "changes" => Ok(AdjustedWeight::Changes),
+            _ => Err(format!(
+                "Invalid weight '{}', expected 'insertions', 'deletions' or 'changes'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AdjustedWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdjustedWeight::Insertions => write!(f, "insertions"),
+            AdjustedWeight::Deletions => write!(f, "deletions"),
+            AdjustedWeight::Changes => write!(f, "changes"),
+        }
+    }
+}
+
+impl From<AdjustedWeight> for MetricConfig {
+    fn from(weight: AdjustedWeight) -> Self {
+        let (insertion, deletion) = match weight {
+            AdjustedWeight::Insertions => (1.0, 0.0),
+            AdjustedWeight::Deletions => (0.0, 1.0),
+            AdjustedWeight::Changes => (1.0, 1.0),
+        };
+        MetricConfig {
+            insertion,
+            deletion,
+            ..MetricConfig::default()
+        }
+    }
+}
+
+/// How the adjusted model scores a contribution, set in the `[adjusted]` table of
+/// `bound.toml`. The default weighs every changed line as 1, like
+/// [`AdjustedWeight::Changes`].
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricConfig {
+    /// Weight of each inserted line.
+    pub insertion: f64,
+    /// Weight of each deleted line.
+    pub deletion: f64,
+    /// Weight of each changed file, binary files included.
+    pub file: f64,
+    /// Weight of each commit, credited once to each owner it changed files of.
+    pub commit: f64,
+    /// Score the lines of a file change as `ln(1 + lines)`, so mass changes don't drown
+    /// out everything else.
+    pub log_scale: bool,
+}
+
+impl Default for MetricConfig {
+    fn default() -> Self {
+        MetricConfig {
+            insertion: 1.0,
+            deletion: 1.0,
+            file: 0.0,
+            commit: 0.0,
+            log_scale: false,
+        }
+    }
+}
+
+impl MetricConfig {
+    /// The first weight that is negative or not finite, which would turn the adjusted
+    /// metrics into NaN or make commit shares meaningless.
+    pub fn invalid_weight(&self) -> Option<(&'static str, f64)> {
+        [
+            ("insertion", self.insertion),
+            ("deletion", self.deletion),
+            ("file", self.file),
+            ("commit", self.commit),
+        ]
+        .into_iter()
+        .find(|(_, weight)| !weight.is_finite() || *weight < 0.0)
+    }
+
+    fn of(&self, share: &OwnerShare) -> f64 {
+        let lines =
+            self.insertion * share.insertions as f64 + self.deletion * share.deletions as f64;
+        let lines = if self.log_scale { lines.ln_1p() } else { lines };
+        lines + self.file
+    }
+}
+
+/// Which of a file's owners its changes are credited to when it has several.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OwnerAttribution {
+    /// Only the first owner listed in CODEOWNERS.
+    FirstOwner,
+    /// Every owner, each with the whole change, so owner totals add up to more than the
+    /// changes made.
+    AllOwners,
+    /// Every owner, with the lines divided between them so totals reconcile.
+    SplitEvenly,
+}
+
+/// How the owner and contributor analyses count changes.
+#[derive(Clone, Copy, Debug)]
+pub struct AnalysisOptions {
+    /// Compute the adjusted metrics, scoring contributions by this.
+    pub adjusted: Option<MetricConfig>,
+    /// Which of a file's owners to credit. When unset the owner analysis credits all of
+    /// them and the contributor analysis the first.
+    pub owner_attribution: Option<OwnerAttribution>,
+    /// How many top contributors to list per owner, or 0 to list them all.
+    pub top_contributors: usize,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions {
+            adjusted: None,
+            owner_attribution: None,
+            top_contributors: 10,
+        }
+    }
+}
+
+/// The part of a file change credited to one of its owners.
+struct OwnerShare<'a> {
+    owner: &'a str,
+    insertions: usize,
+    deletions: usize,
+    binary: bool,
+}
+
+impl OwnerAttribution {
+    fn shares(self, change: &FileChangeWithCodeowner) -> Vec<OwnerShare<'_>> {
+        let owners = change.codeowners.as_deref().unwrap_or_default();
+        let owners = match self {
+            OwnerAttribution::FirstOwner => &owners[..owners.len().min(1)],
+            OwnerAttribution::AllOwners | OwnerAttribution::SplitEvenly => owners,
+        };
+        let parts = match self {
+            OwnerAttribution::SplitEvenly => owners.len(),
+            _ => 1,
+        };
+        // Any remainder goes to the first owners, a line each
+        let split = |total: usize, index: usize| total / parts + usize::from(index < total % parts);
+        owners
+            .iter()
+            .enumerate()
+            .map(|(index, owner)| OwnerShare {
+                owner,
+                insertions: split(change.insertions as usize, index),
+                deletions: split(change.deletions as usize, index),
+                binary: split(change.binary as usize, index) > 0,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for OwnerAttribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "first-owner" => Ok(OwnerAttribution::FirstOwner),
+            "all-owners" => Ok(OwnerAttribution::AllOwners),
+            "split-evenly" => Ok(OwnerAttribution::SplitEvenly),
+            _ => Err(format!(
+                "Invalid owner attribution '{}', expected 'first-owner', 'all-owners' or 'split-evenly'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OwnerAttribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnerAttribution::FirstOwner => write!(f, "first-owner"),
+            OwnerAttribution::AllOwners => write!(f, "all-owners"),
+            OwnerAttribution::SplitEvenly => write!(f, "split-evenly"),
+        }
+    }
+}
+
+impl OwnerInfo {
+    /// Numeric fields available to derived metrics.
+    pub fn metric_fields(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("team_insertions", self.total_insertions_by_team as f64),
+            ("team_deletions", self.total_deletions_by_team as f64),
+            (
+                "team_changes",
+                (self.total_insertions_by_team + self.total_deletions_by_team) as f64,
+            ),
+            ("team_commits", self.total_commits_by_team as f64),
+            ("team_file_touches", self.file_touches_by_team as f64),
+            ("others_insertions", self.total_insertions_by_others as f64),
+            ("others_deletions", self.total_deletions_by_others as f64),
+            (
+                "others_changes",
+                (self.total_insertions_by_others + self.total_deletions_by_others) as f64,
+            ),
+            ("others_commits", self.total_commits_by_others as f64),
+            ("others_file_touches", self.file_touches_by_others as f64),
+            ("team_binary_changes", self.binary_changes_by_team as f64),
+            (
+                "others_binary_changes",
+                self.binary_changes_by_others as f64,
+            ),
+            ("adjusted_team_changes", self.adjusted_changes_by_team),
+            ("adjusted_team_commits", self.adjusted_commits_by_team),
+            ("adjusted_others_changes", self.adjusted_changes_by_others),
+            ("adjusted_others_commits", self.adjusted_commits_by_others),
+            ("contributors", self.contributors as f64),
+            (
+                "first_time_contributors",
+                self.newcomers
+                    .as_ref()
+                    .map_or(0, |newcomers| newcomers.first_time_contributors)
+                    as f64,
+            ),
+            (
+                "first_time_changes",
+                self.newcomers
+                    .as_ref()
+                    .map_or(0, |newcomers| newcomers.changes_by_first_time_contributors)
+                    as f64,
+            ),
+            (
+                "repeat_changes",
+                self.newcomers
+                    .as_ref()
+                    .map_or(0, |newcomers| newcomers.changes_by_repeat_contributors)
+                    as f64,
+            ),
+        ]
+    }
+}
+
+#[derive(Serialize)]
 pub struct ContributorToOwnerInfo {
     pub author_name: String,
     pub author_email: String,
     pub metric_value: usize,
 }
+/// Totals over every analyzed commit, independent of how they are broken down.
+#[derive(Default)]
+pub struct AnalysisSummary {
+    pub total_commits: usize,
+    pub total_insertions: usize,
+    pub total_deletions: usize,
+    pub changes_by_team: usize,
+    pub changes_by_others: usize,
+    pub unowned_changes: usize,
+    /// Changes to binary files, which carry no line counts.
+    pub binary_changes: usize,
+}
+
+impl AnalysisSummary {
+    pub fn total_changes(&self) -> usize {
+        self.total_insertions + self.total_deletions
+    }
+
+    /// Fraction of changed lines that landed in files with at least one owner.
+    pub fn coverage(&self) -> f64 {
+        let total_changes = self.total_changes();
+        if total_changes > 0 {
+            (total_changes - self.unowned_changes) as f64 / total_changes as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub(crate) fn add_commit(&mut self, commit: &CommitInfoWithCodeowner) {
+        self.total_commits += 1;
+        for change in &commit.file_changes {
+            let changes = (change.insertions + change.deletions) as usize;
+            self.total_insertions += change.insertions as usize;
+            self.total_deletions += change.deletions as usize;
+            if change.binary {
+                self.binary_changes += 1;
+            }
+            let is_owned = change
+                .codeowners
+                .as_ref()
+                .is_some_and(|owners| !owners.is_empty());
+            if !is_owned {
+                self.unowned_changes += changes;
+            } else if change.author_is_codeowner.unwrap_or(false) {
+                self.changes_by_team += changes;
+            } else {
+                self.changes_by_others += changes;
+            }
+        }
+    }
+}
+
+pub struct OwnerAnalysis {
+    pub summary: AnalysisSummary,
+    pub owners: Vec<OwnerInfo>,
+}
+
+pub struct ContributorAnalysis {
+    pub summary: AnalysisSummary,
+    pub contributors: Vec<ContributorInfo>,
+}
+
+#[derive(Serialize)]
 pub struct OwnerInfo {
     pub owner: String,
     pub total_insertions_by_team: usize,
     pub total_deletions_by_team: usize,
+    /// Commits changing the owner's files, each counted once however many it changed,
+    /// and either as the team's or as others': as the team's when the author was a member
+    /// of the owner for any of those files.
     pub total_commits_by_team: usize,
     pub total_insertions_by_others: usize,
     pub total_deletions_by_others: usize,
     pub total_commits_by_others: usize,
-    pub adjusted_changes_by_team: usize,
+    /// Changes to one of the owner's files in one commit, which is what the commit counts
+    /// used to count.
+    pub file_touches_by_team: usize,
+    pub file_touches_by_others: usize,
+    pub binary_changes_by_team: usize,
+    pub binary_changes_by_others: usize,
+    pub adjusted_changes_by_team: f64,
     pub adjusted_commits_by_team: f64,
-    pub adjusted_changes_by_others: usize,
+    pub adjusted_changes_by_others: f64,
     pub adjusted_commits_by_others: f64,
     pub top_outside_contributors_by_changes: Vec<ContributorToOwnerInfo>,
     pub top_outside_contributors_by_commits: Vec<ContributorToOwnerInfo>,
     pub top_team_contributors_by_changes: Vec<ContributorToOwnerInfo>,
     pub top_team_contributors_by_commits: Vec<ContributorToOwnerInfo>,
+    /// Distinct people who changed the owner's files, in the team or not.
+    pub contributors: usize,
+    /// Set when the analyzer knows who changed the owner's files before the analyzed
+    /// commits (see [`OwnerAnalyzer::with_prior_contributors`]).
+    pub newcomers: Option<NewcomerBreakdown>,
+    /// Changes to the owner's files by language, most changed first, when the commits were
+    /// classified by [`crate::classify_languages`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<LanguageChanges>,
+    /// Every team member who changed the owner's files, most changes first, for ranking
+    /// them some other way.
+    #[serde(skip)]
+    pub team_contributors: Vec<ContributorTotals>,
+    /// Everyone else who changed the owner's files, most changes first.
+    #[serde(skip)]
+    pub outside_contributors: Vec<ContributorTotals>,
 }
 
-pub fn analyze_by_owner(
-    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
-    adjusted: bool,
-) -> Result<Vec<OwnerInfo>, io::Error> {
-    let mut owners: HashMap<String, OwnerInfo> = HashMap::new();
+#[derive(Clone, Serialize)]
+pub struct ContributorTotals {
+    pub author_name: String,
+    pub author_email: String,
+    pub changes: usize,
+    pub commits: usize,
+}
 
-    let mut team_contributors: HashMap<String, HashMap<(String, String), (usize, usize)>> =
-        HashMap::new();
-    let mut outside_contributors: HashMap<String, HashMap<(String, String), (usize, usize)>> =
-        HashMap::new();
+/// Incremental form of [`analyze_by_owner`], fed one commit at a time.
+pub struct OwnerAnalyzer {
+    adjusted: Option<MetricConfig>,
+    attribution: OwnerAttribution,
+    top_contributors: usize,
+    owners: HashMap<String, OwnerInfo>,
+    summary: AnalysisSummary,
+    team_contributors: HashMap<String, ContributorStats>,
+    outside_contributors: HashMap<String, ContributorStats>,
+    languages: HashMap<String, LanguageTotals>,
+    prior_contributors: Option<OwnerContributors>,
+    progress: Option<Arc<dyn ProgressSink>>,
+}
 
-    for commit_result in commits {
-        let commit = commit_result?;
-        let mut commit_total_insertions: usize = 0;
-        let mut commit_changes_by_owner: HashMap<String, usize> = HashMap::new();
+impl OwnerAnalyzer {
+    pub fn new(options: &AnalysisOptions) -> Self {
+        OwnerAnalyzer {
+            adjusted: options.adjusted,
+            attribution: options
+                .owner_attribution
+                .unwrap_or(OwnerAttribution::AllOwners),
+            top_contributors: options.top_contributors,
+            owners: HashMap::new(),
+            summary: AnalysisSummary::default(),
+            team_contributors: HashMap::new(),
+            outside_contributors: HashMap::new(),
+            languages: HashMap::new(),
+            prior_contributors: None,
+            progress: None,
+        }
+    }
 
-        // First pass: calculate total insertions for this commit
-        for change in &commit.file_changes {
-            if let Some(codeowners) = &change.codeowners {
-                for owner in codeowners {
-                    *commit_changes_by_owner.entry(owner.clone()).or_insert(0) +=
-                        change.insertions as usize;
-                    commit_total_insertions += change.insertions as usize;
+    /// Reports each commit added to `progress`, and the total once finished.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Splits each owner's contributors into those in `prior`, who changed the owner's
+    /// files before the commits analyzed, and first-time contributors.
+    pub fn with_prior_contributors(mut self, prior: OwnerContributors) -> Self {
+        self.prior_contributors = Some(prior);
+        self
+    }
+
+    pub fn add_commit(&mut self, commit: &CommitInfoWithCodeowner) {
+        self.summary.add_commit(commit);
+        if let Some(progress) = &self.progress {
+            progress.commits_processed(self.summary.total_commits);
+        }
+        let weight = self.adjusted.unwrap_or_default();
+        let shares: Vec<_> = commit
+            .file_changes
+            .iter()
+            .map(|change| (change, self.attribution.shares(change)))
+            .collect();
+        let mut commit_total_weight = 0.0;
+        let mut commit_weight_by_owner: HashMap<&str, f64> = HashMap::new();
+
+        // First pass: calculate the total weight of this commit
+        for share in shares.iter().flat_map(|(_, shares)| shares) {
+            let owner_weight = commit_weight_by_owner
+                .entry(share.owner)
+                .or_insert_with(|| {
+                    commit_total_weight += weight.commit;
+                    weight.commit
+                });
+            *owner_weight += weight.of(share);
+            commit_total_weight += weight.of(share);
+        }
+
+        // A commit changing some of an owner's files as a member of the owner and others
+        // as an outsider (possible when files have several owners) is a team commit
+        let team_owners: HashSet<&str> = shares
+            .iter()
+            .filter(|(change, _)| change.author_is_codeowner.unwrap_or(false))
+            .flat_map(|(_, shares)| shares.iter().map(|share| share.owner))
+            .collect();
+
+        // Second pass: update metrics, counting the commit once for each owner
+        let mut counted: HashSet<&str> = HashSet::new();
+        for (change, change_shares) in &shares {
+            for share in change_shares {
+                let owner = share.owner;
+                let owner_info =
+                    self.owners
+                        .entry(owner.to_string())
+                        .or_insert_with(|| OwnerInfo {
+                            owner: owner.to_string(),
+                            total_insertions_by_team: 0,
+                            total_deletions_by_team: 0,
+                            total_commits_by_team: 0,
+                            total_insertions_by_others: 0,
+                            total_deletions_by_others: 0,
+                            total_commits_by_others: 0,
+                            file_touches_by_team: 0,
+                            file_touches_by_others: 0,
+                            binary_changes_by_team: 0,
+                            binary_changes_by_others: 0,
+                            top_outside_contributors_by_changes: Vec::new(),
+                            top_outside_contributors_by_commits: Vec::new(),
+                            top_team_contributors_by_changes: Vec::new(),
+                            top_team_contributors_by_commits: Vec::new(),
+                            contributors: 0,
+                            newcomers: None,
+                            languages: Vec::new(),
+                            team_contributors: Vec::new(),
+                            outside_contributors: Vec::new(),
+                            adjusted_changes_by_team: 0.0,
+                            adjusted_commits_by_team: 0.0,
+                            adjusted_changes_by_others: 0.0,
+                            adjusted_commits_by_others: 0.0,
+                        });
+                let commit_weight = if commit_total_weight > 0.0 {
+                    commit_weight_by_owner.get(owner).unwrap_or(&0.0) / commit_total_weight
+                } else {
+                    0.0
+                };
+
+                if counted.insert(owner) {
+                    let is_team_commit = team_owners.contains(owner);
+                    let (commits, adjusted_changes, adjusted_commits, contributors) =
+                        if is_team_commit {
+                            (
+                                &mut owner_info.total_commits_by_team,
+                                &mut owner_info.adjusted_changes_by_team,
+                                &mut owner_info.adjusted_commits_by_team,
+                                &mut self.team_contributors,
+                            )
+                        } else {
+                            (
+                                &mut owner_info.total_commits_by_others,
+                                &mut owner_info.adjusted_changes_by_others,
+                                &mut owner_info.adjusted_commits_by_others,
+                                &mut self.outside_contributors,
+                            )
+                        };
+                    *commits += 1;
+                    if self.adjusted.is_some() {
+                        *adjusted_changes += weight.commit;
+                        *adjusted_commits += commit_weight;
+                    }
+                    update_contributor_stats(contributors, owner, commit, 0, 1);
+                }
+
+                let is_team_member = change.author_is_codeowner.unwrap_or(false);
+                if let Some(language) = &change.language {
+                    self.languages.entry(owner.to_string()).or_default().add(
+                        language,
+                        share.insertions + share.deletions,
+                        is_team_member,
+                    );
+                }
+                if is_team_member {
+                    owner_info.total_insertions_by_team += share.insertions;
+                    owner_info.total_deletions_by_team += share.deletions;
+                    owner_info.file_touches_by_team += 1;
+                    owner_info.binary_changes_by_team += share.binary as usize;
+                    if self.adjusted.is_some() {
+                        owner_info.adjusted_changes_by_team += weight.of(share);
+                    }
+                    update_contributor_stats(
+                        &mut self.team_contributors,
+                        owner,
+                        commit,
+                        share.insertions + share.deletions,
+                        0,
+                    );
+                } else {
+                    owner_info.total_insertions_by_others += share.insertions;
+                    owner_info.total_deletions_by_others += share.deletions;
+                    owner_info.file_touches_by_others += 1;
+                    owner_info.binary_changes_by_others += share.binary as usize;
+                    if self.adjusted.is_some() {
+                        owner_info.adjusted_changes_by_others += weight.of(share);
+                    }
+                    update_contributor_stats(
+                        &mut self.outside_contributors,
+                        owner,
+                        commit,
+                        share.insertions + share.deletions,
+                        0,
+                    );
                 }
             }
         }
+    }
 
-        // Second pass: update metrics
-        for change in &commit.file_changes {
-            if let Some(codeowners) = &change.codeowners {
-                for owner in codeowners {
-                    let owner_info = owners.entry(owner.clone()).or_insert_with(|| OwnerInfo {
-                        owner: owner.clone(),
-                        total_insertions_by_team: 0,
-                        total_deletions_by_team: 0,
-                        total_commits_by_team: 0,
-                        total_insertions_by_others: 0,
-                        total_deletions_by_others: 0,
-                        total_commits_by_others: 0,
-                        top_outside_contributors_by_changes: Vec::new(),
-                        top_outside_contributors_by_commits: Vec::new(),
-                        top_team_contributors_by_changes: Vec::new(),
-                        top_team_contributors_by_commits: Vec::new(),
-                        adjusted_changes_by_team: 0,
-                        adjusted_commits_by_team: 0.0,
-                        adjusted_changes_by_others: 0,
-                        adjusted_commits_by_others: 0.0,
-                    });
-
-                    let is_team_member = change.author_is_codeowner.unwrap_or(false);
-                    if is_team_member {
-                        owner_info.total_insertions_by_team += change.insertions as usize;
-                        owner_info.total_deletions_by_team += change.deletions as usize;
-                        owner_info.total_commits_by_team += 1;
-                        if adjusted {
-                            let total_changes = (change.insertions + change.deletions) as usize;
-                            owner_info.adjusted_changes_by_team += total_changes;
-                            let commit_weight = if commit_total_insertions > 0 {
-                                *commit_changes_by_owner.get(owner).unwrap_or(&0) as f64
-                                    / commit_total_insertions as f64
-                            } else {
-                                0.0
-                            };
-                            owner_info.adjusted_commits_by_team += commit_weight;
-                        }
-                        update_contributor_stats(&mut team_contributors, owner, &commit, &change);
+    pub fn finish(mut self) -> OwnerAnalysis {
+        if let Some(progress) = &self.progress {
+            progress.commits_finished(self.summary.total_commits);
+        }
+        // Process contributors and update OwnerInfo
+        for (owner, owner_info) in self.owners.iter_mut() {
+            if let Some(languages) = self.languages.remove(owner) {
+                owner_info.languages = languages.finish();
+            }
+            let mut changes_by_contributor: HashMap<&(String, String), usize> = HashMap::new();
+            for stats in [
+                self.team_contributors.get(owner),
+                self.outside_contributors.get(owner),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                for (contributor, (changes, _)) in stats {
+                    *changes_by_contributor.entry(contributor).or_default() += changes;
+                }
+            }
+            owner_info.contributors = changes_by_contributor.len();
+            if let Some(prior) = &self.prior_contributors {
+                let mut newcomers = NewcomerBreakdown::default();
+                for ((name, email), changes) in changes_by_contributor {
+                    if prior.contains(owner, name, email) {
+                        newcomers.repeat_contributors += 1;
+                        newcomers.changes_by_repeat_contributors += changes;
                     } else {
-                        owner_info.total_insertions_by_others += change.insertions as usize;
-                        owner_info.total_deletions_by_others += change.deletions as usize;
-                        owner_info.total_commits_by_others += 1;
-                        if adjusted {
-                            let total_changes = (change.insertions + change.deletions) as usize;
-                            owner_info.adjusted_changes_by_others += total_changes;
-                            let commit_weight = if commit_total_insertions > 0 {
-                                *commit_changes_by_owner.get(owner).unwrap_or(&0) as f64
-                                    / commit_total_insertions as f64
-                            } else {
-                                0.0
-                            };
-                            owner_info.adjusted_commits_by_others += commit_weight;
-                        }
-                        update_contributor_stats(
-                            &mut outside_contributors,
-                            owner,
-                            &commit,
-                            &change,
-                        );
+                        newcomers.first_time_contributors += 1;
+                        newcomers.changes_by_first_time_contributors += changes;
                     }
                 }
+                owner_info.newcomers = Some(newcomers);
             }
+            update_top_contributors(
+                owner_info,
+                &self.team_contributors.get(owner),
+                true,
+                self.top_contributors,
+            );
+            update_top_contributors(
+                owner_info,
+                &self.outside_contributors.get(owner),
+                false,
+                self.top_contributors,
+            );
         }
-    }
 
-    // Process contributors and update OwnerInfo
-    for (owner, owner_info) in owners.iter_mut() {
-        update_top_contributors(owner_info, &team_contributors.get(owner), true);
-        update_top_contributors(owner_info, &outside_contributors.get(owner), false);
+        let mut sorted_owners: Vec<OwnerInfo> = self.owners.into_values().collect();
+        sorted_owners.sort_by(|a, b| a.owner.cmp(&b.owner));
+        OwnerAnalysis {
+            summary: self.summary,
+            owners: sorted_owners,
+        }
     }
+}
 
-    let mut sorted_owners: Vec<OwnerInfo> = owners.into_values().collect();
-    sorted_owners.sort_by(|a, b| a.owner.cmp(&b.owner));
-    Ok(sorted_owners)
+pub fn analyze_by_owner(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    options: &AnalysisOptions,
+) -> Result<OwnerAnalysis, io::Error> {
+    let mut analyzer = OwnerAnalyzer::new(options);
+    for commit_result in commits {
+        analyzer.add_commit(&commit_result?);
+    }
+    Ok(analyzer.finish())
 }
 
 fn update_contributor_stats(
-    contributors: &mut HashMap<String, HashMap<(String, String), (usize, usize)>>,
+    contributors: &mut HashMap<String, ContributorStats>,
     owner: &str,
     commit: &CommitInfoWithCodeowner,
-    change: &FileChangeWithCodeowner,
+    changes: usize,
+    commits: usize,
 ) {
     let owner_contributors = contributors.entry(owner.to_string()).or_default();
     let contributor_key = (commit.author_name.clone(), commit.author_email.clone());
-    let (changes, commits) = owner_contributors.entry(contributor_key).or_insert((0, 0));
-    *changes += change.insertions as usize + change.deletions as usize;
-    *commits += 1;
+    let totals = owner_contributors.entry(contributor_key).or_insert((0, 0));
+    totals.0 += changes;
+    totals.1 += commits;
 }
 
+/// Lists the `top` contributors, or all of them when `top` is 0, by changes and by
+/// commits, along with everyone's totals.
 fn update_top_contributors(
     owner_info: &mut OwnerInfo,
-    contributors: &Option<&HashMap<(String, String), (usize, usize)>>,
+    contributors: &Option<&ContributorStats>,
     is_team: bool,
+    top: usize,
 ) {
     if let Some(contributors) = contributors {
-        let mut contributors: Vec<_> = contributors.iter().collect();
-        contributors.sort_by(|(_, (changes_a, _)), (_, (changes_b, _))| changes_b.cmp(changes_a));
-        let top_by_changes: Vec<ContributorToOwnerInfo> = contributors
+        let top = if top == 0 { usize::MAX } else { top };
+        let mut contributors: Vec<ContributorTotals> = contributors
             .iter()
-            .take(10)
-            .map(|((name, email), (changes, _))| ContributorToOwnerInfo {
+            .map(|((name, email), (changes, commits))| ContributorTotals {
                 author_name: name.clone(),
                 author_email: email.clone(),
-                metric_value: *changes,
+                changes: *changes,
+                commits: *commits,
             })
             .collect();
 
-        contributors.sort_by(|(_, (_, commits_a)), (_, (_, commits_b))| commits_b.cmp(commits_a));
+        contributors.sort_by_key(|contributor| std::cmp::Reverse(contributor.commits));
         let top_by_commits: Vec<ContributorToOwnerInfo> = contributors
             .iter()
-            .take(10)
-            .map(|((name, email), (_, commits))| ContributorToOwnerInfo {
-                author_name: name.clone(),
-                author_email: email.clone(),
-                metric_value: *commits,
+            .take(top)
+            .map(|contributor| ContributorToOwnerInfo {
+                author_name: contributor.author_name.clone(),
+                author_email: contributor.author_email.clone(),
+                metric_value: contributor.commits,
+            })
+            .collect();
+
+        contributors.sort_by_key(|contributor| std::cmp::Reverse(contributor.changes));
+        let top_by_changes: Vec<ContributorToOwnerInfo> = contributors
+            .iter()
+            .take(top)
+            .map(|contributor| ContributorToOwnerInfo {
+                author_name: contributor.author_name.clone(),
+                author_email: contributor.author_email.clone(),
+                metric_value: contributor.changes,
             })
             .collect();
 
         if is_team {
             owner_info.top_team_contributors_by_changes = top_by_changes;
             owner_info.top_team_contributors_by_commits = top_by_commits;
+            owner_info.team_contributors = contributors;
         } else {
             owner_info.top_outside_contributors_by_changes = top_by_changes;
             owner_info.top_outside_contributors_by_commits = top_by_commits;
+            owner_info.outside_contributors = contributors;
         }
     }
 }
+#[derive(Serialize)]
 pub struct ContributionsByOwnerInfo {
     pub owner: String,
     pub total_insertions: usize,
     pub total_deletions: usize,
+    /// Commits changing the owner's files, each counted once however many it changed.
     pub total_commits: usize,
-    pub adjusted_changes: usize,
+    /// Changes to one of the owner's files in one commit.
+    pub file_touches: usize,
+    pub binary_changes: usize,
+    pub adjusted_changes: f64,
     pub adjusted_commits: f64,
+    /// The changes by language, most changed first, when the commits were classified by
+    /// [`crate::classify_languages`]. Changes made as a member of the owner count as
+    /// team changes.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<LanguageChanges>,
+}
+
+impl ContributionsByOwnerInfo {
+    /// Numeric fields available to derived metrics.
+    pub fn metric_fields(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("insertions", self.total_insertions as f64),
+            ("deletions", self.total_deletions as f64),
+            (
+                "changes",
+                (self.total_insertions + self.total_deletions) as f64,
+            ),
+            ("commits", self.total_commits as f64),
+            ("file_touches", self.file_touches as f64),
+            ("binary_changes", self.binary_changes as f64),
+            ("adjusted_changes", self.adjusted_changes),
+            ("adjusted_commits", self.adjusted_commits),
+        ]
+    }
 }
 
+#[derive(Serialize)]
 pub struct ContributorInfo {
     pub author_name: String,
     pub author_email: String,
     pub contributions: Vec<ContributionsByOwnerInfo>,
 }
 
-pub fn analyze_by_contributor(
-    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
-    adjusted: bool,
-) -> Result<Vec<ContributorInfo>, io::Error> {
-    let mut contributors: HashMap<(String, String), Vec<ContributionsByOwnerInfo>> = HashMap::new();
+/// Incremental form of [`analyze_by_contributor`], fed one commit at a time.
+pub struct ContributorAnalyzer {
+    adjusted: Option<MetricConfig>,
+    attribution: OwnerAttribution,
+    contributors: HashMap<(String, String), Vec<ContributionsByOwnerInfo>>,
+    /// Per-language totals by contributor and owner.
+    languages: HashMap<(String, String, String), LanguageTotals>,
+    summary: AnalysisSummary,
+    progress: Option<Arc<dyn ProgressSink>>,
+}
 
-    for commit_result in commits {
-        let commit = commit_result?;
+impl ContributorAnalyzer {
+    pub fn new(options: &AnalysisOptions) -> Self {
+        ContributorAnalyzer {
+            adjusted: options.adjusted,
+            attribution: options
+                .owner_attribution
+                .unwrap_or(OwnerAttribution::FirstOwner),
+            contributors: HashMap::new(),
+            languages: HashMap::new(),
+            summary: AnalysisSummary::default(),
+            progress: None,
+        }
+    }
+
+    /// Reports each commit added to `progress`, and the total once finished.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn add_commit(&mut self, commit: &CommitInfoWithCodeowner) {
+        self.summary.add_commit(commit);
+        if let Some(progress) = &self.progress {
+            progress.commits_processed(self.summary.total_commits);
+        }
         let contributor_key = (commit.author_name.clone(), commit.author_email.clone());
-        let mut commit_total_changes: usize = 0;
-        let mut commit_changes_by_owner: HashMap<String, usize> = HashMap::new();
+        let weight = self.adjusted.unwrap_or_default();
+        let shares: Vec<(&FileChangeWithCodeowner, OwnerShare)> = commit
+            .file_changes
+            .iter()
+            .flat_map(|change| {
+                let shares = self.attribution.shares(change);
+                if shares.is_empty() {
+                    vec![OwnerShare {
+                        owner: "<unowned>",
+                        insertions: change.insertions as usize,
+                        deletions: change.deletions as usize,
+                        binary: change.binary,
+                    }]
+                } else {
+                    shares
+                }
+                .into_iter()
+                .map(move |share| (change, share))
+            })
+            .collect();
+        let mut commit_total_weight = 0.0;
+        let mut commit_weight_by_owner: HashMap<&str, f64> = HashMap::new();
 
-        // First pass: calculate total changes for this commit
-        for change in &commit.file_changes {
-            let owner = match &change.codeowners {
-                Some(codeowners) if !codeowners.is_empty() => codeowners[0].clone(),
-                _ => "<unowned>".to_string(),
-            };
-            let total_changes = (change.insertions + change.deletions) as usize;
-            *commit_changes_by_owner.entry(owner).or_insert(0) += total_changes;
-            commit_total_changes += total_changes;
+        // First pass: calculate the total weight of this commit
+        for (_, share) in &shares {
+            let owner_weight = commit_weight_by_owner
+                .entry(share.owner)
+                .or_insert_with(|| {
+                    commit_total_weight += weight.commit;
+                    weight.commit
+                });
+            *owner_weight += weight.of(share);
+            commit_total_weight += weight.of(share);
         }
 
-        // Second pass: update metrics
-        for change in &commit.file_changes {
-            let owner = match &change.codeowners {
-                Some(codeowners) if !codeowners.is_empty() => codeowners[0].clone(),
-                _ => "<unowned>".to_string(),
+        // Second pass: update metrics, counting the commit once for each owner
+        let contributions = self
+            .contributors
+            .entry(contributor_key.clone())
+            .or_default();
+        let mut counted: HashSet<&str> = HashSet::new();
+        for (change, share) in &shares {
+            if let Some(language) = &change.language {
+                let (name, email) = &contributor_key;
+                self.languages
+                    .entry((name.clone(), email.clone(), share.owner.to_string()))
+                    .or_default()
+                    .add(
+                        language,
+                        share.insertions + share.deletions,
+                        change.author_is_codeowner.unwrap_or(false),
+                    );
+            }
+            let index = match contributions.iter().position(|c| c.owner == share.owner) {
+                Some(index) => index,
+                None => {
+                    contributions.push(ContributionsByOwnerInfo {
+                        owner: share.owner.to_string(),
+                        total_insertions: 0,
+                        total_deletions: 0,
+                        total_commits: 0,
+                        file_touches: 0,
+                        binary_changes: 0,
+                        adjusted_changes: 0.0,
+                        adjusted_commits: 0.0,
+                        languages: Vec::new(),
+                    });
+                    contributions.len() - 1
+                }
             };
+            let contribution = &mut contributions[index];
+            let new_commit = counted.insert(share.owner);
+            contribution.total_insertions += share.insertions;
+            contribution.total_deletions += share.deletions;
+            contribution.total_commits += new_commit as usize;
+            contribution.file_touches += 1;
+            contribution.binary_changes += share.binary as usize;
+            if self.adjusted.is_some() {
+                contribution.adjusted_changes += weight.of(share);
+            }
+            if self.adjusted.is_some() && new_commit {
+                contribution.adjusted_changes += weight.commit;
+                contribution.adjusted_commits += if commit_total_weight > 0.0 {
+                    commit_weight_by_owner.get(share.owner).unwrap_or(&0.0) / commit_total_weight
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
 
-            let contributions = contributors
-                .entry(contributor_key.clone())
-                .or_insert_with(Vec::new);
-            if let Some(contribution) = contributions.iter_mut().find(|c| c.owner == owner) {
-                contribution.total_insertions += change.insertions as usize;
-                contribution.total_deletions += change.deletions as usize;
-                contribution.total_commits += 1;
-                if adjusted {
-                    let total_changes = (change.insertions + change.deletions) as usize;
-                    contribution.adjusted_changes += total_changes;
-                    let commit_weight = if commit_total_changes > 0 {
-                        *commit_changes_by_owner.get(&owner).unwrap_or(&0) as f64
-                            / commit_total_changes as f64
-                    } else {
-                        0.0
-                    };
-                    contribution.adjusted_commits += commit_weight;
+    pub fn finish(mut self) -> ContributorAnalysis {
+        if let Some(progress) = &self.progress {
+            progress.commits_finished(self.summary.total_commits);
+        }
+        let mut result: Vec<ContributorInfo> = self
+            .contributors
+            .into_iter()
+            .map(|((author_name, author_email), mut contributions)| {
+                for contribution in &mut contributions {
+                    let key = (
+                        author_name.clone(),
+                        author_email.clone(),
+                        contribution.owner.clone(),
+                    );
+                    if let Some(languages) = self.languages.remove(&key) {
+                        contribution.languages = languages.finish();
+                    }
                 }
-            } else {
-                contributions.push(ContributionsByOwnerInfo {
-                    owner: owner.clone(),
-                    total_insertions: change.insertions as usize,
-                    total_deletions: change.deletions as usize,
-                    total_commits: 1,
-                    adjusted_changes: if adjusted {
-                        change.insertions as usize
-                    } else {
-                        0
-                    },
-                    adjusted_commits: if adjusted {
-                        if commit_total_changes > 0 {
-                            *commit_changes_by_owner.get(&owner).unwrap_or(&0) as f64
-                                / commit_total_changes as f64
-                        } else {
-                            0.0
-                        }
-                    } else {
-                        0.0
-                    },
-                });
-            }
+                contributions.sort_by_key(|c| std::cmp::Reverse(c.total_commits));
+                ContributorInfo {
+                    author_name,
+                    author_email,
+                    contributions,
+                }
+            })
+            .collect();
+
+        result.sort_by(|a, b| a.author_name.cmp(&b.author_name));
+
+        ContributorAnalysis {
+            summary: self.summary,
+            contributors: result,
         }
     }
+}
 
-    let mut result: Vec<ContributorInfo> = contributors
-        .into_iter()
-        .map(|((author_name, author_email), mut contributions)| {
-            contributions.sort_by(|a, b| b.total_commits.cmp(&a.total_commits));
-            ContributorInfo {
-                author_name,
-                author_email,
-                contributions,
-            }
+pub fn analyze_by_contributor(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    options: &AnalysisOptions,
+) -> Result<ContributorAnalysis, io::Error> {
+    let mut analyzer = ContributorAnalyzer::new(options);
+    for commit_result in commits {
+        analyzer.add_commit(&commit_result?);
+    }
+    Ok(analyzer.finish())
+}
+
+/// Both analyses computed from a single walk over the history.
+pub struct CombinedAnalysis {
+    pub owners: OwnerAnalysis,
+    pub contributors: ContributorAnalysis,
+}
+
+pub fn analyze_all(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    options: &AnalysisOptions,
+) -> Result<CombinedAnalysis, io::Error> {
+    let mut owners = OwnerAnalyzer::new(options);
+    let mut contributors = ContributorAnalyzer::new(options);
+    for commit_result in commits {
+        let commit = commit_result?;
+        owners.add_commit(&commit);
+        contributors.add_commit(&commit);
+    }
+    Ok(CombinedAnalysis {
+        owners: owners.finish(),
+        contributors: contributors.finish(),
+    })
+}
+
+pub struct OwnershipBucketInfo {
+    pub label: String,
+    pub files: usize,
+    pub file_changes: usize,
+    pub total_insertions: usize,
+    pub total_deletions: usize,
+}
+
+const OWNERSHIP_BUCKET_LABELS: [&str; 4] = ["0", "1", "2", "3+"];
+
+fn ownership_bucket(change: &FileChangeWithCodeowner) -> usize {
+    let owner_count = change.codeowners.as_ref().map_or(0, |owners| owners.len());
+    owner_count.min(OWNERSHIP_BUCKET_LABELS.len() - 1)
+}
+
+/// Buckets changed files by how many owners they have (0, 1, 2, 3+) along with the
+/// churn landing in each bucket. A file is counted in the bucket of its most recent
+/// ownership, while churn is attributed to the ownership in effect at each commit.
+pub fn analyze_ownership_distribution(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+) -> Result<Vec<OwnershipBucketInfo>, io::Error> {
+    let mut buckets: Vec<OwnershipBucketInfo> = OWNERSHIP_BUCKET_LABELS
+        .iter()
+        .map(|label| OwnershipBucketInfo {
+            label: label.to_string(),
+            files: 0,
+            file_changes: 0,
+            total_insertions: 0,
+            total_deletions: 0,
         })
         .collect();
+    let mut latest_bucket_by_path: HashMap<String, usize> = HashMap::new();
+
+    // git log yields newest commits first, so the first sighting of a path is its latest state
+    for commit_result in commits {
+        let commit = commit_result?;
+        for change in &commit.file_changes {
+            let bucket = ownership_bucket(change);
+            latest_bucket_by_path
+                .entry(change.path.clone())
+                .or_insert(bucket);
+
+            let bucket_info = &mut buckets[bucket];
+            bucket_info.file_changes += 1;
+            bucket_info.total_insertions += change.insertions as usize;
+            bucket_info.total_deletions += change.deletions as usize;
+        }
+    }
+
+    for bucket in latest_bucket_by_path.into_values() {
+        buckets[bucket].files += 1;
+    }
+
+    Ok(buckets)
+}
+
+pub struct CoverageInfo {
+    pub period: String,
+    pub summary: AnalysisSummary,
+    /// Files in the tree at the newest commit of the period.
+    pub files: usize,
+    /// Of those, the files matched by at least one CODEOWNERS rule.
+    pub owned_files: usize,
+}
+
+impl CoverageInfo {
+    pub fn file_coverage(&self) -> f64 {
+        if self.files > 0 {
+            self.owned_files as f64 / self.files as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// For each period, the share of changed lines that landed in owned files and the share of
+/// files in the tree that were owned at the end of the period.
+pub fn analyze_coverage(
+    periods: Vec<(String, Vec<CommitInfoWithCodeowner>)>,
+    cwd: &PathBuf,
+) -> Result<Vec<CoverageInfo>, io::Error> {
+    let mut coverage = Vec::new();
+
+    for (period, commits) in periods {
+        let Some(newest) = commits.iter().max_by_key(|commit| commit.timestamp) else {
+            continue;
+        };
+        let owners = get_owners_at_commit(&newest.id, cwd)?;
+        let tree = git_tree_files(&newest.id, cwd)?;
+        let owned_files = tree
+            .iter()
+            .filter(|path| owners.of(path).is_some_and(|owners| !owners.is_empty()))
+            .count();
+
+        let mut summary = AnalysisSummary::default();
+        for commit in &commits {
+            summary.add_commit(commit);
+        }
+
+        coverage.push(CoverageInfo {
+            period,
+            summary,
+            files: tree.len(),
+            owned_files,
+        });
+    }
+
+    Ok(coverage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChangeKind;
+
+    fn change(
+        path: &str,
+        owner: &str,
+        insertions: i32,
+        deletions: i32,
+        by_team: bool,
+    ) -> FileChangeWithCodeowner {
+        FileChangeWithCodeowner {
+            insertions,
+            deletions,
+            path: path.to_string(),
+            old_path: None,
+            kind: ChangeKind::Modified,
+            language: None,
+            binary: false,
+            size_before: None,
+            size_after: None,
+            codeowners: Some(vec![owner.to_string()]),
+            author_is_codeowner: Some(by_team),
+        }
+    }
+
+    /// A commit by a member of `@org/a` changing one file of `@org/a` and one of `@org/b`.
+    fn commit() -> CommitInfoWithCodeowner {
+        CommitInfoWithCodeowner {
+            id: "abc".to_string(),
+            author_name: "A".to_string(),
+            author_email: "a@example.com".to_string(),
+            timestamp: 0,
+            message: None,
+            file_changes: vec![
+                change("src/a.rs", "@org/a", 10, 2, true),
+                change("docs/b.md", "@org/b", 4, 0, false),
+            ],
+        }
+    }
+
+    fn options(metric: MetricConfig) -> AnalysisOptions {
+        AnalysisOptions {
+            adjusted: Some(metric),
+            ..AnalysisOptions::default()
+        }
+    }
+
+    fn owner<'a>(analysis: &'a OwnerAnalysis, owner: &str) -> &'a OwnerInfo {
+        analysis
+            .owners
+            .iter()
+            .find(|info| info.owner == owner)
+            .unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn weights_score_lines_files_and_commits() {
+        let metric = MetricConfig {
+            insertion: 1.0,
+            deletion: 0.5,
+            file: 2.0,
+            commit: 3.0,
+            log_scale: false,
+        };
+        let mut analyzer = OwnerAnalyzer::new(&options(metric));
+        analyzer.add_commit(&commit());
+        let analysis = analyzer.finish();
+
+        // 10 + 2 * 0.5 + 2 for the file + 3 for the commit
+        let a = owner(&analysis, "@org/a");
+        assert_close(a.adjusted_changes_by_team, 16.0);
+        assert_close(a.adjusted_commits_by_team, 16.0 / 25.0);
+        // 4 + 2 for the file + 3 for the commit
+        let b = owner(&analysis, "@org/b");
+        assert_close(b.adjusted_changes_by_others, 9.0);
+        assert_close(b.adjusted_commits_by_others, 9.0 / 25.0);
+
+        let analysis =
+            analyze_by_contributor(std::iter::once(Ok(commit())), &options(metric)).unwrap();
+        let contributions = &analysis.contributors[0].contributions;
+        let adjusted = |owner: &str| {
+            contributions
+                .iter()
+                .find(|contribution| contribution.owner == owner)
+                .unwrap()
+                .adjusted_changes
+        };
+        assert_close(adjusted("@org/a"), 16.0);
+        assert_close(adjusted("@org/b"), 9.0);
+    }
 
-    result.sort_by(|a, b| a.author_name.cmp(&b.author_name));
+    #[test]
+    fn commits_count_once_per_owner_as_the_teams_or_others() {
+        // The author is a member of @org/a only through the file @org/b also owns
+        let mut shared = change("src/shared.rs", "@org/a", 3, 0, true);
+        shared.codeowners = Some(vec!["@org/a".to_string(), "@org/b".to_string()]);
+        let commit = CommitInfoWithCodeowner {
+            file_changes: vec![
+                shared,
+                change("src/a.rs", "@org/a", 5, 1, false),
+                change("src/a2.rs", "@org/a", 1, 1, false),
+            ],
+            ..commit()
+        };
+        let mut analyzer = OwnerAnalyzer::new(&AnalysisOptions::default());
+        analyzer.add_commit(&commit);
+        let analysis = analyzer.finish();
 
-    Ok(result)
+        let a = owner(&analysis, "@org/a");
+        assert_eq!(a.total_commits_by_team, 1);
+        assert_eq!(a.total_commits_by_others, 0);
+        assert_eq!(a.file_touches_by_team, 1);
+        assert_eq!(a.file_touches_by_others, 2);
+        assert_eq!(a.total_insertions_by_others, 6);
+        assert_eq!(a.team_contributors[0].commits, 1);
+        assert_eq!(a.outside_contributors[0].commits, 0);
+        assert_eq!(a.outside_contributors[0].changes, 8);
+        assert_eq!(owner(&analysis, "@org/b").total_commits_by_team, 1);
+    }
+
+    #[test]
+    fn log_scale_dampens_large_changes() {
+        let metric = MetricConfig {
+            log_scale: true,
+            ..MetricConfig::default()
+        };
+        let mut analyzer = OwnerAnalyzer::new(&options(metric));
+        analyzer.add_commit(&commit());
+        let analysis = analyzer.finish();
+
+        let (a, b) = (12f64.ln_1p(), 4f64.ln_1p());
+        assert_close(owner(&analysis, "@org/a").adjusted_changes_by_team, a);
+        assert_close(
+            owner(&analysis, "@org/a").adjusted_commits_by_team,
+            a / (a + b),
+        );
+        assert_close(owner(&analysis, "@org/b").adjusted_changes_by_others, b);
+    }
+
+    #[test]
+    fn weight_presets_match_their_metric_configs() {
+        let mut analyzer = OwnerAnalyzer::new(&options(AdjustedWeight::Deletions.into()));
+        analyzer.add_commit(&commit());
+        let analysis = analyzer.finish();
+        assert_close(owner(&analysis, "@org/a").adjusted_changes_by_team, 2.0);
+        assert_close(owner(&analysis, "@org/a").adjusted_commits_by_team, 1.0);
+        assert_close(owner(&analysis, "@org/b").adjusted_commits_by_others, 0.0);
+        assert_eq!(MetricConfig::default(), AdjustedWeight::Changes.into());
+    }
+
+    #[test]
+    fn negative_and_non_finite_weights_are_invalid() {
+        assert_eq!(MetricConfig::default().invalid_weight(), None);
+        let negative = MetricConfig {
+            deletion: -1.0,
+            ..MetricConfig::default()
+        };
+        assert_eq!(negative.invalid_weight(), Some(("deletion", -1.0)));
+        let infinite = MetricConfig {
+            file: f64::INFINITY,
+            ..MetricConfig::default()
+        };
+        assert_eq!(infinite.invalid_weight(), Some(("file", f64::INFINITY)));
+    }
 }
@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::AuthorMembership;
+
+/// A merged pull request with the logins of reviewers who approved it.
+pub struct PullRequestReviewData {
+    pub number: u64,
+    pub author_login: String,
+    /// When the pull request was opened, as GitHub's ISO-8601 timestamp.
+    pub created_at: String,
+    pub files: Vec<String>,
+    pub approvers: Vec<String>,
+    /// When each approver first approved, by login.
+    pub first_approved_at: HashMap<String, String>,
+}
+
+pub struct OwnerReviewInfo {
+    pub owner: String,
+    pub pull_requests: usize,
+    pub approved_by_codeowner: usize,
+    pub approved_by_others_only: usize,
+    pub merged_without_approval: usize,
+}
+
+impl OwnerReviewInfo {
+    /// Fraction of this owner's pull requests that a member of the owner approved.
+    pub fn codeowner_approval_rate(&self) -> f64 {
+        if self.pull_requests > 0 {
+            self.approved_by_codeowner as f64 / self.pull_requests as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Whether `login` counts as `owner`: either the owner names the user directly
+/// (`@login`) or the login's resolved identity is a member of the owning team.
+pub(crate) fn approver_is_codeowner(
+    login: &str,
+    owner: &str,
+    memberships: &AuthorMembership,
+    identities: &HashMap<String, (String, String)>,
+) -> bool {
+    if owner.eq_ignore_ascii_case(&format!("@{}", login)) {
+        return true;
+    }
+    identities
+        .get(login)
+        .is_some_and(|(name, email)| memberships.is_codeowner(name, email, owner))
+}
+
+/// Classifies each pull request, per owner of the files it touched, by whether a member of
+/// that owner approved it. `identities` maps reviewer logins to `(name, email)`.
+pub fn analyze_reviews(
+    pull_requests: &[PullRequestReviewData],
+    owners: &codeowners::Owners,
+    memberships: &AuthorMembership,
+    identities: &HashMap<String, (String, String)>,
+) -> Vec<OwnerReviewInfo> {
+    let mut results: BTreeMap<String, OwnerReviewInfo> = BTreeMap::new();
+
+    for pull_request in pull_requests {
+        let pr_owners: HashSet<String> = pull_request
+            .files
+            .iter()
+            .filter_map(|file| owners.of(file))
+            .flatten()
+            .map(|owner| owner.to_string())
+            .collect();
+
+        for owner in pr_owners {
+            let info = results
+                .entry(owner.clone())
+                .or_insert_with(|| OwnerReviewInfo {
+                    owner: owner.clone(),
+                    pull_requests: 0,
+                    approved_by_codeowner: 0,
+                    approved_by_others_only: 0,
+                    merged_without_approval: 0,
+                });
+            info.pull_requests += 1;
+            if pull_request
+                .approvers
+                .iter()
+                .any(|login| approver_is_codeowner(login, &owner, memberships, identities))
+            {
+                info.approved_by_codeowner += 1;
+            } else if pull_request.approvers.is_empty() {
+                info.merged_without_approval += 1;
+            } else {
+                info.approved_by_others_only += 1;
+            }
+        }
+    }
+
+    results.into_values().collect()
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::{AuthorMembership, CommitInfoWithCodeowner};
+
+/// An owner touched by a commit, with whether the commit's author is one of its members.
+pub struct OwnerAnnotation {
+    pub owner: String,
+    pub changes: usize,
+    pub author_is_member: bool,
+}
+
+/// Owners of the files a commit changed, the owner with the most changed lines first.
+/// Changes to unowned files are not listed.
+pub fn annotate_commit(
+    commit: &CommitInfoWithCodeowner,
+    memberships: &AuthorMembership,
+) -> Vec<OwnerAnnotation> {
+    let mut changes_by_owner: HashMap<&str, usize> = HashMap::new();
+    for change in &commit.file_changes {
+        for owner in change.codeowners.iter().flatten() {
+            *changes_by_owner.entry(owner).or_insert(0) +=
+                (change.insertions + change.deletions) as usize;
+        }
+    }
+
+    let mut annotations: Vec<_> = changes_by_owner
+        .into_iter()
+        .map(|(owner, changes)| OwnerAnnotation {
+            owner: owner.to_string(),
+            changes,
+            author_is_member: owner.eq_ignore_ascii_case(&commit.author_email)
+                || memberships.is_codeowner_at(
+                    &commit.author_name,
+                    &commit.author_email,
+                    owner,
+                    Some(commit.timestamp),
+                ),
+        })
+        .collect();
+    annotations.sort_by(|a, b| b.changes.cmp(&a.changes).then(a.owner.cmp(&b.owner)));
+    annotations
+}
+
+/// Formats a commit like `git log --oneline`, followed by its owners in brackets. Owners
+/// the author is a member of are marked with `*`.
+pub fn format_annotated_commit(
+    commit: &CommitInfoWithCodeowner,
+    annotations: &[OwnerAnnotation],
+) -> String {
+    let short_id = &commit.id[..commit.id.len().min(7)];
+    let subject = commit
+        .message
+        .as_ref()
+        .map(|message| message.subject.as_str())
+        .unwrap_or_default();
+    let owners = if annotations.is_empty() {
+        "unowned".to_string()
+    } else {
+        annotations
+            .iter()
+            .map(|annotation| {
+                let marker = if annotation.author_is_member { "*" } else { "" };
+                format!("{}{}", annotation.owner, marker)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    format!("{} {} [{}]", short_id, subject, owners)
+}
@@ -0,0 +1,136 @@
+use std::io;
+
+use ring::{
+    digest::{Context, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::{AuthorCodeownerMemberships, CommitInfo, NameNormalization};
+
+/// Stable stand-ins for author names and emails, so reports can be shared without the
+/// people in them. The same person gets the same pseudonym in every report made with the
+/// same salt, and since memberships are pseudonymized alike, membership still resolves.
+#[derive(Clone, Debug)]
+pub struct Pseudonyms {
+    /// Mixed into every hash, so pseudonyms can't be reversed by hashing guessed emails.
+    salt: String,
+    /// Names that match each other get the same pseudonym.
+    names: NameNormalization,
+}
+
+impl Pseudonyms {
+    pub fn new(salt: String, names: NameNormalization) -> Self {
+        Pseudonyms { salt, names }
+    }
+
+    /// A salt nobody can guess, for when none was chosen; pseudonyms only match those made
+    /// with the same salt.
+    pub fn random_salt() -> Result<String, io::Error> {
+        let mut bytes = [0u8; 16];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| io::Error::other("Could not generate a random salt"))?;
+        Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    fn hash(&self, kind: &str, value: &str) -> String {
+        let mut context = Context::new(&SHA256);
+        for part in [self.salt.as_str(), kind, value] {
+            context.update(part.as_bytes());
+            context.update(&[0]);
+        }
+        context.finish().as_ref()[..5]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// `Author <hash>`, or an empty name left empty.
+    pub fn name(&self, name: &str) -> String {
+        let name = self.names.normalize(name);
+        if name.is_empty() {
+            return name;
+        }
+        format!("Author {}", self.hash("name", &name))
+    }
+
+    /// `<hash>@anonymized.invalid`, or an empty email left empty.
+    pub fn email(&self, email: &str) -> String {
+        let email = email.trim().to_lowercase();
+        if email.is_empty() {
+            return email;
+        }
+        format!("{}@anonymized.invalid", self.hash("email", &email))
+    }
+
+    /// Replaces the author, committer and co-authors of `commit`.
+    pub fn commit(&self, commit: &mut CommitInfo) {
+        commit.author_name = self.name(&commit.author_name);
+        commit.author_email = self.email(&commit.author_email);
+        commit.committer_name = self.name(&commit.committer_name);
+        commit.committer_email = self.email(&commit.committer_email);
+        if let Some(message) = &mut commit.message {
+            for (key, value) in &mut message.trailers {
+                if !key.eq_ignore_ascii_case("co-authored-by") {
+                    continue;
+                }
+                let Some((name, rest)) = value.split_once('<') else {
+                    continue;
+                };
+                let email = rest.split_once('>').map_or(rest, |(email, _)| email);
+                let pseudonym = format!("{} <{}>", self.name(name), self.email(email));
+                message.body = message.body.replace(value.as_str(), &pseudonym);
+                *value = pseudonym;
+            }
+        }
+    }
+
+    pub fn memberships(
+        &self,
+        memberships: Vec<AuthorCodeownerMemberships>,
+    ) -> Vec<AuthorCodeownerMemberships> {
+        memberships
+            .into_iter()
+            .map(|membership| AuthorCodeownerMemberships {
+                author_email: membership.author_email.map(|email| self.email(&email)),
+                author_name: membership.author_name.map(|name| self.name(&name)),
+                codeowner: membership.codeowner,
+                additional_emails: membership
+                    .additional_emails
+                    .iter()
+                    .map(|email| self.email(email))
+                    .collect(),
+                aliases: membership
+                    .aliases
+                    .iter()
+                    .map(|name| self.name(name))
+                    .collect(),
+                ..membership
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudonyms_depend_on_the_salt() {
+        let salted = |salt: &str| Pseudonyms::new(salt.to_string(), NameNormalization::default());
+        let email = "ada@example.com";
+        assert_eq!(
+            salted("a").email(email),
+            salted("a").email(" Ada@Example.com ")
+        );
+        assert_ne!(salted("a").email(email), salted("b").email(email));
+        assert_ne!(salted("a").name("Ada"), salted("b").name("Ada"));
+    }
+
+    #[test]
+    fn random_salts_differ() {
+        let salt = Pseudonyms::random_salt().unwrap();
+        assert_eq!(salt.len(), 32);
+        assert_ne!(salt, Pseudonyms::random_salt().unwrap());
+    }
+}
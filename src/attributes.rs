@@ -0,0 +1,166 @@
+use std::{io, path::PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::{git_tree_files, read_file_at_commit, FileChangePaths};
+
+const GITATTRIBUTES_FILE: &str = ".gitattributes";
+
+struct LinguistRule {
+    matcher: GlobMatcher,
+    generated: Option<bool>,
+    vendored: Option<bool>,
+}
+
+/// The `linguist-generated` / `linguist-vendored` markers from the `.gitattributes` files
+/// of a tree. Later rules and deeper files take precedence, as in git.
+#[derive(Default)]
+pub struct LinguistAttributes {
+    rules: Vec<LinguistRule>,
+}
+
+fn parse_attribute(attribute: &str, name: &str) -> Option<bool> {
+    if attribute == name || attribute == format!("{}=true", name) {
+        Some(true)
+    } else if attribute == format!("-{}", name)
+        || attribute == format!("!{}", name)
+        || attribute == format!("{}=false", name)
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Converts a gitattributes pattern found in `directory` into a glob over repository paths.
+fn attribute_glob(directory: &str, pattern: &str) -> String {
+    let prefix = if directory.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", directory)
+    };
+    match pattern.strip_prefix('/') {
+        Some(anchored) => format!("{}{}", prefix, anchored),
+        None if pattern.contains('/') => format!("{}{}", prefix, pattern),
+        None => format!("{}**/{}", prefix, pattern),
+    }
+}
+
+impl LinguistAttributes {
+    /// Adds the rules of a `.gitattributes` file located in `directory` (empty for the root).
+    pub fn add_file(&mut self, directory: &str, content: &str) {
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let mut generated = None;
+            let mut vendored = None;
+            for attribute in parts {
+                generated = parse_attribute(attribute, "linguist-generated").or(generated);
+                vendored = parse_attribute(attribute, "linguist-vendored").or(vendored);
+            }
+            if generated.is_none() && vendored.is_none() {
+                continue;
+            }
+            let glob = GlobBuilder::new(&attribute_glob(directory, pattern))
+                .literal_separator(true)
+                .build();
+            if let Ok(glob) = glob {
+                self.rules.push(LinguistRule {
+                    matcher: glob.compile_matcher(),
+                    generated,
+                    vendored,
+                });
+            }
+        }
+    }
+
+    pub fn is_generated(&self, path: &str) -> bool {
+        self.last_match(path, |rule| rule.generated)
+    }
+
+    pub fn is_vendored(&self, path: &str) -> bool {
+        self.last_match(path, |rule| rule.vendored)
+    }
+
+    fn last_match(&self, path: &str, value: impl Fn(&LinguistRule) -> Option<bool>) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| rule.matcher.is_match(path))
+            .find_map(value)
+            .unwrap_or(false)
+    }
+}
+
+fn is_gitattributes(path: &str) -> bool {
+    path == GITATTRIBUTES_FILE || path.ends_with("/.gitattributes")
+}
+
+pub fn get_linguist_attributes_at_commit(
+    commit_id: &str,
+    cwd: &PathBuf,
+) -> Result<LinguistAttributes, io::Error> {
+    let mut attribute_files: Vec<String> = git_tree_files(commit_id, cwd)?
+        .into_iter()
+        .filter(|path| is_gitattributes(path))
+        .collect();
+    attribute_files.sort_by_key(|path| path.matches('/').count());
+
+    let mut attributes = LinguistAttributes::default();
+    for path in attribute_files {
+        if let Some(content) = read_file_at_commit(commit_id, &path, cwd)? {
+            let directory = path
+                .strip_suffix(GITATTRIBUTES_FILE)
+                .unwrap_or("")
+                .trim_end_matches('/');
+            attributes.add_file(directory, &content);
+        }
+    }
+    Ok(attributes)
+}
+
+/// Drops file changes marked `linguist-generated` or `linguist-vendored` by the
+/// `.gitattributes` files in effect at each commit.
+pub fn skip_generated_files<T, I>(
+    commits: I,
+    cwd: PathBuf,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    let mut cached_attributes: Option<LinguistAttributes> = None;
+    commits.filter_map(move |commit_result| {
+        let mut commit = match commit_result {
+            Ok(commit) => commit,
+            Err(e) => return Some(Err(e)),
+        };
+
+        if cached_attributes.is_none() {
+            match get_linguist_attributes_at_commit(commit.commit_id(), &cwd) {
+                Ok(attributes) => cached_attributes = Some(attributes),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        // History is walked newest first, so once a commit edits .gitattributes the
+        // older commits need to re-read the previous version.
+        let edits_attributes = commit
+            .path_changes()
+            .iter()
+            .any(|change| is_gitattributes(change.path));
+
+        let attributes = cached_attributes.as_ref().unwrap();
+        commit.retain_paths(|path| !attributes.is_generated(path) && !attributes.is_vendored(path));
+
+        if edits_attributes {
+            cached_attributes = None;
+        }
+        commit.has_file_changes().then_some(Ok(commit))
+    })
+}
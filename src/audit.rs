@@ -0,0 +1,172 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use ring::digest::{Context, SHA256};
+use serde::Serialize;
+
+/// One line of the run log: who ran which command against what, and a digest of what it
+/// printed, so reported numbers can be traced back to the run that produced them.
+#[derive(Serialize)]
+pub struct RunRecord {
+    pub started_at: String,
+    pub finished_at: String,
+    /// The OS user, from `USER` or `USERNAME`.
+    pub user: Option<String>,
+    pub version: String,
+    /// Command-line arguments, with secrets such as `--token` redacted.
+    pub args: Vec<String>,
+    pub working_directory: PathBuf,
+    /// The repository analyzed, as given on the command line.
+    pub directory: Option<String>,
+    /// The commit HEAD pointed to in `directory` when the run started.
+    pub head: Option<String>,
+    pub exit_code: Option<i32>,
+    /// SHA-256 of everything the run wrote to stdout.
+    pub output_sha256: String,
+    pub output_bytes: u64,
+}
+
+/// Options whose values are left out of the run log.
+const SECRET_OPTIONS: [&str; 2] = ["--token", "--anonymize-salt"];
+
+/// Replaces the values of [`SECRET_OPTIONS`] in `args`.
+pub fn redact_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut redacted = Vec::new();
+    let mut redact_next = false;
+    for arg in args {
+        if redact_next {
+            redacted.push("<redacted>".to_string());
+            redact_next = false;
+        } else if let Some(option) = SECRET_OPTIONS
+            .iter()
+            .find(|option| arg.starts_with(&format!("{}=", option)))
+        {
+            redacted.push(format!("{}=<redacted>", option));
+        } else {
+            redact_next = SECRET_OPTIONS.contains(&arg.as_str());
+            redacted.push(arg);
+        }
+    }
+    redacted
+}
+
+pub fn os_user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+}
+
+/// The commit HEAD points to in `directory`, when it is a git repository.
+pub fn git_head(directory: &Path) -> Option<String> {
+    let output = crate::platform::command("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(directory)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Passes everything written through to `inner` while hashing it, for the digest of what
+/// a run printed.
+pub struct DigestWriter<W> {
+    inner: W,
+    context: Context,
+    bytes: u64,
+}
+
+impl<W: Write> DigestWriter<W> {
+    pub fn new(inner: W) -> Self {
+        DigestWriter {
+            inner,
+            context: Context::new(&SHA256),
+            bytes: 0,
+        }
+    }
+
+    /// The hex SHA-256 and length of what was written.
+    pub fn finish(self) -> (String, u64) {
+        let digest = self
+            .context
+            .finish()
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        (digest, self.bytes)
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.context.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Appends `record` to the JSONL run log at `path`, creating it if needed.
+pub fn append_run_record(path: &Path, record: &RunRecord) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(record).map_err(io::Error::other)?;
+    writeln!(file, "{}", line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_cover_everything_written() {
+        let mut output = Vec::new();
+        let mut writer = DigestWriter::new(&mut output);
+        write!(writer, "hello ").unwrap();
+        writeln!(writer, "world").unwrap();
+        let (digest, bytes) = writer.finish();
+        assert_eq!(output, b"hello world\n");
+        assert_eq!(bytes, 12);
+        assert_eq!(
+            digest,
+            "a948904f2f0f479b8f8197694b30184b0d2ed1c1cd2a1ec0fb85d299a192a447"
+        );
+    }
+
+    #[test]
+    fn secret_options_are_redacted() {
+        let args = [
+            "analyze-by-owner",
+            "--token",
+            "abc",
+            "--token=def",
+            "--anonymize",
+            "--anonymize-salt",
+            "pepper",
+            "--anonymize-salt=paprika",
+            "-q",
+        ];
+        assert_eq!(
+            redact_args(args.map(String::from)),
+            [
+                "analyze-by-owner",
+                "--token",
+                "<redacted>",
+                "--token=<redacted>",
+                "--anonymize",
+                "--anonymize-salt",
+                "<redacted>",
+                "--anonymize-salt=<redacted>",
+                "-q"
+            ]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::{io, thread};
+
+use tokio::sync::mpsc;
+
+/// Reads commits (or any fallible items) on a background thread into a bounded buffer and
+/// hands them out in batches.
+///
+/// At most `buffer` items are read ahead: once the buffer is full the reader stops pulling
+/// from the iterator, which in turn stops reading `git`'s output until batches are taken.
+/// Dropping the batches stops the reader.
+pub struct CommitBatches<T> {
+    receiver: mpsc::Receiver<io::Result<T>>,
+    pending_error: Option<io::Error>,
+}
+
+impl<T: Send + 'static> CommitBatches<T> {
+    pub fn new<I>(commits: I, buffer: usize) -> Self
+    where
+        I: Iterator<Item = io::Result<T>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        thread::spawn(move || {
+            for item in commits {
+                // The receiver was dropped, so nobody wants the rest
+                if sender.blocking_send(item).is_err() {
+                    break;
+                }
+            }
+        });
+
+        CommitBatches {
+            receiver,
+            pending_error: None,
+        }
+    }
+
+    /// Waits for the next item, then takes up to `n` items that are already buffered.
+    /// Returns an empty batch once the history is exhausted.
+    ///
+    /// An error ends the batch early: the items before it are returned and the error is
+    /// returned by the following call.
+    pub async fn next_batch(&mut self, n: usize) -> Result<Vec<T>, io::Error> {
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+        let mut batch = Vec::new();
+        if n == 0 {
+            return Ok(batch);
+        }
+        let Some(first) = self.receiver.recv().await else {
+            return Ok(batch);
+        };
+        batch.push(first?);
+        self.fill(&mut batch, n);
+        Ok(batch)
+    }
+
+    /// Like [`CommitBatches::next_batch`], for callers outside an async runtime.
+    pub fn blocking_next_batch(&mut self, n: usize) -> Result<Vec<T>, io::Error> {
+        if let Some(e) = self.pending_error.take() {
+            return Err(e);
+        }
+        let mut batch = Vec::new();
+        if n == 0 {
+            return Ok(batch);
+        }
+        let Some(first) = self.receiver.blocking_recv() else {
+            return Ok(batch);
+        };
+        batch.push(first?);
+        self.fill(&mut batch, n);
+        Ok(batch)
+    }
+
+    /// Tops `batch` up to `n` items from the buffer without waiting for more.
+    fn fill(&mut self, batch: &mut Vec<T>, n: usize) {
+        while batch.len() < n {
+            match self.receiver.try_recv() {
+                Ok(Ok(item)) => batch.push(item),
+                Ok(Err(e)) => {
+                    self.pending_error = Some(e);
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, io, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{get_owners_at_commit, normalize_path, owner::is_author_codeowner, AuthorMembership};
+
+#[derive(Serialize)]
+pub struct BlameAuthor {
+    pub author_name: String,
+    pub author_email: String,
+    pub lines: usize,
+    /// Whether the author is a member of one of the file's owners.
+    pub is_owner: bool,
+}
+
+/// Who last changed each line of a file, split between the teams owning it and everyone
+/// else.
+#[derive(Serialize)]
+pub struct BlameOwnership {
+    pub path: String,
+    pub owners: Vec<String>,
+    pub lines: usize,
+    /// Lines last changed by a member of one of `owners`.
+    pub owner_lines: usize,
+    /// Sorted by lines, most first.
+    pub authors: Vec<BlameAuthor>,
+}
+
+impl BlameOwnership {
+    pub fn outside_lines(&self) -> usize {
+        self.lines - self.owner_lines
+    }
+
+    /// The fraction of lines last changed by the owners; 0 for an empty file.
+    pub fn owner_share(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            self.owner_lines as f64 / self.lines as f64
+        }
+    }
+}
+
+/// The author of each line of `path` at `rev`, from `git blame --line-porcelain`.
+fn blame_line_authors(
+    path: &str,
+    rev: &str,
+    cwd: &PathBuf,
+) -> Result<Vec<(String, String)>, io::Error> {
+    let output = crate::platform::command("git")
+        .args(["blame", "--line-porcelain", rev, "--", path])
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    // Each line is its commit's headers followed by the line itself, indented by a tab.
+    // Lines aren't necessarily UTF-8, but the headers read here are.
+    let mut authors = Vec::new();
+    let mut name = String::new();
+    let mut email = String::new();
+    for line in output.stdout.split(|byte| *byte == b'\n') {
+        if line.starts_with(b"\t") {
+            authors.push((name.clone(), email.clone()));
+        } else if let Some(author) = line.strip_prefix(b"author ") {
+            name = String::from_utf8_lossy(author).to_string();
+        } else if let Some(mail) = line.strip_prefix(b"author-mail ") {
+            let mail = String::from_utf8_lossy(mail);
+            email = mail
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string();
+        }
+    }
+    Ok(authors)
+}
+
+/// Blames `path` as of `rev` and resolves each line's author against `memberships`, to
+/// tell how much of the file as it stands was written by the teams that own it under the
+/// CODEOWNERS at `rev`.
+pub fn blame_ownership(
+    path: &str,
+    rev: &str,
+    cwd: &PathBuf,
+    memberships: &AuthorMembership,
+) -> Result<BlameOwnership, io::Error> {
+    let path = normalize_path(path).into_owned();
+    let owners: Vec<String> = get_owners_at_commit(rev, cwd)?
+        .of(&path)
+        .map(|owners| owners.iter().map(|owner| owner.to_string()).collect())
+        .unwrap_or_default();
+
+    let mut lines_by_author: HashMap<(String, String), usize> = HashMap::new();
+    let line_authors = blame_line_authors(&path, rev, cwd)?;
+    let lines = line_authors.len();
+    for author in line_authors {
+        *lines_by_author.entry(author).or_default() += 1;
+    }
+    let mut authors: Vec<BlameAuthor> = lines_by_author
+        .into_iter()
+        .map(|((author_name, author_email), lines)| BlameAuthor {
+            is_owner: is_author_codeowner(memberships, &owners, &author_name, &author_email, None),
+            author_name,
+            author_email,
+            lines,
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.lines
+            .cmp(&a.lines)
+            .then_with(|| a.author_email.cmp(&b.author_email))
+    });
+    let owner_lines = authors
+        .iter()
+        .filter(|author| author.is_owner)
+        .map(|author| author.lines)
+        .sum();
+
+    Ok(BlameOwnership {
+        path,
+        owners,
+        lines,
+        owner_lines,
+        authors,
+    })
+}
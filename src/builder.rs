@@ -0,0 +1,243 @@
+use std::{io, path::PathBuf, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    alias_commit_owners, analyze_by_contributor, analyze_by_owner, analyze_by_project,
+    attribute_co_authors, filter_commit_paths, format_date, git_log_commits_with_codeowners,
+    AdjustedWeight, AnalysisOptions, AuthorCodeownerMemberships, AuthorSelection, CoauthorMode,
+    CodeownersMode, CommitAttribution, CommitDate, CommitInfoWithCodeowner, ContributorAnalysis,
+    GitLogOptions, MergeMode, MetricConfig, NameNormalization, OwnerAliases, OwnerAnalysis,
+    OwnerAttribution, PathFilter, ProgressSink, ProjectAnalysis, ProjectIndex, RenameDetection,
+};
+
+pub type CommitsWithCodeowners =
+    Box<dyn Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>> + Send>;
+
+/// An analysis of one repository's history, configured step by step and run by one of
+/// [`Analysis::by_owner`], [`Analysis::by_contributor`] or [`Analysis::by_project`].
+///
+/// This is the orchestration the command line does, for tools that embed bound: it reads
+/// the history with ownership, applies owner aliases, path filters and co-author credit,
+/// and hands the commits to the chosen analysis. History covers all time up to now unless
+/// narrowed with [`Analysis::since`] and [`Analysis::until`].
+#[derive(Clone)]
+pub struct Analysis {
+    repo: PathBuf,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    memberships: Option<Vec<AuthorCodeownerMemberships>>,
+    log_options: GitLogOptions,
+    adjusted: bool,
+    adjusted_metric: MetricConfig,
+    owner_attribution: Option<OwnerAttribution>,
+    top_contributors: usize,
+    path_filter: PathFilter,
+    owner_aliases: OwnerAliases,
+    co_authors: Option<CoauthorMode>,
+}
+
+impl Analysis {
+    pub fn new(repo: impl Into<PathBuf>) -> Self {
+        Analysis {
+            repo: repo.into(),
+            since: None,
+            until: None,
+            memberships: None,
+            log_options: GitLogOptions::default(),
+            adjusted: false,
+            adjusted_metric: MetricConfig::default(),
+            owner_attribution: None,
+            top_contributors: AnalysisOptions::default().top_contributors,
+            path_filter: PathFilter::default(),
+            owner_aliases: OwnerAliases::default(),
+            co_authors: None,
+        }
+    }
+
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Who belongs to which owner. Without memberships every change counts as being by
+    /// someone outside the owning team.
+    pub fn memberships(mut self, memberships: Vec<AuthorCodeownerMemberships>) -> Self {
+        self.memberships = Some(memberships);
+        self
+    }
+
+    /// Also compute the adjusted metrics, which weigh each commit by how its changes are
+    /// spread across owners.
+    pub fn adjusted(mut self, adjusted: bool) -> Self {
+        self.adjusted = adjusted;
+        self
+    }
+
+    /// What the adjusted metrics weigh changes by; changes when unset.
+    pub fn adjusted_weight(mut self, weight: AdjustedWeight) -> Self {
+        self.adjusted_metric = weight.into();
+        self
+    }
+
+    /// How the adjusted metrics score contributions, for weights beyond the
+    /// [`AdjustedWeight`] presets.
+    pub fn adjusted_metric(mut self, metric: MetricConfig) -> Self {
+        self.adjusted_metric = metric;
+        self
+    }
+
+    /// Which of a file's owners its changes are credited to. By default the owner analysis
+    /// credits all of them and the contributor analysis the first.
+    pub fn owner_attribution(mut self, attribution: OwnerAttribution) -> Self {
+        self.owner_attribution = Some(attribution);
+        self
+    }
+
+    /// How many top contributors to list per owner, or 0 for all of them; 10 when unset.
+    pub fn top_contributors(mut self, top: usize) -> Self {
+        self.top_contributors = top;
+        self
+    }
+
+    /// Revision or range to read; HEAD when unset.
+    pub fn rev(mut self, rev: impl Into<String>) -> Self {
+        self.log_options.rev = Some(rev.into());
+        self
+    }
+
+    pub fn merges(mut self, merges: MergeMode) -> Self {
+        self.log_options.merges = merges;
+        self
+    }
+
+    pub fn renames(mut self, renames: RenameDetection) -> Self {
+        self.log_options.renames = renames;
+        self
+    }
+
+    pub fn name_normalization(mut self, names: NameNormalization) -> Self {
+        self.log_options.name_normalization = names;
+        self
+    }
+
+    /// Replaces the options for reading history, for those without a method of their
+    /// own. Overrides earlier calls to [`Analysis::rev`], [`Analysis::merges`],
+    /// [`Analysis::renames`], [`Analysis::name_normalization`], [`Analysis::progress`],
+    /// [`Analysis::authors`], [`Analysis::attribute_to`] and [`Analysis::date`].
+    pub fn log_options(mut self, options: GitLogOptions) -> Self {
+        self.log_options = options;
+        self
+    }
+
+    /// Reports each commit read from the history to `progress`.
+    pub fn progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.log_options.progress = Some(progress);
+        self
+    }
+
+    /// Only read commits by the authors `authors` selects.
+    pub fn authors(mut self, authors: AuthorSelection) -> Self {
+        self.log_options.authors = authors;
+        self
+    }
+
+    /// Credit commits to their committers rather than their authors.
+    pub fn attribute_to(mut self, attribution: CommitAttribution) -> Self {
+        self.log_options.attribute_to = attribution;
+        self
+    }
+
+    /// Own every commit by a single CODEOWNERS file rather than the one of its time.
+    pub fn codeowners_mode(mut self, mode: CodeownersMode) -> Self {
+        self.log_options.codeowners_mode = mode;
+        self
+    }
+
+    /// Select commits in the range by `date` rather than by committer date.
+    pub fn date(mut self, date: CommitDate) -> Self {
+        self.log_options.date = date;
+        self
+    }
+
+    /// Only count changes to paths that pass `filter`.
+    pub fn path_filter(mut self, filter: PathFilter) -> Self {
+        self.path_filter = filter;
+        self
+    }
+
+    pub fn owner_aliases(mut self, aliases: OwnerAliases) -> Self {
+        self.owner_aliases = aliases;
+        self
+    }
+
+    /// Credit `Co-authored-by` trailers as described by `mode`.
+    pub fn co_authors(mut self, mode: CoauthorMode) -> Self {
+        self.co_authors = Some(mode);
+        self
+    }
+
+    /// The commits the analyses are computed from, with every configured adjustment
+    /// applied.
+    pub fn commits(&self) -> Result<CommitsWithCodeowners, io::Error> {
+        let since = format_date(self.since.unwrap_or(DateTime::UNIX_EPOCH));
+        let until = format_date(self.until.unwrap_or_else(Utc::now));
+        let options = GitLogOptions {
+            include_message: self.log_options.include_message || self.co_authors.is_some(),
+            ..self.log_options.clone()
+        };
+        let memberships = self.memberships.clone().unwrap_or_default();
+
+        let mut commits: CommitsWithCodeowners = Box::new(git_log_commits_with_codeowners(
+            &since,
+            &until,
+            &self.repo,
+            &options,
+            self.memberships.clone(),
+        )?);
+        if let Some(mode) = self.co_authors {
+            commits = Box::new(attribute_co_authors(
+                commits,
+                mode,
+                &memberships,
+                &options.name_normalization,
+            ));
+        }
+        if !self.owner_aliases.is_empty() {
+            commits = Box::new(alias_commit_owners(commits, self.owner_aliases.clone()));
+        }
+        if !self.path_filter.is_empty() {
+            commits = Box::new(filter_commit_paths(commits, self.path_filter.clone()));
+        }
+        Ok(commits)
+    }
+
+    fn analysis_options(&self) -> AnalysisOptions {
+        AnalysisOptions {
+            adjusted: self.adjusted.then_some(self.adjusted_metric),
+            owner_attribution: self.owner_attribution,
+            top_contributors: self.top_contributors,
+        }
+    }
+
+    pub fn by_owner(&self) -> Result<OwnerAnalysis, io::Error> {
+        analyze_by_owner(self.commits()?, &self.analysis_options())
+    }
+
+    pub fn by_contributor(&self) -> Result<ContributorAnalysis, io::Error> {
+        analyze_by_contributor(self.commits()?, &self.analysis_options())
+    }
+
+    /// Groups changes by the projects found in the tree at the analyzed revision.
+    pub fn by_project(&self) -> Result<ProjectAnalysis, io::Error> {
+        let rev = self.log_options.rev.as_deref().unwrap_or("HEAD");
+        let tip = rev.rsplit_once("..").map_or(rev, |(_, tip)| tip);
+        let index = ProjectIndex::detect(tip, &self.repo)?;
+        analyze_by_project(self.commits()?, index)
+    }
+}
@@ -0,0 +1,105 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::{ChangeKind, CommitInfoWithCodeowner};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckMode {
+    Warn,
+    Block,
+}
+
+impl FromStr for CheckMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warn" => Ok(CheckMode::Warn),
+            "block" => Ok(CheckMode::Block),
+            _ => Err(format!("Invalid mode '{}', expected 'warn' or 'block'", s)),
+        }
+    }
+}
+
+impl fmt::Display for CheckMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckMode::Warn => write!(f, "warn"),
+            CheckMode::Block => write!(f, "block"),
+        }
+    }
+}
+
+/// How `bound check` and `bound gate` report their findings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CheckFormat {
+    #[default]
+    Text,
+    /// GitHub Actions workflow commands, shown as annotations on the pull request.
+    GithubAnnotations,
+}
+
+impl FromStr for CheckFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(CheckFormat::Text),
+            "github-annotations" => Ok(CheckFormat::GithubAnnotations),
+            _ => Err(format!(
+                "Invalid format '{}', expected 'text' or 'github-annotations'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CheckFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckFormat::Text => write!(f, "text"),
+            CheckFormat::GithubAnnotations => write!(f, "github-annotations"),
+        }
+    }
+}
+
+/// A change to an owned file by an author who is not a member of any of its owners.
+pub struct OwnershipViolation {
+    pub commit_id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub path: String,
+    /// Whether the file was added, changed or deleted, as deleting an owned file is often
+    /// the more serious change.
+    pub kind: ChangeKind,
+    pub codeowners: Vec<String>,
+}
+
+/// Finds changes to owned files made by non-members. Changes whose membership is unknown
+/// (no memberships were loaded) are not reported.
+pub fn find_ownership_violations(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+) -> Result<Vec<OwnershipViolation>, io::Error> {
+    let mut violations = Vec::new();
+
+    for commit_result in commits {
+        let commit = commit_result?;
+        for change in commit.file_changes {
+            let Some(codeowners) = change.codeowners else {
+                continue;
+            };
+            if codeowners.is_empty() || change.author_is_codeowner != Some(false) {
+                continue;
+            }
+            violations.push(OwnershipViolation {
+                commit_id: commit.id.clone(),
+                author_name: commit.author_name.clone(),
+                author_email: commit.author_email.clone(),
+                path: change.path,
+                kind: change.kind,
+                codeowners,
+            });
+        }
+    }
+
+    Ok(violations)
+}
@@ -0,0 +1,122 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::{
+    owner::is_author_codeowner, AuthorCodeownerMemberships, AuthorMembership,
+    CommitInfoWithCodeowner, FileChangeWithCodeowner, NameNormalization,
+};
+
+/// How a commit with `Co-authored-by` trailers is credited to its authors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoauthorMode {
+    /// Divide each file's changed lines evenly between the authors.
+    Split,
+    /// Credit every author with all of the commit's changes.
+    Duplicate,
+}
+
+impl FromStr for CoauthorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "split" => Ok(CoauthorMode::Split),
+            "duplicate" => Ok(CoauthorMode::Duplicate),
+            _ => Err(format!(
+                "Invalid co-author mode '{}', expected 'split' or 'duplicate'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CoauthorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoauthorMode::Split => write!(f, "split"),
+            CoauthorMode::Duplicate => write!(f, "duplicate"),
+        }
+    }
+}
+
+/// Replaces each commit with co-authors by one commit per author, so analyzers credit
+/// everyone who worked on it rather than only whoever committed it. Each copy counts as a
+/// commit of its author. Membership of the co-authors is looked up in `memberships`, with
+/// names compared as `names` normalizes them.
+///
+/// Commits must have been read with [`crate::GitLogOptions::include_message`] for their
+/// trailers to be known.
+pub fn attribute_co_authors<I>(
+    commits: I,
+    mode: CoauthorMode,
+    memberships: &[AuthorCodeownerMemberships],
+    names: &NameNormalization,
+) -> impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>
+where
+    I: Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+{
+    let membership_index = AuthorMembership::with_name_normalization(memberships, names);
+    commits.flat_map(move |commit| match commit {
+        Ok(commit) => split_commit(commit, mode, &membership_index)
+            .into_iter()
+            .map(Ok)
+            .collect(),
+        Err(e) => vec![Err(e)],
+    })
+}
+
+fn split_commit(
+    commit: CommitInfoWithCodeowner,
+    mode: CoauthorMode,
+    memberships: &AuthorMembership,
+) -> Vec<CommitInfoWithCodeowner> {
+    let contributors = commit.contributors();
+    if contributors.len() == 1 {
+        return vec![commit];
+    }
+
+    let count = contributors.len() as i32;
+    contributors
+        .iter()
+        .enumerate()
+        .map(|(index, (author_name, author_email))| {
+            // The first author keeps the remainder so no lines are lost
+            let share = |lines: i32| match mode {
+                CoauthorMode::Duplicate => lines,
+                CoauthorMode::Split if index == 0 => lines / count + lines % count,
+                CoauthorMode::Split => lines / count,
+            };
+            CommitInfoWithCodeowner {
+                id: commit.id.clone(),
+                author_name: author_name.clone(),
+                author_email: author_email.clone(),
+                timestamp: commit.timestamp,
+                message: commit.message.clone(),
+                file_changes: commit
+                    .file_changes
+                    .iter()
+                    .map(|change| FileChangeWithCodeowner {
+                        insertions: share(change.insertions),
+                        deletions: share(change.deletions),
+                        path: change.path.clone(),
+                        old_path: change.old_path.clone(),
+                        kind: change.kind,
+                        language: change.language.clone(),
+                        binary: change.binary,
+                        size_before: change.size_before,
+                        size_after: change.size_after,
+                        codeowners: change.codeowners.clone(),
+                        author_is_codeowner: change.author_is_codeowner.map(|_| {
+                            is_author_codeowner(
+                                memberships,
+                                change.codeowners.as_deref().unwrap_or_default(),
+                                author_name,
+                                author_email,
+                                Some(commit.timestamp),
+                            )
+                        }),
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::{
+    format_date, get_codeowners_at_commit, git_tree_files, isolate::git_output,
+    parse_codeowners_rules, preview::owners_of, resolve_date, DateBound, OwnerAliases,
+};
+
+/// A CODEOWNERS rule whose owners differ between two versions. Owners are empty on the
+/// side where the rule doesn't exist, so added and removed rules are changes too.
+#[derive(Serialize)]
+pub struct CodeownersRuleChange {
+    pub pattern: String,
+    pub owners_before: Vec<String>,
+    pub owners_after: Vec<String>,
+}
+
+/// A file whose owners differ between two versions of CODEOWNERS.
+#[derive(Serialize)]
+pub struct FileOwnerChange {
+    pub path: String,
+    pub owners_before: Vec<String>,
+    pub owners_after: Vec<String>,
+}
+
+pub struct CodeownersDiff {
+    pub added_rules: Vec<CodeownersRuleChange>,
+    pub removed_rules: Vec<CodeownersRuleChange>,
+    /// Rules kept with a different set of owners.
+    pub changed_rules: Vec<CodeownersRuleChange>,
+    /// Files compared, which are those that exist in the later version.
+    pub files: usize,
+    /// Sorted by path.
+    pub reassigned_files: Vec<FileOwnerChange>,
+}
+
+/// The owners of each pattern in CODEOWNERS content. A pattern listed more than once takes
+/// its last owners, as the last matching rule wins.
+fn codeowners_rules(content: &str) -> BTreeMap<String, Vec<String>> {
+    parse_codeowners_rules(content)
+        .into_iter()
+        .map(|rule| (rule.pattern, rule.owners))
+        .collect()
+}
+
+/// Compares the rules of the `before` and `after` CODEOWNERS contents and the owners they
+/// assign to each of `files`, with owners resolved through `aliases`.
+pub fn diff_codeowners(
+    before: &str,
+    after: &str,
+    files: &[String],
+    aliases: &OwnerAliases,
+) -> CodeownersDiff {
+    let rules_before = codeowners_rules(before);
+    let mut rules_after = codeowners_rules(after);
+    let mut diff = CodeownersDiff {
+        added_rules: Vec::new(),
+        removed_rules: Vec::new(),
+        changed_rules: Vec::new(),
+        files: files.len(),
+        reassigned_files: Vec::new(),
+    };
+
+    for (pattern, owners_before) in rules_before {
+        match rules_after.remove(&pattern) {
+            None => diff.removed_rules.push(CodeownersRuleChange {
+                pattern,
+                owners_before,
+                owners_after: Vec::new(),
+            }),
+            Some(owners_after) if owners_after != owners_before => {
+                diff.changed_rules.push(CodeownersRuleChange {
+                    pattern,
+                    owners_before,
+                    owners_after,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added_rules = rules_after
+        .into_iter()
+        .map(|(pattern, owners_after)| CodeownersRuleChange {
+            pattern,
+            owners_before: Vec::new(),
+            owners_after,
+        })
+        .collect();
+
+    let before = codeowners::from_reader(Cursor::new(before.to_string()));
+    let after = codeowners::from_reader(Cursor::new(after.to_string()));
+    let mut files: Vec<&String> = files.iter().collect();
+    files.sort();
+    for path in files {
+        let owners_before = owners_of(&before, path, aliases);
+        let owners_after = owners_of(&after, path, aliases);
+        if owners_before != owners_after {
+            diff.reassigned_files.push(FileOwnerChange {
+                path: path.clone(),
+                owners_before: owners_before.into_iter().collect(),
+                owners_after: owners_after.into_iter().collect(),
+            });
+        }
+    }
+    diff
+}
+
+/// Resolves `spec` to a commit: a revision when git knows it as one, otherwise a date (as
+/// accepted by [`crate::resolve_date`]) taken as the last commit on `tip` at that time.
+pub fn resolve_revision_or_date(spec: &str, tip: &str, cwd: &Path) -> Result<String, io::Error> {
+    if let Ok(commit) = git_output(
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", spec),
+        ],
+        cwd,
+    ) {
+        return Ok(commit);
+    }
+    let date = resolve_date(spec, DateBound::Until, Utc::now()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' is neither a revision nor a date", spec),
+        )
+    })?;
+    let commit = git_output(
+        &[
+            "rev-list",
+            "-1",
+            &format!("--before={}", format_date(date)),
+            tip,
+        ],
+        cwd,
+    )?;
+    if commit.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No commit before {}", spec),
+        ));
+    }
+    Ok(commit)
+}
+
+/// Diffs CODEOWNERS between the commits `from` and `to`, comparing the owners of the files
+/// in the tree at `to`.
+pub fn diff_codeowners_between(
+    from: &str,
+    to: &str,
+    cwd: &PathBuf,
+    aliases: &OwnerAliases,
+) -> Result<CodeownersDiff, io::Error> {
+    let before = get_codeowners_at_commit(from, cwd)?.unwrap_or_default();
+    let after = get_codeowners_at_commit(to, cwd)?.unwrap_or_default();
+    let files = git_tree_files(to, cwd)?;
+    Ok(diff_codeowners(&before, &after, &files, aliases))
+}
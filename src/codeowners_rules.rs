@@ -0,0 +1,168 @@
+use globset::Glob;
+
+/// One rule of a CODEOWNERS file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeownersRule {
+    /// 1-based line number in the file.
+    pub line: usize,
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
+
+/// The rules of CODEOWNERS content in file order, skipping blank lines and comments.
+pub fn parse_codeowners_rules(content: &str) -> Vec<CodeownersRule> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next().filter(|pattern| !pattern.starts_with('#'))?;
+            Some(CodeownersRule {
+                line: index + 1,
+                pattern: pattern.to_string(),
+                owners: parts
+                    .take_while(|owner| !owner.starts_with('#'))
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// A rule that matches no file a later rule doesn't match too, so its owners never apply.
+pub struct ShadowedRule {
+    pub rule: CodeownersRule,
+    /// The first later rule matching every path `rule` matches.
+    pub shadowed_by: CodeownersRule,
+}
+
+/// What a pattern matches, reduced to path segments.
+struct PatternShape {
+    /// Matches from the repository root rather than at any depth.
+    anchored: bool,
+    segments: Vec<String>,
+    /// Only matches directories (and so everything under them), from a trailing `/` or
+    /// `/**`.
+    directory: bool,
+}
+
+fn pattern_shape(pattern: &str) -> Option<PatternShape> {
+    let (unanchored_prefix, pattern) = match pattern.strip_prefix("**/") {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let (directory, pattern) = if let Some(rest) = pattern.strip_suffix("/**") {
+        (true, rest)
+    } else if let Some(rest) = pattern.strip_suffix('/') {
+        (true, rest)
+    } else {
+        (false, pattern)
+    };
+    let (leading_slash, pattern) = match pattern.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let segments: Vec<String> = pattern.split('/').map(str::to_string).collect();
+    // A `**` inside the pattern spans any number of segments, which this can't reason about
+    if segments
+        .iter()
+        .any(|segment| segment.is_empty() || segment == "**")
+    {
+        return None;
+    }
+    Some(PatternShape {
+        anchored: !unanchored_prefix && (leading_slash || segments.len() > 1),
+        segments,
+        directory,
+    })
+}
+
+fn has_glob(segment: &str) -> bool {
+    segment.contains(['*', '?', '['])
+}
+
+/// Whether every name matching the segment `narrow` also matches `broad`.
+fn segment_within(narrow: &str, broad: &str) -> bool {
+    if narrow == broad || broad == "*" {
+        return true;
+    }
+    if !has_glob(narrow) {
+        return Glob::new(broad).is_ok_and(|glob| glob.compile_matcher().is_match(narrow));
+    }
+    // `*.min.js` is within `*.js`
+    match (narrow.strip_prefix('*'), broad.strip_prefix('*')) {
+        (Some(narrow_suffix), Some(broad_suffix)) => {
+            !has_glob(narrow_suffix)
+                && !has_glob(broad_suffix)
+                && narrow_suffix.ends_with(broad_suffix)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `broad` matches every path `narrow` matches. Only answers yes when that is
+/// certain; patterns it can't reason about are never considered to cover others.
+pub fn pattern_covers(broad: &str, narrow: &str) -> bool {
+    if matches!(broad, "*" | "**" | "/**") || broad == narrow {
+        return true;
+    }
+    let (Some(broad), Some(narrow)) = (pattern_shape(broad), pattern_shape(narrow)) else {
+        return false;
+    };
+
+    if !broad.anchored {
+        // A single unanchored segment matches any file or directory of that name anywhere
+        let [name] = broad.segments.as_slice() else {
+            return false;
+        };
+        let last = narrow.segments.len() - 1;
+        return narrow.segments.iter().enumerate().any(|(index, segment)| {
+            // The last segment is only known to be a directory if the pattern says so
+            let is_directory = index < last || narrow.directory;
+            (is_directory || !broad.directory) && segment_within(segment, name)
+        });
+    }
+
+    if !narrow.anchored || narrow.segments.len() < broad.segments.len() {
+        return false;
+    }
+    let depth = broad.segments.len();
+    if !broad
+        .segments
+        .iter()
+        .zip(&narrow.segments)
+        .all(|(broad, narrow)| segment_within(narrow, broad))
+    {
+        return false;
+    }
+    let last = &broad.segments[depth - 1];
+    if broad.directory {
+        // Only what's under the directory, which the narrow pattern must be inside of
+        narrow.segments.len() > depth || narrow.directory
+    } else if has_glob(last) {
+        // A wildcard at the end only matches files directly in the directory
+        narrow.segments.len() == depth && !narrow.directory
+    } else {
+        // A plain name matches a file or a directory with everything under it
+        true
+    }
+}
+
+/// Rules that a later rule always overrides, in file order. The last matching rule wins in
+/// CODEOWNERS, so such rules can be removed without changing anything.
+pub fn find_shadowed_rules(content: &str) -> Vec<ShadowedRule> {
+    let rules = parse_codeowners_rules(content);
+    rules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| {
+            let shadowed_by = rules[index + 1..]
+                .iter()
+                .find(|later| pattern_covers(&later.pattern, &rule.pattern))?;
+            Some(ShadowedRule {
+                rule: rule.clone(),
+                shadowed_by: shadowed_by.clone(),
+            })
+        })
+        .collect()
+}
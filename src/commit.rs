@@ -1,9 +1,26 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::fmt;
 use std::io;
 use std::io::{BufRead, BufReader, Read};
 use std::iter::Peekable;
 use std::path::PathBuf;
-use std::process::{ChildStdout, Command, Stdio};
+use std::process::{Child, ChildStdout, ExitStatus, Stdio};
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    normalize_path, resolve_date,
+    sizes::{fill_file_sizes, BlobReader},
+    AuthorSelection, CodeownersMode, CodeownersSource, DateBound, FileSize, NameNormalization,
+    OwnerAliases, ProgressSink, Pseudonyms,
+};
 
 pub struct LineReader<R> {
     reader: BufReader<R>,
@@ -25,7 +42,7 @@ impl<R: Read> Iterator for LineReader<R> {
         match self.reader.read_line(&mut line) {
             Ok(0) => None,
             Ok(_) => {
-                // println!("Debug: {}", line);
+                tracing::trace!(line = line.trim_end(), "git output");
                 Some(Ok(line.trim_end().to_string()))
             }
             Err(e) => Some(Err(e)),
@@ -35,11 +52,51 @@ impl<R: Read> Iterator for LineReader<R> {
 
 pub struct CommitIterator<R: Read> {
     lines: Peekable<LineReader<R>>,
+    with_message: bool,
 }
 
 impl<R: Read> CommitIterator<R> {
-    fn new(lines: Peekable<LineReader<R>>) -> Self {
-        CommitIterator { lines }
+    fn new(lines: Peekable<LineReader<R>>, with_message: bool) -> Self {
+        CommitIterator {
+            lines,
+            with_message,
+        }
+    }
+
+    /// Reads the subject, trailers and body lines of LOG_MESSAGE_FORMAT, up to the body
+    /// terminator.
+    fn parse_message(&mut self) -> Option<CommitMessage> {
+        let subject = self.lines.next()?.ok()?;
+        let trailers = self.lines.next()?.ok()?;
+        let mut body = Vec::new();
+        loop {
+            let line = self.lines.next()?.ok()?;
+            if let Some(last) = line.strip_suffix(MESSAGE_TERMINATOR) {
+                body.push(last.to_string());
+                break;
+            }
+            body.push(line);
+        }
+        Some(CommitMessage {
+            subject,
+            body: body.join("\n").trim().to_string(),
+            trailers: trailers
+                .split(TRAILER_SEPARATOR)
+                .filter_map(|trailer| trailer.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect(),
+        })
+    }
+
+    /// Discards lines up to the next commit header so a malformed entry only produces a
+    /// single error instead of one per remaining line.
+    fn skip_to_next_commit(&mut self) {
+        while let Some(line) = self.lines.peek() {
+            if matches!(line, Ok(line) if line == "COMMIT") {
+                break;
+            }
+            self.lines.next();
+        }
     }
 }
 
@@ -49,53 +106,87 @@ impl<R: Read> Iterator for CommitIterator<R> {
     fn next(&mut self) -> Option<Self::Item> {
         let mut commit_info = CommitInfo {
             id: String::new(),
-            timestamp: 0,
+            author_date: DateTime::default(),
+            committer_date: DateTime::default(),
             author_name: String::new(),
             author_email: String::new(),
+            committer_name: String::new(),
+            committer_email: String::new(),
+            parents: Vec::new(),
+            message: None,
             file_changes: Vec::new(),
         };
 
         // Parse commit header and check for EOF
-        if let Some(Ok(line)) = self.lines.next() {
-            if line != "COMMIT" {
+        match self.lines.next()? {
+            Ok(line) if line == "COMMIT" => {}
+            Ok(line) => {
+                self.skip_to_next_commit();
                 return Some(Err(io::Error::new(
                     io::ErrorKind::InvalidData,
-                    "Expected COMMIT",
+                    format!("Expected COMMIT, found '{}'", line),
                 )));
             }
-        } else {
-            return None;
-        }
-
-        // Parse commit details
-        for _ in 0..4 {
-            if let Some(Ok(line)) = self.lines.next() {
-                match commit_info.id.is_empty() {
-                    true => commit_info.id = line,
-                    false => match commit_info.timestamp {
-                        0 => {
-                            commit_info.timestamp = match line.parse() {
-                                Ok(timestamp) => timestamp,
-                                Err(e) => {
-                                    return Some(Err(io::Error::new(io::ErrorKind::InvalidData, e)))
-                                }
-                            };
-                        }
-                        _ => match commit_info.author_name.is_empty() {
-                            true => commit_info.author_name = line,
-                            false => commit_info.author_email = line,
-                        },
-                    },
+            Err(e) => {
+                self.skip_to_next_commit();
+                return Some(Err(e));
+            }
+        }
+
+        // Parse commit details, one line per placeholder of LOG_HEADER_FORMAT
+        let mut header = Vec::with_capacity(LOG_HEADER_LINES);
+        for _ in 0..LOG_HEADER_LINES {
+            match self.lines.next() {
+                Some(Ok(line)) => header.push(line),
+                _ => {
+                    self.skip_to_next_commit();
+                    return Some(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Incomplete commit info",
+                    )));
+                }
+            }
+        }
+        let mut header = header.into_iter();
+        commit_info.id = header.next().unwrap_or_default();
+        let dates = (
+            parse_unix_date(&header.next().unwrap_or_default()),
+            parse_unix_date(&header.next().unwrap_or_default()),
+        );
+        (commit_info.author_date, commit_info.committer_date) = match dates {
+            (Ok(author_date), Ok(committer_date)) => (author_date, committer_date),
+            (Err(e), _) | (_, Err(e)) => {
+                self.skip_to_next_commit();
+                return Some(Err(e));
+            }
+        };
+        commit_info.parents = header
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|parent| parent.to_string())
+            .collect();
+        commit_info.author_name = header.next().unwrap_or_default();
+        commit_info.author_email = header.next().unwrap_or_default();
+        commit_info.committer_name = header.next().unwrap_or_default();
+        commit_info.committer_email = header.next().unwrap_or_default();
+
+        if self.with_message {
+            match self.parse_message() {
+                Some(message) => commit_info.message = Some(message),
+                None => {
+                    self.skip_to_next_commit();
+                    return Some(Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("Incomplete commit message in {}", commit_info.id),
+                    )));
                 }
-            } else {
-                return Some(Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "Incomplete commit info",
-                )));
             }
         }
 
-        // Parse file changes
+        // Parse file changes: the `--raw` status of each file, then its `--numstat` counts
+        // in the same order
+        let mut kinds = Vec::new();
         while let Some(Ok(line)) = self.lines.peek() {
             if line == "COMMIT" {
                 break;
@@ -105,38 +196,214 @@ impl<R: Read> Iterator for CommitIterator<R> {
                 self.lines.next();
                 continue;
             }
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() == 3 {
-                commit_info.file_changes.push(FileChange {
-                    insertions: parts[0].parse().unwrap_or(0),
-                    deletions: parts[1].parse().unwrap_or(0),
-                    path: parts[2].to_string(),
-                });
+            if let Some(kind) = parse_raw_status(line) {
+                kinds.push(kind);
+            } else if let Some(mut change) = parse_numstat(line) {
+                if let Some(kind) = kinds.get(commit_info.file_changes.len()) {
+                    change.kind = *kind;
+                }
+                commit_info.file_changes.push(change);
             } else {
-                return Some(Err(io::Error::new(
+                let error = io::Error::new(
                     io::ErrorKind::InvalidData,
-                    format!("Invalid file change format: '{}'", line),
-                )));
+                    format!(
+                        "Invalid file change format in {}: '{}'",
+                        commit_info.id, line
+                    ),
+                );
+                self.skip_to_next_commit();
+                return Some(Err(error));
             }
             self.lines.next(); // Consume the peeked line
         }
+        // A read error part way through the file changes would otherwise leave them short
+        if let Some(Err(_)) = self.lines.peek() {
+            return self.lines.next().map(|line| line.and(Ok(commit_info)));
+        }
 
         Some(Ok(commit_info))
     }
 }
 
+fn parse_unix_date(seconds: &str) -> Result<DateTime<Utc>, io::Error> {
+    seconds
+        .parse()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid commit date '{}'", seconds),
+            )
+        })
+}
+
+#[derive(Serialize)]
 pub struct CommitInfo {
     pub id: String,
-    pub timestamp: i64,
+    /// When the change was originally made.
+    pub author_date: DateTime<Utc>,
+    /// When the commit was last written, which rebases, amends and cherry-picks update.
+    pub committer_date: DateTime<Utc>,
     pub author_name: String,
     pub author_email: String,
+    /// Who wrote the commit, such as whoever applied a patch or rebased a branch.
+    pub committer_name: String,
+    pub committer_email: String,
+    pub parents: Vec<String>,
+    /// Only read when [`GitLogOptions::include_message`] is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<CommitMessage>,
     pub file_changes: Vec<FileChange>,
 }
 
+impl CommitInfo {
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+
+    pub fn date(&self, date: CommitDate) -> DateTime<Utc> {
+        match date {
+            CommitDate::Author => self.author_date,
+            CommitDate::Committer => self.committer_date,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct CommitMessage {
+    pub subject: String,
+    /// Everything after the subject, trailers included.
+    pub body: String,
+    /// `(key, value)` pairs such as `("Co-authored-by", "Name <email>")`, in order.
+    pub trailers: Vec<(String, String)>,
+}
+
+impl CommitMessage {
+    /// `(name, email)` of each `Co-authored-by:` trailer.
+    pub fn co_authors(&self) -> Vec<(String, String)> {
+        self.trailers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("co-authored-by"))
+            .filter_map(|(_, value)| {
+                let (name, rest) = value.split_once('<')?;
+                let email = rest.split_once('>').map_or(rest, |(email, _)| email);
+                Some((name.trim().to_string(), email.trim().to_string()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
 pub struct FileChange {
     pub insertions: i32,
     pub deletions: i32,
+    /// The path after the change; ownership is resolved against it.
     pub path: String,
+    /// The path before the change, for renames and copies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub kind: ChangeKind,
+    /// Only set once the commits are classified by [`crate::classify_languages`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Git reports no line counts for binary files, so they count as 0 lines.
+    pub binary: bool,
+    /// Only read when [`GitLogOptions::include_file_sizes`] is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_before: Option<FileSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_after: Option<FileSize>,
+}
+
+/// What a change did to its file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    /// Changed in place, including changes to only the file mode or type.
+    #[default]
+    Modified,
+    Deleted,
+    Renamed,
+    Copied,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Modified => write!(f, "modified"),
+            ChangeKind::Deleted => write!(f, "deleted"),
+            ChangeKind::Renamed => write!(f, "renamed"),
+            ChangeKind::Copied => write!(f, "copied"),
+        }
+    }
+}
+
+/// Parses the status letter of a `--raw` line, `:mode mode blob blob status\tpath...`,
+/// where renames and copies carry a similarity score such as `R086`.
+fn parse_raw_status(line: &str) -> Option<ChangeKind> {
+    let (fields, _) = line.strip_prefix(':')?.split_once('\t')?;
+    let status = fields.split_whitespace().nth(4)?;
+    Some(match status.chars().next()? {
+        'A' => ChangeKind::Added,
+        'D' => ChangeKind::Deleted,
+        'R' => ChangeKind::Renamed,
+        'C' => ChangeKind::Copied,
+        _ => ChangeKind::Modified,
+    })
+}
+
+/// Parses a `--numstat` line, `insertions\tdeletions\tpath`. Binary files have `-` for
+/// both counts.
+fn parse_numstat(line: &str) -> Option<FileChange> {
+    let parts: Vec<&str> = line.split('\t').collect();
+    let [insertions, deletions, path] = parts.as_slice() else {
+        return None;
+    };
+    let path = normalize_path(path);
+    let (old_path, path) = match parse_rename(&path) {
+        Some((old_path, new_path)) => (Some(old_path), new_path),
+        None => (None, path.into_owned()),
+    };
+    Some(FileChange {
+        insertions: insertions.parse().unwrap_or(0),
+        deletions: deletions.parse().unwrap_or(0),
+        path,
+        // Refined from the `--raw` status when there is one
+        kind: if old_path.is_some() {
+            ChangeKind::Renamed
+        } else {
+            ChangeKind::Modified
+        },
+        old_path,
+        language: None,
+        binary: *insertions == "-",
+        size_before: None,
+        size_after: None,
+    })
+}
+
+/// Splits git's rename notation, `old => new` or `src/{old => new}.rs` with the common
+/// parts outside the braces, into `(old path, new path)`.
+fn parse_rename(path: &str) -> Option<(String, String)> {
+    let (old, new) = path.split_once(" => ")?;
+    let (Some(open), Some(close)) = (old.rfind('{'), new.find('}')) else {
+        return Some((old.to_string(), new.to_string()));
+    };
+    let (prefix, old) = (&old[..open], &old[open + 1..]);
+    let (new, suffix) = (&new[..close], &new[close + 1..]);
+    // An empty side such as `src/{ => lib}/a.rs` or `{lib => }/a.rs` leaves a separator
+    // too many behind
+    let join = |middle: &str| {
+        let suffix = match suffix.strip_prefix('/') {
+            Some(rest) if middle.is_empty() && (prefix.is_empty() || prefix.ends_with('/')) => rest,
+            _ => suffix,
+        };
+        format!("{}{}{}", prefix, middle, suffix)
+    };
+    Some((join(old), join(new)))
 }
 
 fn execute_git<I, S>(args: I, cwd: &PathBuf) -> Result<LineReader<ChildStdout>, io::Error>
@@ -144,38 +411,665 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let output = Command::new("git")
+    let output = crate::platform::command("git")
         .args(args)
         .current_dir(cwd)
         .stdout(Stdio::piped())
         .spawn()?
         .stdout
-        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Could not capture stdout"))?;
+        .ok_or_else(|| io::Error::other("Could not capture stdout"))?;
 
     Ok(LineReader::new(output))
 }
 
-fn parse_commit<R: Read>(lines: LineReader<R>) -> CommitIterator<R> {
-    CommitIterator::new(lines.peekable())
+fn parse_commit<R: Read>(lines: LineReader<R>, with_message: bool) -> CommitIterator<R> {
+    CommitIterator::new(lines.peekable(), with_message)
+}
+
+/// A running `git log` whose exit status is checked once its output ends, so a git that
+/// died part way through is an error rather than a silently shortened history.
+struct GitLogStream {
+    child: Child,
+    commits: CommitIterator<ChildStdout>,
+    /// Collects git's stderr on a thread, so a chatty git can't block on a full pipe.
+    stderr: Option<thread::JoinHandle<String>>,
+    finished: bool,
+}
+
+impl GitLogStream {
+    fn spawn(
+        program: &str,
+        args: &[String],
+        cwd: &PathBuf,
+        with_message: bool,
+    ) -> Result<Self, io::Error> {
+        tracing::debug!(?args, cwd = %cwd.display(), "Running git");
+        let mut child = crate::platform::command(program)
+            .args(args)
+            .current_dir(cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("Could not capture stdout"))?;
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut output = String::new();
+                let _ = stderr.read_to_string(&mut output);
+                output
+            })
+        });
+        Ok(GitLogStream {
+            child,
+            commits: parse_commit(LineReader::new(stdout), with_message),
+            stderr,
+            finished: false,
+        })
+    }
+
+    fn stderr(&mut self) -> String {
+        self.stderr
+            .take()
+            .and_then(|stderr| stderr.join().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// What git prints when reading the repository failed underneath it, as on a flaky network
+/// filesystem, rather than because of what it was asked to read.
+const IO_FAILURES: &[&str] = &[
+    "Input/output error",
+    "unable to read",
+    "short read",
+    "Stale file handle",
+    "Resource temporarily unavailable",
+];
+
+/// The error for a git that exited with `status`: interrupted when it was killed or failed
+/// to read the repository, so it is retried, otherwise the `fatal:` message it printed.
+fn exit_error(status: ExitStatus, stderr: &str) -> io::Error {
+    if status.code().is_none() {
+        return io::Error::new(
+            io::ErrorKind::Interrupted,
+            format!("git log was killed ({})", status),
+        );
+    }
+    let kind = if IO_FAILURES.iter().any(|failure| stderr.contains(failure)) {
+        io::ErrorKind::Interrupted
+    } else {
+        io::ErrorKind::Other
+    };
+    match stderr.lines().find(|line| line.starts_with("fatal:")) {
+        Some(fatal) => io::Error::new(kind, format!("git log failed: {}", fatal)),
+        None => io::Error::new(kind, format!("git log exited with {}", status)),
+    }
+}
+
+impl Iterator for GitLogStream {
+    type Item = io::Result<CommitInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.commits.next();
+        // The last commit may have been cut short, so check how git exited before handing it out
+        if !self.finished && self.commits.lines.peek().is_none() {
+            self.finished = true;
+            let status = self.child.wait();
+            let stderr = self.stderr();
+            match status {
+                Ok(status) if status.success() => {
+                    if !stderr.trim().is_empty() {
+                        tracing::warn!(stderr = stderr.trim(), "git log printed warnings");
+                    }
+                }
+                Ok(status) => return Some(Err(exit_error(status, &stderr))),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        item
+    }
+}
+
+impl Drop for GitLogStream {
+    fn drop(&mut self) {
+        if !self.finished {
+            tracing::debug!(
+                pid = self.child.id(),
+                "Stopping git log before its output ended"
+            );
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            self.stderr();
+        }
+    }
+}
+
+/// Failures that restarting git may get past: interrupted reads, a git that was killed and
+/// one that couldn't read the repository. A git that gave up on what it was asked, on a bad
+/// revision or a missing repository, would only fail again.
+fn is_transient(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::Interrupted
+}
+
+/// Runs `git log`, restarting it after transient failures from past the commits already
+/// read, as described by `retry`.
+struct RetryingGitLog {
+    /// The git to run, a stand-in in tests.
+    program: String,
+    args: Vec<String>,
+    cwd: PathBuf,
+    with_message: bool,
+    retry: GitRetry,
+    stream: Option<GitLogStream>,
+    /// Commits (and malformed entries) read so far, skipped when git is restarted.
+    consumed: usize,
+    /// Consecutive failures without any progress in between.
+    failures: u32,
+    gave_up: bool,
+}
+
+impl RetryingGitLog {
+    /// Waits before the next attempt, or returns `error` once the retries are used up.
+    fn retry_after(&mut self, error: io::Error) -> Option<io::Error> {
+        self.stream = None;
+        if self.failures >= self.retry.attempts {
+            self.gave_up = true;
+            if self.failures == 0 {
+                return Some(error);
+            }
+            return Some(io::Error::new(
+                error.kind(),
+                format!("{} (after {} retries)", error, self.failures),
+            ));
+        }
+        let backoff = self.retry.backoff * 2u32.saturating_pow(self.failures);
+        tracing::warn!(
+            error = %error,
+            backoff_ms = backoff.as_millis() as u64,
+            attempt = self.failures + 1,
+            "git log failed, retrying"
+        );
+        thread::sleep(backoff);
+        self.failures += 1;
+        None
+    }
+}
+
+impl Iterator for RetryingGitLog {
+    type Item = io::Result<CommitInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.gave_up {
+            let stream = match &mut self.stream {
+                Some(stream) => stream,
+                None => {
+                    let mut args = self.args.clone();
+                    if self.consumed > 0 {
+                        args.insert(1, format!("--skip={}", self.consumed));
+                    }
+                    match GitLogStream::spawn(&self.program, &args, &self.cwd, self.with_message) {
+                        Ok(stream) => self.stream.insert(stream),
+                        Err(e) if is_transient(&e) => match self.retry_after(e) {
+                            Some(e) => return Some(Err(e)),
+                            None => continue,
+                        },
+                        Err(e) => {
+                            self.gave_up = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            };
+            match stream.next()? {
+                Err(e) if is_transient(&e) => {
+                    if let Some(e) = self.retry_after(e) {
+                        return Some(Err(e));
+                    }
+                }
+                item => {
+                    self.consumed += 1;
+                    self.failures = 0;
+                    return Some(item);
+                }
+            }
+        }
+        None
+    }
+}
+
+const LOG_HEADER_FORMAT: &str = "--format=COMMIT%n%H%n%at%n%ct%n%P%n%an%n%ae%n%cn%n%ce";
+const LOG_HEADER_LINES: usize = 8;
+/// Appended to LOG_HEADER_FORMAT: the subject, the trailers on a single line and the
+/// multi-line body, closed by MESSAGE_TERMINATOR since the body may contain anything.
+const LOG_MESSAGE_FORMAT: &str = "%n%s%n%(trailers:unfold,separator=%x1f)%n%b%x1e";
+const TRAILER_SEPARATOR: char = '\x1f';
+const MESSAGE_TERMINATOR: char = '\x1e';
+
+/// How merge commits are treated when walking history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Skip merge commits entirely (`--no-merges`); suits squash-merge workflows.
+    #[default]
+    Exclude,
+    /// Include merge commits with their diff against the first parent, alongside the
+    /// commits they merged.
+    Include,
+    /// Follow only the first-parent chain, so each merge carries the whole diff of the
+    /// branch it merged and the branch's own commits are not visited.
+    FirstParent,
+}
+
+impl FromStr for MergeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "exclude" => Ok(MergeMode::Exclude),
+            "include" => Ok(MergeMode::Include),
+            "first-parent" => Ok(MergeMode::FirstParent),
+            _ => Err(format!(
+                "Invalid merge mode '{}', expected 'first-parent', 'include' or 'exclude'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MergeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeMode::Exclude => write!(f, "exclude"),
+            MergeMode::Include => write!(f, "include"),
+            MergeMode::FirstParent => write!(f, "first-parent"),
+        }
+    }
+}
+
+/// Which of a commit's dates `--since` and `--until` select it by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommitDate {
+    /// When the change was originally made, which survives rebases.
+    Author,
+    /// When the commit was last written, as `git log --since` goes by.
+    #[default]
+    Committer,
+}
+
+impl FromStr for CommitDate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "author" => Ok(CommitDate::Author),
+            "committer" => Ok(CommitDate::Committer),
+            _ => Err(format!(
+                "Invalid commit date '{}', expected 'author' or 'committer'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CommitDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitDate::Author => write!(f, "author"),
+            CommitDate::Committer => write!(f, "committer"),
+        }
+    }
+}
+
+/// Whom a commit's changes are credited to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommitAttribution {
+    /// Whoever wrote the change.
+    #[default]
+    Author,
+    /// Whoever committed it, which in patch-series workflows is the maintainer who applied
+    /// it, and after a rebase whoever rebased.
+    Committer,
+}
+
+impl FromStr for CommitAttribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "author" => Ok(CommitAttribution::Author),
+            "committer" => Ok(CommitAttribution::Committer),
+            _ => Err(format!(
+                "Invalid attribution '{}', expected 'author' or 'committer'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CommitAttribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitAttribution::Author => write!(f, "author"),
+            CommitAttribution::Committer => write!(f, "committer"),
+        }
+    }
+}
+
+/// How `git log` pairs up deleted and added files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenameDetection {
+    /// Report renames as a deletion and an addition (`--no-renames`).
+    Off,
+    /// Detect renamed files (`-M`).
+    #[default]
+    Renames,
+    /// Detect renamed files and files copied from ones changed in the same commit (`-C`).
+    Copies,
+}
+
+impl FromStr for RenameDetection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(RenameDetection::Off),
+            "renames" => Ok(RenameDetection::Renames),
+            "copies" => Ok(RenameDetection::Copies),
+            _ => Err(format!(
+                "Invalid rename detection '{}', expected 'off', 'renames' or 'copies'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RenameDetection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameDetection::Off => write!(f, "off"),
+            RenameDetection::Renames => write!(f, "renames"),
+            RenameDetection::Copies => write!(f, "copies"),
+        }
+    }
+}
+
+/// How `git log` is retried when it fails part way, as it occasionally does on network
+/// filesystems. Restarted runs skip the commits already read.
+#[derive(Clone, Copy, Debug)]
+pub struct GitRetry {
+    /// Retries of each failure; 0 disables retrying.
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each further one.
+    pub backoff: Duration,
+}
+
+impl Default for GitRetry {
+    fn default() -> Self {
+        GitRetry {
+            attempts: 0,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Options controlling which history `git log` walks.
+#[derive(Clone, Default)]
+pub struct GitLogOptions {
+    /// Revision or range to read (`origin/main`, a SHA, `A..B`); HEAD when unset.
+    pub rev: Option<String>,
+    /// Only commits changing these paths are read, and only their changes to them; all
+    /// commits when empty.
+    pub paths: Vec<String>,
+    pub merges: MergeMode,
+    pub renames: RenameDetection,
+    /// Whom commits are credited to. The credited identity replaces the commit's author, so
+    /// everything downstream of the log goes by it.
+    pub attribute_to: CommitAttribution,
+    /// Credit merge commits to the author of the merged branch tip (the second parent),
+    /// which for PR merges is the PR author rather than whoever pressed merge.
+    pub attribute_merges_to_pr_author: bool,
+    /// Read each commit's message and trailers into [`CommitInfo::message`].
+    pub include_message: bool,
+    /// Read the size of each changed file before and after the commit.
+    pub include_file_sizes: bool,
+    /// How author names are matched against memberships.
+    pub name_normalization: NameNormalization,
+    /// Former owner names mapped to current ones, applied to historical CODEOWNERS files.
+    pub renamed_owners: OwnerAliases,
+    /// Where CODEOWNERS is read from, when not from the analyzed commits themselves.
+    pub codeowners_source: Option<CodeownersSource>,
+    /// Whether commits are owned by the CODEOWNERS file of their time or a single one.
+    pub codeowners_mode: CodeownersMode,
+    /// CODEOWNERS content applied to every commit, in place of the file each was made
+    /// under. Takes precedence over `codeowners_mode`.
+    pub fixed_codeowners: Option<String>,
+    pub retry: GitRetry,
+    /// Told about each commit read by [`crate::git_log_commits_with_codeowners`].
+    pub progress: Option<Arc<dyn ProgressSink>>,
+    /// Only commits by these authors are read, after merges are credited.
+    pub authors: AuthorSelection,
+    /// The date the range of a log is applied to.
+    pub date: CommitDate,
+    /// Replaces the people in each commit once it has been selected, for `--anonymize`.
+    pub pseudonyms: Option<Pseudonyms>,
 }
 
 pub fn git_log_commits(
     since: &str,
     until: &str,
     cwd: &PathBuf,
+    options: &GitLogOptions,
 ) -> Result<impl Iterator<Item = Result<CommitInfo, io::Error>>, io::Error> {
-    execute_git(
-        [
-            "log",
-            "--no-merges",
-            "--format=COMMIT%n%H%n%at%n%an%n%ae",
-            "--numstat",
-            &format!("--since={}", since),
-            &format!("--until={}", until),
-        ],
-        cwd,
-    )
-    .map(parse_commit)
+    crate::check_date_range(since, until)?;
+    let mut format = LOG_HEADER_FORMAT.to_string();
+    if options.include_message {
+        format.push_str(LOG_MESSAGE_FORMAT);
+    }
+    let mut args = vec![
+        "log".to_string(),
+        format,
+        "--raw".to_string(),
+        "--numstat".to_string(),
+        format!("--since={}", since),
+    ];
+    // git only limits by committer date. Commits are authored no later than they are
+    // committed, so --since still narrows the walk, but --until would drop commits authored
+    // in range and rebased after it, leaving the author date range to be applied here.
+    let author_range = match options.date {
+        CommitDate::Author => {
+            let now = Utc::now();
+            let resolve = |date: &str, bound| {
+                resolve_date(date, bound, now)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+            };
+            Some((
+                resolve(since, DateBound::Since)?,
+                resolve(until, DateBound::Until)?,
+            ))
+        }
+        CommitDate::Committer => {
+            args.push(format!("--until={}", until));
+            None
+        }
+    };
+    match options.merges {
+        MergeMode::Exclude => args.push("--no-merges".to_string()),
+        MergeMode::Include => args.push("--diff-merges=first-parent".to_string()),
+        MergeMode::FirstParent => {
+            args.push("--first-parent".to_string());
+            args.push("--diff-merges=first-parent".to_string());
+        }
+    }
+    args.push(
+        match options.renames {
+            RenameDetection::Off => "--no-renames",
+            RenameDetection::Renames => "-M",
+            RenameDetection::Copies => "-C",
+        }
+        .to_string(),
+    );
+    if let Some(rev) = &options.rev {
+        args.push(rev.clone());
+    }
+    if options.rev.is_some() || !options.paths.is_empty() {
+        // Disambiguate revisions from paths with the same name
+        args.push("--".to_string());
+        args.extend(options.paths.iter().cloned());
+    }
+
+    let commits = RetryingGitLog {
+        program: "git".to_string(),
+        args,
+        cwd: cwd.clone(),
+        with_message: options.include_message,
+        retry: options.retry,
+        stream: None,
+        consumed: 0,
+        failures: 0,
+        gave_up: false,
+    };
+    let attribute_to = options.attribute_to;
+    let attribute_merges = options.attribute_merges_to_pr_author;
+    let mut blobs = if options.include_file_sizes {
+        Some(BlobReader::new(cwd)?)
+    } else {
+        None
+    };
+    let authors = options.authors.matcher()?;
+    let pseudonyms = options.pseudonyms.clone();
+    let cwd = cwd.clone();
+
+    Ok(commits.filter_map(move |commit_result| {
+        let read = || {
+            let mut commit = commit_result?;
+            if attribute_to == CommitAttribution::Committer {
+                commit.author_name = commit.committer_name.clone();
+                commit.author_email = commit.committer_email.clone();
+            }
+            if attribute_merges && commit.is_merge() {
+                let (author_name, author_email) = git_commit_author(&commit.parents[1], &cwd)?;
+                commit.author_name = author_name;
+                commit.author_email = author_email;
+            }
+            if !authors.matches(&commit.author_name, &commit.author_email) {
+                return Ok(None);
+            }
+            if let Some((since, until)) = author_range {
+                if commit.author_date < since || commit.author_date > until {
+                    return Ok(None);
+                }
+            }
+            if let Some(blobs) = &mut blobs {
+                fill_file_sizes(&mut commit, blobs)?;
+            }
+            if let Some(pseudonyms) = &pseudonyms {
+                pseudonyms.commit(&mut commit);
+            }
+            Ok(Some(commit))
+        };
+        read().transpose()
+    }))
+}
+
+fn git_commit_author(commit_id: &str, cwd: &PathBuf) -> Result<(String, String), io::Error> {
+    let mut lines = execute_git(["log", "-1", "--format=%an%n%ae", commit_id], cwd)?;
+    let author_name = lines.next().transpose()?.unwrap_or_default();
+    let author_email = lines.next().transpose()?.unwrap_or_default();
+    Ok((author_name, author_email))
+}
+
+/// Builds a pseudo-commit from the changes not yet committed on top of HEAD, authored by
+/// the current git identity. With `staged_only`, only changes in the index are included;
+/// otherwise unstaged and untracked files are included too.
+///
+/// The pseudo-commit's id is `HEAD`, so lookups by commit id (such as linguist
+/// attributes) resolve against the last commit.
+pub fn git_worktree_changes(cwd: &PathBuf, staged_only: bool) -> Result<CommitInfo, io::Error> {
+    let (author_name, author_email) = git_author_ident(cwd)?;
+    let now = Utc::now();
+
+    let diff_args = if staged_only {
+        vec!["diff", "--cached", "--raw", "--numstat", "HEAD"]
+    } else {
+        vec!["diff", "--raw", "--numstat", "HEAD"]
+    };
+    let mut file_changes = Vec::new();
+    let mut kinds = Vec::new();
+    for line in execute_git(diff_args, cwd)? {
+        let line = line?;
+        if let Some(kind) = parse_raw_status(&line) {
+            kinds.push(kind);
+            continue;
+        }
+        let mut change = parse_numstat(&line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid file change format in working tree: '{}'", line),
+            )
+        })?;
+        if let Some(kind) = kinds.get(file_changes.len()) {
+            change.kind = *kind;
+        }
+        file_changes.push(change);
+    }
+
+    if !staged_only {
+        for path in execute_git(["ls-files", "--others", "--exclude-standard"], cwd)? {
+            let path = path?;
+            let content = std::fs::read(cwd.join(&path))?;
+            // Binary files count as no line changes, as they do in `--numstat`
+            let binary = content.contains(&0);
+            let insertions = if binary {
+                0
+            } else {
+                content
+                    .split(|byte| *byte == b'\n')
+                    .filter(|l| !l.is_empty())
+                    .count() as i32
+            };
+            file_changes.push(FileChange {
+                insertions,
+                deletions: 0,
+                path,
+                old_path: None,
+                kind: ChangeKind::Added,
+                language: None,
+                binary,
+                size_before: None,
+                size_after: None,
+            });
+        }
+    }
+
+    Ok(CommitInfo {
+        id: "HEAD".to_string(),
+        author_date: now,
+        committer_date: now,
+        committer_name: author_name.clone(),
+        committer_email: author_email.clone(),
+        author_name,
+        author_email,
+        parents: Vec::new(),
+        message: None,
+        file_changes,
+    })
+}
+
+/// Name and email git would record as the author of a new commit.
+fn git_author_ident(cwd: &PathBuf) -> Result<(String, String), io::Error> {
+    let ident = execute_git(["var", "GIT_AUTHOR_IDENT"], cwd)?
+        .next()
+        .transpose()?
+        .unwrap_or_default();
+    // Formatted as "Name <email> timestamp timezone"
+    let (name, rest) = ident.split_once(" <").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid author identity '{}'", ident),
+        )
+    })?;
+    let email = rest.split_once('>').map(|(email, _)| email).unwrap_or(rest);
+    Ok((name.to_string(), email.to_string()))
 }
 
 pub fn git_file_versions<'a>(
@@ -195,12 +1089,48 @@ pub fn git_file_versions<'a>(
     }))
 }
 
+/// A `git cat-file --batch` per repository that files have been read from, kept for the
+/// rest of the run so each read doesn't start a git process of its own.
+static BLOB_READERS: LazyLock<Mutex<HashMap<PathBuf, BlobReader>>> =
+    LazyLock::new(Default::default);
+
+/// Content of `file_path` at `commit_id`, or `None` when it doesn't exist there. An empty
+/// `commit_id` reads the file from the index.
 pub fn read_file_at_commit(
     commit_id: &str,
     file_path: &str,
     cwd: &PathBuf,
 ) -> Result<Option<String>, io::Error> {
-    let output = Command::new("git")
+    // The batch protocol is line based, so such paths can't be asked for through it
+    if file_path.contains('\n') {
+        return show_file_at_commit(commit_id, file_path, cwd);
+    }
+    let mut readers = BLOB_READERS.lock().unwrap();
+    let reader = match readers.entry(cwd.clone()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(BlobReader::new(cwd)?),
+    };
+    let content = match reader.content(commit_id, file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            // Whatever was left unread would be taken for the answer to the next request
+            readers.remove(cwd);
+            return Err(e);
+        }
+    };
+    content
+        .map(|content| {
+            String::from_utf8(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .transpose()
+}
+
+fn show_file_at_commit(
+    commit_id: &str,
+    file_path: &str,
+    cwd: &PathBuf,
+) -> Result<Option<String>, io::Error> {
+    let output = crate::platform::command("git")
         .args(["show", &format!("{}:{}", commit_id, file_path)])
         .current_dir(cwd)
         .output()?;
@@ -214,7 +1144,302 @@ pub fn read_file_at_commit(
         if stderr.starts_with("fatal: path") {
             Ok(None)
         } else {
-            Err(std::io::Error::new(std::io::ErrorKind::Other, stderr))
+            Err(std::io::Error::other(stderr))
         }
     }
 }
+
+/// `(commit timestamp, commit id)` of the commits on the first-parent history of `rev`
+/// that changed one of `paths`, oldest first.
+pub(crate) fn git_path_revisions(
+    rev: &str,
+    paths: &[&str],
+    cwd: &PathBuf,
+) -> Result<Vec<(i64, String)>, io::Error> {
+    let output = crate::platform::command("git")
+        .args(["log", "--first-parent", "--format=%ct %H", rev, "--"])
+        .args(paths)
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git log {} failed in {}: {}",
+            rev,
+            cwd.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let mut revisions = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let (timestamp, id) = line.split_once(' ').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid log line '{}'", line),
+            )
+        })?;
+        let timestamp = timestamp
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        revisions.push((timestamp, id.to_string()));
+    }
+    revisions.reverse();
+    Ok(revisions)
+}
+
+/// Lists every file path in the tree of `commit_id`.
+pub fn git_tree_files(commit_id: &str, cwd: &PathBuf) -> Result<Vec<String>, io::Error> {
+    execute_git(["ls-tree", "-r", "--name-only", commit_id], cwd)?.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tag_commit, CommitTag};
+
+    /// A `git log` entry as LOG_HEADER_FORMAT prints it, followed by `file_lines`.
+    fn log_entry(id: &str, file_lines: &[&str]) -> String {
+        let mut entry = format!(
+            "COMMIT\n{}\n1700000000\n1700000000\n\nA\na@example.com\nA\na@example.com\n\n",
+            id
+        );
+        for line in file_lines {
+            entry.push_str(line);
+            entry.push('\n');
+        }
+        entry
+    }
+
+    fn parse_log(log: &str) -> Vec<CommitInfo> {
+        parse_commit(LineReader::new(log.as_bytes()), false)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn braced_renames_are_tagged_mass_rename() {
+        let lines: Vec<String> = (0..5)
+            .map(|i| format!("1\t1\tsrc/{{old => new}}/file{}.rs", i))
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let commits = parse_log(&log_entry("abc", &lines));
+
+        let change = &commits[0].file_changes[0];
+        assert_eq!(change.path, "src/new/file0.rs");
+        assert_eq!(change.old_path.as_deref(), Some("src/old/file0.rs"));
+        assert_eq!(change.kind, ChangeKind::Renamed);
+        assert_eq!(tag_commit(&commits[0]), vec![CommitTag::MassRename]);
+    }
+
+    #[test]
+    fn renames_with_braces_keep_the_common_parts() {
+        assert_eq!(
+            parse_rename("src/{old => new}/lib.rs"),
+            Some(("src/old/lib.rs".to_string(), "src/new/lib.rs".to_string()))
+        );
+        assert_eq!(
+            parse_rename("{old => new}/lib.rs"),
+            Some(("old/lib.rs".to_string(), "new/lib.rs".to_string()))
+        );
+        assert_eq!(
+            parse_rename("src/{a.rs => b.rs}"),
+            Some(("src/a.rs".to_string(), "src/b.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn renames_without_braces_are_whole_paths() {
+        assert_eq!(
+            parse_rename("README => docs/README.md"),
+            Some(("README".to_string(), "docs/README.md".to_string()))
+        );
+        assert_eq!(parse_rename("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn renames_with_an_empty_side_drop_the_extra_separator() {
+        assert_eq!(
+            parse_rename("src/{ => lib}/a.rs"),
+            Some(("src/a.rs".to_string(), "src/lib/a.rs".to_string()))
+        );
+        assert_eq!(
+            parse_rename("src/{lib => }/a.rs"),
+            Some(("src/lib/a.rs".to_string(), "src/a.rs".to_string()))
+        );
+        assert_eq!(
+            parse_rename("{ => lib}/a.rs"),
+            Some(("a.rs".to_string(), "lib/a.rs".to_string()))
+        );
+        assert_eq!(
+            parse_rename("{lib => }/a.rs"),
+            Some(("lib/a.rs".to_string(), "a.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn numstat_lines_parse_counts_and_renames() {
+        let change = parse_numstat("3\t1\tsrc/{a => b}.rs").unwrap();
+        assert_eq!((change.insertions, change.deletions), (3, 1));
+        assert_eq!(change.path, "src/b.rs");
+        assert_eq!(change.old_path.as_deref(), Some("src/a.rs"));
+        assert_eq!(change.kind, ChangeKind::Renamed);
+
+        let binary = parse_numstat("-\t-\tlogo.png").unwrap();
+        assert!(binary.binary);
+        assert_eq!((binary.insertions, binary.deletions), (0, 0));
+        assert_eq!(binary.kind, ChangeKind::Modified);
+
+        assert!(parse_numstat("3\tsrc/a.rs").is_none());
+    }
+
+    #[test]
+    fn raw_status_letters_map_to_change_kinds() {
+        let raw = |status: &str| {
+            parse_raw_status(&format!(
+                ":100644 100644 1234567 89abcde {}\tsrc/a.rs",
+                status
+            ))
+        };
+        assert_eq!(raw("A"), Some(ChangeKind::Added));
+        assert_eq!(raw("M"), Some(ChangeKind::Modified));
+        assert_eq!(raw("D"), Some(ChangeKind::Deleted));
+        assert_eq!(raw("R086"), Some(ChangeKind::Renamed));
+        assert_eq!(raw("C100"), Some(ChangeKind::Copied));
+        assert_eq!(raw("T"), Some(ChangeKind::Modified));
+        assert_eq!(parse_raw_status("1\t2\tsrc/a.rs"), None);
+    }
+
+    #[test]
+    fn raw_statuses_match_numstat_lines_in_order() {
+        let log = log_entry(
+            "abc",
+            &[
+                ":000000 100644 0000000 1234567 A\tnew.rs",
+                ":100644 000000 1234567 0000000 D\tgone.rs",
+                ":100644 100644 1234567 89abcde R090\told/x.rs\tnew/x.rs",
+                ":100644 100644 1234567 89abcde M\tsrc/lib.rs",
+                "",
+                "10\t0\tnew.rs",
+                "0\t7\tgone.rs",
+                "1\t1\t{old => new}/x.rs",
+                "2\t2\tsrc/lib.rs",
+            ],
+        ) + &log_entry("def", &["1\t0\ta.rs"]);
+        let commits = parse_log(&log);
+        let kinds = |commit: &CommitInfo| -> Vec<(String, ChangeKind)> {
+            commit
+                .file_changes
+                .iter()
+                .map(|change| (change.path.clone(), change.kind))
+                .collect()
+        };
+        assert_eq!(
+            kinds(&commits[0]),
+            vec![
+                ("new.rs".to_string(), ChangeKind::Added),
+                ("gone.rs".to_string(), ChangeKind::Deleted),
+                ("new/x.rs".to_string(), ChangeKind::Renamed),
+                ("src/lib.rs".to_string(), ChangeKind::Modified),
+            ]
+        );
+        // Statuses don't carry over from one commit to the next
+        assert_eq!(
+            kinds(&commits[1]),
+            vec![("a.rs".to_string(), ChangeKind::Modified)]
+        );
+    }
+
+    fn git_log(args: &[&str], attempts: u32) -> RetryingGitLog {
+        RetryingGitLog {
+            program: "git".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cwd: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+            with_message: false,
+            retry: GitRetry {
+                attempts,
+                backoff: Duration::from_secs(60),
+            },
+            stream: None,
+            consumed: 0,
+            failures: 0,
+            gave_up: false,
+        }
+    }
+
+    #[test]
+    fn only_interruptions_are_transient() {
+        assert!(is_transient(&io::Error::from(io::ErrorKind::Interrupted)));
+        for kind in [
+            io::ErrorKind::NotFound,
+            io::ErrorKind::InvalidData,
+            io::ErrorKind::UnexpectedEof,
+            io::ErrorKind::Other,
+        ] {
+            assert!(!is_transient(&io::Error::from(kind)), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn fatal_git_errors_are_not_retried() {
+        // The minute-long backoff would make any retry obvious
+        let mut log = git_log(&["log", "no-such-revision-of-bound", "--"], 3);
+        let Some(Err(error)) = log.next() else {
+            panic!("expected git log to fail");
+        };
+        assert!(!is_transient(&error));
+        assert!(error.to_string().starts_with("git log failed: fatal:"));
+        assert!(!error.to_string().contains("retries"));
+        assert!(log.next().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn io_failures_are_retried_past_the_commits_read() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let first = log_entry("aaa", &[]) + &log_entry("bbb", &[]);
+        let rest = log_entry("bbb", &[]) + &log_entry("ccc", &[]);
+        std::fs::write(dir.path().join("first"), first).unwrap();
+        std::fs::write(dir.path().join("rest"), rest).unwrap();
+        // Fails like git on a flaky NFS mount the first time, then succeeds
+        let stub = dir.path().join("git");
+        std::fs::write(
+            &stub,
+            format!(
+                "#!/bin/sh\n\
+                 cd '{dir}'\n\
+                 echo \"$@\" >> calls\n\
+                 if [ ! -e failed ]; then\n\
+                 touch failed; cat first\n\
+                 echo 'fatal: unable to read 0123abcd: Input/output error' >&2; exit 128\n\
+                 fi\n\
+                 cat rest\n",
+                dir = dir.path().display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut log = git_log(&["log", "HEAD"], 1);
+        log.program = stub.display().to_string();
+        log.retry.backoff = Duration::from_millis(1);
+        let ids = log
+            .map(|commit| commit.map(|commit| commit.id))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(ids, ["aaa", "bbb", "ccc"]);
+        let calls = std::fs::read_to_string(dir.path().join("calls")).unwrap();
+        assert_eq!(calls, "log HEAD\nlog --skip=1 HEAD\n");
+    }
+
+    #[test]
+    fn missing_programs_are_not_retried() {
+        let mut log = git_log(&["log"], 3);
+        log.cwd = PathBuf::from("/no/such/directory/of/bound");
+        let Some(Err(error)) = log.next() else {
+            panic!("expected git to fail to start");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(log.next().is_none());
+    }
+}
@@ -0,0 +1,322 @@
+use std::{collections::HashSet, fmt, io, str::FromStr};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{format_date, AnalysisSummary, ContributorAnalysis, OwnerAnalysis, OwnerInfo};
+
+/// The window an analysis is compared with to show trends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComparisonWindow {
+    /// The window of equal length ending just before the analyzed one starts.
+    Previous,
+}
+
+impl FromStr for ComparisonWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "previous" => Ok(ComparisonWindow::Previous),
+            _ => Err(format!(
+                "Invalid comparison window '{}', expected 'previous'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ComparisonWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComparisonWindow::Previous => write!(f, "previous"),
+        }
+    }
+}
+
+impl ComparisonWindow {
+    /// The `(since, until)` of the window to compare `since..until` with, both formatted
+    /// like [`format_date`].
+    pub fn range(&self, since: &str, until: &str) -> Result<(String, String), io::Error> {
+        let parse = |date: &str| {
+            DateTime::parse_from_rfc3339(date)
+                .map(|date| date.with_timezone(&Utc))
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Cannot compare with a window before '{}'", date),
+                    )
+                })
+        };
+        let (since, until) = (parse(since)?, parse(until)?);
+        match self {
+            ComparisonWindow::Previous => {
+                let previous_until = since - Duration::seconds(1);
+                let previous_since = previous_until - (until - since);
+                Ok((format_date(previous_since), format_date(previous_until)))
+            }
+        }
+    }
+}
+
+/// A metric in the analyzed window next to its value in the compared one.
+#[derive(Clone, Debug, Serialize)]
+pub struct MetricChange {
+    pub metric: &'static str,
+    pub previous: f64,
+    pub current: f64,
+    /// The change relative to the previous value, or `None` when that was 0.
+    pub percent_change: Option<f64>,
+}
+
+impl MetricChange {
+    pub fn new(metric: &'static str, previous: f64, current: f64) -> Self {
+        MetricChange {
+            metric,
+            previous,
+            current,
+            percent_change: (previous != 0.0).then(|| (current - previous) * 100.0 / previous),
+        }
+    }
+
+    pub fn change(&self) -> f64 {
+        self.current - self.previous
+    }
+}
+
+fn summary_metrics(previous: &AnalysisSummary, current: &AnalysisSummary) -> Vec<MetricChange> {
+    let count = |metric, value: fn(&AnalysisSummary) -> usize| {
+        MetricChange::new(metric, value(previous) as f64, value(current) as f64)
+    };
+    vec![
+        count("commits", |summary| summary.total_commits),
+        count("changes", AnalysisSummary::total_changes),
+        count("team_changes", |summary| summary.changes_by_team),
+        count("others_changes", |summary| summary.changes_by_others),
+        count("unowned_changes", |summary| summary.unowned_changes),
+        MetricChange::new(
+            "coverage",
+            previous.coverage() * 100.0,
+            current.coverage() * 100.0,
+        ),
+    ]
+}
+
+/// The changes of one owner's metrics.
+#[derive(Clone, Debug, Serialize)]
+pub struct OwnerComparison {
+    pub owner: String,
+    pub metrics: Vec<MetricChange>,
+}
+
+/// An owner analysis compared with the analysis of another window.
+pub struct OwnerAnalysisComparison {
+    pub previous_since: String,
+    pub previous_until: String,
+    pub summary: Vec<MetricChange>,
+    /// Owners in the order of the current analysis, followed by those only changed in
+    /// the previous window.
+    pub owners: Vec<OwnerComparison>,
+}
+
+fn owner_metrics(previous: Option<&OwnerInfo>, current: Option<&OwnerInfo>) -> Vec<MetricChange> {
+    let count = |metric, value: fn(&OwnerInfo) -> usize| {
+        MetricChange::new(
+            metric,
+            previous.map_or(0, value) as f64,
+            current.map_or(0, value) as f64,
+        )
+    };
+    vec![
+        count("team_changes", |info| {
+            info.total_insertions_by_team + info.total_deletions_by_team
+        }),
+        count("team_commits", |info| info.total_commits_by_team),
+        count("others_changes", |info| {
+            info.total_insertions_by_others + info.total_deletions_by_others
+        }),
+        count("others_commits", |info| info.total_commits_by_others),
+        count("contributors", |info| info.contributors),
+    ]
+}
+
+fn find_owner<'a>(analysis: &'a OwnerAnalysis, owner: &str) -> Option<&'a OwnerInfo> {
+    analysis.owners.iter().find(|info| info.owner == owner)
+}
+
+/// Compares `current` with `previous`, the analysis of the window from `previous_since` to
+/// `previous_until`.
+pub fn compare_owner_analyses(
+    previous: &OwnerAnalysis,
+    current: &OwnerAnalysis,
+    previous_since: &str,
+    previous_until: &str,
+) -> OwnerAnalysisComparison {
+    let mut seen = HashSet::new();
+    let mut owners = Vec::new();
+    for info in current.owners.iter().chain(&previous.owners) {
+        if !seen.insert(info.owner.as_str()) {
+            continue;
+        }
+        owners.push(OwnerComparison {
+            owner: info.owner.clone(),
+            metrics: owner_metrics(
+                find_owner(previous, &info.owner),
+                find_owner(current, &info.owner),
+            ),
+        });
+    }
+    OwnerAnalysisComparison {
+        previous_since: previous_since.to_string(),
+        previous_until: previous_until.to_string(),
+        summary: summary_metrics(&previous.summary, &current.summary),
+        owners,
+    }
+}
+
+/// The changes of one contributor's metrics for one owner.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContributorComparison {
+    pub author_name: String,
+    pub author_email: String,
+    pub owner: String,
+    pub metrics: Vec<MetricChange>,
+}
+
+/// A contributor analysis compared with the analysis of another window.
+pub struct ContributorAnalysisComparison {
+    pub previous_since: String,
+    pub previous_until: String,
+    pub summary: Vec<MetricChange>,
+    /// Contributions in the order of the current analysis, followed by those only made in
+    /// the previous window.
+    pub contributions: Vec<ContributorComparison>,
+}
+
+/// A contributor's name and email and the owner they contributed to.
+type ContributionKey<'a> = (&'a str, &'a str, &'a str);
+
+/// Changes and commits by contributor and owner.
+fn contribution_totals(analysis: &ContributorAnalysis) -> Vec<(ContributionKey<'_>, [usize; 2])> {
+    analysis
+        .contributors
+        .iter()
+        .flat_map(|info| {
+            info.contributions.iter().map(move |contribution| {
+                (
+                    (
+                        info.author_name.as_str(),
+                        info.author_email.as_str(),
+                        contribution.owner.as_str(),
+                    ),
+                    [
+                        contribution.total_insertions + contribution.total_deletions,
+                        contribution.total_commits,
+                    ],
+                )
+            })
+        })
+        .collect()
+}
+
+/// Compares `current` with `previous`, the analysis of the window from `previous_since` to
+/// `previous_until`.
+pub fn compare_contributor_analyses(
+    previous: &ContributorAnalysis,
+    current: &ContributorAnalysis,
+    previous_since: &str,
+    previous_until: &str,
+) -> ContributorAnalysisComparison {
+    let previous_totals = contribution_totals(previous);
+    let current_totals = contribution_totals(current);
+    let lookup = |totals: &[(ContributionKey, [usize; 2])], key| {
+        totals
+            .iter()
+            .find(|(other, _)| *other == key)
+            .map_or([0, 0], |(_, values)| *values)
+    };
+    let mut seen = HashSet::new();
+    let mut contributions = Vec::new();
+    for (key, _) in current_totals.iter().chain(&previous_totals) {
+        if !seen.insert(*key) {
+            continue;
+        }
+        let [previous_changes, previous_commits] = lookup(&previous_totals, *key);
+        let [current_changes, current_commits] = lookup(&current_totals, *key);
+        let (author_name, author_email, owner) = *key;
+        contributions.push(ContributorComparison {
+            author_name: author_name.to_string(),
+            author_email: author_email.to_string(),
+            owner: owner.to_string(),
+            metrics: vec![
+                MetricChange::new("changes", previous_changes as f64, current_changes as f64),
+                MetricChange::new("commits", previous_commits as f64, current_commits as f64),
+            ],
+        });
+    }
+    ContributorAnalysisComparison {
+        previous_since: previous_since.to_string(),
+        previous_until: previous_until.to_string(),
+        summary: summary_metrics(&previous.summary, &current.summary),
+        contributions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previous_windows_end_just_before_and_last_as_long() {
+        let previous = ComparisonWindow::Previous
+            .range("2024-04-01T00:00:00Z", "2024-06-30T23:59:59Z")
+            .unwrap();
+        assert_eq!(
+            previous,
+            (
+                "2024-01-01T00:00:00Z".to_string(),
+                "2024-03-31T23:59:59Z".to_string()
+            )
+        );
+        let previous = ComparisonWindow::Previous
+            .range("2024-03-01T12:00:00+02:00", "2024-03-08T10:00:00Z")
+            .unwrap();
+        assert_eq!(
+            previous,
+            (
+                "2024-02-23T09:59:59Z".to_string(),
+                "2024-03-01T09:59:59Z".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn unresolved_dates_cannot_be_compared() {
+        let error = ComparisonWindow::Previous
+            .range("90d", "2024-03-08T10:00:00Z")
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn percent_changes_are_relative_to_the_previous_value() {
+        let grown = MetricChange::new("commits", 8.0, 12.0);
+        assert_eq!(grown.change(), 4.0);
+        assert_eq!(grown.percent_change, Some(50.0));
+        let shrunk = MetricChange::new("commits", 10.0, 5.0);
+        assert_eq!(shrunk.change(), -5.0);
+        assert_eq!(shrunk.percent_change, Some(-50.0));
+        assert_eq!(
+            MetricChange::new("commits", 3.0, 3.0).percent_change,
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn changes_from_zero_have_no_percentage() {
+        let new = MetricChange::new("commits", 0.0, 7.0);
+        assert_eq!(new.change(), 7.0);
+        assert_eq!(new.percent_change, None);
+        assert_eq!(MetricChange::new("commits", 0.0, 0.0).percent_change, None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::Path, str::FromStr};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::{normalize_path, CommitInfoWithCodeowner};
+
+/// What the owner and contributor analyses attribute changes to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OwnerGrouping {
+    /// The owners CODEOWNERS assigns.
+    #[default]
+    Owner,
+    /// The components of a component map.
+    Component,
+}
+
+impl FromStr for OwnerGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "owner" => Ok(OwnerGrouping::Owner),
+            "component" => Ok(OwnerGrouping::Component),
+            _ => Err(format!(
+                "Invalid grouping '{}', expected 'owner' or 'component'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OwnerGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnerGrouping::Owner => write!(f, "owner"),
+            OwnerGrouping::Component => write!(f, "component"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ComponentGlobs {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Logical components of a repository, each made up of the files matching its globs.
+#[derive(Clone)]
+pub struct ComponentMap {
+    components: Vec<(String, GlobSet)>,
+}
+
+impl ComponentMap {
+    /// Parses YAML mapping each component name to a glob or a list of globs:
+    ///
+    /// ```yaml
+    /// web: ["web/**", "packages/ui/**"]
+    /// api: services/api/**
+    /// ```
+    ///
+    /// As in path filters, `*` does not cross directory separators; use `**` for that.
+    pub fn parse(content: &str) -> Result<Self, io::Error> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let components: BTreeMap<String, ComponentGlobs> = serde_yaml::from_str(content)
+            .map_err(|e| invalid(format!("Invalid component map: {}", e)))?;
+        let components = components
+            .into_iter()
+            .map(|(name, globs)| {
+                let globs = match globs {
+                    ComponentGlobs::One(glob) => vec![glob],
+                    ComponentGlobs::Many(globs) => globs,
+                };
+                let mut builder = GlobSetBuilder::new();
+                for glob in &globs {
+                    builder.add(
+                        GlobBuilder::new(&normalize_path(glob))
+                            .literal_separator(true)
+                            .build()
+                            .map_err(|e| invalid(format!("Component {}: {}", name, e)))?,
+                    );
+                }
+                let globs = builder
+                    .build()
+                    .map_err(|e| invalid(format!("Component {}: {}", name, e)))?;
+                Ok((name, globs))
+            })
+            .collect::<Result<_, io::Error>>()?;
+        Ok(ComponentMap { components })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The components `path` belongs to, by name. A file can be in several components,
+    /// like a file with several owners, or in none.
+    pub fn components_of(&self, path: &str) -> Vec<String> {
+        self.components
+            .iter()
+            .filter(|(_, globs)| globs.is_match(path))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// Replaces the owners of every file change with the components the file belongs to, so
+/// the analyses report by component. Whether the author is a codeowner is left as computed
+/// from CODEOWNERS, so team changes are still those by the owners of the changed files.
+pub fn map_commit_components<I>(
+    commits: I,
+    components: ComponentMap,
+) -> impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>
+where
+    I: Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+{
+    commits.map(move |commit_result| {
+        let mut commit = commit_result?;
+        for change in &mut commit.file_changes {
+            change.codeowners = Some(components.components_of(&change.path));
+        }
+        Ok(commit)
+    })
+}
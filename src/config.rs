@@ -0,0 +1,131 @@
+use std::{collections::BTreeMap, io, path::Path};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{DerivedMetric, MetricConfig, MetricError, NameNormalization, ScorecardConfig};
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error(transparent)]
+    Metric(#[from] MetricError),
+    #[error("Invalid config: [adjusted] {0} weight {1} must be a finite number of at least 0")]
+    AdjustedWeight(&'static str, f64),
+}
+
+/// Settings read from `bound.toml`.
+#[derive(Deserialize, Default)]
+pub struct BoundConfig {
+    /// Derived metrics as `name = "expression"`, added as extra report columns.
+    #[serde(default)]
+    pub metrics: BTreeMap<String, String>,
+    /// Email addresses per owner, as `"@org/team" = ["team@example.com"]`, used by
+    /// `notify owners`.
+    #[serde(default)]
+    pub contacts: BTreeMap<String, Vec<String>>,
+    /// Names to report owners under, as `"@org/payments-eu" = "payments"`. Owners sharing
+    /// an alias are aggregated together.
+    #[serde(default)]
+    pub owner_aliases: BTreeMap<String, String>,
+    /// Former names of renamed owners, as `"@org/payments" = "@org/payments-platform"`.
+    /// Unlike aliases, renames apply before membership is checked, so historical changes
+    /// count toward the renamed team.
+    #[serde(default)]
+    pub renamed_owners: BTreeMap<String, String>,
+    /// How author names are compared against the memberships file.
+    #[serde(default)]
+    pub name_normalization: NameNormalization,
+    /// Globs of security-sensitive paths, such as `"**/auth/**"`, reported on by
+    /// `security-report`.
+    #[serde(default)]
+    pub security_paths: Vec<String>,
+    /// More author patterns for `--exclude-bots` to drop, such as `"release-robot*"`,
+    /// matched like `--exclude-author`.
+    #[serde(default)]
+    pub bot_authors: Vec<String>,
+    /// Metric weights and grade thresholds for `scorecard`.
+    #[serde(default)]
+    pub scorecard: ScorecardConfig,
+    /// Languages of files the built-in map doesn't know or gets wrong for `--by-language`,
+    /// as `".bzl" = "Starlark"` by extension or `"WORKSPACE" = "Starlark"` by file name.
+    #[serde(default)]
+    pub languages: BTreeMap<String, String>,
+    /// How `--adjusted` scores contributions when no `--weight` is given.
+    #[serde(default)]
+    pub adjusted: MetricConfig,
+}
+
+impl BoundConfig {
+    pub fn derived_metrics(&self) -> Result<Vec<DerivedMetric>, MetricError> {
+        self.metrics
+            .iter()
+            .map(|(name, expression)| DerivedMetric::parse(name, expression))
+            .collect()
+    }
+}
+
+fn parse_config(content: &str) -> Result<BoundConfig, ConfigError> {
+    let config: BoundConfig = toml::from_str(content)?;
+    if let Some((name, weight)) = config.adjusted.invalid_weight() {
+        return Err(ConfigError::AdjustedWeight(name, weight));
+    }
+    Ok(config)
+}
+
+/// Loads the config at `path`, falling back to defaults when the file does not exist.
+pub fn load_config(path: &Path) -> Result<BoundConfig, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => parse_config(&content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BoundConfig::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusted_weights_are_read() {
+        let config = parse_config(
+            "[adjusted]\ninsertion = 1.0\ndeletion = 0.5\nfile = 2\nlog_scale = true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.adjusted,
+            MetricConfig {
+                insertion: 1.0,
+                deletion: 0.5,
+                file: 2.0,
+                commit: 0.0,
+                log_scale: true,
+            }
+        );
+    }
+
+    #[test]
+    fn negative_or_infinite_adjusted_weights_are_rejected() {
+        for table in ["deletion = -1.0", "commit = inf", "file = nan"] {
+            let error = parse_config(&format!("[adjusted]\n{}\n", table))
+                .err()
+                .unwrap();
+            assert!(
+                matches!(error, ConfigError::AdjustedWeight(_, _)),
+                "{}",
+                table
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_adjusted_keys_are_rejected() {
+        assert!(matches!(
+            parse_config("[adjusted]\nlines = 2\n"),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,294 @@
+use std::io;
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DateError {
+    #[error(
+        "Unrecognized date '{0}', expected e.g. 2024-01-31, 'now', 'today', '90d', '2 weeks ago' or 'last quarter'"
+    )]
+    Unrecognized(String),
+}
+
+/// Which end of a range a date is for. Dates naming a whole span (`2024-01-31`, `today`,
+/// `last quarter`) resolve to its first second as a start and its last second as an end, so
+/// the span is included either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateBound {
+    Since,
+    Until,
+}
+
+#[derive(Clone, Copy)]
+enum Span {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+fn parse_span(unit: &str) -> Option<Span> {
+    match unit.trim_end_matches('s') {
+        "day" => Some(Span::Day),
+        "week" => Some(Span::Week),
+        "month" => Some(Span::Month),
+        "quarter" => Some(Span::Quarter),
+        "year" => Some(Span::Year),
+        _ => None,
+    }
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+}
+
+/// First day of the span containing `date`; weeks start on Monday.
+fn span_start(date: NaiveDate, span: Span) -> NaiveDate {
+    let first_of_month = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap();
+    match span {
+        Span::Day => date,
+        Span::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        Span::Month => first_of_month(date.month()),
+        Span::Quarter => first_of_month((date.month() - 1) / 3 * 3 + 1),
+        Span::Year => first_of_month(1),
+    }
+}
+
+fn shift(date: NaiveDate, span: Span, count: i64) -> Option<NaiveDate> {
+    let months = |months: i64| {
+        if months >= 0 {
+            date.checked_add_months(Months::new(months as u32))
+        } else {
+            date.checked_sub_months(Months::new(-months as u32))
+        }
+    };
+    match span {
+        Span::Day => date.checked_add_signed(Duration::days(count)),
+        Span::Week => date.checked_add_signed(Duration::weeks(count)),
+        Span::Month => months(count),
+        Span::Quarter => months(count * 3),
+        Span::Year => months(count * 12),
+    }
+}
+
+/// The start or end of the span starting on `start`, depending on `bound`.
+fn span_bound(start: NaiveDate, span: Span, bound: DateBound) -> Option<DateTime<Utc>> {
+    match bound {
+        DateBound::Since => Some(midnight(start)),
+        DateBound::Until => Some(midnight(shift(start, span, 1)?) - Duration::seconds(1)),
+    }
+}
+
+/// A count and unit such as `90d`, `2w`, `6 months` or `1 week ago`, subtracted from now.
+fn parse_offset(input: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let input = input.strip_suffix(" ago").unwrap_or(input).trim();
+    let split = input.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = input.split_at(split);
+    let count: i64 = count.parse().ok()?;
+    let unit = unit.trim();
+
+    let duration = match unit {
+        "s" | "second" | "seconds" => Duration::seconds(count),
+        "min" | "minute" | "minutes" => Duration::minutes(count),
+        "h" | "hour" | "hours" => Duration::hours(count),
+        "d" => Duration::days(count),
+        "w" => Duration::weeks(count),
+        "m" => return now.checked_sub_months(Months::new(count as u32)),
+        "y" => return now.checked_sub_months(Months::new(count as u32 * 12)),
+        _ => {
+            let span = parse_span(unit)?;
+            let date = shift(now.date_naive(), span, -count)?;
+            return Some(Utc.from_utc_datetime(&date.and_time(now.time())));
+        }
+    };
+    now.checked_sub_signed(duration)
+}
+
+/// Resolves `input` against `now` (in UTC). Accepts absolute dates (`2024-01-31`,
+/// `2024-01-31 12:00:00`, RFC 3339), `now`, `today`, `yesterday`, offsets (`90d`, `2w`, `6m`,
+/// `1y`, `2 weeks ago`) and calendar spans (`this month`, `last quarter`, `last year`).
+pub fn resolve_date(
+    input: &str,
+    bound: DateBound,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, DateError> {
+    let normalized = input.trim().to_lowercase();
+    let today = now.date_naive();
+    let unrecognized = || DateError::Unrecognized(input.to_string());
+
+    let resolved = match normalized.as_str() {
+        "now" => Some(now),
+        "today" => span_bound(today, Span::Day, bound),
+        "yesterday" => span_bound(today - Days::new(1), Span::Day, bound),
+        _ => {
+            if let Ok(date) = DateTime::parse_from_rfc3339(input.trim()) {
+                Some(date.with_timezone(&Utc))
+            } else if let Ok(date) = NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%d %H:%M:%S")
+            {
+                Some(Utc.from_utc_datetime(&date))
+            } else if let Ok(date) = NaiveDate::parse_from_str(&normalized, "%Y-%m-%d") {
+                span_bound(date, Span::Day, bound)
+            } else if let Some((relative, unit)) = normalized.split_once(' ') {
+                match (relative, parse_span(unit.trim())) {
+                    ("this", Some(span)) => span_bound(span_start(today, span), span, bound),
+                    ("last", Some(span)) => {
+                        let start =
+                            shift(span_start(today, span), span, -1).ok_or_else(unrecognized)?;
+                        span_bound(start, span, bound)
+                    }
+                    _ => parse_offset(&normalized, now),
+                }
+            } else {
+                parse_offset(&normalized, now)
+            }
+        }
+    };
+
+    resolved.ok_or_else(unrecognized)
+}
+
+/// Formats `date` the way both git and GitHub's timestamps read it, so resolved dates can
+/// be compared as strings against `merged_at` and similar fields.
+pub fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Fails when `since` is after `until`, which would otherwise silently select no commits.
+/// Dates that can't be resolved are left for git to interpret.
+pub fn check_date_range(since: &str, until: &str) -> Result<(), io::Error> {
+    let now = Utc::now();
+    if let (Ok(start), Ok(end)) = (
+        resolve_date(since, DateBound::Since, now),
+        resolve_date(until, DateBound::Until, now),
+    ) {
+        if start > end {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "--since {} is after --until {}, which selects no commits",
+                    since, until
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 15, 10, 30, 0).unwrap()
+    }
+
+    fn resolve(input: &str, bound: DateBound) -> String {
+        format_date(resolve_date(input, bound, now()).unwrap())
+    }
+
+    #[test]
+    fn absolute_dates_cover_the_whole_day() {
+        assert_eq!(
+            resolve("2024-01-31", DateBound::Since),
+            "2024-01-31T00:00:00Z"
+        );
+        assert_eq!(
+            resolve("2024-01-31", DateBound::Until),
+            "2024-01-31T23:59:59Z"
+        );
+        assert_eq!(
+            resolve("2024-01-31 12:00:00", DateBound::Until),
+            "2024-01-31T12:00:00Z"
+        );
+        assert_eq!(
+            resolve("2024-01-31T12:00:00+02:00", DateBound::Since),
+            "2024-01-31T10:00:00Z"
+        );
+    }
+
+    #[test]
+    fn named_days_resolve_against_now() {
+        assert_eq!(resolve("now", DateBound::Since), "2024-05-15T10:30:00Z");
+        assert_eq!(resolve("Today", DateBound::Since), "2024-05-15T00:00:00Z");
+        assert_eq!(resolve("today", DateBound::Until), "2024-05-15T23:59:59Z");
+        assert_eq!(
+            resolve("yesterday", DateBound::Until),
+            "2024-05-14T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn offsets_count_back_from_now() {
+        assert_eq!(resolve("90d", DateBound::Since), "2024-02-15T10:30:00Z");
+        assert_eq!(resolve("2w", DateBound::Since), "2024-05-01T10:30:00Z");
+        assert_eq!(
+            resolve("2 weeks ago", DateBound::Since),
+            "2024-05-01T10:30:00Z"
+        );
+        assert_eq!(resolve("6m", DateBound::Since), "2023-11-15T10:30:00Z");
+        assert_eq!(
+            resolve("1 month ago", DateBound::Since),
+            "2024-04-15T10:30:00Z"
+        );
+        assert_eq!(resolve("1y", DateBound::Since), "2023-05-15T10:30:00Z");
+        assert_eq!(resolve("3h", DateBound::Since), "2024-05-15T07:30:00Z");
+    }
+
+    #[test]
+    fn calendar_spans_include_all_of_the_span() {
+        assert_eq!(
+            resolve("this week", DateBound::Since),
+            "2024-05-13T00:00:00Z"
+        );
+        assert_eq!(
+            resolve("this month", DateBound::Since),
+            "2024-05-01T00:00:00Z"
+        );
+        assert_eq!(
+            resolve("this month", DateBound::Until),
+            "2024-05-31T23:59:59Z"
+        );
+        assert_eq!(
+            resolve("last quarter", DateBound::Since),
+            "2024-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            resolve("last quarter", DateBound::Until),
+            "2024-03-31T23:59:59Z"
+        );
+        assert_eq!(
+            resolve("last year", DateBound::Since),
+            "2023-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            resolve("last year", DateBound::Until),
+            "2023-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn unrecognized_dates_are_errors() {
+        for input in ["someday", "last fortnight", "2024-02-30", "d90", ""] {
+            assert!(
+                resolve_date(input, DateBound::Since, now()).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn ranges_must_not_end_before_they_start() {
+        assert!(check_date_range("2024-01-01", "2024-01-31").is_ok());
+        assert!(check_date_range("2024-01-31", "2024-01-31").is_ok());
+        let error = check_date_range("2024-02-01", "2024-01-31").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        // Left for git to interpret
+        assert!(check_date_range("someday", "2024-01-31").is_ok());
+    }
+}
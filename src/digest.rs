@@ -0,0 +1,223 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use crate::{get_codeowners_at_commit, owner::CODEOWNERS_LOCATIONS, CommitInfoWithCodeowner};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestFormat {
+    Markdown,
+    Slack,
+}
+
+impl FromStr for DigestFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" => Ok(DigestFormat::Markdown),
+            "slack" => Ok(DigestFormat::Slack),
+            _ => Err(format!(
+                "Invalid digest format '{}', expected 'markdown' or 'slack'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DigestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestFormat::Markdown => write!(f, "markdown"),
+            DigestFormat::Slack => write!(f, "slack"),
+        }
+    }
+}
+
+pub struct DigestCommit {
+    pub id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub changes: usize,
+}
+
+pub struct OwnerDigest {
+    pub owner: String,
+    pub changes_by_team: usize,
+    pub changes_by_others: usize,
+    pub commits_by_team: usize,
+    pub commits_by_others: usize,
+    pub large_commits: Vec<DigestCommit>,
+    pub new_outside_contributors: Vec<(String, String)>,
+    pub codeowners_edits: Vec<DigestCommit>,
+}
+
+impl OwnerDigest {
+    fn new(owner: &str) -> Self {
+        OwnerDigest {
+            owner: owner.to_string(),
+            changes_by_team: 0,
+            changes_by_others: 0,
+            commits_by_team: 0,
+            commits_by_others: 0,
+            large_commits: Vec::new(),
+            new_outside_contributors: Vec::new(),
+            codeowners_edits: Vec::new(),
+        }
+    }
+}
+
+const MAX_LARGE_COMMITS: usize = 5;
+
+/// Builds one digest per owner for the commits in `commits`. `prior_commits` is the history
+/// before the digest window and is only used to decide which outside contributors are new.
+pub fn build_owner_digests(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    prior_commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    large_commit_threshold: usize,
+    cwd: &PathBuf,
+) -> Result<Vec<OwnerDigest>, io::Error> {
+    let mut known_contributors: HashMap<String, HashSet<(String, String)>> = HashMap::new();
+    for commit_result in prior_commits {
+        let commit = commit_result?;
+        let contributor = (commit.author_name.clone(), commit.author_email.clone());
+        for change in &commit.file_changes {
+            for owner in change.codeowners.iter().flatten() {
+                known_contributors
+                    .entry(owner.clone())
+                    .or_default()
+                    .insert(contributor.clone());
+            }
+        }
+    }
+
+    let mut digests: BTreeMap<String, OwnerDigest> = BTreeMap::new();
+    let mut new_contributors: HashMap<String, HashSet<(String, String)>> = HashMap::new();
+
+    for commit_result in commits {
+        let commit = commit_result?;
+        let contributor = (commit.author_name.clone(), commit.author_email.clone());
+        // (changes, touched by a team member) per owner for this commit
+        let mut commit_owners: HashMap<String, (usize, bool)> = HashMap::new();
+
+        for change in &commit.file_changes {
+            let is_team_member = change.author_is_codeowner.unwrap_or(false);
+            for owner in change.codeowners.iter().flatten() {
+                let entry = commit_owners.entry(owner.clone()).or_insert((0, false));
+                entry.0 += (change.insertions + change.deletions) as usize;
+                entry.1 |= is_team_member;
+
+                let digest = digests
+                    .entry(owner.clone())
+                    .or_insert_with(|| OwnerDigest::new(owner));
+                if is_team_member {
+                    digest.changes_by_team += (change.insertions + change.deletions) as usize;
+                } else {
+                    digest.changes_by_others += (change.insertions + change.deletions) as usize;
+                    let is_known = known_contributors
+                        .get(owner)
+                        .is_some_and(|known| known.contains(&contributor));
+                    if !is_known
+                        && new_contributors
+                            .entry(owner.clone())
+                            .or_default()
+                            .insert(contributor.clone())
+                    {
+                        digest.new_outside_contributors.push(contributor.clone());
+                    }
+                }
+            }
+        }
+
+        for (owner, (changes, by_team)) in commit_owners {
+            let digest = digests
+                .entry(owner.clone())
+                .or_insert_with(|| OwnerDigest::new(&owner));
+            if by_team {
+                digest.commits_by_team += 1;
+            } else {
+                digest.commits_by_others += 1;
+            }
+            if changes >= large_commit_threshold {
+                digest.large_commits.push(digest_commit(&commit, changes));
+            }
+        }
+
+        let touches_codeowners = commit
+            .file_changes
+            .iter()
+            .any(|change| CODEOWNERS_LOCATIONS.contains(&change.path.as_str()));
+        if touches_codeowners {
+            let changes = commit
+                .file_changes
+                .iter()
+                .filter(|change| CODEOWNERS_LOCATIONS.contains(&change.path.as_str()))
+                .map(|change| (change.insertions + change.deletions) as usize)
+                .sum();
+            for owner in codeowners_edit_affected_owners(&commit.id, cwd)? {
+                digests
+                    .entry(owner.clone())
+                    .or_insert_with(|| OwnerDigest::new(&owner))
+                    .codeowners_edits
+                    .push(digest_commit(&commit, changes));
+            }
+        }
+    }
+
+    let mut digests: Vec<OwnerDigest> = digests.into_values().collect();
+    for digest in &mut digests {
+        digest
+            .large_commits
+            .sort_by_key(|commit| std::cmp::Reverse(commit.changes));
+        digest.large_commits.truncate(MAX_LARGE_COMMITS);
+    }
+    Ok(digests)
+}
+
+fn digest_commit(commit: &CommitInfoWithCodeowner, changes: usize) -> DigestCommit {
+    DigestCommit {
+        id: commit.id.clone(),
+        author_name: commit.author_name.clone(),
+        author_email: commit.author_email.clone(),
+        timestamp: commit.timestamp,
+        changes,
+    }
+}
+
+/// Owners named on CODEOWNERS rules that were added, removed or modified by `commit_id`.
+fn codeowners_edit_affected_owners(
+    commit_id: &str,
+    cwd: &PathBuf,
+) -> Result<HashSet<String>, io::Error> {
+    let after = get_codeowners_at_commit(commit_id, cwd)?.unwrap_or_default();
+    // The root commit has no parent, in which case every rule is new
+    let before = get_codeowners_at_commit(&format!("{}^", commit_id), cwd)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    let rules_before = codeowners_rules(&before);
+    let rules_after = codeowners_rules(&after);
+
+    Ok(rules_before
+        .symmetric_difference(&rules_after)
+        .flat_map(|rule| {
+            rule.split_whitespace()
+                .skip(1)
+                .take_while(|owner| !owner.starts_with('#'))
+        })
+        .map(|owner| owner.to_string())
+        .collect())
+}
+
+fn codeowners_rules(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
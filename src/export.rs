@@ -0,0 +1,128 @@
+use std::{io, path::PathBuf};
+
+use rusqlite::{params, Connection};
+use thiserror::Error;
+
+use crate::{format_tags, tag_commit, AuthorCodeownerMemberships, CommitInfoWithCodeowner};
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+pub struct ExportStats {
+    pub commits: usize,
+    pub file_changes: usize,
+    pub memberships: usize,
+}
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE commits (
+    id TEXT PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    author_name TEXT NOT NULL,
+    author_email TEXT NOT NULL,
+    tags TEXT NOT NULL
+);
+CREATE TABLE file_changes (
+    id INTEGER PRIMARY KEY,
+    commit_id TEXT NOT NULL REFERENCES commits(id),
+    path TEXT NOT NULL,
+    insertions INTEGER NOT NULL,
+    deletions INTEGER NOT NULL,
+    author_is_codeowner INTEGER
+);
+CREATE TABLE file_change_owners (
+    file_change_id INTEGER NOT NULL REFERENCES file_changes(id),
+    owner TEXT NOT NULL
+);
+CREATE TABLE memberships (
+    author_email TEXT,
+    author_name TEXT,
+    codeowner TEXT NOT NULL,
+    additional_emails TEXT NOT NULL,
+    aliases TEXT NOT NULL
+);
+CREATE INDEX file_changes_commit_id ON file_changes(commit_id);
+CREATE INDEX file_change_owners_owner ON file_change_owners(owner);
+";
+
+/// Writes commits, file changes, their owners and the memberships into a fresh SQLite
+/// database at `path`, replacing any existing file.
+pub fn export_to_sqlite(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    memberships: &[AuthorCodeownerMemberships],
+    path: &PathBuf,
+) -> Result<ExportStats, ExportError> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SQLITE_SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    let mut stats = ExportStats {
+        commits: 0,
+        file_changes: 0,
+        memberships: 0,
+    };
+
+    {
+        let mut insert_commit = tx.prepare(
+            "INSERT INTO commits (id, timestamp, author_name, author_email, tags) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut insert_change = tx.prepare(
+            "INSERT INTO file_changes (commit_id, path, insertions, deletions, author_is_codeowner) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut insert_owner =
+            tx.prepare("INSERT INTO file_change_owners (file_change_id, owner) VALUES (?1, ?2)")?;
+        let mut insert_membership = tx.prepare(
+            "INSERT INTO memberships (author_email, author_name, codeowner, additional_emails, aliases) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+
+        for commit_result in commits {
+            let commit = commit_result?;
+            insert_commit.execute(params![
+                commit.id,
+                commit.timestamp,
+                commit.author_name,
+                commit.author_email,
+                format_tags(&tag_commit(&commit))
+            ])?;
+            stats.commits += 1;
+
+            for change in &commit.file_changes {
+                insert_change.execute(params![
+                    commit.id,
+                    change.path,
+                    change.insertions,
+                    change.deletions,
+                    change.author_is_codeowner
+                ])?;
+                let file_change_id = tx.last_insert_rowid();
+                for owner in change.codeowners.iter().flatten() {
+                    insert_owner.execute(params![file_change_id, owner])?;
+                }
+                stats.file_changes += 1;
+            }
+        }
+
+        for membership in memberships {
+            insert_membership.execute(params![
+                membership.author_email,
+                membership.author_name,
+                membership.codeowner,
+                membership.additional_emails.join(","),
+                membership.aliases.join(",")
+            ])?;
+            stats.memberships += 1;
+        }
+    }
+
+    tx.commit()?;
+    Ok(stats)
+}
@@ -0,0 +1,73 @@
+use std::{io, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    git_log_commits_with_codeowners, normalize_path, owner::CODEOWNERS_LOCATIONS,
+    AuthorCodeownerMemberships, GitLogOptions,
+};
+
+/// A commit that changed a file, with who owned the file at the time.
+#[derive(Clone, Debug, Serialize)]
+pub struct FileHistoryEntry {
+    pub commit_id: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub insertions: i32,
+    pub deletions: i32,
+    pub binary: bool,
+    /// Owners of the file under the CODEOWNERS in effect for the commit.
+    pub owners: Vec<String>,
+    /// Whether the author was a member of one of `owners`; unknown without memberships.
+    pub author_is_owner: Option<bool>,
+}
+
+/// Each commit in the range that changed `path`, newest first, with the file's owners when
+/// it was made and whether its author was one of them. CODEOWNERS is read as `options`
+/// say, as for [`crate::git_log_commits_with_codeowners`].
+pub fn file_history(
+    path: &str,
+    since: &str,
+    until: &str,
+    cwd: &PathBuf,
+    options: &GitLogOptions,
+    memberships: Option<Vec<AuthorCodeownerMemberships>>,
+) -> Result<impl Iterator<Item = Result<FileHistoryEntry, io::Error>>, io::Error> {
+    let path = normalize_path(path).into_owned();
+    // Commits changing CODEOWNERS are read too, so the owners follow its history
+    let paths = std::iter::once(path.clone())
+        .chain(
+            CODEOWNERS_LOCATIONS
+                .iter()
+                .map(|location| location.to_string()),
+        )
+        .collect();
+    let options = GitLogOptions {
+        paths,
+        ..options.clone()
+    };
+    let commits = git_log_commits_with_codeowners(since, until, cwd, &options, memberships)?;
+
+    Ok(commits.filter_map(move |commit| {
+        let commit = match commit {
+            Ok(commit) => commit,
+            Err(e) => return Some(Err(e)),
+        };
+        let change = commit
+            .file_changes
+            .into_iter()
+            .find(|change| change.path == path)?;
+        Some(Ok(FileHistoryEntry {
+            commit_id: commit.id,
+            author_name: commit.author_name,
+            author_email: commit.author_email,
+            timestamp: commit.timestamp,
+            insertions: change.insertions,
+            deletions: change.deletions,
+            binary: change.binary,
+            owners: change.codeowners.unwrap_or_default(),
+            author_is_owner: change.author_is_codeowner,
+        }))
+    }))
+}
@@ -0,0 +1,359 @@
+use std::io;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::{normalize_path, ChangeKind, CommitInfo, CommitInfoWithCodeowner, LanguageMap};
+
+/// A file change as seen through [`FileChangePaths::path_changes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathChange<'a> {
+    pub path: &'a str,
+    /// The path before a rename or copy.
+    pub old_path: Option<&'a str>,
+    pub kind: ChangeKind,
+    pub insertions: i32,
+    pub deletions: i32,
+}
+
+/// Commit types whose file changes can be inspected and narrowed down by path.
+pub trait FileChangePaths {
+    fn commit_id(&self) -> &str;
+    fn retain_paths(&mut self, keep: impl Fn(&str) -> bool);
+    fn has_file_changes(&self) -> bool;
+    /// The path, kind and line counts of each file change.
+    fn path_changes(&self) -> Vec<PathChange<'_>>;
+    /// Paths of the changed files git considers binary.
+    fn binary_paths(&self) -> Vec<&str>;
+    /// Counts each binary file change as a single inserted line, so binary-only commits
+    /// still carry weight in line-based metrics.
+    fn count_binary_changes(&mut self);
+    /// Renames the owners of each file change, merging owners that end up with the same
+    /// name. Commits without owner information are left unchanged.
+    fn map_owners(&mut self, _rename: impl Fn(&str) -> String) {}
+    /// Sets the language of each file change from its path.
+    fn classify_languages(&mut self, languages: &LanguageMap);
+    /// Scales the line counts of every file change down in proportion so they add up to at
+    /// most `max`.
+    fn cap_line_changes(&mut self, max: usize);
+}
+
+/// Scales `(insertions, deletions)` pairs down in proportion so they add up to at most
+/// `max`, rounding down.
+fn cap_counts<'a>(counts: impl Iterator<Item = (&'a mut i32, &'a mut i32)>, max: usize) {
+    let mut counts: Vec<_> = counts.collect();
+    let total: u64 = counts
+        .iter()
+        .map(|(insertions, deletions)| (**insertions + **deletions) as u64)
+        .sum();
+    if total <= max as u64 {
+        return;
+    }
+    for (insertions, deletions) in &mut counts {
+        **insertions = (**insertions as u64 * max as u64 / total) as i32;
+        **deletions = (**deletions as u64 * max as u64 / total) as i32;
+    }
+}
+
+impl FileChangePaths for CommitInfo {
+    fn commit_id(&self) -> &str {
+        &self.id
+    }
+
+    fn retain_paths(&mut self, keep: impl Fn(&str) -> bool) {
+        self.file_changes.retain(|change| keep(&change.path));
+    }
+
+    fn has_file_changes(&self) -> bool {
+        !self.file_changes.is_empty()
+    }
+
+    fn path_changes(&self) -> Vec<PathChange<'_>> {
+        self.file_changes
+            .iter()
+            .map(|change| PathChange {
+                path: &change.path,
+                old_path: change.old_path.as_deref(),
+                kind: change.kind,
+                insertions: change.insertions,
+                deletions: change.deletions,
+            })
+            .collect()
+    }
+
+    fn binary_paths(&self) -> Vec<&str> {
+        self.file_changes
+            .iter()
+            .filter(|change| change.binary)
+            .map(|change| change.path.as_str())
+            .collect()
+    }
+
+    fn count_binary_changes(&mut self) {
+        for change in self.file_changes.iter_mut().filter(|change| change.binary) {
+            change.insertions = 1;
+        }
+    }
+
+    fn classify_languages(&mut self, languages: &LanguageMap) {
+        for change in &mut self.file_changes {
+            change.language = Some(languages.language(&change.path).to_string());
+        }
+    }
+
+    fn cap_line_changes(&mut self, max: usize) {
+        cap_counts(
+            self.file_changes
+                .iter_mut()
+                .map(|change| (&mut change.insertions, &mut change.deletions)),
+            max,
+        );
+    }
+}
+
+impl FileChangePaths for CommitInfoWithCodeowner {
+    fn commit_id(&self) -> &str {
+        &self.id
+    }
+
+    fn map_owners(&mut self, rename: impl Fn(&str) -> String) {
+        for change in &mut self.file_changes {
+            if let Some(owners) = &mut change.codeowners {
+                let mut renamed: Vec<String> = Vec::with_capacity(owners.len());
+                for owner in owners.iter() {
+                    let owner = rename(owner);
+                    if !renamed.contains(&owner) {
+                        renamed.push(owner);
+                    }
+                }
+                *owners = renamed;
+            }
+        }
+    }
+
+    fn retain_paths(&mut self, keep: impl Fn(&str) -> bool) {
+        self.file_changes.retain(|change| keep(&change.path));
+    }
+
+    fn has_file_changes(&self) -> bool {
+        !self.file_changes.is_empty()
+    }
+
+    fn path_changes(&self) -> Vec<PathChange<'_>> {
+        self.file_changes
+            .iter()
+            .map(|change| PathChange {
+                path: &change.path,
+                old_path: change.old_path.as_deref(),
+                kind: change.kind,
+                insertions: change.insertions,
+                deletions: change.deletions,
+            })
+            .collect()
+    }
+
+    fn binary_paths(&self) -> Vec<&str> {
+        self.file_changes
+            .iter()
+            .filter(|change| change.binary)
+            .map(|change| change.path.as_str())
+            .collect()
+    }
+
+    fn count_binary_changes(&mut self) {
+        for change in self.file_changes.iter_mut().filter(|change| change.binary) {
+            change.insertions = 1;
+        }
+    }
+
+    fn classify_languages(&mut self, languages: &LanguageMap) {
+        for change in &mut self.file_changes {
+            change.language = Some(languages.language(&change.path).to_string());
+        }
+    }
+
+    fn cap_line_changes(&mut self, max: usize) {
+        cap_counts(
+            self.file_changes
+                .iter_mut()
+                .map(|change| (&mut change.insertions, &mut change.deletions)),
+            max,
+        );
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(&normalize_path(pattern))
+                .literal_separator(true)
+                .build()?,
+        );
+    }
+    Ok(Some(builder.build()?))
+}
+
+impl PathFilter {
+    /// Paths must match at least one `include` glob (when any are given) and none of the
+    /// `exclude` globs. `*` does not cross directory separators; use `**` for that.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        Ok(PathFilter {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|globs| globs.is_match(path))
+    }
+}
+
+/// Authors `--exclude-bots` drops: GitHub App accounts, whose names and emails carry a
+/// `[bot]` suffix, and automation known to commit under an identity of its own.
+pub const BOT_AUTHORS: &[&str] = &[
+    "*[bot]",
+    "*[bot]@*",
+    "dependabot*",
+    "renovate*",
+    "greenkeeper*",
+    "snyk-bot*",
+    "github-actions*",
+    "action@github.com",
+    "*@dependabot.com",
+    "*@renovateapp.com",
+];
+
+/// Which commit authors to read, by patterns matched case-insensitively. Only `*` and `?`
+/// are wildcards, so bot names like `renovate[bot]` can be given as they are.
+#[derive(Clone, Debug, Default)]
+pub struct AuthorSelection {
+    /// Author names to keep. With `emails`, an author matching either is kept; with
+    /// neither, every author is.
+    pub names: Vec<String>,
+    pub emails: Vec<String>,
+    /// Authors to drop, matched against both the name and the email.
+    pub exclude: Vec<String>,
+}
+
+impl AuthorSelection {
+    /// Also drops bots: those in [`BOT_AUTHORS`] and any matching `extra`.
+    pub fn excluding_bots(mut self, extra: &[String]) -> Self {
+        self.exclude
+            .extend(BOT_AUTHORS.iter().map(|pattern| pattern.to_string()));
+        self.exclude.extend(extra.iter().cloned());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.emails.is_empty() && self.exclude.is_empty()
+    }
+
+    pub(crate) fn matcher(&self) -> Result<AuthorMatcher, io::Error> {
+        let build = |patterns: &[String]| {
+            build_author_glob_set(patterns).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid author pattern: {}", e),
+                )
+            })
+        };
+        Ok(AuthorMatcher {
+            names: build(&self.names)?,
+            emails: build(&self.emails)?,
+            exclude: build(&self.exclude)?,
+        })
+    }
+}
+
+fn build_author_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern: String = pattern
+            .chars()
+            .map(|c| match c {
+                '\\' => "\\\\".to_string(),
+                '[' | ']' | '{' | '}' => format!("[{}]", c),
+                c => c.to_string(),
+            })
+            .collect();
+        builder.add(GlobBuilder::new(&pattern).case_insensitive(true).build()?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+pub(crate) struct AuthorMatcher {
+    names: Option<GlobSet>,
+    emails: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl AuthorMatcher {
+    pub(crate) fn matches(&self, name: &str, email: &str) -> bool {
+        let selected = match (&self.names, &self.emails) {
+            (None, None) => true,
+            (names, emails) => {
+                names.as_ref().is_some_and(|names| names.is_match(name))
+                    || emails.as_ref().is_some_and(|emails| emails.is_match(email))
+            }
+        };
+        selected
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(name) || exclude.is_match(email))
+    }
+}
+
+/// Drops file changes whose paths don't pass `filter`, and commits left without any.
+pub fn filter_commit_paths<T, I>(
+    commits: I,
+    filter: PathFilter,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    commits.filter_map(move |commit_result| match commit_result {
+        Ok(mut commit) => {
+            commit.retain_paths(|path| filter.matches(path));
+            commit.has_file_changes().then_some(Ok(commit))
+        }
+        Err(e) => Some(Err(e)),
+    })
+}
+
+/// Counts binary file changes as one inserted line each (see
+/// [`FileChangePaths::count_binary_changes`]).
+pub fn count_binary_changes<T, I>(commits: I) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    commits.map(|commit_result| {
+        let mut commit = commit_result?;
+        commit.count_binary_changes();
+        Ok(commit)
+    })
+}
@@ -0,0 +1,108 @@
+use std::{fmt, str::FromStr};
+
+use crate::{AnalysisSummary, OwnerReviewInfo};
+
+/// A percentage threshold, given as `10%` or `10`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Percentage(f64);
+
+impl Percentage {
+    /// The percentage as a fraction between 0 and 1.
+    pub fn fraction(&self) -> f64 {
+        self.0 / 100.0
+    }
+}
+
+impl FromStr for Percentage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = s.trim().strip_suffix('%').unwrap_or(s.trim()).trim();
+        match number.parse::<f64>() {
+            Ok(value) if (0.0..=100.0).contains(&value) => Ok(Percentage(value)),
+            _ => Err(format!(
+                "Invalid percentage '{}', expected a number from 0 to 100 such as '10%'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Percentage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GateBound {
+    /// The metric may not exceed the threshold.
+    Max,
+    /// The metric may not fall below the threshold.
+    Min,
+}
+
+/// A metric checked against its threshold.
+pub struct GateCheck {
+    pub metric: &'static str,
+    pub bound: GateBound,
+    pub threshold: Percentage,
+    /// The metric over the range as a fraction, or `None` when there was nothing to
+    /// measure it on, which passes.
+    pub value: Option<f64>,
+    /// What the metric was measured on, such as "12 of 340 changed lines".
+    pub detail: String,
+}
+
+impl GateCheck {
+    pub fn passed(&self) -> bool {
+        match (self.value, self.bound) {
+            (None, _) => true,
+            (Some(value), GateBound::Max) => value <= self.threshold.fraction(),
+            (Some(value), GateBound::Min) => value >= self.threshold.fraction(),
+        }
+    }
+}
+
+/// The thresholds `bound gate` enforces; metrics without one aren't checked.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GateThresholds {
+    pub max_unowned_changes: Option<Percentage>,
+    pub min_owner_review: Option<Percentage>,
+}
+
+/// Checks the changes in `summary` and the pull requests in `reviews` against
+/// `thresholds`. Reviews are only needed when an owner review threshold is set.
+pub fn evaluate_gate(
+    thresholds: &GateThresholds,
+    summary: &AnalysisSummary,
+    reviews: &[OwnerReviewInfo],
+) -> Vec<GateCheck> {
+    let mut checks = Vec::new();
+    if let Some(threshold) = thresholds.max_unowned_changes {
+        let total = summary.total_changes();
+        checks.push(GateCheck {
+            metric: "unowned changes",
+            bound: GateBound::Max,
+            threshold,
+            value: (total > 0).then(|| summary.unowned_changes as f64 / total as f64),
+            detail: format!("{} of {} changed lines", summary.unowned_changes, total),
+        });
+    }
+    if let Some(threshold) = thresholds.min_owner_review {
+        // A pull request counts once for each owner whose files it changed
+        let pull_requests: usize = reviews.iter().map(|info| info.pull_requests).sum();
+        let approved: usize = reviews.iter().map(|info| info.approved_by_codeowner).sum();
+        checks.push(GateCheck {
+            metric: "owner review",
+            bound: GateBound::Min,
+            threshold,
+            value: (pull_requests > 0).then(|| approved as f64 / pull_requests as f64),
+            detail: format!(
+                "{} of {} owned pull requests approved by a codeowner",
+                approved, pull_requests
+            ),
+        });
+    }
+    checks
+}
@@ -1,7 +1,22 @@
-use std::io;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fmt, io,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
 
 use thiserror::Error;
 
+use crate::{
+    response_cache::{CachedResponse, ResponseCache},
+    ProgressSink, UserInfoCache,
+};
+
 #[derive(Error, Debug)]
 pub enum GHCliError {
     #[error("IO error: {0}")]
@@ -10,10 +25,12 @@ pub enum GHCliError {
     Reqwest(#[from] reqwest::Error),
     #[error("GitHub API error: {0}")]
     GithubApi(String),
+    #[error("GitHub App authentication error: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
 }
 
 pub fn get_token() -> Result<String, GHCliError> {
-    let output = std::process::Command::new("gh")
+    let output = crate::platform::command("gh")
         .arg("auth")
         .arg("token")
         .output()
@@ -31,9 +48,92 @@ pub fn get_token() -> Result<String, GHCliError> {
     }
 }
 
+/// Credentials of a GitHub App installation, exchanged for a short-lived installation token.
+#[derive(Clone)]
+pub struct GithubAppAuth {
+    pub app_id: String,
+    pub installation_id: String,
+    pub private_key_path: PathBuf,
+}
+
+/// Explicitly configured GitHub credentials; see [`GithubApi::new_with_auth`].
+#[derive(Clone, Default)]
+pub struct GithubAuth {
+    pub token: Option<String>,
+    pub app: Option<GithubAppAuth>,
+}
+
+#[derive(Serialize)]
+struct AppClaims {
+    iat: u64,
+    exp: u64,
+    iss: String,
+}
+
+async fn get_app_installation_token(
+    client: &reqwest::Client,
+    app: &GithubAppAuth,
+) -> Result<String, GHCliError> {
+    let key = std::fs::read(&app.private_key_path)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    // Backdated to allow for clock drift; GitHub rejects lifetimes over ten minutes
+    let claims = AppClaims {
+        iat: now - 60,
+        exp: now + 9 * 60,
+        iss: app.app_id.clone(),
+    };
+    let jwt = jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_rsa_pem(&key)?,
+    )?;
+
+    let response = client
+        .post(format!(
+            "https://api.github.com/app/installations/{}/access_tokens",
+            app.installation_id
+        ))
+        .header("Authorization", format!("Bearer {}", jwt))
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .header("User-Agent", "bound-cli")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(GHCliError::GithubApi(format!(
+            "Creating an installation token failed: {}",
+            response.status()
+        )));
+    }
+
+    let json: serde_json::Value = response.json().await?;
+    json["token"]
+        .as_str()
+        .map(|token| token.to_string())
+        .ok_or_else(|| GHCliError::GithubApi("Installation token missing".to_string()))
+}
+
+/// API calls made through a [`GithubApi`] and the rate limit reported by the last response.
+#[derive(Clone, Default, Serialize)]
+pub struct GithubApiUsage {
+    pub calls: usize,
+    /// Of the calls, those answered from the response cache as unchanged.
+    pub not_modified: usize,
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// When the rate limit window resets, in seconds since the Unix epoch.
+    pub reset: Option<u64>,
+}
+
 pub struct GithubApi {
     token: String,
     client: reqwest::Client,
+    usage: Mutex<GithubApiUsage>,
+    progress: Option<Arc<dyn ProgressSink>>,
+    responses: Mutex<ResponseCache>,
 }
 
 impl GithubApi {
@@ -64,25 +164,46 @@ impl GithubApi {
         let mut current_url = format!("https://api.github.com{}", path);
 
         loop {
-            let response = self
-                .client
-                .request(method.clone(), &current_url)
-                .header("Authorization", format!("token {}", self.token))
-                .header("X-GitHub-Api-Version", "2022-11-28")
-                .header("User-Agent", "bound-cli")
-                .send()
-                .await?;
-
-            if !response.status().is_success() {
-                return Err(GHCliError::GithubApi(format!(
-                    "GitHub API request failed: {}",
-                    response.status()
-                )));
-            }
+            // Only listings read with GET are cached, as only they can be asked for again
+            let cached = (method == reqwest::Method::GET)
+                .then(|| self.responses.lock().unwrap().get(&current_url).cloned())
+                .flatten();
+            let etag = cached.as_ref().map(|cached| cached.etag.as_str());
+            let response = self.send(method.clone(), &current_url, etag).await?;
 
-            let next_url = Self::get_next_page_url(&response);
-
-            let json: serde_json::Value = response.json().await?;
+            let (json, next_url) = match cached {
+                Some(cached) if response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+                    self.usage.lock().unwrap().not_modified += 1;
+                    tracing::debug!(url = %current_url, "Using cached response");
+                    (cached.body, cached.next)
+                }
+                _ => {
+                    if !response.status().is_success() {
+                        return Err(GHCliError::GithubApi(format!(
+                            "GitHub API request failed: {}",
+                            response.status()
+                        )));
+                    }
+                    let next_url = Self::get_next_page_url(&response);
+                    let etag = response
+                        .headers()
+                        .get(reqwest::header::ETAG)
+                        .and_then(|etag| etag.to_str().ok())
+                        .map(str::to_string);
+                    let json: serde_json::Value = response.json().await?;
+                    if let Some(etag) = etag.filter(|_| method == reqwest::Method::GET) {
+                        self.responses.lock().unwrap().insert(
+                            &current_url,
+                            CachedResponse {
+                                etag,
+                                body: json.clone(),
+                                next: next_url.clone(),
+                            },
+                        );
+                    }
+                    (json, next_url)
+                }
+            };
             if let Some(results) = json.as_array() {
                 all_results.extend_from_slice(results);
             } else {
@@ -99,10 +220,136 @@ impl GithubApi {
         Ok(all_results)
     }
 
+    fn with_token(token: String, client: reqwest::Client) -> Self {
+        GithubApi {
+            token,
+            client,
+            usage: Mutex::new(GithubApiUsage::default()),
+            progress: None,
+            responses: Mutex::new(ResponseCache::disabled()),
+        }
+    }
+
+    /// Asks for listings already in `cache` with their ETags, so unchanged ones cost no
+    /// rate limit, and adds the ones received. Saved by [`GithubApi::save_response_cache`].
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.responses = Mutex::new(cache);
+        self
+    }
+
+    pub fn save_response_cache(&self) -> Result<(), io::Error> {
+        self.responses.lock().unwrap().save()
+    }
+
+    fn record_usage(&self, response: &reqwest::Response) {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+        };
+        let calls = {
+            let mut usage = self.usage.lock().unwrap();
+            usage.calls += 1;
+            usage.limit = header("x-ratelimit-limit").or(usage.limit);
+            usage.remaining = header("x-ratelimit-remaining").or(usage.remaining);
+            usage.reset = header("x-ratelimit-reset").or(usage.reset);
+            usage.calls
+        };
+        if let Some(progress) = &self.progress {
+            progress.api_page_fetched(calls);
+        }
+    }
+
+    pub fn usage(&self) -> GithubApiUsage {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// Reports every response received, including each page of paginated results, to
+    /// `progress`.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
     pub fn new() -> Result<Self, GHCliError> {
         let token = get_token()?;
         let client = reqwest::Client::new();
-        Ok(GithubApi { token, client })
+        Ok(GithubApi::with_token(token, client))
+    }
+
+    /// Authenticates with the first of: an explicit token, a GitHub App installation, the
+    /// `GITHUB_TOKEN` environment variable, or `gh auth token`, so no `gh` CLI is needed
+    /// when any of the others is available.
+    pub async fn new_with_auth(auth: &GithubAuth) -> Result<Self, GHCliError> {
+        let client = reqwest::Client::new();
+        let token = if let Some(token) = &auth.token {
+            tracing::debug!("Authenticating with the given token");
+            token.clone()
+        } else if let Some(app) = &auth.app {
+            tracing::debug!(app_id = %app.app_id, "Authenticating as a GitHub App installation");
+            get_app_installation_token(&client, app).await?
+        } else if let Some(token) = env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty()) {
+            tracing::debug!("Authenticating with GITHUB_TOKEN");
+            token
+        } else {
+            tracing::debug!("Authenticating with gh auth token");
+            get_token()?
+        };
+        Ok(GithubApi::with_token(token, client))
+    }
+
+    /// How long to wait for the rate limit to reset, when it has run out.
+    fn rate_limit_wait(&self) -> Option<Duration> {
+        let usage = self.usage.lock().unwrap();
+        if usage.remaining != Some(0) {
+            return None;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        // A second past the reset, as GitHub's clock and ours may disagree slightly
+        Some(Duration::from_secs(usage.reset?.saturating_sub(now) + 1))
+    }
+
+    /// Sends a request, first waiting out the rate limit when it has run out. A request
+    /// refused for the limit, as concurrent ones can be after another used it up, is sent
+    /// again once it resets.
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        etag: Option<&str>,
+    ) -> Result<reqwest::Response, GHCliError> {
+        let mut retried = false;
+        loop {
+            if let Some(wait) = self.rate_limit_wait() {
+                tracing::warn!(
+                    wait_secs = wait.as_secs(),
+                    "GitHub rate limit exhausted, waiting for it to reset"
+                );
+                tokio::time::sleep(wait).await;
+            }
+            let mut request = self
+                .client
+                .request(method.clone(), url)
+                .header("Authorization", format!("token {}", self.token))
+                .header("X-GitHub-Api-Version", "2022-11-28")
+                .header("User-Agent", "bound-cli");
+            if let Some(etag) = etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            let response = request.send().await?;
+            self.record_usage(&response);
+            tracing::debug!(%method, url, status = %response.status(), "GitHub API request");
+            let rate_limited = matches!(
+                response.status(),
+                reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::TOO_MANY_REQUESTS
+            ) && self.rate_limit_wait().is_some();
+            if !rate_limited || retried {
+                return Ok(response);
+            }
+            retried = true;
+        }
     }
 
     async fn request(
@@ -111,16 +358,7 @@ impl GithubApi {
         path: &str,
     ) -> Result<reqwest::Response, GHCliError> {
         let url = format!("https://api.github.com{}", path);
-        let response = self
-            .client
-            .request(method, &url)
-            .header("Authorization", format!("token {}", self.token))
-            .header("X-GitHub-Api-Version", "2022-11-28")
-            .header("User-Agent", "bound-cli")
-            .send()
-            .await?;
-
-        Ok(response)
+        self.send(method, &url, None).await
     }
 
     async fn request_ok_json(
@@ -156,21 +394,167 @@ pub async fn get_github_org_logins(api: &GithubApi) -> Result<Vec<String>, GHCli
     Ok(orgs)
 }
 
-pub async fn get_github_team_slugs(api: &GithubApi, org: &str) -> Result<Vec<String>, GHCliError> {
+/// A team of an organization, and the team it is nested under, if any.
+#[derive(Clone, Debug)]
+pub struct GithubTeam {
+    pub slug: String,
+    pub parent: Option<String>,
+}
+
+pub async fn get_github_teams(api: &GithubApi, org: &str) -> Result<Vec<GithubTeam>, GHCliError> {
     let path = format!("/orgs/{}/teams", org);
     let json = api
         .request_ok_json_paginated(reqwest::Method::GET, &path)
         .await?;
-    let slugs = json
-        .into_iter()
+    let slug = |team: &serde_json::Value| {
+        team.get("slug")
+            .and_then(|slug| slug.as_str())
+            .map(|slug| slug.to_string())
+    };
+    let teams = json
+        .iter()
         .filter_map(|team| {
-            team.as_object()
-                .and_then(|team| team.get("slug"))
-                .and_then(|slug| slug.as_str())
-                .map(|slug| slug.to_string())
+            Some(GithubTeam {
+                slug: slug(team)?,
+                parent: team.get("parent").and_then(slug),
+            })
         })
-        .collect::<Vec<String>>();
-    Ok(slugs)
+        .collect();
+    Ok(teams)
+}
+
+pub async fn get_github_team_slugs(api: &GithubApi, org: &str) -> Result<Vec<String>, GHCliError> {
+    let teams = get_github_teams(api, org).await?;
+    Ok(teams.into_iter().map(|team| team.slug).collect())
+}
+
+/// A member being added to or removed from a team, from the organization's audit log.
+#[derive(Clone, Debug)]
+pub struct TeamMembershipEvent {
+    /// The team as a codeowner, `@org/team`.
+    pub codeowner: String,
+    pub login: String,
+    pub added: bool,
+    /// Seconds since the Unix epoch.
+    pub timestamp: i64,
+}
+
+/// The `team.add_member` and `team.remove_member` events in the audit log of `org`, oldest
+/// first. The audit log API needs GitHub Enterprise Cloud and a token allowed to read the
+/// organization's audit log, and only goes back as far as GitHub retains events.
+pub async fn get_team_membership_events(
+    api: &GithubApi,
+    org: &str,
+) -> Result<Vec<TeamMembershipEvent>, GHCliError> {
+    let path = format!(
+        "/orgs/{}/audit-log?phrase=action:team&order=asc&per_page=100",
+        org
+    );
+    let json = api
+        .request_ok_json_paginated(reqwest::Method::GET, &path)
+        .await?;
+    let mut events: Vec<TeamMembershipEvent> = json
+        .iter()
+        .filter_map(|event| {
+            let added = match event.get("action")?.as_str()? {
+                "team.add_member" => true,
+                "team.remove_member" => false,
+                _ => return None,
+            };
+            let milliseconds = event
+                .get("@timestamp")
+                .or_else(|| event.get("created_at"))?
+                .as_i64()?;
+            Some(TeamMembershipEvent {
+                codeowner: format!("@{}", event.get("team")?.as_str()?),
+                login: event.get("user")?.as_str()?.to_string(),
+                added,
+                timestamp: milliseconds / 1000,
+            })
+        })
+        .collect();
+    events.sort_by_key(|event| event.timestamp);
+    Ok(events)
+}
+
+/// The teams nested under `slug`, at any depth.
+pub fn descendant_teams(teams: &[GithubTeam], slug: &str) -> Vec<String> {
+    let mut descendants: Vec<String> = Vec::new();
+    let mut parents = vec![slug.to_string()];
+    while let Some(parent) = parents.pop() {
+        for team in teams {
+            if team.parent.as_deref() == Some(parent.as_str()) && !descendants.contains(&team.slug)
+            {
+                descendants.push(team.slug.clone());
+                parents.push(team.slug.clone());
+            }
+        }
+    }
+    descendants
+}
+
+/// Whether the members of a team include those of the teams nested under it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TeamMembership {
+    /// Members of child teams are members of their parents too, as GitHub treats them
+    /// for review requests and permissions.
+    #[default]
+    Inherited,
+    /// Only those added to the team itself. GitHub lists child team members as members
+    /// of the parent, so anyone also in a child team is taken to be there only.
+    Direct,
+}
+
+impl FromStr for TeamMembership {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inherited" => Ok(TeamMembership::Inherited),
+            "direct" => Ok(TeamMembership::Direct),
+            _ => Err(format!(
+                "Invalid team membership '{}', expected 'inherited' or 'direct'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TeamMembership {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeamMembership::Inherited => write!(f, "inherited"),
+            TeamMembership::Direct => write!(f, "direct"),
+        }
+    }
+}
+
+/// The members of `team_slug` with those of the teams nested under it, out of `teams`,
+/// rolled up or left out as `membership` says.
+pub async fn get_github_team_members_nested(
+    api: &GithubApi,
+    org: &str,
+    teams: &[GithubTeam],
+    team_slug: &str,
+    membership: TeamMembership,
+) -> Result<Vec<String>, GHCliError> {
+    let mut members = get_github_team_members(api, org, team_slug).await?;
+    let mut nested = HashSet::new();
+    for child in descendant_teams(teams, team_slug) {
+        nested.extend(get_github_team_members(api, org, &child).await?);
+    }
+    match membership {
+        TeamMembership::Inherited => {
+            let mut nested: Vec<String> = nested
+                .into_iter()
+                .filter(|member| !members.contains(member))
+                .collect();
+            nested.sort();
+            members.extend(nested);
+        }
+        TeamMembership::Direct => members.retain(|member| !nested.contains(member)),
+    }
+    Ok(members)
 }
 
 pub async fn get_github_team_members(
@@ -218,3 +602,263 @@ pub async fn get_user_info(
         Ok(None)
     }
 }
+
+/// Like [`get_user_info`], answered from `cache` when the user was looked up recently.
+/// Users that don't exist are not cached.
+pub async fn get_user_info_cached(
+    api: &GithubApi,
+    login: &str,
+    cache: &mut UserInfoCache,
+) -> Result<Option<(String, String)>, GHCliError> {
+    if let Some(info) = cache.get(login) {
+        return Ok(Some(info));
+    }
+    let info = get_user_info(api, login).await?;
+    if let Some(info) = &info {
+        cache.insert(login, info.clone());
+    }
+    Ok(info)
+}
+
+/// `(name, email)` of each of `logins` found, as with [`get_user_info_cached`], fetching
+/// up to `concurrency` of those not cached at a time.
+pub async fn get_users_info_cached(
+    api: &GithubApi,
+    logins: &[String],
+    cache: &mut UserInfoCache,
+    concurrency: usize,
+) -> Result<HashMap<String, (String, String)>, GHCliError> {
+    let mut users = HashMap::new();
+    let mut missing = Vec::new();
+    for login in logins {
+        match cache.get(login) {
+            Some(info) => {
+                users.insert(login.clone(), info);
+            }
+            None => missing.push(login.clone()),
+        }
+    }
+    let mut fetched = stream::iter(missing)
+        .map(|login| async move {
+            let info = get_user_info(api, &login).await;
+            (login, info)
+        })
+        .buffer_unordered(concurrency.max(1));
+    while let Some((login, info)) = fetched.next().await {
+        if let Some(info) = info? {
+            cache.insert(&login, info.clone());
+            users.insert(login, info);
+        }
+    }
+    Ok(users)
+}
+
+/// Emails `login` authored commits in `repo` (`owner/name`) with, most used first, from
+/// the latest page of their commits.
+pub async fn get_commit_author_emails(
+    api: &GithubApi,
+    repo: &str,
+    login: &str,
+) -> Result<Vec<String>, GHCliError> {
+    let path = format!("/repos/{}/commits?author={}&per_page=100", repo, login);
+    let json = api.request_ok_json(reqwest::Method::GET, &path).await?;
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for commit in json.as_array().into_iter().flatten() {
+        let is_author = commit
+            .get("author")
+            .and_then(|author| author.get("login"))
+            .and_then(|author_login| author_login.as_str())
+            .is_some_and(|author_login| author_login.eq_ignore_ascii_case(login));
+        let email = commit
+            .get("commit")
+            .and_then(|commit| commit.get("author"))
+            .and_then(|author| author.get("email"))
+            .and_then(|email| email.as_str());
+        if let (true, Some(email)) = (is_author, email) {
+            *counts.entry(email.to_string()).or_default() += 1;
+        }
+    }
+    let mut emails: Vec<_> = counts.into_iter().collect();
+    emails.sort_by(|(_, a), (_, b)| b.cmp(a));
+    Ok(emails.into_iter().map(|(email, _)| email).collect())
+}
+
+pub struct PullRequestSummary {
+    pub number: u64,
+    pub author_login: String,
+    pub created_at: String,
+    pub merged_at: String,
+    /// The commit GitHub made when merging: the merge commit, the squashed commit, or the
+    /// last commit rebased.
+    pub merge_commit_sha: Option<String>,
+}
+
+/// Lists pull requests in `repo` (`owner/name`) merged between `since` and `until`, which
+/// are compared against GitHub's ISO-8601 `merged_at` timestamps (e.g. `2024-01-31`).
+pub async fn get_merged_pull_requests(
+    api: &GithubApi,
+    repo: &str,
+    since: &str,
+    until: &str,
+) -> Result<Vec<PullRequestSummary>, GHCliError> {
+    let path = format!(
+        "/repos/{}/pulls?state=closed&sort=updated&direction=desc&per_page=100",
+        repo
+    );
+    let json = api
+        .request_ok_json_paginated(reqwest::Method::GET, &path)
+        .await?;
+    let pull_requests = json
+        .into_iter()
+        .filter_map(|pr| {
+            let pr = pr.as_object()?;
+            let merged_at = pr.get("merged_at")?.as_str()?;
+            // Compare on the precision of `until` so that a bare date includes the whole day
+            let merged_prefix = &merged_at[..merged_at.len().min(until.len())];
+            if merged_at < since || merged_prefix > until {
+                return None;
+            }
+            Some(PullRequestSummary {
+                number: pr.get("number")?.as_u64()?,
+                author_login: pr
+                    .get("user")
+                    .and_then(|user| user.get("login"))
+                    .and_then(|login| login.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                created_at: pr
+                    .get("created_at")
+                    .and_then(|created_at| created_at.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                merged_at: merged_at.to_string(),
+                merge_commit_sha: pr
+                    .get("merge_commit_sha")
+                    .and_then(|sha| sha.as_str())
+                    .map(str::to_string),
+            })
+        })
+        .collect::<Vec<PullRequestSummary>>();
+    Ok(pull_requests)
+}
+
+pub async fn get_pull_request_files(
+    api: &GithubApi,
+    repo: &str,
+    number: u64,
+) -> Result<Vec<String>, GHCliError> {
+    let path = format!("/repos/{}/pulls/{}/files?per_page=100", repo, number);
+    let json = api
+        .request_ok_json_paginated(reqwest::Method::GET, &path)
+        .await?;
+    let files = json
+        .into_iter()
+        .filter_map(|file| {
+            file.as_object()
+                .and_then(|file| file.get("filename"))
+                .and_then(|filename| filename.as_str())
+                .map(|filename| filename.to_string())
+        })
+        .collect::<Vec<String>>();
+    Ok(files)
+}
+
+pub struct PullRequestReview {
+    pub login: String,
+    pub state: String,
+    pub submitted_at: Option<String>,
+}
+
+pub async fn get_pull_request_reviews(
+    api: &GithubApi,
+    repo: &str,
+    number: u64,
+) -> Result<Vec<PullRequestReview>, GHCliError> {
+    let path = format!("/repos/{}/pulls/{}/reviews?per_page=100", repo, number);
+    let json = api
+        .request_ok_json_paginated(reqwest::Method::GET, &path)
+        .await?;
+    let reviews = json
+        .into_iter()
+        .filter_map(|review| {
+            let review = review.as_object()?;
+            Some(PullRequestReview {
+                login: review.get("user")?.get("login")?.as_str()?.to_string(),
+                state: review.get("state")?.as_str()?.to_string(),
+                submitted_at: review
+                    .get("submitted_at")
+                    .and_then(|submitted_at| submitted_at.as_str())
+                    .map(|submitted_at| submitted_at.to_string()),
+            })
+        })
+        .collect::<Vec<PullRequestReview>>();
+    Ok(reviews)
+}
+
+/// Extracts `owner/name` from a GitHub remote URL (HTTPS or SSH).
+pub fn parse_github_repo(remote_url: &str) -> Option<String> {
+    let path = remote_url
+        .trim()
+        .strip_prefix("https://github.com/")
+        .or_else(|| remote_url.trim().strip_prefix("git@github.com:"))
+        .or_else(|| remote_url.trim().strip_prefix("ssh://git@github.com/"))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    (path.split('/').count() == 2).then(|| path.to_string())
+}
+
+/// Reads the `owner/name` of the GitHub repository the `origin` remote points at.
+pub fn get_origin_github_repo(cwd: &PathBuf) -> Result<Option<String>, GHCliError> {
+    let output = crate::platform::command("git")
+        .args(["remote", "get-url", "origin"])
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(parse_github_repo(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The login in a GitHub noreply address, `12345+login@users.noreply.github.com` or the
+/// older `login@users.noreply.github.com`.
+pub fn parse_noreply_login(email: &str) -> Option<String> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    if !domain.eq_ignore_ascii_case("users.noreply.github.com") {
+        return None;
+    }
+    let login = local.split_once('+').map_or(local, |(_, login)| login);
+    (!login.is_empty()).then(|| login.to_string())
+}
+
+/// Noreply addresses found in the history of the repository at `cwd`, as author emails
+/// or in `Co-authored-by` trailers, keyed by lowercased login.
+pub fn get_noreply_emails(cwd: &PathBuf) -> Result<HashMap<String, String>, GHCliError> {
+    let output = crate::platform::command("git")
+        .args([
+            "log",
+            "--format=%aE%n%(trailers:key=Co-authored-by,valueonly)",
+            "HEAD",
+        ])
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(GHCliError::Io(io::Error::other(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+    let mut emails = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // Trailers read `Name <email>`
+        let email = line
+            .rsplit_once('<')
+            .and_then(|(_, rest)| rest.strip_suffix('>'))
+            .unwrap_or(line)
+            .trim();
+        if let Some(login) = parse_noreply_login(email) {
+            emails
+                .entry(login.to_lowercase())
+                .or_insert_with(|| email.to_string());
+        }
+    }
+    Ok(emails)
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Serialize;
+
+use crate::{normalize_path, CommitInfoWithCodeowner};
+
+/// The format a heatmap is written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeatmapFormat {
+    /// A header of owners, then a row per path with its changes under each owner.
+    #[default]
+    Csv,
+    /// `{"rows": [...], "owners": [...], "cells": [[...], ...]}`, cells indexed by row then
+    /// owner.
+    Json,
+}
+
+impl FromStr for HeatmapFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(HeatmapFormat::Csv),
+            "json" => Ok(HeatmapFormat::Json),
+            _ => Err(format!(
+                "Invalid heatmap format '{}', expected 'csv' or 'json'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HeatmapFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeatmapFormat::Csv => write!(f, "csv"),
+            HeatmapFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Changed lines by path and owner.
+#[derive(Serialize)]
+pub struct Heatmap {
+    pub rows: Vec<String>,
+    /// By changes, most first. Files without owners count under `unowned`.
+    pub owners: Vec<String>,
+    /// `cells[row][owner]`.
+    pub cells: Vec<Vec<usize>>,
+}
+
+/// The label of the row for files directly in the repository root.
+const ROOT_ROW: &str = "/";
+const UNOWNED: &str = "unowned";
+
+/// Which row each changed file falls in.
+enum HeatmapRows {
+    /// The file's top-level directory.
+    Directories,
+    /// Every glob the file matches, so a file can count in several rows or in none.
+    Globs(Vec<(String, GlobMatcher)>),
+}
+
+impl HeatmapRows {
+    fn rows_of(&self, path: &str) -> Vec<String> {
+        match self {
+            HeatmapRows::Directories => vec![match path.split_once('/') {
+                Some((directory, _)) => directory.to_string(),
+                None => ROOT_ROW.to_string(),
+            }],
+            HeatmapRows::Globs(globs) => globs
+                .iter()
+                .filter(|(_, matcher)| matcher.is_match(path))
+                .map(|(glob, _)| glob.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Adds up the changed lines in `commits` by owner, with a row per glob in `row_globs`, or
+/// per top-level directory when there are none. A change to a file with several owners
+/// counts in full for each, as in the owner analysis.
+pub fn build_heatmap(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    row_globs: &[String],
+) -> Result<Heatmap, io::Error> {
+    let rows = if row_globs.is_empty() {
+        HeatmapRows::Directories
+    } else {
+        HeatmapRows::Globs(
+            row_globs
+                .iter()
+                .map(|glob| {
+                    let matcher = GlobBuilder::new(&normalize_path(glob))
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+                        .compile_matcher();
+                    Ok((glob.clone(), matcher))
+                })
+                .collect::<Result<_, io::Error>>()?,
+        )
+    };
+
+    let mut changes: BTreeMap<String, HashMap<String, usize>> = BTreeMap::new();
+    let mut owner_totals: HashMap<String, usize> = HashMap::new();
+    for commit_result in commits {
+        let commit = commit_result?;
+        for change in &commit.file_changes {
+            let lines = (change.insertions + change.deletions) as usize;
+            let owners = match &change.codeowners {
+                Some(owners) if !owners.is_empty() => owners.clone(),
+                _ => vec![UNOWNED.to_string()],
+            };
+            for row in rows.rows_of(&change.path) {
+                let row_changes = changes.entry(row).or_default();
+                for owner in &owners {
+                    *row_changes.entry(owner.clone()).or_default() += lines;
+                    *owner_totals.entry(owner.clone()).or_default() += lines;
+                }
+            }
+        }
+    }
+
+    let mut owners: Vec<(String, usize)> = owner_totals.into_iter().collect();
+    owners.sort_by(|(a, total_a), (b, total_b)| total_b.cmp(total_a).then(a.cmp(b)));
+    let owners: Vec<String> = owners.into_iter().map(|(owner, _)| owner).collect();
+    // Globs that matched nothing still get a row, so the rows are the ones asked for
+    if let HeatmapRows::Globs(globs) = &rows {
+        for (glob, _) in globs {
+            changes.entry(glob.clone()).or_default();
+        }
+    }
+    let (rows, cells) = changes
+        .into_iter()
+        .map(|(row, row_changes)| {
+            let cells = owners
+                .iter()
+                .map(|owner| row_changes.get(owner).copied().unwrap_or(0))
+                .collect();
+            (row, cells)
+        })
+        .unzip();
+    Ok(Heatmap {
+        rows,
+        owners,
+        cells,
+    })
+}
+
+impl Heatmap {
+    pub fn write(&self, writer: impl Write, format: HeatmapFormat) -> Result<(), io::Error> {
+        match format {
+            HeatmapFormat::Csv => self.write_csv(writer),
+            HeatmapFormat::Json => {
+                let mut writer = writer;
+                serde_json::to_writer(&mut writer, self)?;
+                writeln!(writer)
+            }
+        }
+    }
+
+    fn write_csv(&self, writer: impl Write) -> Result<(), io::Error> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(std::iter::once("path").chain(self.owners.iter().map(String::as_str)))?;
+        for (row, cells) in self.rows.iter().zip(&self.cells) {
+            csv.write_record(
+                std::iter::once(row.clone()).chain(cells.iter().map(|cell| cell.to_string())),
+            )?;
+        }
+        csv.flush()
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::CheckMode;
+
+const HOOK_MARKER: &str = "# Installed by bound install-hooks";
+
+/// Resolves the hooks directory, honouring `core.hooksPath` and linked worktrees.
+fn git_hooks_dir(cwd: &PathBuf) -> Result<PathBuf, io::Error> {
+    let output = crate::platform::command("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    let hooks_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(if hooks_dir.is_absolute() {
+        hooks_dir
+    } else {
+        cwd.join(hooks_dir)
+    })
+}
+
+fn pre_push_hook_script(bound_path: &Path, codeowners_path: &Path, mode: CheckMode) -> String {
+    format!(
+        r#"#!/bin/sh
+{marker}
+# Warns (or blocks, see `git config bound.prePushMode warn|block`) when pushed commits
+# modify files owned by teams the author is not a member of.
+
+mode=$(git config --get bound.prePushMode || echo "{mode}")
+zero=$(git hash-object --stdin </dev/null | tr '[0-9a-f]' '0')
+status=0
+
+while read -r local_ref local_sha remote_ref remote_sha; do
+    if [ "$local_sha" = "$zero" ]; then
+        continue
+    fi
+    if [ "$remote_sha" = "$zero" ]; then
+        base=$(git rev-parse --abbrev-ref origin/HEAD 2>/dev/null || echo origin/main)
+        range="$base..$local_sha"
+    else
+        range="$remote_sha..$local_sha"
+    fi
+    if [ "$mode" = "block" ]; then
+        "{bound}" check --ref "$range" --codeowners-path "{codeowners}" --block || status=1
+    else
+        "{bound}" check --ref "$range" --codeowners-path "{codeowners}"
+    fi
+done
+
+exit $status
+"#,
+        marker = HOOK_MARKER,
+        mode = mode,
+        bound = bound_path.display(),
+        codeowners = codeowners_path.display(),
+    )
+}
+
+/// Writes a pre-push hook running `bound check` over the pushed range. An existing hook
+/// that was not installed by bound is only replaced when `force` is set.
+pub fn install_pre_push_hook(
+    cwd: &PathBuf,
+    bound_path: &Path,
+    codeowners_path: &Path,
+    mode: CheckMode,
+    force: bool,
+) -> Result<PathBuf, io::Error> {
+    let hooks_dir = git_hooks_dir(cwd)?;
+    fs::create_dir_all(&hooks_dir)?;
+    let hook_path = hooks_dir.join("pre-push");
+
+    if hook_path.exists() && !force {
+        let existing = fs::read_to_string(&hook_path)?;
+        if !existing.contains(HOOK_MARKER) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists and was not installed by bound",
+                    hook_path.display()
+                ),
+            ));
+        }
+    }
+
+    fs::write(
+        &hook_path,
+        pre_push_hook_script(bound_path, codeowners_path, mode),
+    )?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(hook_path)
+}
@@ -0,0 +1,77 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+
+use crate::remote::run_git;
+
+pub(crate) fn git_output(args: &[&str], cwd: &Path) -> Result<String, io::Error> {
+    let output = crate::platform::command("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A private repository for a single run, with the same refs and HEAD as the one it was
+/// made from, so concurrent runs against one repository don't contend on its locks.
+///
+/// Objects are borrowed from the original through `objects/info/alternates` rather than
+/// copied, which makes creating one cheap; like a remote clone it has no checkout. The
+/// repository is deleted when this is dropped.
+pub struct IsolatedRepo {
+    dir: TempDir,
+}
+
+impl IsolatedRepo {
+    pub fn new(source: &Path) -> Result<Self, io::Error> {
+        // Git runs inside the new repository, where a relative source would mean itself
+        let source = &source.canonicalize()?;
+        let dir = tempfile::Builder::new().prefix("bound-").tempdir()?;
+        let path = dir.path();
+        let common_dir = PathBuf::from(git_output(
+            &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+            source,
+        )?);
+
+        run_git(&["init", "--quiet"], path)?;
+        let git_dir = path.join(".git");
+        fs::write(
+            git_dir.join("objects/info/alternates"),
+            format!("{}\n", common_dir.join("objects").display()),
+        )?;
+        // A shallow source's boundary commits have parents that aren't there
+        if common_dir.join("shallow").exists() {
+            fs::copy(common_dir.join("shallow"), git_dir.join("shallow"))?;
+        }
+        let source_str = source.to_string_lossy();
+        run_git(
+            &[
+                "fetch",
+                "--quiet",
+                "--no-tags",
+                "--update-head-ok",
+                source_str.as_ref(),
+                "+refs/*:refs/*",
+            ],
+            path,
+        )?;
+        if let Ok(head) = git_output(&["rev-parse", "--verify", "HEAD"], source) {
+            run_git(&["update-ref", "--no-deref", "HEAD", &head], path)?;
+        }
+        Ok(IsolatedRepo { dir })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+}
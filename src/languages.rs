@@ -0,0 +1,190 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+};
+
+use serde::Serialize;
+
+use crate::FileChangePaths;
+
+/// The language of files no extension or name identifies.
+pub const OTHER_LANGUAGE: &str = "Other";
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("bash", "Shell"),
+    ("c", "C"),
+    ("cc", "C++"),
+    ("cfg", "Config"),
+    ("conf", "Config"),
+    ("cpp", "C++"),
+    ("cs", "C#"),
+    ("css", "CSS"),
+    ("cxx", "C++"),
+    ("dart", "Dart"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("go", "Go"),
+    ("gradle", "Gradle"),
+    ("graphql", "GraphQL"),
+    ("h", "C"),
+    ("hpp", "C++"),
+    ("hs", "Haskell"),
+    ("html", "HTML"),
+    ("ini", "Config"),
+    ("java", "Java"),
+    ("js", "JavaScript"),
+    ("json", "JSON"),
+    ("jsx", "JavaScript"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("lua", "Lua"),
+    ("m", "Objective-C"),
+    ("md", "Markdown"),
+    ("mjs", "JavaScript"),
+    ("mm", "Objective-C"),
+    ("php", "PHP"),
+    ("pl", "Perl"),
+    ("proto", "Protocol Buffers"),
+    ("ps1", "PowerShell"),
+    ("py", "Python"),
+    ("r", "R"),
+    ("rb", "Ruby"),
+    ("rs", "Rust"),
+    ("rst", "reStructuredText"),
+    ("scala", "Scala"),
+    ("scss", "CSS"),
+    ("sh", "Shell"),
+    ("sql", "SQL"),
+    ("swift", "Swift"),
+    ("tf", "Terraform"),
+    ("toml", "TOML"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("txt", "Text"),
+    ("vue", "Vue"),
+    ("xml", "XML"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("zsh", "Shell"),
+];
+
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("BUILD", "Starlark"),
+    ("CMakeLists.txt", "CMake"),
+    ("CODEOWNERS", "Config"),
+    ("Dockerfile", "Dockerfile"),
+    ("Gemfile", "Ruby"),
+    ("Makefile", "Makefile"),
+    ("Rakefile", "Ruby"),
+];
+
+/// Classifies files into languages by extension, or by name for files such as
+/// `Dockerfile` that have none.
+pub struct LanguageMap {
+    extensions: HashMap<String, String>,
+    file_names: HashMap<String, String>,
+}
+
+impl Default for LanguageMap {
+    fn default() -> Self {
+        let owned = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(key, language)| (key.to_string(), language.to_string()))
+                .collect()
+        };
+        LanguageMap {
+            extensions: owned(EXTENSIONS),
+            file_names: owned(FILE_NAMES),
+        }
+    }
+}
+
+impl LanguageMap {
+    /// The built-in map with `overrides` from the `[languages]` table of `bound.toml`
+    /// added, where keys starting with a dot are extensions (`".bzl" = "Starlark"`) and
+    /// others file names (`"WORKSPACE" = "Starlark"`).
+    pub fn with_overrides(overrides: &BTreeMap<String, String>) -> Self {
+        let mut map = LanguageMap::default();
+        for (key, language) in overrides {
+            match key.strip_prefix('.') {
+                Some(extension) => map
+                    .extensions
+                    .insert(extension.to_lowercase(), language.clone()),
+                None => map.file_names.insert(key.clone(), language.clone()),
+            };
+        }
+        map
+    }
+
+    /// The language of the file at `path`, or [`OTHER_LANGUAGE`].
+    pub fn language(&self, path: &str) -> &str {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if let Some(language) = self.file_names.get(name) {
+            return language;
+        }
+        name.rsplit_once('.')
+            .filter(|(stem, _)| !stem.is_empty())
+            .and_then(|(_, extension)| self.extensions.get(&extension.to_lowercase()))
+            .map_or(OTHER_LANGUAGE, String::as_str)
+    }
+}
+
+/// Sets the language of each file change, so the analyses break their totals down by it.
+pub fn classify_languages<T: FileChangePaths>(
+    commits: impl Iterator<Item = Result<T, io::Error>>,
+    languages: LanguageMap,
+) -> impl Iterator<Item = Result<T, io::Error>> {
+    commits.map(move |commit| {
+        let mut commit = commit?;
+        commit.classify_languages(&languages);
+        Ok(commit)
+    })
+}
+
+/// Changed lines in one language, by the owning team and by everyone else.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LanguageChanges {
+    pub language: String,
+    pub team_changes: usize,
+    pub others_changes: usize,
+}
+
+impl LanguageChanges {
+    pub fn total_changes(&self) -> usize {
+        self.team_changes + self.others_changes
+    }
+}
+
+/// Running per-language totals, listed most changed first once finished.
+#[derive(Default)]
+pub(crate) struct LanguageTotals {
+    totals: HashMap<String, LanguageChanges>,
+}
+
+impl LanguageTotals {
+    pub(crate) fn add(&mut self, language: &str, changes: usize, by_team: bool) {
+        let totals = self
+            .totals
+            .entry(language.to_string())
+            .or_insert_with(|| LanguageChanges {
+                language: language.to_string(),
+                ..LanguageChanges::default()
+            });
+        if by_team {
+            totals.team_changes += changes;
+        } else {
+            totals.others_changes += changes;
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<LanguageChanges> {
+        let mut languages: Vec<LanguageChanges> = self.totals.into_values().collect();
+        languages.sort_by(|a, b| {
+            b.total_changes()
+                .cmp(&a.total_changes())
+                .then_with(|| a.language.cmp(&b.language))
+        });
+        languages
+    }
+}
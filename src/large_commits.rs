@@ -0,0 +1,93 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::{FileChangePaths, WarningCategory, Warnings};
+
+/// What happens to commits changing more lines than `--max-commit-changes`, such as
+/// reformatting or vendoring commits that would otherwise swamp every owner's numbers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LargeCommitPolicy {
+    /// Scale the commit's line counts down in proportion to add up to the limit.
+    #[default]
+    Cap,
+    /// Leave the commit out.
+    Drop,
+    /// Count the commit in full, only reporting it.
+    Flag,
+}
+
+impl FromStr for LargeCommitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cap" => Ok(LargeCommitPolicy::Cap),
+            "drop" => Ok(LargeCommitPolicy::Drop),
+            "flag" => Ok(LargeCommitPolicy::Flag),
+            _ => Err(format!(
+                "Invalid large commit policy '{}', expected 'cap', 'drop' or 'flag'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LargeCommitPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LargeCommitPolicy::Cap => write!(f, "cap"),
+            LargeCommitPolicy::Drop => write!(f, "drop"),
+            LargeCommitPolicy::Flag => write!(f, "flag"),
+        }
+    }
+}
+
+/// Applies `policy` to commits changing more than `max` lines, recording each of them in
+/// `warnings`.
+pub fn limit_large_commits<T, I>(
+    commits: I,
+    max: usize,
+    policy: LargeCommitPolicy,
+    warnings: &Warnings,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    let warnings = warnings.clone();
+    commits.filter_map(move |commit_result| {
+        let mut commit = match commit_result {
+            Ok(commit) => commit,
+            Err(e) => return Some(Err(e)),
+        };
+        let changes: usize = commit
+            .path_changes()
+            .iter()
+            .map(|change| (change.insertions + change.deletions) as usize)
+            .sum();
+        if changes <= max {
+            return Some(Ok(commit));
+        }
+        let action = match policy {
+            LargeCommitPolicy::Cap => "capped",
+            LargeCommitPolicy::Drop => "dropped",
+            LargeCommitPolicy::Flag => "counted in full",
+        };
+        warnings.add(
+            WarningCategory::LargeCommit,
+            format!(
+                "{} changed {} lines, {}",
+                commit.commit_id(),
+                changes,
+                action
+            ),
+        );
+        match policy {
+            LargeCommitPolicy::Cap => {
+                commit.cap_line_changes(max);
+                Some(Ok(commit))
+            }
+            LargeCommitPolicy::Drop => None,
+            LargeCommitPolicy::Flag => Some(Ok(commit)),
+        }
+    })
+}
@@ -1,16 +1,200 @@
+mod aliases;
 mod analyze;
+mod analyze_reviews;
+mod annotate;
+mod anonymize;
+mod attributes;
+mod audit;
+mod batch;
+mod blame;
+mod builder;
+mod check;
+mod coauthors;
+mod codeowners_diff;
+mod codeowners_rules;
 mod commit;
+mod compare;
+mod components;
+mod config;
+mod dates;
+mod digest;
+mod export;
+mod file_history;
+mod filter;
+mod gate;
 mod github;
+mod heatmap;
+mod hooks;
+mod isolate;
+mod languages;
+mod large_commits;
+mod logging;
+mod membership_history;
+mod membership_provider;
+mod membership_rules;
+mod metrics;
+mod names;
+mod newcomers;
+mod notify;
+mod org_export;
+mod output;
 mod owner;
+mod owner_files;
+mod owners_tree;
+mod period;
+mod platform;
+mod preview;
+mod progress;
+mod projects;
+mod pull_requests;
+mod refresh;
+mod remote;
+mod response_cache;
+mod review_load;
+mod scorecard;
+mod security;
+mod sizes;
+mod skip;
+mod stale;
+mod suggest;
+mod summary;
+mod tags;
+mod team_size;
+mod user_cache;
+mod warnings;
+mod watch;
 
-pub use analyze::{analyze_by_contributor, analyze_by_owner, ContributorToOwnerInfo, OwnerInfo};
-pub use commit::{git_file_versions, git_log_commits, read_file_at_commit, CommitInfo, FileChange};
+pub use aliases::{alias_commit_owners, read_owner_renames_from_tsv, OwnerAliases};
+pub use analyze::{
+    analyze_all, analyze_by_contributor, analyze_by_owner, analyze_coverage,
+    analyze_ownership_distribution, AdjustedWeight, AnalysisOptions, AnalysisSummary,
+    CombinedAnalysis, ContributionsByOwnerInfo, ContributorAnalysis, ContributorAnalyzer,
+    ContributorInfo, ContributorToOwnerInfo, ContributorTotals, CoverageInfo, MetricConfig,
+    OwnerAnalysis, OwnerAnalyzer, OwnerAttribution, OwnerInfo, OwnershipBucketInfo,
+};
+pub use analyze_reviews::{analyze_reviews, OwnerReviewInfo, PullRequestReviewData};
+pub use annotate::{annotate_commit, format_annotated_commit, OwnerAnnotation};
+pub use anonymize::Pseudonyms;
+pub use attributes::{get_linguist_attributes_at_commit, skip_generated_files, LinguistAttributes};
+pub use audit::{append_run_record, git_head, os_user, redact_args, DigestWriter, RunRecord};
+pub use batch::CommitBatches;
+pub use blame::{blame_ownership, BlameAuthor, BlameOwnership};
+pub use builder::{Analysis, CommitsWithCodeowners};
+pub use check::{find_ownership_violations, CheckFormat, CheckMode, OwnershipViolation};
+pub use coauthors::{attribute_co_authors, CoauthorMode};
+pub use codeowners_diff::{
+    diff_codeowners, diff_codeowners_between, resolve_revision_or_date, CodeownersDiff,
+    CodeownersRuleChange, FileOwnerChange,
+};
+pub use codeowners_rules::{
+    find_shadowed_rules, parse_codeowners_rules, pattern_covers, CodeownersRule, ShadowedRule,
+};
+pub use commit::{
+    git_file_versions, git_log_commits, git_tree_files, git_worktree_changes, read_file_at_commit,
+    ChangeKind, CommitAttribution, CommitDate, CommitInfo, CommitMessage, FileChange,
+    GitLogOptions, GitRetry, MergeMode, RenameDetection,
+};
+pub use compare::{
+    compare_contributor_analyses, compare_owner_analyses, ComparisonWindow,
+    ContributorAnalysisComparison, ContributorComparison, MetricChange, OwnerAnalysisComparison,
+    OwnerComparison,
+};
+pub use components::{map_commit_components, ComponentMap, OwnerGrouping};
+pub use config::{load_config, BoundConfig, ConfigError};
+pub use dates::{check_date_range, format_date, resolve_date, DateBound, DateError};
+pub use digest::{build_owner_digests, DigestCommit, DigestFormat, OwnerDigest};
+pub use export::{export_to_sqlite, ExportError, ExportStats};
+pub use file_history::{file_history, FileHistoryEntry};
+pub use filter::{
+    count_binary_changes, filter_commit_paths, AuthorSelection, FileChangePaths, PathChange,
+    PathFilter, BOT_AUTHORS,
+};
+pub use gate::{evaluate_gate, GateBound, GateCheck, GateThresholds, Percentage};
 pub use github::{
-    get_github_org_logins, get_github_team_members, get_github_team_slugs, get_token,
-    get_user_info, GHCliError, GithubApi,
+    descendant_teams, get_commit_author_emails, get_github_org_logins, get_github_team_members,
+    get_github_team_members_nested, get_github_team_slugs, get_github_teams,
+    get_merged_pull_requests, get_noreply_emails, get_origin_github_repo, get_pull_request_files,
+    get_pull_request_reviews, get_team_membership_events, get_token, get_user_info,
+    get_user_info_cached, get_users_info_cached, parse_github_repo, parse_noreply_login,
+    GHCliError, GithubApi, GithubApiUsage, GithubAppAuth, GithubAuth, GithubTeam,
+    PullRequestReview, PullRequestSummary, TeamMembership, TeamMembershipEvent,
+};
+pub use heatmap::{build_heatmap, Heatmap, HeatmapFormat};
+pub use hooks::install_pre_push_hook;
+pub use isolate::IsolatedRepo;
+pub use languages::{classify_languages, LanguageChanges, LanguageMap, OTHER_LANGUAGE};
+pub use large_commits::{limit_large_commits, LargeCommitPolicy};
+pub use logging::{init_logging, LogFormat};
+pub use membership_history::dated_team_memberships;
+pub use membership_provider::{
+    GithubOrgProvider, GithubOrgStats, MembershipProvider, TsvMembershipProvider,
+};
+pub use membership_rules::{git_authors, MembershipRules};
+pub use metrics::{DerivedMetric, MetricError};
+pub use names::NameNormalization;
+pub use newcomers::{owner_contributors, NewcomerBreakdown, OwnerContributors};
+pub use notify::{
+    build_owner_notifications, format_notification_email, send_notification,
+    unowned_paths_by_adjacent_owner, OwnerNotification,
+};
+pub use org_export::{ExportUser, OrgExport};
+pub use output::{
+    github_annotation, write_markdown_table, write_ndjson, write_ndjson_stream, AtomicOutput,
+    OutputFormat, PeriodRecord, RepoRecord,
 };
 pub use owner::{
-    get_all_codeowners, get_codeowners_at_commit, git_log_commits_with_codeowners,
-    read_memberships_from_tsv, write_memberships_to_tsv, AuthorCodeownerMemberships,
-    CommitInfoWithCodeowner, FileChangeWithCodeowner,
+    find_codeowners_at_commit, find_worktree_codeowners, get_all_codeowners,
+    get_codeowners_at_commit, get_owners_at_commit, get_worktree_codeowners,
+    git_log_commits_with_codeowners, read_memberships_from_tsv, worktree_commit_with_codeowners,
+    write_memberships_to_tsv, AuthorCodeownerMemberships, AuthorMembership, CodeownersMode,
+    CodeownersSource, CommitInfoWithCodeowner, FileChangeWithCodeowner,
+};
+pub use owner_files::{
+    analyze_owner_files, OwnerFilesAnalysis, OwnerPathInfo, PathContributorInfo,
+};
+pub use owners_tree::{build_owners_tree, owners_tree_at, OwnersTreeNode};
+pub use period::{group_commits_by_period, period_key, PeriodGrouping};
+pub use platform::normalize_path;
+pub use preview::{
+    apply_codeowners_patch, preview_codeowners_change, CodeownersPreview, OwnershipImpact,
+    PatchError,
+};
+pub use progress::ProgressSink;
+pub use projects::{
+    analyze_by_project, Project, ProjectAnalysis, ProjectAnalyzer, ProjectIndex, ProjectInfo,
+    ProjectKind, ProjectOwnerInfo,
+};
+pub use pull_requests::{group_commits_by_pull_request, pull_request_numbers, PullRequestSource};
+pub use refresh::{
+    merge_team_member_changes, read_team_member_state, write_team_member_state, RefreshStats,
+    TeamMemberRecord,
+};
+pub use remote::{
+    deepen_shallow_clone, fetch_remote, is_remote_url, remote_cache_path, repo_name,
+    shallow_boundary,
+};
+pub use response_cache::ResponseCache;
+pub use review_load::{analyze_review_load, ReviewLoadInfo};
+pub use scorecard::{
+    build_scorecards, owned_files_by_owner, OwnerScorecard, ScoreComponent, ScorecardConfig,
+    ScorecardMetric,
+};
+pub use security::{find_sensitive_changes, SensitiveChange};
+pub use sizes::FileSize;
+pub use skip::skip_errors;
+pub use stale::{analyze_stale_ownership, StaleOwnershipInfo};
+pub use suggest::{suggest_owners, OwnerSuggestion};
+pub use summary::{summarize_repository, OutsideContributionShare, RepositorySummary};
+pub use tags::{exclude_tagged_commits, format_tags, tag_commit, CommitTag};
+pub use team_size::{
+    analyze_team_size_history, append_team_size_history, read_team_size_history,
+    snapshot_team_sizes, team_size_on, TeamSizeSnapshot, TeamSizeTrend,
+};
+pub use user_cache::UserInfoCache;
+pub use warnings::{
+    invalid_codeowners_lines, warn_binary_files, warn_invalid_codeowners, warn_unmatched_authors,
+    WarningCategory, WarningSummary, Warnings,
+};
+pub use watch::{
+    fetch_watched_repository, resolve_watched_commit, watch_delta, WatchDelta, WatchInterval,
 };
@@ -1,16 +1,65 @@
 use anyhow::Result;
 
 use bound::{
-    get_github_team_members, get_github_team_slugs, get_user_info, git_log_commits,
-    read_memberships_from_tsv, AuthorCodeownerMemberships,
+    get_user_info_cached, git_log_commits, init_logging, read_memberships_from_tsv, AdjustedWeight,
+    AnalysisOptions, AnalysisSummary, AtomicOutput, AuthorCodeownerMemberships, AuthorMembership,
+    AuthorSelection, BlameOwnership, BoundConfig, CheckFormat, CheckMode, CoauthorMode,
+    CodeownersDiff, CodeownersMode, CodeownersPreview, CodeownersSource, CommitAttribution,
+    CommitDate, CommitInfoWithCodeowner, CommitTag, ComparisonWindow, ComponentMap,
+    ContributorAnalysis, ContributorAnalysisComparison, ContributorComparison, ContributorInfo,
+    ContributorToOwnerInfo, CoverageInfo, DateBound, DateError, DerivedMetric, DigestFormat,
+    DigestWriter, FileChangePaths, GateBound, GateCheck, GateThresholds, GitLogOptions, GitRetry,
+    GithubAppAuth, GithubAuth, GithubOrgProvider, GithubOrgStats, HeatmapFormat, LanguageMap,
+    LargeCommitPolicy, LogFormat, MembershipProvider, MembershipRules, MergeMode, MetricChange,
+    MetricConfig, NameNormalization, OutputFormat, OwnerAliases, OwnerAnalysis,
+    OwnerAnalysisComparison, OwnerAnalyzer, OwnerAttribution, OwnerContributors, OwnerDigest,
+    OwnerFilesAnalysis, OwnerGrouping, OwnerReviewInfo, OwnerScorecard, OwnerSuggestion,
+    OwnersTreeNode, PathFilter, Percentage, PeriodGrouping, PeriodRecord, ProgressSink,
+    ProjectAnalysis, ProjectInfo, Pseudonyms, PullRequestReviewData, PullRequestSource,
+    RenameDetection, RepoRecord, RepositorySummary, ResponseCache, RunRecord, ScorecardMetric,
+    SensitiveChange, StaleOwnershipInfo, TeamMembership, TeamSizeTrend, UserInfoCache,
+    WarningCategory, Warnings, WatchDelta, WatchInterval,
+};
+use clap::{Args, Parser, Subcommand};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
-use clap::{Parser, Subcommand};
-use std::{collections::HashMap, path::PathBuf};
 
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
 
 use std::collections::HashSet;
 
+/// Set by `--quiet`, which leaves only the command's output, warnings and errors.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints progress or status text to stderr, keeping stdout for the command's output, unless
+/// `--quiet` was given.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// A progress bar, or spinner without a length, drawn on stderr unless `--quiet` was given.
+fn progress_bar(len: Option<u64>) -> ProgressBar {
+    let target = if QUIET.load(Ordering::Relaxed) {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    };
+    ProgressBar::with_draw_target(len, target)
+}
+
 pub fn create_author_codeowner_map(
     memberships: Vec<AuthorCodeownerMemberships>,
 ) -> HashMap<(String, String), HashSet<String>> {
@@ -29,547 +78,5842 @@ pub fn create_author_codeowner_map(
     map
 }
 
-async fn get_all_org_members(
-    api: &GithubApi,
-    org: &str,
-) -> Result<Vec<AuthorCodeownerMemberships>> {
+/// A spinner shown while a provider fetches team members.
+fn team_fetch_progress() -> ProgressBar {
     let progress_style = ProgressStyle::default_spinner()
         .template("{spinner:.green} {msg}")
         .unwrap();
-    let progress = ProgressBar::new_spinner();
+    let progress = progress_bar(None);
     progress.set_style(progress_style);
-    progress.set_message("Fetching GitHub team slugs...");
+    progress.enable_steady_tick(std::time::Duration::from_millis(100));
+    progress.set_message("Fetching codeowning team members...");
+    progress
+}
+
+fn report_org_fetch(stats: &GithubOrgStats, org: &str) {
+    status!(
+        "Fetched {} Github Teams in {}, eliminated {} non-codeowning teams.",
+        stats.teams,
+        org,
+        stats.teams - stats.codeowning_teams,
+    );
+    if stats.emails_from_history + stats.emails_from_commits > 0 {
+        status!(
+            "Found emails for {} members without a public one: {} from noreply addresses in the history, {} from the commits API.",
+            stats.emails_from_history + stats.emails_from_commits,
+            stats.emails_from_history,
+            stats.emails_from_commits
+        );
+    }
+}
 
-    let teams = get_github_team_slugs(api, org).await?;
+/// The repository whose commits API is asked for the email of members without a public
+/// one: `repo`, or the origin remote's.
+fn commits_repo(repo: &Option<String>, lookup_commits: bool) -> Result<Option<String>> {
+    Ok(match repo {
+        _ if !lookup_commits => None,
+        Some(repo) => Some(repo.clone()),
+        None => bound::get_origin_github_repo(&PathBuf::from("."))?,
+    })
+}
 
-    progress.finish_with_message("GitHub team slugs fetched successfully.");
+fn print_analysis_summary(out: &mut dyn Write, summary: &AnalysisSummary) -> Result<()> {
+    let share = |changes: usize| {
+        if summary.total_changes() > 0 {
+            changes as f64 * 100.0 / summary.total_changes() as f64
+        } else {
+            0.0
+        }
+    };
+    writeln!(out, "Summary:")?;
+    writeln!(out, "  Commits: {}", summary.total_commits)?;
+    writeln!(
+        out,
+        "  Changes: {} (+{}, -{})",
+        summary.total_changes(),
+        summary.total_insertions,
+        summary.total_deletions
+    )?;
+    writeln!(
+        out,
+        "  Team Changes: {} ({:.1}%)",
+        summary.changes_by_team,
+        share(summary.changes_by_team)
+    )?;
+    writeln!(
+        out,
+        "  Others Changes: {} ({:.1}%)",
+        summary.changes_by_others,
+        share(summary.changes_by_others)
+    )?;
+    writeln!(
+        out,
+        "  Unowned Changes: {} ({:.1}%)",
+        summary.unowned_changes,
+        share(summary.unowned_changes)
+    )?;
+    if summary.binary_changes > 0 {
+        writeln!(out, "  Binary File Changes: {}", summary.binary_changes)?;
+    }
+    writeln!(out, "  Coverage: {:.1}%", summary.coverage() * 100.0)?;
+    writeln!(out)?;
+    Ok(())
+}
 
-    let num_teams = teams.len();
+/// The repository and period a part of the owner or contributor output is for, when a
+/// run is split up by them. Each one set becomes a leading TSV column and an NDJSON field.
+#[derive(Clone, Copy, Default)]
+struct OutputGroup<'a> {
+    repo: Option<&'a str>,
+    period: Option<&'a str>,
+}
 
-    let progress_style = ProgressStyle::default_spinner()
-        .template("{spinner:.green} {msg}")
-        .unwrap();
-    let progress = ProgressBar::new_spinner();
-    progress.set_style(progress_style);
-    progress.set_message("Fetching all codeowners...");
+impl OutputGroup<'_> {
+    fn columns(with_repo: bool, with_period: bool) -> Vec<&'static str> {
+        [with_repo.then_some("repo"), with_period.then_some("period")]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
 
-    let all_codeowners = bound::get_all_codeowners(&std::path::PathBuf::from("."))?;
+    fn values(&self) -> Vec<String> {
+        [self.repo, self.period]
+            .into_iter()
+            .flatten()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.repo.is_none() && self.period.is_none()
+    }
 
-    progress.finish_with_message("All codeowners fetched successfully.");
+    /// Heading for the part in text and markdown output.
+    fn heading(&self) -> String {
+        self.values().join(" / ")
+    }
+}
 
-    // Filter teams to only include those that are codeowners
-    let teams: Vec<String> = teams
+fn print_analysis_summary_tsv(
+    out: &mut dyn Write,
+    summary: &AnalysisSummary,
+    group: OutputGroup,
+) -> Result<()> {
+    let labels: String = [("repo", group.repo), ("period", group.period)]
         .into_iter()
-        .filter(|team| all_codeowners.contains(&format!("@{}/{}", org, team)))
+        .filter_map(|(name, value)| value.map(|value| format!("{}={} ", name, value)))
         .collect();
+    writeln!(out,
+        "# {}commits={} changes={} team_changes={} others_changes={} unowned_changes={} binary_changes={} coverage={:.4}",
+        labels,
+        summary.total_commits,
+        summary.total_changes(),
+        summary.changes_by_team,
+        summary.changes_by_others,
+        summary.unowned_changes,
+        summary.binary_changes,
+        summary.coverage()
+    )?;
+    Ok(())
+}
 
-    println!(
-        "Fetched {} Github Teams in {}, eliminated {} non-codeowning teams.",
-        num_teams,
-        org,
-        num_teams - teams.len(),
-    );
+fn print_owner_analysis(
+    out: &mut dyn Write,
+    analysis: &OwnerAnalysis,
+    adjusted: bool,
+) -> Result<()> {
+    print_analysis_summary(out, &analysis.summary)?;
+    for owner_info in &analysis.owners {
+        writeln!(out, "Owner: {}", owner_info.owner)?;
+        writeln!(
+            out,
+            "  Team Changes: {} (+{}, -{})",
+            owner_info.total_insertions_by_team + owner_info.total_deletions_by_team,
+            owner_info.total_insertions_by_team,
+            owner_info.total_deletions_by_team
+        )?;
+        writeln!(
+            out,
+            "  Team Commits: {:.2}",
+            owner_info.total_commits_by_team
+        )?;
+        if adjusted {
+            writeln!(
+                out,
+                "  Adjusted Team Changes: {:.2} (Commits: {:.2})",
+                owner_info.adjusted_changes_by_team, owner_info.adjusted_commits_by_team
+            )?;
+        }
+        writeln!(
+            out,
+            "  Others Changes: {} (+{}, -{})",
+            owner_info.total_insertions_by_others + owner_info.total_deletions_by_others,
+            owner_info.total_insertions_by_others,
+            owner_info.total_deletions_by_others
+        )?;
+        writeln!(
+            out,
+            "  Others Commits: {:.2}",
+            owner_info.total_commits_by_others
+        )?;
+        match &owner_info.newcomers {
+            Some(newcomers) => writeln!(
+                out,
+                "  Contributors: {} ({} first-time, {:.1}% of changes)",
+                owner_info.contributors,
+                newcomers.first_time_contributors,
+                newcomers.first_time_share() * 100.0
+            )?,
+            None => writeln!(out, "  Contributors: {}", owner_info.contributors)?,
+        }
+        if !owner_info.languages.is_empty() {
+            writeln!(out, "  Changes by Language:")?;
+            for language in &owner_info.languages {
+                writeln!(
+                    out,
+                    "    {}: {} (team {}, others {})",
+                    language.language,
+                    language.total_changes(),
+                    language.team_changes,
+                    language.others_changes
+                )?;
+            }
+        }
+        if adjusted {
+            writeln!(
+                out,
+                "  Adjusted Others Changes: {:.2} (Commits: {:.2})",
+                owner_info.adjusted_changes_by_others, owner_info.adjusted_commits_by_others
+            )?;
+        }
+        writeln!(out, "  Top Outside Contributors by Changes:")?;
+        for contributor in &owner_info.top_outside_contributors_by_changes {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out, "  Top Outside Contributors by Commits:")?;
+        for contributor in &owner_info.top_outside_contributors_by_commits {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out, "  Top Team Contributors by Changes:")?;
+        for contributor in &owner_info.top_team_contributors_by_changes {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out, "  Top Team Contributors by Commits:")?;
+        for contributor in &owner_info.top_team_contributors_by_commits {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
 
-    let mut all_members = HashSet::new();
-    let mut team_members = HashMap::new();
-    let progress = ProgressBar::new(teams.len() as u64);
-    let pb_style = ProgressStyle::default_bar()
-        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} teams")
-        .unwrap_or_else(|_| ProgressStyle::default_bar());
-    progress.set_style(pb_style);
-    for team in teams {
-        let members = get_github_team_members(api, org, &team).await?;
-        all_members.extend(members.iter().cloned());
-        team_members.insert(team, members);
-        progress.inc(1);
+/// Memberships and the owner whose members are kept.
+type AuthorFilter = Option<(AuthorMembership, String)>;
+
+fn is_filtered_out(filter_authors: &AuthorFilter, contributor_info: &ContributorInfo) -> bool {
+    filter_authors.as_ref().is_some_and(|(memberships, owner)| {
+        !memberships.is_codeowner(
+            &contributor_info.author_name,
+            &contributor_info.author_email,
+            owner,
+        )
+    })
+}
+
+fn metric_columns(metrics: &[DerivedMetric], fields: &[(&str, f64)]) -> Result<Vec<String>> {
+    metrics
+        .iter()
+        .map(|metric| Ok(format!("{:.4}", metric.evaluate(fields)?)))
+        .collect()
+}
+
+fn print_owner_tsv_header(
+    out: &mut dyn Write,
+    adjusted: bool,
+    group_columns: &[&str],
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    let mut columns = group_columns.to_vec();
+    columns.extend([
+        "owner",
+        "team_changes",
+        "team_insertions",
+        "team_deletions",
+        "team_commits",
+        "others_changes",
+        "others_insertions",
+        "others_deletions",
+        "others_commits",
+    ]);
+    if adjusted {
+        columns.extend([
+            "adjusted_team_changes",
+            "adjusted_team_commits",
+            "adjusted_others_changes",
+            "adjusted_others_commits",
+        ]);
     }
-    progress.finish_with_message("All teams processed");
+    columns.extend(metrics.iter().map(|metric| metric.name.as_str()));
+    writeln!(out, "{}", columns.join("\t"))?;
+    Ok(())
+}
 
-    let total_members = all_members.len();
-    let member_progress = ProgressBar::new(total_members as u64);
-    let member_style = ProgressStyle::default_bar()
-        .template("[{elapsed_precise}] {bar:40.green/white} {pos}/{len} members")
-        .unwrap_or_else(|_| ProgressStyle::default_bar());
-    member_progress.set_style(member_style);
-
-    let mut user_cache: HashMap<String, (String, String)> = HashMap::new();
-    let mut acms = Vec::new();
-    for (team, members) in team_members {
-        for member in members {
-            let (name, email) = if let Some(info) = user_cache.get(&member) {
-                info.clone()
-            } else if let Some(info) = get_user_info(api, &member).await? {
-                user_cache.insert(member.clone(), info.clone());
-                info
-            } else {
-                member_progress.inc(1);
-                continue;
-            };
-            acms.push(AuthorCodeownerMemberships {
-                author_email: Some(email),
-                author_name: Some(name),
-                codeowner: format!("@{}/{}", org, team),
-            });
-            member_progress.inc(1);
+fn print_owner_tsv(
+    out: &mut dyn Write,
+    analysis: &OwnerAnalysis,
+    adjusted: bool,
+    group: OutputGroup,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    for owner_info in &analysis.owners {
+        let mut row = group.values();
+        row.extend([
+            owner_info.owner.clone(),
+            (owner_info.total_insertions_by_team + owner_info.total_deletions_by_team).to_string(),
+            owner_info.total_insertions_by_team.to_string(),
+            owner_info.total_deletions_by_team.to_string(),
+            owner_info.total_commits_by_team.to_string(),
+            (owner_info.total_insertions_by_others + owner_info.total_deletions_by_others)
+                .to_string(),
+            owner_info.total_insertions_by_others.to_string(),
+            owner_info.total_deletions_by_others.to_string(),
+            owner_info.total_commits_by_others.to_string(),
+        ]);
+        if adjusted {
+            row.extend([
+                format!("{:.2}", owner_info.adjusted_changes_by_team),
+                format!("{:.2}", owner_info.adjusted_commits_by_team),
+                format!("{:.2}", owner_info.adjusted_changes_by_others),
+                format!("{:.2}", owner_info.adjusted_commits_by_others),
+            ]);
         }
+        row.extend(metric_columns(metrics, &owner_info.metric_fields())?);
+        writeln!(out, "{}", row.join("\t"))?;
+    }
+    Ok(())
+}
+
+fn print_contributor_tsv_header(
+    out: &mut dyn Write,
+    adjusted: bool,
+    group_columns: &[&str],
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    let mut columns = group_columns.to_vec();
+    columns.extend(["author_name", "author_email", "owner", "commits", "changes"]);
+    if adjusted {
+        columns.extend(["adjusted_commits", "adjusted_changes"]);
     }
+    columns.extend(metrics.iter().map(|metric| metric.name.as_str()));
+    writeln!(out, "{}", columns.join("\t"))?;
+    Ok(())
+}
 
-    member_progress.finish_with_message("All members processed");
+/// Prints the warnings collected over the run to stderr, a few examples per category, and
+/// writes all of them as JSON to `json_path` when given.
+fn print_warnings_summary(warnings: &Warnings, json_path: &Option<PathBuf>) -> Result<()> {
+    const EXAMPLES: usize = 5;
+    let summary = warnings.summary();
+    if !summary.is_empty() {
+        eprintln!();
+        eprintln!("Warnings:");
+    }
+    for category in &summary {
+        eprintln!("  {}: {}", category.category, category.count);
+        for message in category.messages.iter().take(EXAMPLES) {
+            eprintln!("    {}", message);
+        }
+        if category.count > EXAMPLES {
+            eprintln!("    ... and {} more", category.count - EXAMPLES);
+        }
+    }
 
-    Ok(acms)
+    if let Some(path) = json_path {
+        std::fs::write(path, serde_json::to_string_pretty(&summary)?)?;
+    }
+    Ok(())
 }
 
-#[derive(Parser)]
-#[command(author, version, about, long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
+/// Reports the GitHub API calls made and the rate limit left on stderr, so it stays out of
+/// the command's output, and as JSON to `json_path` when given.
+fn report_api_usage(api: &GithubApi, json_path: &Option<PathBuf>) -> Result<()> {
+    let usage = api.usage();
+    let budget = match (usage.remaining, usage.limit) {
+        (Some(remaining), Some(limit)) => format!("{} of {} requests remaining", remaining, limit),
+        _ => "rate limit unknown".to_string(),
+    };
+    let reset = usage
+        .reset
+        .and_then(|reset| chrono::DateTime::from_timestamp(reset as i64, 0))
+        .map(|reset| format!(", resets at {}", bound::format_date(reset)))
+        .unwrap_or_default();
+    let not_modified = if usage.not_modified > 0 {
+        format!(" ({} unchanged since cached)", usage.not_modified)
+    } else {
+        String::new()
+    };
+    status!(
+        "GitHub API: {} calls made{}, {}{}",
+        usage.calls,
+        not_modified,
+        budget,
+        reset
+    );
+
+    if let Some(path) = json_path {
+        std::fs::write(path, serde_json::to_string_pretty(&usage)?)?;
+    }
+    Ok(())
 }
-#[derive(Subcommand)]
-enum DevCommands {
-    GhGetToken,
-    GhGetTeamSlugs {
-        org: String,
-    },
-    GhGetTeamMembers {
-        #[arg(short, long)]
-        org: String,
-        #[arg(short, long)]
-        team: String,
-    },
-    GhGetUserNameEmail {
-        logins: Vec<String>,
-    },
-    GhGetOrgLogins,
-    PrintCommits {
-        #[arg(short, long)]
-        since: String,
-        #[arg(short, long)]
-        until: String,
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-        #[arg(long)]
-        tsv: bool,
-    },
-    GetCodeowners {
-        #[arg(short, long)]
-        commit: String,
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-    },
-    GetAllCodeowners {
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-    },
-    PrintCommitsWithCodeowners {
-        #[arg(short, long)]
-        since: String,
-        #[arg(short, long)]
-        until: String,
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-        #[arg(short, long, default_value = "codeowners.tsv")]
-        codeowners_path: Option<PathBuf>,
-        #[arg(long)]
-        tsv: bool,
-    },
+
+/// `--format` takes precedence over the older `--tsv` flag; clap rejects passing both.
+fn is_tsv(tsv: bool, format: Option<OutputFormat>) -> bool {
+    tsv || format == Some(OutputFormat::Tsv)
 }
-#[derive(Subcommand)]
-enum Commands {
-    #[command(subcommand)]
-    Dev(DevCommands),
-    Init {
-        org: String,
 
-        #[arg(short, long, default_value = "codeowners.tsv")]
-        codeowners_path: PathBuf,
-    },
-    AnalyzeByOwner {
-        #[arg(short, long)]
-        since: String,
-        #[arg(short, long)]
-        until: String,
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-        #[arg(short, long, default_value = "codeowners.tsv")]
-        codeowners_path: PathBuf,
-        #[arg(long)]
-        adjusted: bool,
-    },
-    AnalyzeByContributor {
-        #[arg(short, long)]
-        since: String,
-        #[arg(short, long)]
-        until: String,
-        #[arg(short, long, default_value = ".")]
-        directory: PathBuf,
-        #[arg(short, long, default_value = "codeowners.tsv")]
-        codeowners_path: PathBuf,
-        #[arg(short, long)]
-        owner: Option<String>,
-        #[arg(long)]
-        tsv: bool,
-        #[arg(long)]
-        adjusted: bool,
-    },
+/// `--format`, with the older `--tsv` flag read as `--format tsv`.
+fn output_format(tsv: bool, format: Option<OutputFormat>) -> Option<OutputFormat> {
+    if tsv {
+        Some(OutputFormat::Tsv)
+    } else {
+        format
+    }
 }
 
-use bound::GithubApi;
+/// Markdown is only rendered for the owner and contributor reports.
+fn reject_markdown(format: Option<OutputFormat>) -> Result<()> {
+    if format == Some(OutputFormat::Markdown) {
+        anyhow::bail!(
+            "--format markdown is only supported by analyze-by-owner, analyze-by-contributor, analyze-by-project and scorecard"
+        );
+    }
+    Ok(())
+}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    match &cli.command {
-        Commands::Dev(dev_command) => match dev_command {
-            DevCommands::GhGetToken => {
-                let token = bound::get_token()?;
-                println!("Token: {}", token);
-            }
-            DevCommands::GhGetTeamSlugs { org } => {
-                let api = GithubApi::new()?;
-                let slugs = bound::get_github_team_slugs(&api, org).await?;
-                for slug in slugs {
-                    println!("{}", slug);
-                }
-            }
-            DevCommands::GhGetTeamMembers { org, team } => {
-                let api = GithubApi::new()?;
-                let members = bound::get_github_team_members(&api, org, team).await?;
-                for member in members {
-                    println!("{}", member);
-                }
-            }
-            DevCommands::GhGetUserNameEmail { logins } => {
-                let api = GithubApi::new()?;
-                for login in logins {
-                    match bound::get_user_info(&api, login).await? {
-                        Some((name, email)) => {
-                            if email.is_empty() {
-                                println!("{} <not found>", name);
-                            } else {
-                                println!("{} <{}>", name, email);
-                            }
-                        }
-                        None => println!("{} <not found>", login),
-                    }
-                }
+/// Streamed output read by something that stops early, like `head`, ends without an error.
+fn ignore_broken_pipe(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn print_ndjson<'a, T: Serialize + 'a>(
+    out: &mut dyn Write,
+    records: impl Iterator<Item = &'a T>,
+    group: OutputGroup,
+) -> Result<()> {
+    for record in records {
+        ignore_broken_pipe(match (group.repo, group.period) {
+            (Some(repo), Some(period)) => bound::write_ndjson(
+                out,
+                &RepoRecord {
+                    repo,
+                    record: &PeriodRecord { period, record },
+                },
+            ),
+            (Some(repo), None) => bound::write_ndjson(out, &RepoRecord { repo, record }),
+            (None, Some(period)) => bound::write_ndjson(out, &PeriodRecord { period, record }),
+            (None, None) => bound::write_ndjson(out, record),
+        })?;
+    }
+    Ok(())
+}
+
+fn print_contributor_tsv(
+    out: &mut dyn Write,
+    analysis: &ContributorAnalysis,
+    filter_authors: &AuthorFilter,
+    adjusted: bool,
+    group: OutputGroup,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    for contributor_info in &analysis.contributors {
+        if is_filtered_out(filter_authors, contributor_info) {
+            continue;
+        }
+
+        for contribution in &contributor_info.contributions {
+            let mut row = group.values();
+            row.extend([
+                contributor_info.author_name.clone(),
+                contributor_info.author_email.clone(),
+                contribution.owner.clone(),
+                contribution.total_commits.to_string(),
+                (contribution.total_insertions + contribution.total_deletions).to_string(),
+            ]);
+            if adjusted {
+                row.extend([
+                    format!("{:.2}", contribution.adjusted_commits),
+                    format!("{:.2}", contribution.adjusted_changes),
+                ]);
             }
-            DevCommands::GhGetOrgLogins => {
-                let api = GithubApi::new()?;
-                let orgs = bound::get_github_org_logins(&api).await?;
-                for org in orgs {
-                    println!("{}", org);
-                }
+            row.extend(metric_columns(metrics, &contribution.metric_fields())?);
+            writeln!(out, "{}", row.join("\t"))?;
+        }
+    }
+    Ok(())
+}
+
+fn print_markdown_table(out: &mut dyn Write, header: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    ignore_broken_pipe(bound::write_markdown_table(out, header, rows))?;
+    Ok(())
+}
+
+fn print_analysis_summary_markdown(out: &mut dyn Write, summary: &AnalysisSummary) -> Result<()> {
+    let share = |changes: usize| {
+        if summary.total_changes() > 0 {
+            format!(
+                "{} ({:.1}%)",
+                changes,
+                changes as f64 / summary.total_changes() as f64 * 100.0
+            )
+        } else {
+            changes.to_string()
+        }
+    };
+    print_markdown_table(
+        out,
+        &[
+            "Commits",
+            "Changes",
+            "Team changes",
+            "Others changes",
+            "Unowned changes",
+            "Binary file changes",
+            "Coverage",
+        ],
+        &[vec![
+            summary.total_commits.to_string(),
+            format!(
+                "{} (+{}, -{})",
+                summary.total_changes(),
+                summary.total_insertions,
+                summary.total_deletions
+            ),
+            share(summary.changes_by_team),
+            share(summary.changes_by_others),
+            share(summary.unowned_changes),
+            summary.binary_changes.to_string(),
+            format!("{:.1}%", summary.coverage() * 100.0),
+        ]],
+    )?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// `Name (value)` for each contributor, for a single table cell.
+fn contributor_list(contributors: &[ContributorToOwnerInfo]) -> String {
+    contributors
+        .iter()
+        .map(|contributor| format!("{} ({})", contributor.author_name, contributor.metric_value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_owner_markdown(
+    out: &mut dyn Write,
+    analysis: &OwnerAnalysis,
+    adjusted: bool,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    print_analysis_summary_markdown(out, &analysis.summary)?;
+    let mut header = vec![
+        "Owner",
+        "Team changes",
+        "Team commits",
+        "Others changes",
+        "Others commits",
+    ];
+    if adjusted {
+        header.extend([
+            "Adjusted team changes",
+            "Adjusted team commits",
+            "Adjusted others changes",
+            "Adjusted others commits",
+        ]);
+    }
+    header.extend(metrics.iter().map(|metric| metric.name.as_str()));
+    header.extend([
+        "Top outside contributors by changes",
+        "Top team contributors by changes",
+    ]);
+
+    let mut rows = Vec::new();
+    for owner_info in &analysis.owners {
+        let mut row = vec![
+            owner_info.owner.clone(),
+            format!(
+                "{} (+{}, -{})",
+                owner_info.total_insertions_by_team + owner_info.total_deletions_by_team,
+                owner_info.total_insertions_by_team,
+                owner_info.total_deletions_by_team
+            ),
+            owner_info.total_commits_by_team.to_string(),
+            format!(
+                "{} (+{}, -{})",
+                owner_info.total_insertions_by_others + owner_info.total_deletions_by_others,
+                owner_info.total_insertions_by_others,
+                owner_info.total_deletions_by_others
+            ),
+            owner_info.total_commits_by_others.to_string(),
+        ];
+        if adjusted {
+            row.extend([
+                format!("{:.2}", owner_info.adjusted_changes_by_team),
+                format!("{:.2}", owner_info.adjusted_commits_by_team),
+                format!("{:.2}", owner_info.adjusted_changes_by_others),
+                format!("{:.2}", owner_info.adjusted_commits_by_others),
+            ]);
+        }
+        row.extend(metric_columns(metrics, &owner_info.metric_fields())?);
+        row.extend([
+            contributor_list(&owner_info.top_outside_contributors_by_changes),
+            contributor_list(&owner_info.top_team_contributors_by_changes),
+        ]);
+        rows.push(row);
+    }
+    print_markdown_table(out, &header, &rows)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_contributor_markdown(
+    out: &mut dyn Write,
+    analysis: &ContributorAnalysis,
+    filter_authors: &AuthorFilter,
+    adjusted: bool,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    print_analysis_summary_markdown(out, &analysis.summary)?;
+    let mut header = vec!["Contributor", "Email", "Owner", "Commits", "Changes"];
+    if adjusted {
+        header.extend(["Adjusted commits", "Adjusted changes"]);
+    }
+    header.extend(metrics.iter().map(|metric| metric.name.as_str()));
+
+    let mut rows = Vec::new();
+    for contributor_info in &analysis.contributors {
+        if is_filtered_out(filter_authors, contributor_info) {
+            continue;
+        }
+        for contribution in &contributor_info.contributions {
+            let mut row = vec![
+                contributor_info.author_name.clone(),
+                contributor_info.author_email.clone(),
+                contribution.owner.clone(),
+                contribution.total_commits.to_string(),
+                format!(
+                    "{} (+{}, -{})",
+                    contribution.total_insertions + contribution.total_deletions,
+                    contribution.total_insertions,
+                    contribution.total_deletions
+                ),
+            ];
+            if adjusted {
+                row.extend([
+                    format!("{:.2}", contribution.adjusted_commits),
+                    format!("{:.2}", contribution.adjusted_changes),
+                ]);
             }
-            DevCommands::PrintCommits {
-                since,
-                until,
-                directory,
-                tsv,
-            } => {
-                let commits = git_log_commits(since, until, directory)?;
-                if *tsv {
-                    println!(
-                        "commit_id\tauthor_name\tauthor_email\tdate\tpath\tinsertions\tdeletions"
-                    );
-                    for commit in commits {
-                        let commit = commit?;
-                        for change in commit.file_changes {
-                            println!(
-                                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
-                                commit.id,
+            row.extend(metric_columns(metrics, &contribution.metric_fields())?);
+            rows.push(row);
+        }
+    }
+    print_markdown_table(out, &header, &rows)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Prints the owner analysis for one part of a run in the chosen format. Without a group,
+/// the TSV header comes after the summary; otherwise it was printed once up front.
+fn print_owner_report(
+    out: &mut dyn Write,
+    analysis: &OwnerAnalysis,
+    group: OutputGroup,
+    format: Option<OutputFormat>,
+    adjusted: bool,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    if format == Some(OutputFormat::Ndjson) {
+        print_ndjson(out, analysis.owners.iter(), group)?;
+    } else if format == Some(OutputFormat::Markdown) {
+        if !group.is_empty() {
+            writeln!(out, "### {}", group.heading())?;
+            writeln!(out)?;
+        }
+        print_owner_markdown(out, analysis, adjusted, metrics)?;
+    } else if format == Some(OutputFormat::Tsv) {
+        print_analysis_summary_tsv(out, &analysis.summary, group)?;
+        if group.is_empty() {
+            print_owner_tsv_header(out, adjusted, &[], metrics)?;
+        }
+        print_owner_tsv(out, analysis, adjusted, group, metrics)?;
+    } else {
+        print_group_heading(out, group)?;
+        print_owner_analysis(out, analysis, adjusted)?;
+    }
+    Ok(())
+}
+
+/// Like [`print_owner_report`], for the contributor analysis.
+fn print_contributor_report(
+    out: &mut dyn Write,
+    analysis: &ContributorAnalysis,
+    filter_authors: &AuthorFilter,
+    group: OutputGroup,
+    format: Option<OutputFormat>,
+    adjusted: bool,
+    metrics: &[DerivedMetric],
+) -> Result<()> {
+    if format == Some(OutputFormat::Ndjson) {
+        print_ndjson(
+            out,
+            analysis
+                .contributors
+                .iter()
+                .filter(|info| !is_filtered_out(filter_authors, info)),
+            group,
+        )?;
+    } else if format == Some(OutputFormat::Markdown) {
+        if !group.is_empty() {
+            writeln!(out, "### {}", group.heading())?;
+            writeln!(out)?;
+        }
+        print_contributor_markdown(out, analysis, filter_authors, adjusted, metrics)?;
+    } else if format == Some(OutputFormat::Tsv) {
+        print_analysis_summary_tsv(out, &analysis.summary, group)?;
+        if group.is_empty() {
+            print_contributor_tsv_header(out, adjusted, &[], metrics)?;
+        }
+        print_contributor_tsv(out, analysis, filter_authors, adjusted, group, metrics)?;
+    } else {
+        print_group_heading(out, group)?;
+        print_contributor_analysis(out, analysis, filter_authors, adjusted)?;
+    }
+    Ok(())
+}
+
+fn metric_label(metric: &str) -> String {
+    metric
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect::<String>()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Coverage is a percentage, every other compared metric a count.
+fn format_metric_value(metric: &MetricChange, value: f64) -> String {
+    if metric.metric == "coverage" {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// `value` of `metric` for text and markdown, with coverage as a percentage.
+fn format_metric_amount(metric: &MetricChange, value: f64) -> String {
+    let unit = if metric.metric == "coverage" { "%" } else { "" };
+    format!("{}{}", format_metric_value(metric, value), unit)
+}
+
+/// The current value of `metric` annotated with its change, like `12 (+4, +50.0%)`.
+/// Coverage changes by percentage points.
+fn format_metric_change(metric: &MetricChange) -> String {
+    let change_unit = if metric.metric == "coverage" {
+        " pts"
+    } else {
+        ""
+    };
+    let change = format_metric_value(metric, metric.change());
+    let sign = if metric.change() >= 0.0 { "+" } else { "" };
+    let current = format_metric_amount(metric, metric.current);
+    match metric.percent_change {
+        Some(percent) => format!(
+            "{} ({}{}{}, {:+.1}%)",
+            current, sign, change, change_unit, percent
+        ),
+        None => format!("{} ({}{}{})", current, sign, change, change_unit),
+    }
+}
+
+fn metric_change_columns(metric: &MetricChange) -> [String; 5] {
+    [
+        metric.metric.to_string(),
+        format_metric_value(metric, metric.previous),
+        format_metric_value(metric, metric.current),
+        format_metric_value(metric, metric.change()),
+        metric
+            .percent_change
+            .map_or_else(|| "-".to_string(), |percent| format!("{:.1}", percent)),
+    ]
+}
+
+fn print_metric_changes(out: &mut dyn Write, indent: &str, metrics: &[MetricChange]) -> Result<()> {
+    for metric in metrics {
+        writeln!(
+            out,
+            "{}{}: {}",
+            indent,
+            metric_label(metric.metric),
+            format_metric_change(metric)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_comparison_summary(
+    out: &mut dyn Write,
+    previous_since: &str,
+    previous_until: &str,
+    summary: &[MetricChange],
+) -> Result<()> {
+    writeln!(
+        out,
+        "Compared with {} to {}",
+        previous_since, previous_until
+    )?;
+    writeln!(out, "Summary:")?;
+    print_metric_changes(out, "  ", summary)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_comparison_summary_markdown(
+    out: &mut dyn Write,
+    previous_since: &str,
+    previous_until: &str,
+    summary: &[MetricChange],
+) -> Result<()> {
+    writeln!(
+        out,
+        "Compared with {} to {}",
+        previous_since, previous_until
+    )?;
+    writeln!(out)?;
+    let rows: Vec<Vec<String>> = summary
+        .iter()
+        .map(|metric| {
+            vec![
+                metric_label(metric.metric),
+                format_metric_amount(metric, metric.previous),
+                format_metric_change(metric),
+            ]
+        })
+        .collect();
+    print_markdown_table(out, &["Metric", "Previous", "Current"], &rows)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_comparison_tsv_header(
+    out: &mut dyn Write,
+    group_columns: &[&str],
+    key_columns: &[&str],
+) -> Result<()> {
+    let mut columns = group_columns.to_vec();
+    columns.extend(key_columns);
+    columns.extend(["metric", "previous", "current", "change", "percent_change"]);
+    writeln!(out, "{}", columns.join("\t"))?;
+    Ok(())
+}
+
+/// Prints `metrics` as TSV rows starting with the group columns and then `keys`.
+fn print_comparison_tsv_rows(
+    out: &mut dyn Write,
+    group: OutputGroup,
+    keys: &[&str],
+    metrics: &[MetricChange],
+) -> Result<()> {
+    for metric in metrics {
+        let mut row = group.values();
+        row.extend(keys.iter().map(|key| key.to_string()));
+        row.extend(metric_change_columns(metric));
+        writeln!(out, "{}", row.join("\t"))?;
+    }
+    Ok(())
+}
+
+/// Prints the owner analysis compared with the previous window, each metric annotated with
+/// its change.
+fn print_owner_comparison(
+    out: &mut dyn Write,
+    comparison: &OwnerAnalysisComparison,
+    group: OutputGroup,
+    format: Option<OutputFormat>,
+    tsv: bool,
+) -> Result<()> {
+    if format == Some(OutputFormat::Ndjson) {
+        print_ndjson(out, comparison.owners.iter(), group)?;
+    } else if format == Some(OutputFormat::Markdown) {
+        if !group.is_empty() {
+            writeln!(out, "### {}", group.heading())?;
+            writeln!(out)?;
+        }
+        print_comparison_summary_markdown(
+            out,
+            &comparison.previous_since,
+            &comparison.previous_until,
+            &comparison.summary,
+        )?;
+        let mut rows = Vec::new();
+        for owner in &comparison.owners {
+            for metric in &owner.metrics {
+                rows.push(vec![
+                    owner.owner.clone(),
+                    metric_label(metric.metric),
+                    format_metric_amount(metric, metric.previous),
+                    format_metric_change(metric),
+                ]);
+            }
+        }
+        print_markdown_table(out, &["Owner", "Metric", "Previous", "Current"], &rows)?;
+    } else if tsv {
+        if group.is_empty() {
+            print_comparison_tsv_header(out, &[], &["owner"])?;
+        }
+        print_comparison_tsv_rows(out, group, &["(summary)"], &comparison.summary)?;
+        for owner in &comparison.owners {
+            print_comparison_tsv_rows(out, group, &[&owner.owner], &owner.metrics)?;
+        }
+    } else {
+        print_group_heading(out, group)?;
+        print_comparison_summary(
+            out,
+            &comparison.previous_since,
+            &comparison.previous_until,
+            &comparison.summary,
+        )?;
+        for owner in &comparison.owners {
+            writeln!(out, "Owner: {}", owner.owner)?;
+            print_metric_changes(out, "  ", &owner.metrics)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Like [`print_owner_comparison`], for the contributor analysis.
+fn print_contributor_comparison(
+    out: &mut dyn Write,
+    comparison: &ContributorAnalysisComparison,
+    filter_authors: &AuthorFilter,
+    group: OutputGroup,
+    format: Option<OutputFormat>,
+    tsv: bool,
+) -> Result<()> {
+    let contributions: Vec<&ContributorComparison> = comparison
+        .contributions
+        .iter()
+        .filter(|contribution| {
+            filter_authors.as_ref().is_none_or(|(memberships, owner)| {
+                memberships.is_codeowner(
+                    &contribution.author_name,
+                    &contribution.author_email,
+                    owner,
+                )
+            })
+        })
+        .collect();
+    if format == Some(OutputFormat::Ndjson) {
+        print_ndjson(out, contributions.into_iter(), group)?;
+    } else if format == Some(OutputFormat::Markdown) {
+        if !group.is_empty() {
+            writeln!(out, "### {}", group.heading())?;
+            writeln!(out)?;
+        }
+        print_comparison_summary_markdown(
+            out,
+            &comparison.previous_since,
+            &comparison.previous_until,
+            &comparison.summary,
+        )?;
+        let mut rows = Vec::new();
+        for contribution in contributions {
+            for metric in &contribution.metrics {
+                rows.push(vec![
+                    format!(
+                        "{} <{}>",
+                        contribution.author_name, contribution.author_email
+                    ),
+                    contribution.owner.clone(),
+                    metric_label(metric.metric),
+                    format_metric_amount(metric, metric.previous),
+                    format_metric_change(metric),
+                ]);
+            }
+        }
+        print_markdown_table(
+            out,
+            &["Contributor", "Owner", "Metric", "Previous", "Current"],
+            &rows,
+        )?;
+    } else if tsv {
+        if group.is_empty() {
+            print_comparison_tsv_header(out, &[], &["author_name", "author_email", "owner"])?;
+        }
+        print_comparison_tsv_rows(out, group, &["", "", "(summary)"], &comparison.summary)?;
+        for contribution in contributions {
+            print_comparison_tsv_rows(
+                out,
+                group,
+                &[
+                    &contribution.author_name,
+                    &contribution.author_email,
+                    &contribution.owner,
+                ],
+                &contribution.metrics,
+            )?;
+        }
+    } else {
+        print_group_heading(out, group)?;
+        print_comparison_summary(
+            out,
+            &comparison.previous_since,
+            &comparison.previous_until,
+            &comparison.summary,
+        )?;
+        // Contributions only made in the previous window come last, so gather each
+        // contributor's owners first
+        let mut by_contributor: Vec<((&str, &str), Vec<&ContributorComparison>)> = Vec::new();
+        for contribution in contributions {
+            let key = (
+                contribution.author_name.as_str(),
+                contribution.author_email.as_str(),
+            );
+            match by_contributor.iter_mut().find(|(other, _)| *other == key) {
+                Some((_, owners)) => owners.push(contribution),
+                None => by_contributor.push((key, vec![contribution])),
+            }
+        }
+        for ((name, email), owners) in by_contributor {
+            writeln!(out, "Contributor: {} <{}>", name, email)?;
+            for contribution in owners {
+                writeln!(out, "  Owner: {}", contribution.owner)?;
+                print_metric_changes(out, "    ", &contribution.metrics)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_group_heading(out: &mut dyn Write, group: OutputGroup) -> Result<()> {
+    if let Some(repo) = group.repo {
+        writeln!(out, "Repository: {}", repo)?;
+    }
+    if let Some(period) = group.period {
+        writeln!(out, "Period: {}", period)?;
+    }
+    if !group.is_empty() {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn print_contributor_analysis(
+    out: &mut dyn Write,
+    analysis: &ContributorAnalysis,
+    filter_authors: &AuthorFilter,
+    adjusted: bool,
+) -> Result<()> {
+    print_analysis_summary(out, &analysis.summary)?;
+    for contributor_info in &analysis.contributors {
+        if is_filtered_out(filter_authors, contributor_info) {
+            continue;
+        }
+
+        writeln!(
+            out,
+            "Contributor: {} <{}>",
+            contributor_info.author_name, contributor_info.author_email
+        )?;
+        for contribution in &contributor_info.contributions {
+            writeln!(out, "  Owner: {}", contribution.owner)?;
+            writeln!(
+                out,
+                "    Changes: {} (+{}, -{})",
+                contribution.total_insertions + contribution.total_deletions,
+                contribution.total_insertions,
+                contribution.total_deletions
+            )?;
+            writeln!(out, "    Commits: {}", contribution.total_commits)?;
+            if adjusted {
+                writeln!(
+                    out,
+                    "    Adjusted Changes: {:.2}",
+                    contribution.adjusted_changes
+                )?;
+                writeln!(
+                    out,
+                    "    Adjusted Commits: {:.2}",
+                    contribution.adjusted_commits
+                )?;
+            }
+            if !contribution.languages.is_empty() {
+                let languages: Vec<String> = contribution
+                    .languages
+                    .iter()
+                    .map(|language| format!("{} {}", language.language, language.total_changes()))
+                    .collect();
+                writeln!(out, "    Languages: {}", languages.join(", "))?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn print_project_analysis(out: &mut dyn Write, analysis: &ProjectAnalysis) -> Result<()> {
+    print_analysis_summary(out, &analysis.summary)?;
+    for project in &analysis.projects {
+        writeln!(
+            out,
+            "Project: {} ({}) [{}]",
+            project.name,
+            if project.path.is_empty() {
+                "."
+            } else {
+                &project.path
+            },
+            project.kind
+        )?;
+        writeln!(
+            out,
+            "  Changes: {} (+{}, -{})",
+            project.total_changes(),
+            project.total_insertions,
+            project.total_deletions
+        )?;
+        writeln!(out, "  Commits: {}", project.total_commits)?;
+        writeln!(
+            out,
+            "  Team Changes: {} ({:.1}%)",
+            project.changes_by_team,
+            project.team_share() * 100.0
+        )?;
+        writeln!(out, "  Others Changes: {}", project.changes_by_others)?;
+        writeln!(out, "  Unowned Changes: {}", project.unowned_changes)?;
+        writeln!(out, "  Owners by Changes:")?;
+        for owner in &project.owners {
+            writeln!(out, "    {}: {}", owner.owner, owner.changes)?;
+        }
+        writeln!(out, "  Top Contributors by Changes:")?;
+        for contributor in &project.top_contributors_by_changes {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out)?;
+    }
+    if analysis.changes_outside_projects > 0 {
+        writeln!(
+            out,
+            "Changes outside projects: {}",
+            analysis.changes_outside_projects
+        )?;
+    }
+    Ok(())
+}
+
+/// Owners as `owner:changes`, most changes first, for a single column.
+fn project_owners(project: &ProjectInfo) -> String {
+    project
+        .owners
+        .iter()
+        .map(|owner| format!("{}:{}", owner.owner, owner.changes))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn print_project_tsv(out: &mut dyn Write, analysis: &ProjectAnalysis) -> Result<()> {
+    print_analysis_summary_tsv(out, &analysis.summary, OutputGroup::default())?;
+    writeln!(out, "project\tpath\tkind\tcommits\tchanges\tinsertions\tdeletions\tteam_changes\tothers_changes\tunowned_changes\towners")?;
+    for project in &analysis.projects {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            project.name,
+            project.path,
+            project.kind,
+            project.total_commits,
+            project.total_changes(),
+            project.total_insertions,
+            project.total_deletions,
+            project.changes_by_team,
+            project.changes_by_others,
+            project.unowned_changes,
+            project_owners(project)
+        )?;
+    }
+    Ok(())
+}
+
+/// Review counts under each owner's alias, summing owners that share one.
+fn alias_review_owners(
+    reviews: Vec<OwnerReviewInfo>,
+    aliases: &OwnerAliases,
+) -> Vec<OwnerReviewInfo> {
+    let mut aliased: BTreeMap<String, OwnerReviewInfo> = BTreeMap::new();
+    for info in reviews {
+        let owner = aliases.resolve(&info.owner).to_string();
+        let entry = aliased
+            .entry(owner.clone())
+            .or_insert_with(|| OwnerReviewInfo {
+                owner,
+                pull_requests: 0,
+                approved_by_codeowner: 0,
+                approved_by_others_only: 0,
+                merged_without_approval: 0,
+            });
+        entry.pull_requests += info.pull_requests;
+        entry.approved_by_codeowner += info.approved_by_codeowner;
+        entry.approved_by_others_only += info.approved_by_others_only;
+        entry.merged_without_approval += info.merged_without_approval;
+    }
+    aliased.into_values().collect()
+}
+
+fn format_timestamp_date(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map_or_else(
+            || "-".to_string(),
+            |date| date.format("%Y-%m-%d").to_string(),
+        )
+}
+
+fn print_scorecards(out: &mut dyn Write, scorecards: &[OwnerScorecard]) -> Result<()> {
+    for scorecard in scorecards {
+        writeln!(out, "Owner: {}", scorecard.owner)?;
+        writeln!(out, "  Score: {:.2} ({})", scorecard.score, scorecard.grade)?;
+        for component in &scorecard.components {
+            writeln!(
+                out,
+                "  {}: {:.2} (score {:.2}, weight {})",
+                component.metric, component.value, component.score, component.weight
+            )?;
+        }
+        writeln!(
+            out,
+            "  Owned Files: {} ({} changed by the team)",
+            scorecard.owned_files, scorecard.team_changed_files
+        )?;
+        writeln!(out, "  Team Changes: {}", scorecard.team_changes)?;
+        writeln!(out, "  Others Changes: {}", scorecard.others_changes)?;
+        writeln!(
+            out,
+            "  Last Team Change: {}",
+            format_timestamp_date(scorecard.last_team_change)
+        )?;
+        writeln!(out, "  Top Team Contributors by Changes:")?;
+        for contributor in &scorecard.top_team_contributors_by_changes {
+            writeln!(
+                out,
+                "    {} <{}>: {}",
+                contributor.author_name, contributor.author_email, contributor.metric_value
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// One `metric` and `metric_score` column pair per metric, empty where it wasn't measured.
+fn scorecard_metric_columns(scorecard: &OwnerScorecard) -> Vec<String> {
+    ScorecardMetric::ALL
+        .into_iter()
+        .flat_map(|metric| {
+            match scorecard
+                .components
+                .iter()
+                .find(|component| component.metric == metric)
+            {
+                Some(component) => [
+                    format!("{:.4}", component.value),
+                    format!("{:.4}", component.score),
+                ],
+                None => [String::new(), String::new()],
+            }
+        })
+        .collect()
+}
+
+fn print_scorecard_tsv(out: &mut dyn Write, scorecards: &[OwnerScorecard]) -> Result<()> {
+    writeln!(out, "owner\tgrade\tscore\tcoverage\tcoverage_score\tbus_factor\tbus_factor_score\toutside_ratio\toutside_ratio_score\treview_coverage\treview_coverage_score\tstaleness\tstaleness_score\towned_files\tteam_changed_files\tteam_changes\tothers_changes\tlast_team_change")?;
+    for scorecard in scorecards {
+        let mut row = vec![
+            scorecard.owner.clone(),
+            scorecard.grade.clone(),
+            format!("{:.4}", scorecard.score),
+        ];
+        row.extend(scorecard_metric_columns(scorecard));
+        row.extend([
+            scorecard.owned_files.to_string(),
+            scorecard.team_changed_files.to_string(),
+            scorecard.team_changes.to_string(),
+            scorecard.others_changes.to_string(),
+            format_timestamp_date(scorecard.last_team_change),
+        ]);
+        writeln!(out, "{}", row.join("\t"))?;
+    }
+    Ok(())
+}
+
+fn print_scorecard_markdown(out: &mut dyn Write, scorecards: &[OwnerScorecard]) -> Result<()> {
+    let rows: Vec<Vec<String>> = scorecards
+        .iter()
+        .map(|scorecard| {
+            let mut row = vec![
+                scorecard.owner.clone(),
+                scorecard.grade.clone(),
+                format!("{:.2}", scorecard.score),
+            ];
+            row.extend(ScorecardMetric::ALL.into_iter().map(|metric| {
+                let Some(component) = scorecard
+                    .components
+                    .iter()
+                    .find(|component| component.metric == metric)
+                else {
+                    return "-".to_string();
+                };
+                match metric {
+                    ScorecardMetric::BusFactor | ScorecardMetric::Staleness => {
+                        format!("{:.0}", component.value)
+                    }
+                    _ => format!("{:.1}%", component.value * 100.0),
+                }
+            }));
+            row.extend([
+                format!(
+                    "{} / {}",
+                    scorecard.team_changed_files, scorecard.owned_files
+                ),
+                format_timestamp_date(scorecard.last_team_change),
+            ]);
+            row
+        })
+        .collect();
+    print_markdown_table(
+        out,
+        &[
+            "Owner",
+            "Grade",
+            "Score",
+            "Coverage",
+            "Bus factor",
+            "Outside ratio",
+            "Review coverage",
+            "Days since team change",
+            "Files changed by team",
+            "Last team change",
+        ],
+        &rows,
+    )?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_project_markdown(out: &mut dyn Write, analysis: &ProjectAnalysis) -> Result<()> {
+    print_analysis_summary_markdown(out, &analysis.summary)?;
+    let rows: Vec<Vec<String>> = analysis
+        .projects
+        .iter()
+        .map(|project| {
+            vec![
+                project.name.clone(),
+                project.kind.to_string(),
+                project.total_commits.to_string(),
+                format!(
+                    "{} (+{}, -{})",
+                    project.total_changes(),
+                    project.total_insertions,
+                    project.total_deletions
+                ),
+                format!(
+                    "{} ({:.1}%)",
+                    project.changes_by_team,
+                    project.team_share() * 100.0
+                ),
+                project.changes_by_others.to_string(),
+                project.unowned_changes.to_string(),
+                project
+                    .owners
+                    .iter()
+                    .map(|owner| format!("{} ({})", owner.owner, owner.changes))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]
+        })
+        .collect();
+    print_markdown_table(
+        out,
+        &[
+            "Project",
+            "Kind",
+            "Commits",
+            "Changes",
+            "Team changes",
+            "Others changes",
+            "Unowned changes",
+            "Owners",
+        ],
+        &rows,
+    )?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_owner_files(
+    out: &mut dyn Write,
+    analysis: &OwnerFilesAnalysis,
+    limit: usize,
+    tsv: bool,
+) -> Result<()> {
+    let sections = [
+        ("file", "Files", &analysis.files),
+        ("directory", "Directories", &analysis.directories),
+    ];
+    if tsv {
+        writeln!(out, "kind\tpath\tchanges\tinsertions\tdeletions\tcommits\tauthor_name\tauthor_email\tauthor_changes\tauthor_commits\tteam_member")?;
+        for (kind, _, paths) in sections {
+            for info in paths.iter().take(limit) {
+                for contributor in &info.contributors {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                        kind,
+                        info.path,
+                        info.total_changes(),
+                        info.total_insertions,
+                        info.total_deletions,
+                        info.total_commits,
+                        contributor.author_name,
+                        contributor.author_email,
+                        contributor.changes,
+                        contributor.commits,
+                        contributor.is_team_member
+                    )?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if analysis.files.is_empty() {
+        writeln!(out, "No changes to files owned by {}", analysis.owner)?;
+        return Ok(());
+    }
+    for (_, title, paths) in sections {
+        writeln!(out, "{} owned by {}:", title, analysis.owner)?;
+        for info in paths.iter().take(limit) {
+            writeln!(
+                out,
+                "  {}: {} changes (+{}, -{}), {} commits",
+                info.path,
+                info.total_changes(),
+                info.total_insertions,
+                info.total_deletions,
+                info.total_commits
+            )?;
+            for contributor in &info.contributors {
+                let membership = if contributor.is_team_member {
+                    "team"
+                } else {
+                    "outside"
+                };
+                writeln!(
+                    out,
+                    "    {} <{}> ({}): {} changes, {} commits",
+                    contributor.author_name,
+                    contributor.author_email,
+                    membership,
+                    contributor.changes,
+                    contributor.commits
+                )?;
+            }
+        }
+        if paths.len() > limit {
+            writeln!(out, "  ... and {} more", paths.len() - limit)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn print_sensitive_changes(
+    out: &mut dyn Write,
+    changes: &[SensitiveChange],
+    tsv: bool,
+) -> Result<()> {
+    let owners = |change: &SensitiveChange| {
+        if change.codeowners.is_empty() {
+            "unowned".to_string()
+        } else {
+            change.codeowners.join(", ")
+        }
+    };
+    if tsv {
+        writeln!(out, "commit_id\tdate\tauthor_name\tauthor_email\torg_member\tpath\tinsertions\tdeletions\tcodeowners")?;
+        for change in changes {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                change.commit_id,
+                change.timestamp,
+                change.author_name,
+                change.author_email,
+                change.org_member,
+                change.path,
+                change.insertions,
+                change.deletions,
+                owners(change)
+            )?;
+        }
+        return Ok(());
+    }
+
+    if changes.is_empty() {
+        writeln!(out, "No changes to security-sensitive paths by non-owners")?;
+        return Ok(());
+    }
+    let outside = changes.iter().filter(|change| !change.org_member).count();
+    writeln!(out,
+        "{} changes to security-sensitive paths by non-owners, {} by people outside the organization",
+        changes.len(),
+        outside
+    )?;
+    writeln!(out)?;
+    for change in changes {
+        let marker = if change.org_member { "" } else { " [outside]" };
+        writeln!(
+            out,
+            "{} {} <{}>{} modified {} (+{}, -{}) owned by {}",
+            &change.commit_id[..change.commit_id.len().min(7)],
+            change.author_name,
+            change.author_email,
+            marker,
+            change.path,
+            change.insertions,
+            change.deletions,
+            owners(change)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_codeowners_preview(
+    out: &mut dyn Write,
+    preview: &CodeownersPreview,
+    tsv: bool,
+) -> Result<()> {
+    if tsv {
+        writeln!(
+            out,
+            "owner\tfiles_gained\tfiles_lost\tchanges_gained\tchanges_lost"
+        )?;
+        for impact in &preview.impacts {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                impact.owner,
+                impact.files_gained,
+                impact.files_lost,
+                impact.changes_gained,
+                impact.changes_lost
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "Files reassigned: {} of {} ({} unowned before, {} after)",
+        preview.files_reassigned,
+        preview.files,
+        preview.unowned_files_before,
+        preview.unowned_files_after
+    )?;
+    writeln!(
+        out,
+        "Changes reattributed: {} of {} changed lines in range",
+        preview.changes_reassigned, preview.changes
+    )?;
+    if preview.impacts.is_empty() {
+        writeln!(out, "No owner gains or loses any files")?;
+        return Ok(());
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<30} {:>12} {:>10} {:>14} {:>12}",
+        "Owner", "Files Gained", "Files Lost", "Changes Gained", "Changes Lost"
+    )?;
+    for impact in &preview.impacts {
+        writeln!(
+            out,
+            "{:<30} {:>12} {:>10} {:>14} {:>12}",
+            impact.owner,
+            impact.files_gained,
+            impact.files_lost,
+            impact.changes_gained,
+            impact.changes_lost
+        )?;
+    }
+    Ok(())
+}
+
+fn print_codeowners_diff(out: &mut dyn Write, diff: &CodeownersDiff, tsv: bool) -> Result<()> {
+    let rules = [
+        ("rule_added", '+', &diff.added_rules),
+        ("rule_removed", '-', &diff.removed_rules),
+        ("rule_changed", '~', &diff.changed_rules),
+    ];
+    if tsv {
+        writeln!(out, "change\tpattern\towners_before\towners_after")?;
+        for (change, _, rules) in rules {
+            for rule in rules {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}",
+                    change,
+                    rule.pattern,
+                    rule.owners_before.join(","),
+                    rule.owners_after.join(",")
+                )?;
+            }
+        }
+        for file in &diff.reassigned_files {
+            writeln!(
+                out,
+                "file\t{}\t{}\t{}",
+                file.path,
+                file.owners_before.join(","),
+                file.owners_after.join(",")
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "Rules: {} added, {} removed, {} changed",
+        diff.added_rules.len(),
+        diff.removed_rules.len(),
+        diff.changed_rules.len()
+    )?;
+    let owners = |owners: &[String]| {
+        if owners.is_empty() {
+            "(unowned)".to_string()
+        } else {
+            owners.join(" ")
+        }
+    };
+    for (_, marker, rules) in rules {
+        for rule in rules {
+            match marker {
+                '+' => writeln!(out, "  + {} {}", rule.pattern, owners(&rule.owners_after))?,
+                '-' => writeln!(out, "  - {} {}", rule.pattern, owners(&rule.owners_before))?,
+                _ => writeln!(
+                    out,
+                    "  ~ {} {} -> {}",
+                    rule.pattern,
+                    owners(&rule.owners_before),
+                    owners(&rule.owners_after)
+                )?,
+            }
+        }
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Files changing owners: {} of {}",
+        diff.reassigned_files.len(),
+        diff.files
+    )?;
+    for file in &diff.reassigned_files {
+        writeln!(
+            out,
+            "  {}: {} -> {}",
+            file.path,
+            owners(&file.owners_before),
+            owners(&file.owners_after)
+        )?;
+    }
+    Ok(())
+}
+
+/// A directory's name followed by its file count and owners, or the owners with the most
+/// files when it has several.
+fn owners_tree_label(node: &OwnersTreeNode) -> String {
+    let name = match node.path.rsplit_once('/') {
+        _ if node.path.is_empty() => ".".to_string(),
+        Some((_, name)) => format!("{}/", name),
+        None => format!("{}/", node.path),
+    };
+    let files = if node.files == 1 { "file" } else { "files" };
+    let owners = match &node.uniform_owners {
+        Some(owners) if owners.is_empty() => "(unowned)".to_string(),
+        Some(owners) => owners.join(" "),
+        None => {
+            let mut by_files: Vec<(&String, &usize)> = node.files_by_owner.iter().collect();
+            by_files.sort_by_key(|(_, files)| std::cmp::Reverse(**files));
+            let mut parts: Vec<String> = by_files
+                .iter()
+                .take(3)
+                .map(|(owner, files)| format!("{} {}", owner, files))
+                .collect();
+            if by_files.len() > 3 {
+                parts.push(format!("{} more", by_files.len() - 3));
+            }
+            if node.unowned_files > 0 {
+                parts.push(format!("unowned {}", node.unowned_files));
+            }
+            format!("mixed: {}", parts.join(", "))
+        }
+    };
+    format!("{} ({} {}) {}", name, node.files, files, owners)
+}
+
+fn print_owners_tree(out: &mut dyn Write, children: &[OwnersTreeNode], prefix: &str) -> Result<()> {
+    for (index, child) in children.iter().enumerate() {
+        let last = index + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        writeln!(out, "{}{}{}", prefix, branch, owners_tree_label(child))?;
+        print_owners_tree(out, &child.children, &format!("{}{}", prefix, indent))?;
+    }
+    Ok(())
+}
+
+fn print_owners_tree_tsv(out: &mut dyn Write, node: &OwnersTreeNode) -> Result<()> {
+    let owners = match &node.uniform_owners {
+        Some(owners) => owners.join(","),
+        None => "mixed".to_string(),
+    };
+    writeln!(
+        out,
+        "{}\t{}\t{}\t{}",
+        if node.path.is_empty() {
+            "."
+        } else {
+            &node.path
+        },
+        node.files,
+        owners,
+        node.unowned_files
+    )?;
+    for child in &node.children {
+        print_owners_tree_tsv(out, child)?;
+    }
+    Ok(())
+}
+
+fn print_blame_ownership(
+    out: &mut dyn Write,
+    blame: &BlameOwnership,
+    tsv: bool,
+    top: usize,
+) -> Result<()> {
+    let owners = if blame.owners.is_empty() {
+        "unowned".to_string()
+    } else {
+        blame.owners.join(",")
+    };
+    if tsv {
+        writeln!(
+            out,
+            "# path={} owners={} lines={} owner_lines={} outside_lines={} owner_share={:.4}",
+            blame.path,
+            owners,
+            blame.lines,
+            blame.owner_lines,
+            blame.outside_lines(),
+            blame.owner_share()
+        )?;
+        writeln!(out, "author_name\tauthor_email\tlines\tis_owner")?;
+        for author in &blame.authors {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                author.author_name, author.author_email, author.lines, author.is_owner
+            )?;
+        }
+        return Ok(());
+    }
+    let share = |lines: usize| {
+        if blame.lines == 0 {
+            0.0
+        } else {
+            lines as f64 / blame.lines as f64 * 100.0
+        }
+    };
+    writeln!(out, "File: {}", blame.path)?;
+    writeln!(out, "  Owners: {}", owners)?;
+    writeln!(out, "  Lines: {}", blame.lines)?;
+    writeln!(
+        out,
+        "  Owner Lines: {} ({:.1}%)",
+        blame.owner_lines,
+        share(blame.owner_lines)
+    )?;
+    writeln!(
+        out,
+        "  Outside Lines: {} ({:.1}%)",
+        blame.outside_lines(),
+        share(blame.outside_lines())
+    )?;
+    writeln!(out, "  Top Authors by Lines:")?;
+    for author in blame.authors.iter().take(top) {
+        writeln!(
+            out,
+            "    {} <{}>: {}{}",
+            author.author_name,
+            author.author_email,
+            author.lines,
+            if author.is_owner { " (owner)" } else { "" }
+        )?;
+    }
+    Ok(())
+}
+
+fn print_gate_checks(
+    out: &mut dyn Write,
+    checks: &[GateCheck],
+    since: &str,
+    until: &str,
+) -> Result<()> {
+    writeln!(out, "Ownership gate from {} to {}:", since, until)?;
+    for check in checks {
+        let bound = match check.bound {
+            GateBound::Max => "at most",
+            GateBound::Min => "at least",
+        };
+        let value = match check.value {
+            Some(value) => format!("{:.1}%", value * 100.0),
+            None => "n/a".to_string(),
+        };
+        writeln!(
+            out,
+            "  {} {}: {} ({}), expected {} {}",
+            if check.passed() { "PASS" } else { "FAIL" },
+            check.metric,
+            value,
+            check.detail,
+            bound,
+            check.threshold
+        )?;
+    }
+    Ok(())
+}
+
+/// Failed thresholds as errors on the CODEOWNERS file, where ownership is fixed, and
+/// passed ones as notices.
+fn print_gate_annotations(
+    out: &mut dyn Write,
+    checks: &[GateCheck],
+    codeowners_location: Option<&str>,
+) -> Result<()> {
+    for check in checks {
+        let value = match check.value {
+            Some(value) => format!("{:.1}%", value * 100.0),
+            None => "n/a".to_string(),
+        };
+        let bound = match check.bound {
+            GateBound::Max => "at most",
+            GateBound::Min => "at least",
+        };
+        let message = format!(
+            "{} is {} ({}), expected {} {}",
+            check.metric, value, check.detail, bound, check.threshold
+        );
+        let annotation = if check.passed() {
+            bound::github_annotation("notice", None, None, "Ownership gate passed", &message)
+        } else {
+            bound::github_annotation(
+                "error",
+                codeowners_location,
+                None,
+                "Ownership gate failed",
+                &message,
+            )
+        };
+        writeln!(out, "{}", annotation)?;
+    }
+    Ok(())
+}
+
+fn print_watch_delta(out: &mut dyn Write, delta: &WatchDelta, tsv: bool) -> Result<()> {
+    let checked_at = bound::format_date(chrono::Utc::now());
+    let from = &delta.from[..delta.from.len().min(12)];
+    let to = &delta.to[..delta.to.len().min(12)];
+    if tsv {
+        for owner in &delta.owners {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                checked_at,
+                delta.from,
+                delta.to,
+                delta.summary.total_commits,
+                owner.owner,
+                owner.team_changes,
+                owner.outside_changes
+            )?;
+        }
+        return Ok(());
+    }
+
+    let total = delta.summary.total_changes();
+    let share = |changes: usize| {
+        if total > 0 {
+            changes as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+    writeln!(out)?;
+    writeln!(out, "New commits {}..{} at {}:", from, to, checked_at)?;
+    writeln!(out, "  Commits: {}", delta.summary.total_commits)?;
+    writeln!(
+        out,
+        "  Changes: {} (+{}, -{})",
+        total, delta.summary.total_insertions, delta.summary.total_deletions
+    )?;
+    writeln!(
+        out,
+        "  Changes by Owning Teams: {} ({:.1}%)",
+        delta.summary.changes_by_team,
+        share(delta.summary.changes_by_team)
+    )?;
+    writeln!(
+        out,
+        "  Unowned Changes: {} ({:.1}%)",
+        delta.summary.unowned_changes,
+        share(delta.summary.unowned_changes)
+    )?;
+    for owner in &delta.owners {
+        writeln!(
+            out,
+            "    {}: {} by the team, {} from outside",
+            owner.owner, owner.team_changes, owner.outside_changes
+        )?;
+    }
+    Ok(())
+}
+
+fn print_repository_summary(
+    out: &mut dyn Write,
+    summary: &RepositorySummary,
+    since: &str,
+    until: &str,
+    tsv: bool,
+) -> Result<()> {
+    let totals = &summary.summary;
+    if tsv {
+        writeln!(out,
+            "# since={} until={} commits={} changes={} team_changes={} team_share={:.4} unowned_changes={} unowned_share={:.4} owners={} files={} unowned_files={}",
+            since,
+            until,
+            totals.total_commits,
+            totals.total_changes(),
+            totals.changes_by_team,
+            summary.team_share(),
+            totals.unowned_changes,
+            summary.unowned_share(),
+            summary.owners,
+            summary.files,
+            summary.unowned_files
+        )?;
+        writeln!(out, "owner\tteam_changes\toutside_changes\toutside_share")?;
+        for owner in &summary.top_outside_owners {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{:.4}",
+                owner.owner,
+                owner.team_changes,
+                owner.outside_changes,
+                owner.outside_share()
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(out, "Summary from {} to {}:", since, until)?;
+    writeln!(out, "  Commits: {}", totals.total_commits)?;
+    writeln!(
+        out,
+        "  Changes: {} (+{}, -{})",
+        totals.total_changes(),
+        totals.total_insertions,
+        totals.total_deletions
+    )?;
+    writeln!(
+        out,
+        "  Changes by Owning Teams: {} ({:.1}%)",
+        totals.changes_by_team,
+        summary.team_share() * 100.0
+    )?;
+    writeln!(
+        out,
+        "  Unowned Changes: {} ({:.1}%)",
+        totals.unowned_changes,
+        summary.unowned_share() * 100.0
+    )?;
+    writeln!(
+        out,
+        "  Unowned Files: {} of {} ({:.1}%)",
+        summary.unowned_files,
+        summary.files,
+        summary.unowned_file_share() * 100.0
+    )?;
+    if summary.top_outside_owners.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "  Top Owners by Outside Contribution (of {} owners):",
+        summary.owners
+    )?;
+    for owner in &summary.top_outside_owners {
+        writeln!(
+            out,
+            "    {}: {:.1}% outside ({} of {} changes)",
+            owner.owner,
+            owner.outside_share() * 100.0,
+            owner.outside_changes,
+            owner.team_changes + owner.outside_changes
+        )?;
+    }
+    Ok(())
+}
+
+fn print_stale_ownership(
+    out: &mut dyn Write,
+    stale: &[StaleOwnershipInfo],
+    tsv: bool,
+) -> Result<()> {
+    let top_contributor = |info: &StaleOwnershipInfo| {
+        info.top_outside_contributors
+            .first()
+            .map_or_else(String::new, |contributor| {
+                format!("{} <{}>", contributor.author_name, contributor.author_email)
+            })
+    };
+    if tsv {
+        writeln!(out, "line\tpattern\towners\tteam_changes\toutside_changes\tlast_team_change\ttop_outside_contributor")?;
+        for info in stale {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                info.line,
+                info.pattern,
+                info.owners.join(","),
+                info.team_changes,
+                info.outside_changes,
+                format_timestamp_date(info.last_team_change),
+                top_contributor(info)
+            )?;
+        }
+        return Ok(());
+    }
+
+    if stale.is_empty() {
+        writeln!(out, "No CODEOWNERS rules with stale ownership")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "{:>5} {:<30} {:<30} {:>12} {:>15} {:>16}  Top Outside Contributor",
+        "Line", "Pattern", "Owners", "Team Changes", "Outside Changes", "Last Team Change"
+    )?;
+    for info in stale {
+        writeln!(
+            out,
+            "{:>5} {:<30} {:<30} {:>12} {:>15} {:>16}  {}",
+            info.line,
+            info.pattern,
+            info.owners.join(" "),
+            info.team_changes,
+            info.outside_changes,
+            format_timestamp_date(info.last_team_change),
+            top_contributor(info)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_owner_suggestions(
+    out: &mut dyn Write,
+    suggestions: &[OwnerSuggestion],
+    tsv: bool,
+) -> Result<()> {
+    if tsv {
+        writeln!(out, "pattern\towner\towner_changes\ttotal_changes\tfiles")?;
+        for suggestion in suggestions {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                suggestion.pattern,
+                suggestion.owner,
+                suggestion.owner_changes,
+                suggestion.total_changes,
+                suggestion.files
+            )?;
+        }
+        return Ok(());
+    }
+
+    if suggestions.is_empty() {
+        writeln!(out, "# No owners to suggest")?;
+        return Ok(());
+    }
+    for suggestion in suggestions {
+        writeln!(
+            out,
+            "# {} made {} of {} changed lines ({:.0}%) in {} unowned {}",
+            suggestion.owner,
+            suggestion.owner_changes,
+            suggestion.total_changes,
+            suggestion.share() * 100.0,
+            suggestion.files,
+            if suggestion.files == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        )?;
+        writeln!(out, "{}", suggestion.codeowners_line())?;
+    }
+    Ok(())
+}
+
+fn print_coverage(out: &mut dyn Write, coverage: &[CoverageInfo], tsv: bool) -> Result<()> {
+    if tsv {
+        writeln!(
+            out,
+            "period\tchanges\towned_changes\tchange_coverage\tfiles\towned_files\tfile_coverage"
+        )?;
+    } else {
+        writeln!(
+            out,
+            "{:<10} {:>10} {:>14} {:>16} {:>8} {:>12} {:>14}",
+            "Period",
+            "Changes",
+            "Owned Changes",
+            "Change Coverage",
+            "Files",
+            "Owned Files",
+            "File Coverage"
+        )?;
+    }
+    for info in coverage {
+        let changes = info.summary.total_changes();
+        let owned_changes = changes - info.summary.unowned_changes;
+        if tsv {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{:.4}\t{}\t{}\t{:.4}",
+                info.period,
+                changes,
+                owned_changes,
+                info.summary.coverage(),
+                info.files,
+                info.owned_files,
+                info.file_coverage()
+            )?;
+        } else {
+            writeln!(
+                out,
+                "{:<10} {:>10} {:>14} {:>15.1}% {:>8} {:>12} {:>13.1}%",
+                info.period,
+                changes,
+                owned_changes,
+                info.summary.coverage() * 100.0,
+                info.files,
+                info.owned_files,
+                info.file_coverage() * 100.0
+            )?;
+        }
+    }
+
+    if let (false, Some(first), Some(last)) = (tsv, coverage.first(), coverage.last()) {
+        let change = (last.file_coverage() - first.file_coverage()) * 100.0;
+        let trend = if change > 0.0 {
+            "improved"
+        } else if change < 0.0 {
+            "declined"
+        } else {
+            "was unchanged"
+        };
+        writeln!(out)?;
+        writeln!(
+            out,
+            "File coverage {} from {:.1}% to {:.1}% between {} and {}",
+            trend,
+            first.file_coverage() * 100.0,
+            last.file_coverage() * 100.0,
+            first.period,
+            last.period
+        )?;
+    }
+    Ok(())
+}
+
+fn format_team_size(team_size: Option<usize>) -> String {
+    team_size.map_or_else(|| "-".to_string(), |size| size.to_string())
+}
+
+fn print_team_size_tsv(out: &mut dyn Write, trends: &[TeamSizeTrend]) -> Result<()> {
+    writeln!(
+        out,
+        "period\towner\tteam_size\tteam_changes\tothers_changes\tchanges_per_member\towned_files"
+    )?;
+    for trend in trends {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            trend.period,
+            trend.owner,
+            format_team_size(trend.team_size),
+            trend.changes_by_team,
+            trend.changes_by_others,
+            trend
+                .changes_per_member()
+                .map_or_else(|| "-".to_string(), |value| format!("{:.2}", value)),
+            trend.owned_files
+        )?;
+    }
+    Ok(())
+}
+
+fn percent_change(from: usize, to: usize) -> Option<f64> {
+    (from > 0).then(|| (to as f64 - from as f64) * 100.0 / from as f64)
+}
+
+fn describe_change(noun: &str, grew: &str, shrank: &str, from: usize, to: usize) -> String {
+    match percent_change(from, to) {
+        Some(change) if change > 0.0 => format!("{} {} {:.0}%", noun, grew, change),
+        Some(change) if change < 0.0 => format!("{} {} {:.0}%", noun, shrank, -change),
+        Some(_) => format!("{} unchanged", noun),
+        None => format!("{} {} -> {}", noun, from, to),
+    }
+}
+
+fn print_team_size_trends(out: &mut dyn Write, trends: &[TeamSizeTrend]) -> Result<()> {
+    let mut by_owner: BTreeMap<&str, Vec<&TeamSizeTrend>> = BTreeMap::new();
+    for trend in trends {
+        by_owner.entry(&trend.owner).or_default().push(trend);
+    }
+
+    for (owner, owner_trends) in by_owner {
+        writeln!(out, "Owner: {}", owner)?;
+        for trend in &owner_trends {
+            writeln!(out,
+                "  {}: team size {}, team changes {}, others changes {}, changes per member {}, owned files {}",
+                trend.period,
+                format_team_size(trend.team_size),
+                trend.changes_by_team,
+                trend.changes_by_others,
+                trend
+                    .changes_per_member()
+                    .map_or_else(|| "-".to_string(), |value| format!("{:.2}", value)),
+                trend.owned_files
+            )?;
+        }
+        if let (Some(first), Some(last)) = (owner_trends.first(), owner_trends.last()) {
+            if owner_trends.len() > 1 {
+                let team = match (first.team_size, last.team_size) {
+                    (Some(from), Some(to)) => describe_change("team", "grew", "shrank", from, to),
+                    _ => "team size unknown".to_string(),
+                };
+                writeln!(
+                    out,
+                    "  {} to {}: {}, {}",
+                    first.period,
+                    last.period,
+                    team,
+                    describe_change(
+                        "owned area",
+                        "grew",
+                        "shrank",
+                        first.owned_files,
+                        last.owned_files
+                    )
+                )?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn print_owner_digests(
+    out: &mut dyn Write,
+    digests: &[OwnerDigest],
+    since: &str,
+    until: &str,
+    format: DigestFormat,
+) -> Result<()> {
+    let (heading, bold) = match format {
+        DigestFormat::Markdown => ("## ", "**"),
+        DigestFormat::Slack => ("", "*"),
+    };
+    let short_id = |id: &str| id.chars().take(10).collect::<String>();
+
+    writeln!(
+        out,
+        "{}Ownership digest ({} to {}){}",
+        bold, since, until, bold
+    )?;
+    writeln!(out)?;
+    for digest in digests {
+        writeln!(out, "{}{}{}{}", heading, bold, digest.owner, bold)?;
+        writeln!(
+            out,
+            "- Changes: {} by team, {} by others",
+            digest.changes_by_team, digest.changes_by_others
+        )?;
+        writeln!(
+            out,
+            "- Commits: {} by team, {} by others",
+            digest.commits_by_team, digest.commits_by_others
+        )?;
+        if !digest.large_commits.is_empty() {
+            writeln!(out, "- Notable large commits:")?;
+            for commit in &digest.large_commits {
+                writeln!(
+                    out,
+                    "  - `{}` {} <{}>: {} changes",
+                    short_id(&commit.id),
+                    commit.author_name,
+                    commit.author_email,
+                    commit.changes
+                )?;
+            }
+        }
+        if !digest.new_outside_contributors.is_empty() {
+            writeln!(out, "- New outside contributors:")?;
+            for (name, email) in &digest.new_outside_contributors {
+                writeln!(out, "  - {} <{}>", name, email)?;
+            }
+        }
+        if !digest.codeowners_edits.is_empty() {
+            writeln!(out, "- CODEOWNERS edits affecting this owner:")?;
+            for commit in &digest.codeowners_edits {
+                writeln!(
+                    out,
+                    "  - `{}` {} <{}>",
+                    short_id(&commit.id),
+                    commit.author_name,
+                    commit.author_email
+                )?;
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn parse_since(s: &str) -> Result<String, DateError> {
+    let date = bound::resolve_date(s, DateBound::Since, chrono::Utc::now())?;
+    Ok(bound::format_date(date))
+}
+
+fn parse_until(s: &str) -> Result<String, DateError> {
+    let date = bound::resolve_date(s, DateBound::Until, chrono::Utc::now())?;
+    Ok(bound::format_date(date))
+}
+
+#[derive(Args)]
+struct TeamFetchArgs {
+    #[arg(long, default_value_t = TeamMembership::Inherited)]
+    team_membership: TeamMembership,
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    #[arg(long = "ref", visible_alias = "branch")]
+    git_ref: Option<String>,
+    #[arg(long, default_value_t = MergeMode::Exclude)]
+    merges: MergeMode,
+    #[arg(long, default_value_t = RenameDetection::Renames)]
+    renames: RenameDetection,
+    #[arg(long, default_value_t = CommitAttribution::Author)]
+    attribute_to: CommitAttribution,
+    #[arg(long)]
+    attribute_merges_to_pr_author: bool,
+    #[arg(long)]
+    skip_errors: bool,
+    #[arg(long = "path-glob")]
+    path_globs: Vec<String>,
+    #[arg(long = "exclude-glob")]
+    exclude_globs: Vec<String>,
+    #[arg(long = "exclude-tag")]
+    exclude_tags: Vec<CommitTag>,
+    #[arg(long = "author")]
+    authors: Vec<String>,
+    #[arg(long = "author-email")]
+    author_emails: Vec<String>,
+    #[arg(long = "exclude-author")]
+    exclude_authors: Vec<String>,
+    #[arg(long)]
+    exclude_bots: bool,
+    #[arg(long)]
+    skip_generated: bool,
+    #[arg(long)]
+    include_messages: bool,
+    #[arg(long)]
+    file_sizes: bool,
+    #[arg(long)]
+    count_binary: bool,
+    #[arg(long)]
+    max_commit_changes: Option<usize>,
+    #[arg(long, default_value_t = LargeCommitPolicy::Cap, requires = "max_commit_changes")]
+    large_commit_policy: LargeCommitPolicy,
+    #[arg(long)]
+    codeowners_ref: Option<String>,
+    #[arg(long)]
+    codeowners_repo: Option<PathBuf>,
+    #[arg(long, default_value_t = CodeownersMode::Historical)]
+    codeowners_mode: CodeownersMode,
+    #[arg(long, default_value_t = 0)]
+    git_retries: u32,
+    #[arg(long, default_value_t = 1)]
+    git_retry_delay: u64,
+    #[arg(long, default_value_t = CommitDate::Committer)]
+    date: CommitDate,
+}
+
+impl HistoryArgs {
+    fn log_options(&self, context: &HistoryContext) -> GitLogOptions {
+        GitLogOptions {
+            rev: self.git_ref.clone(),
+            paths: Vec::new(),
+            merges: self.merges,
+            renames: self.renames,
+            attribute_to: self.attribute_to,
+            attribute_merges_to_pr_author: self.attribute_merges_to_pr_author,
+            include_message: self.include_messages,
+            include_file_sizes: self.file_sizes,
+            name_normalization: context.name_normalization.clone(),
+            renamed_owners: context.renamed_owners.clone(),
+            codeowners_source: self.codeowners_source(),
+            codeowners_mode: self.codeowners_mode.clone(),
+            fixed_codeowners: None,
+            retry: GitRetry {
+                attempts: self.git_retries,
+                backoff: Duration::from_secs(self.git_retry_delay),
+            },
+            progress: None,
+            authors: self.author_selection(context),
+            date: self.date,
+            pseudonyms: context.pseudonyms.clone(),
+        }
+    }
+
+    fn author_selection(&self, context: &HistoryContext) -> AuthorSelection {
+        let authors = AuthorSelection {
+            names: self.authors.clone(),
+            emails: self.author_emails.clone(),
+            exclude: self.exclude_authors.clone(),
+        };
+        if self.exclude_bots {
+            authors.excluding_bots(&context.bot_authors)
+        } else {
+            authors
+        }
+    }
+
+    /// The last commit of the analyzed history, for reading the tree as it is now.
+    fn tip(&self) -> &str {
+        match &self.git_ref {
+            Some(rev) => rev.rsplit_once("..").map_or(rev, |(_, tip)| tip),
+            None => "HEAD",
+        }
+    }
+
+    fn codeowners_source(&self) -> Option<CodeownersSource> {
+        if self.codeowners_ref.is_none() && self.codeowners_repo.is_none() {
+            return None;
+        }
+        Some(CodeownersSource {
+            repo: self.codeowners_repo.clone(),
+            rev: self
+                .codeowners_ref
+                .clone()
+                .unwrap_or_else(|| "HEAD".to_string()),
+        })
+    }
+
+    /// Log options for the analyze commands, which also read commit trailers when
+    /// co-authors are to be credited and count commits on a spinner while reading.
+    fn log_options_with_co_authors(
+        &self,
+        context: &HistoryContext,
+        split_coauthors: Option<CoauthorMode>,
+    ) -> GitLogOptions {
+        GitLogOptions {
+            include_message: self.include_messages || split_coauthors.is_some(),
+            progress: Some(Arc::new(CommitProgress::default())),
+            ..self.log_options(context)
+        }
+    }
+
+    fn commits<T: FileChangePaths + 'static>(
+        &self,
+        commits: impl Iterator<Item = io::Result<T>> + 'static,
+        directory: &Path,
+        context: &HistoryContext,
+    ) -> Result<Box<dyn Iterator<Item = io::Result<T>>>> {
+        let mut commits: Box<dyn Iterator<Item = io::Result<T>>> = Box::new(commits);
+        if self.skip_errors {
+            commits = Box::new(bound::skip_errors(commits, &context.warnings));
+        }
+        if !context.owner_aliases.is_empty() {
+            commits = Box::new(bound::alias_commit_owners(
+                commits,
+                context.owner_aliases.clone(),
+            ));
+        }
+        if self.skip_generated {
+            commits = Box::new(bound::skip_generated_files(
+                commits,
+                directory.to_path_buf(),
+            ));
+        }
+        let path_filter = PathFilter::new(&self.path_globs, &self.exclude_globs)?;
+        if !path_filter.is_empty() {
+            commits = Box::new(bound::filter_commit_paths(commits, path_filter));
+        }
+        if !self.exclude_tags.is_empty() {
+            commits = Box::new(bound::exclude_tagged_commits(
+                commits,
+                self.exclude_tags.clone(),
+            ));
+        }
+        if self.count_binary {
+            commits = Box::new(bound::count_binary_changes(commits));
+        } else {
+            commits = Box::new(bound::warn_binary_files(commits, &context.warnings));
+        }
+        if let Some(max) = self.max_commit_changes {
+            commits = Box::new(bound::limit_large_commits(
+                commits,
+                max,
+                self.large_commit_policy,
+                &context.warnings,
+            ));
+        }
+        bound::warn_invalid_codeowners(
+            self.git_ref.as_deref().unwrap_or("HEAD"),
+            &directory.to_path_buf(),
+            &context.warnings,
+        )?;
+        Ok(commits)
+    }
+}
+
+/// Runs the owner analysis, telling first-time contributors apart when `prior` says who
+/// changed each owner's files before.
+fn analyze_owners(
+    commits: impl Iterator<Item = io::Result<CommitInfoWithCodeowner>>,
+    options: &AnalysisOptions,
+    prior: Option<&OwnerContributors>,
+) -> io::Result<OwnerAnalysis> {
+    let mut analyzer = OwnerAnalyzer::new(options);
+    if let Some(prior) = prior {
+        analyzer = analyzer.with_prior_contributors(prior.clone());
+    }
+    for commit in commits {
+        analyzer.add_commit(&commit?);
+    }
+    Ok(analyzer.finish())
+}
+
+/// The component map to report by, when grouping by component.
+fn component_map(grouping: OwnerGrouping, path: &Path) -> Result<Option<ComponentMap>> {
+    Ok(match grouping {
+        OwnerGrouping::Owner => None,
+        OwnerGrouping::Component => Some(ComponentMap::load(path).map_err(|e| {
+            anyhow::anyhow!("Could not read components from {}: {}", path.display(), e)
+        })?),
+    })
+}
+
+fn with_components(
+    commits: CommitStream<CommitInfoWithCodeowner>,
+    components: &Option<ComponentMap>,
+) -> CommitStream<CommitInfoWithCodeowner> {
+    match components {
+        Some(components) => Box::new(bound::map_commit_components(commits, components.clone())),
+        None => commits,
+    }
+}
+
+/// The pull request each commit in `directories` came from, learned from `source`.
+async fn pull_request_numbers(
+    source: PullRequestSource,
+    directories: &[PathBuf],
+    since: &str,
+    until: &str,
+    history: &HistoryArgs,
+    github_auth: &GithubAuth,
+) -> Result<HashMap<String, u64>> {
+    let mut numbers = HashMap::new();
+    let api = match source {
+        PullRequestSource::Git => None,
+        PullRequestSource::Github => Some(GithubApi::new_with_auth(github_auth).await?),
+    };
+    for directory in directories {
+        numbers.extend(bound::pull_request_numbers(
+            since,
+            history.git_ref.as_deref(),
+            directory,
+        )?);
+        let Some(api) = &api else {
+            continue;
+        };
+        let repo = bound::get_origin_github_repo(directory)?
+            .ok_or_else(|| anyhow::anyhow!("No GitHub remote found in {}", directory.display()))?;
+        for pull_request in bound::get_merged_pull_requests(api, &repo, since, until).await? {
+            if let Some(sha) = pull_request.merge_commit_sha {
+                numbers.entry(sha).or_insert(pull_request.number);
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+fn with_pull_requests(
+    commits: CommitStream<CommitInfoWithCodeowner>,
+    numbers: &Option<HashMap<String, u64>>,
+) -> Result<CommitStream<CommitInfoWithCodeowner>> {
+    Ok(match numbers {
+        Some(numbers) => Box::new(
+            bound::group_commits_by_pull_request(commits, numbers)?
+                .into_iter()
+                .map(Ok),
+        ),
+        None => commits,
+    })
+}
+
+type CommitStream<T> = Box<dyn Iterator<Item = io::Result<T>>>;
+
+/// Classifies the language of each file change with `--by-language`, using the languages
+/// configured in `overrides` on top of the built-in ones.
+fn with_languages(
+    commits: CommitStream<CommitInfoWithCodeowner>,
+    by_language: bool,
+    overrides: &BTreeMap<String, String>,
+) -> CommitStream<CommitInfoWithCodeowner> {
+    if by_language {
+        Box::new(bound::classify_languages(
+            commits,
+            LanguageMap::with_overrides(overrides),
+        ))
+    } else {
+        commits
+    }
+}
+
+/// The adjusted model to analyze with when `adjusted`: the `--weight` preset if given,
+/// else the `[adjusted]` table of `bound.toml`.
+fn adjusted_metric(
+    adjusted: bool,
+    weight: Option<AdjustedWeight>,
+    config: &BoundConfig,
+) -> Option<MetricConfig> {
+    adjusted.then(|| weight.map_or(config.adjusted, MetricConfig::from))
+}
+
+/// Language breakdowns are only rendered in the text and NDJSON reports.
+fn reject_language_table(by_language: bool, tsv: bool, format: Option<OutputFormat>) -> Result<()> {
+    if by_language && (tsv || format == Some(OutputFormat::Markdown)) {
+        anyhow::bail!("--by-language is only supported with text and ndjson output");
+    }
+    Ok(())
+}
+
+/// The commits to analyze for each of `directories`, labelled with its name from `names`.
+/// A single repository, or several with `merge` so owners of the same name are combined
+/// across them, is analyzed as one unlabelled run.
+fn repository_runs<T: 'static>(
+    directories: &[PathBuf],
+    names: &[String],
+    merge: bool,
+    mut commits_of: impl FnMut(&PathBuf) -> Result<CommitStream<T>>,
+) -> Result<Vec<(Option<String>, CommitStream<T>)>> {
+    let mut runs = Vec::new();
+    for (directory, name) in directories.iter().zip(names) {
+        runs.push((Some(name.clone()), commits_of(directory)?));
+    }
+    if merge || runs.len() == 1 {
+        let commits: CommitStream<T> = Box::new(runs.into_iter().flat_map(|(_, commits)| commits));
+        return Ok(vec![(None, commits)]);
+    }
+    Ok(runs)
+}
+
+/// Credits the co-authors of each commit according to `mode`, when one is given.
+fn with_co_authors(
+    commits: impl Iterator<Item = io::Result<CommitInfoWithCodeowner>> + 'static,
+    mode: Option<CoauthorMode>,
+    memberships: &[AuthorCodeownerMemberships],
+    context: &HistoryContext,
+) -> Box<dyn Iterator<Item = io::Result<CommitInfoWithCodeowner>>> {
+    match mode {
+        Some(mode) => Box::new(bound::attribute_co_authors(
+            commits,
+            mode,
+            memberships,
+            &context.name_normalization,
+        )),
+        None => Box::new(commits),
+    }
+}
+
+/// Settings applied to every history walk that don't come from the command line.
+struct HistoryContext {
+    warnings: Warnings,
+    owner_aliases: OwnerAliases,
+    renamed_owners: OwnerAliases,
+    name_normalization: NameNormalization,
+    /// Extra author patterns treated as bots, from the config.
+    bot_authors: Vec<String>,
+    /// Stand-ins for the people in the history, with `--anonymize`.
+    pseudonyms: Option<Pseudonyms>,
+    /// Memberships inferred from authors, with `--membership-rules`.
+    membership_rules: Option<MembershipRules>,
+}
+
+impl HistoryContext {
+    /// The memberships at `path` and those the rules infer for the authors of the
+    /// repositories in `directories`, pseudonymized alike with the history so they still
+    /// match. With rules, a missing memberships file is no error, so the rules can be used on
+    /// their own.
+    fn read_memberships<'a>(
+        &self,
+        path: &PathBuf,
+        directories: impl IntoIterator<Item = &'a PathBuf>,
+    ) -> io::Result<Vec<AuthorCodeownerMemberships>> {
+        let mut memberships = match (read_memberships_from_tsv(path), &self.membership_rules) {
+            (Err(e), Some(_)) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            (memberships, _) => memberships?,
+        };
+        // Rules match the real authors, so they're applied before pseudonymizing
+        if let Some(rules) = &self.membership_rules {
+            for directory in directories {
+                memberships.extend(rules.memberships(bound::git_authors(directory)?));
+            }
+        }
+        Ok(match &self.pseudonyms {
+            Some(pseudonyms) => pseudonyms.memberships(memberships),
+            None => memberships,
+        })
+    }
+
+    /// GitHub users' commit identities, pseudonymized like the memberships they're matched to.
+    fn identities(&self, identities: LoginIdentities) -> LoginIdentities {
+        let Some(pseudonyms) = &self.pseudonyms else {
+            return identities;
+        };
+        identities
+            .into_iter()
+            .map(|(login, (name, email))| {
+                (login, (pseudonyms.name(&name), pseudonyms.email(&email)))
+            })
+            .collect()
+    }
+}
+
+/// A spinner counting the commits read, shown from the first commit and cleared once all
+/// have been read so it never mixes with the output.
+#[derive(Default)]
+struct CommitProgress {
+    spinner: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressSink for CommitProgress {
+    fn commits_processed(&self, count: usize) {
+        let mut spinner = self.spinner.lock().unwrap();
+        let spinner = spinner.get_or_insert_with(|| {
+            let progress = progress_bar(None);
+            progress.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{spinner:.green} {pos} commits read")
+                    .unwrap(),
+            );
+            progress
+        });
+        spinner.set_position(count as u64);
+    }
+
+    fn commits_finished(&self, _count: usize) {
+        if let Some(spinner) = self.spinner.lock().unwrap().take() {
+            spinner.finish_and_clear();
+        }
+    }
+}
+
+type LoginIdentities = HashMap<String, (String, String)>;
+
+async fn fetch_pull_request_reviews(
+    api: &GithubApi,
+    repo: &str,
+    since: &str,
+    until: &str,
+    user_cache: &mut UserInfoCache,
+) -> Result<(Vec<PullRequestReviewData>, LoginIdentities)> {
+    let progress_style = ProgressStyle::default_spinner()
+        .template("{spinner:.green} {msg}")
+        .unwrap();
+    let progress = progress_bar(None);
+    progress.set_style(progress_style);
+    progress.set_message("Fetching merged pull requests...");
+
+    let summaries = bound::get_merged_pull_requests(api, repo, since, until).await?;
+
+    progress.finish_with_message(format!("Fetched {} merged pull requests.", summaries.len()));
+
+    let progress = progress_bar(Some(summaries.len() as u64));
+    let pb_style = ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} pull requests")
+        .unwrap_or_else(|_| ProgressStyle::default_bar());
+    progress.set_style(pb_style);
+
+    let mut identities: LoginIdentities = HashMap::new();
+    let mut pull_requests = Vec::new();
+    for summary in summaries {
+        let files = bound::get_pull_request_files(api, repo, summary.number).await?;
+        let reviews = bound::get_pull_request_reviews(api, repo, summary.number).await?;
+        let mut first_approved_at: HashMap<String, String> = HashMap::new();
+        let approvers: Vec<String> = reviews
+            .into_iter()
+            .filter(|review| review.state == "APPROVED" && review.login != summary.author_login)
+            .inspect(|review| {
+                if let Some(submitted_at) = &review.submitted_at {
+                    first_approved_at
+                        .entry(review.login.clone())
+                        .and_modify(|first| {
+                            if submitted_at < first {
+                                first.clone_from(submitted_at);
+                            }
+                        })
+                        .or_insert_with(|| submitted_at.clone());
+                }
+            })
+            .map(|review| review.login)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        for login in &approvers {
+            if !identities.contains_key(login) {
+                if let Some(info) = get_user_info_cached(api, login, user_cache).await? {
+                    identities.insert(login.clone(), info);
+                }
+            }
+        }
+        pull_requests.push(PullRequestReviewData {
+            number: summary.number,
+            author_login: summary.author_login,
+            created_at: summary.created_at,
+            files,
+            approvers,
+            first_approved_at,
+        });
+        progress.inc(1);
+    }
+    progress.finish_with_message("All pull requests processed");
+
+    Ok((pull_requests, identities))
+}
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[arg(long, global = true, default_value = "bound.toml")]
+    config: PathBuf,
+    #[arg(long, global = true)]
+    remote_cache: Option<PathBuf>,
+    #[arg(long, global = true)]
+    cleanup_remote: bool,
+    #[arg(long, global = true)]
+    isolate: bool,
+    #[arg(long, global = true)]
+    allow_shallow: bool,
+    #[arg(long, global = true)]
+    fetch_depth: Option<u32>,
+    #[arg(long, global = true)]
+    no_cache: bool,
+    #[arg(long, global = true, default_value_t = 7)]
+    user_cache_days: i64,
+    #[arg(long, global = true)]
+    token: Option<String>,
+    #[arg(long, global = true)]
+    api_usage_json: Option<PathBuf>,
+    #[arg(long, global = true)]
+    warnings_json: Option<PathBuf>,
+    #[arg(long, global = true)]
+    audit_log: Option<PathBuf>,
+    #[arg(long, global = true)]
+    renamed_owners: Option<PathBuf>,
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+    #[arg(long, global = true)]
+    anonymize: bool,
+    #[arg(long, global = true, requires = "anonymize")]
+    anonymize_salt: Option<String>,
+    #[arg(long, global = true)]
+    membership_rules: Option<PathBuf>,
+    #[arg(long, global = true, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+    #[arg(long, global = true, requires_all = ["github_app_installation_id", "github_app_key"])]
+    github_app_id: Option<String>,
+    #[arg(long, global = true, requires = "github_app_id")]
+    github_app_installation_id: Option<String>,
+    #[arg(long, global = true, requires = "github_app_id")]
+    github_app_key: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Commands,
+}
+#[derive(Subcommand)]
+enum DevCommands {
+    GhGetToken,
+    GhGetTeamSlugs {
+        org: String,
+    },
+    GhGetTeamMembers {
+        #[arg(short, long)]
+        org: String,
+        #[arg(short, long)]
+        team: String,
+    },
+    GhGetUserNameEmail {
+        logins: Vec<String>,
+    },
+    GhGetOrgLogins,
+    PrintCommits {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+    },
+    GetCodeowners {
+        #[arg(short, long)]
+        commit: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+    },
+    GetAllCodeowners {
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+    },
+    PrintCommitsWithCodeowners {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: Option<PathBuf>,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+    },
+}
+#[derive(Subcommand)]
+enum ExportCommands {
+    Sqlite {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: Option<PathBuf>,
+        #[arg(short, long, default_value = "bound.db")]
+        output: PathBuf,
+    },
+    Heatmap {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(long = "row-glob")]
+        row_globs: Vec<String>,
+        #[arg(long, default_value_t = HeatmapFormat::Csv)]
+        format: HeatmapFormat,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+#[derive(Subcommand)]
+enum NotifyCommands {
+    Owners {
+        #[arg(short, long, default_value = "1 week ago", value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = "now", value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "bound")]
+        from: String,
+        #[arg(long, default_value = "sendmail")]
+        sendmail: PathBuf,
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+#[derive(Subcommand)]
+enum Commands {
+    #[command(subcommand)]
+    Dev(DevCommands),
+    #[command(subcommand)]
+    Export(ExportCommands),
+    #[command(subcommand)]
+    Notify(NotifyCommands),
+    Init {
+        org: String,
+
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "codeowners.state.tsv")]
+        state_path: PathBuf,
+        #[arg(long, default_value = "team_sizes.tsv")]
+        team_size_history: PathBuf,
+        #[arg(short, long)]
+        repo: Option<String>,
+        #[arg(long)]
+        no_commit_lookup: bool,
+        #[arg(long, conflicts_with_all = ["repo", "no_commit_lookup"])]
+        from_export: Option<PathBuf>,
+        #[arg(long, conflicts_with = "from_export")]
+        with_history: bool,
+        #[command(flatten)]
+        team_fetch: TeamFetchArgs,
+    },
+    Refresh {
+        org: String,
+
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "codeowners.state.tsv")]
+        state_path: PathBuf,
+        #[arg(long, default_value = "team_sizes.tsv")]
+        team_size_history: PathBuf,
+        #[arg(short, long)]
+        repo: Option<String>,
+        #[arg(long)]
+        no_commit_lookup: bool,
+        #[command(flatten)]
+        team_fetch: TeamFetchArgs,
+    },
+    Analyze {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, conflicts_with_all = ["owners", "contributors"])]
+        all: bool,
+        #[arg(long)]
+        owners: bool,
+        #[arg(long)]
+        contributors: bool,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long)]
+        adjusted: bool,
+        #[arg(long, requires = "adjusted")]
+        weight: Option<AdjustedWeight>,
+        #[arg(long)]
+        owner_attribution: Option<OwnerAttribution>,
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        #[arg(long, num_args = 0..=1, default_missing_value = "split")]
+        split_coauthors: Option<CoauthorMode>,
+    },
+    AnalyzeByOwner {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: Vec<PathBuf>,
+        #[arg(long)]
+        merge_owners: bool,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+        #[arg(long)]
+        adjusted: bool,
+        #[arg(long, requires = "adjusted")]
+        weight: Option<AdjustedWeight>,
+        #[arg(long)]
+        owner_attribution: Option<OwnerAttribution>,
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        #[arg(long)]
+        group_by: Option<PeriodGrouping>,
+        #[arg(long)]
+        newcomers: bool,
+        #[arg(long, default_value_t = OwnerGrouping::Owner)]
+        grouping: OwnerGrouping,
+        #[arg(long, default_value = "components.yml")]
+        components_path: PathBuf,
+        #[arg(long)]
+        pull_requests: Option<PullRequestSource>,
+        #[arg(long, num_args = 0..=1, default_missing_value = "split")]
+        split_coauthors: Option<CoauthorMode>,
+        #[arg(long)]
+        by_language: bool,
+        #[arg(long, conflicts_with = "group_by")]
+        compare_with: Option<ComparisonWindow>,
+    },
+    AnalyzeByContributor {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: Vec<PathBuf>,
+        #[arg(long)]
+        merge_owners: bool,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(short, long)]
+        owner: Option<String>,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+        #[arg(long)]
+        adjusted: bool,
+        #[arg(long, requires = "adjusted")]
+        weight: Option<AdjustedWeight>,
+        #[arg(long)]
+        owner_attribution: Option<OwnerAttribution>,
+        #[arg(long)]
+        group_by: Option<PeriodGrouping>,
+        #[arg(long, default_value_t = OwnerGrouping::Owner)]
+        grouping: OwnerGrouping,
+        #[arg(long, default_value = "components.yml")]
+        components_path: PathBuf,
+        #[arg(long)]
+        pull_requests: Option<PullRequestSource>,
+        #[arg(long, num_args = 0..=1, default_missing_value = "split")]
+        split_coauthors: Option<CoauthorMode>,
+        #[arg(long)]
+        by_language: bool,
+        #[arg(long, conflicts_with = "group_by")]
+        compare_with: Option<ComparisonWindow>,
+    },
+    AnalyzeByProject {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+    },
+    Digest {
+        #[arg(short, long, default_value = "1 week ago", value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = "now", value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "1970-01-01", value_parser = parse_since)]
+        history_since: String,
+        #[arg(long, default_value_t = 500)]
+        large_commit_threshold: usize,
+        #[arg(long, default_value_t = DigestFormat::Markdown)]
+        format: DigestFormat,
+    },
+    Check {
+        #[arg(short, long, default_value = "1970-01-01", value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = "now", value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        block: bool,
+        #[arg(long, conflicts_with = "staged")]
+        worktree: bool,
+        #[arg(long)]
+        staged: bool,
+        #[arg(long, default_value_t = CheckFormat::Text)]
+        format: CheckFormat,
+    },
+    Gate {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = "now", value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, required_unless_present = "min_owner_review")]
+        max_unowned_changes: Option<Percentage>,
+        #[arg(long)]
+        min_owner_review: Option<Percentage>,
+        #[arg(short, long)]
+        repo: Option<String>,
+        #[arg(long, default_value_t = CheckFormat::Text)]
+        format: CheckFormat,
+    },
+    InstallHooks {
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value_t = CheckMode::Warn)]
+        mode: CheckMode,
+        #[arg(long)]
+        force: bool,
+    },
+    AnalyzeReviews {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(short, long)]
+        repo: Option<String>,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnalyzeReviewLoad {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(short, long)]
+        repo: Option<String>,
+        #[arg(long)]
+        tsv: bool,
+    },
+    Scorecard {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        reviews: bool,
+        #[arg(short, long, requires = "reviews")]
+        repo: Option<String>,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+    },
+    AnalyzeTeamSize {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "team_sizes.tsv")]
+        team_size_history: PathBuf,
+        #[arg(long, default_value_t = PeriodGrouping::Quarter)]
+        group_by: PeriodGrouping,
+        #[arg(long)]
+        tsv: bool,
+    },
+    PreviewCodeowners {
+        #[arg(long)]
+        patch: PathBuf,
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(long)]
+        tsv: bool,
+    },
+    Simulate {
+        #[arg(long)]
+        codeowners_file: PathBuf,
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, conflicts_with = "tsv")]
+        format: Option<OutputFormat>,
+        #[arg(long)]
+        adjusted: bool,
+        #[arg(long, requires = "adjusted")]
+        weight: Option<AdjustedWeight>,
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    Summary {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    Watch {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value = "10m")]
+        interval: WatchInterval,
+        #[arg(long)]
+        fetch: bool,
+        #[arg(long)]
+        tsv: bool,
+    },
+    OwnersTree {
+        #[arg(long)]
+        depth: Option<usize>,
+        #[arg(long = "ref", default_value = "HEAD")]
+        git_ref: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+    },
+    BlameOwners {
+        path: String,
+        #[arg(long = "ref", default_value = "HEAD")]
+        git_ref: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    CodeownersDiff {
+        #[arg(long)]
+        from: String,
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnalyzeCoverage {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(long, default_value_t = PeriodGrouping::Month)]
+        group_by: PeriodGrouping,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnalyzeOwnershipDistribution {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnalyzeOwnerFiles {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(short, long)]
+        owner: String,
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnalyzeStaleOwnership {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value_t = 0)]
+        max_team_changes: usize,
+        #[arg(long)]
+        tsv: bool,
+    },
+    SuggestOwners {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long, default_value_t = 0.0)]
+        min_share: f64,
+        #[arg(long)]
+        tsv: bool,
+    },
+    SecurityReport {
+        #[arg(short, long, value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(long)]
+        tsv: bool,
+    },
+    AnnotateLog {
+        #[arg(short, long, default_value = "1 week ago", value_parser = parse_since)]
+        since: String,
+        #[arg(short, long, default_value = "now", value_parser = parse_until)]
+        until: String,
+        #[arg(short, long, default_value = ".")]
+        directory: PathBuf,
+        #[command(flatten)]
+        history: HistoryArgs,
+        #[arg(short, long, default_value = "codeowners.tsv")]
+        codeowners_path: PathBuf,
+        #[arg(short = 'n', long)]
+        max_count: Option<usize>,
+    },
+}
+
+impl Commands {
+    /// Whether the command works on the checked-out files rather than on history.
+    fn uses_worktree(&self) -> bool {
+        match self {
+            Commands::Check {
+                worktree, staged, ..
+            } => *worktree || *staged,
+            Commands::InstallHooks { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The repository directory of commands that read history, with the earliest date
+    /// they read from.
+    fn directory_mut(&mut self) -> Option<(&mut PathBuf, Option<&str>)> {
+        match self {
+            Commands::Dev(DevCommands::PrintCommits {
+                directory, since, ..
+            })
+            | Commands::Dev(DevCommands::PrintCommitsWithCodeowners {
+                directory, since, ..
+            })
+            | Commands::Export(ExportCommands::Sqlite {
+                directory, since, ..
+            })
+            | Commands::Export(ExportCommands::Heatmap {
+                directory, since, ..
+            })
+            | Commands::Notify(NotifyCommands::Owners {
+                directory, since, ..
+            })
+            | Commands::Analyze {
+                directory, since, ..
+            }
+            | Commands::AnalyzeByProject {
+                directory, since, ..
+            }
+            | Commands::Check {
+                directory, since, ..
+            }
+            | Commands::AnalyzeReviews {
+                directory, since, ..
+            }
+            | Commands::AnalyzeReviewLoad {
+                directory, since, ..
+            }
+            | Commands::Scorecard {
+                directory, since, ..
+            }
+            | Commands::AnalyzeTeamSize {
+                directory, since, ..
+            }
+            | Commands::AnalyzeCoverage {
+                directory, since, ..
+            }
+            | Commands::PreviewCodeowners {
+                directory, since, ..
+            }
+            | Commands::AnalyzeOwnershipDistribution {
+                directory, since, ..
+            }
+            | Commands::AnalyzeOwnerFiles {
+                directory, since, ..
+            }
+            | Commands::AnalyzeStaleOwnership {
+                directory, since, ..
+            }
+            | Commands::SuggestOwners {
+                directory, since, ..
+            }
+            | Commands::SecurityReport {
+                directory, since, ..
+            }
+            | Commands::AnnotateLog {
+                directory, since, ..
+            }
+            | Commands::Simulate {
+                directory, since, ..
+            }
+            | Commands::Summary {
+                directory, since, ..
+            }
+            | Commands::Gate {
+                directory, since, ..
+            }
+            | Commands::Watch {
+                directory, since, ..
+            } => Some((directory, Some(since.as_str()))),
+            Commands::Digest {
+                directory,
+                history_since,
+                ..
+            } => Some((directory, Some(history_since.as_str()))),
+            Commands::Dev(DevCommands::GetCodeowners { directory, .. })
+            | Commands::Dev(DevCommands::GetAllCodeowners { directory })
+            | Commands::OwnersTree { directory, .. }
+            | Commands::BlameOwners { directory, .. }
+            | Commands::CodeownersDiff { directory, .. }
+            | Commands::InstallHooks { directory, .. } => Some((directory, None)),
+            _ => None,
+        }
+    }
+
+    /// Like [`Commands::directory_mut`], for every repository a command reads.
+    fn directories_mut(&mut self) -> Vec<(&mut PathBuf, Option<&str>)> {
+        match self {
+            Commands::AnalyzeByOwner {
+                directory, since, ..
+            }
+            | Commands::AnalyzeByContributor {
+                directory, since, ..
+            } => directory
+                .iter_mut()
+                .map(|directory| (directory, Some(since.as_str())))
+                .collect(),
+            _ => self.directory_mut().into_iter().collect(),
+        }
+    }
+}
+
+use bound::GithubApi;
+
+/// A run being recorded in the audit log with `--audit-log`.
+struct AuditedRun {
+    path: PathBuf,
+    started_at: String,
+    /// The repository analyzed, as given on the command line.
+    directory: Option<PathBuf>,
+    head: Option<String>,
+}
+
+impl AuditedRun {
+    /// Notes what is about to run, before remote repositories are replaced by their clones.
+    fn start(path: PathBuf, cli: &mut Cli) -> Self {
+        let directory = cli
+            .command
+            .directories_mut()
+            .into_iter()
+            .next()
+            .map(|(directory, _)| directory.clone());
+        AuditedRun {
+            path,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            head: directory.as_deref().and_then(bound::git_head),
+            directory,
+        }
+    }
+
+    /// Appends the record of the run, which exited with `exit_code` after printing output
+    /// with the given digest and length.
+    fn finish(self, exit_code: i32, (output_sha256, output_bytes): (String, u64)) -> Result<()> {
+        let record = RunRecord {
+            started_at: self.started_at,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            user: bound::os_user(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            args: bound::redact_args(
+                std::env::args_os()
+                    .skip(1)
+                    .map(|arg| arg.to_string_lossy().to_string()),
+            ),
+            working_directory: std::env::current_dir()?,
+            head: self.head,
+            directory: self
+                .directory
+                .map(|directory| directory.to_string_lossy().to_string()),
+            exit_code: Some(exit_code),
+            output_sha256,
+            output_bytes,
+        };
+        bound::append_run_record(&self.path, &record)?;
+        Ok(())
+    }
+}
+
+impl Cli {
+    /// The file `--output` sends stdout to. The exports that write a file of their own take
+    /// `--output` as that file instead.
+    fn output_file(&self) -> Option<&Path> {
+        match &self.command {
+            Commands::Export(_) => None,
+            _ => self.output.as_deref(),
+        }
+    }
+
+    fn user_info_cache(&self) -> UserInfoCache {
+        match UserInfoCache::default_path() {
+            Some(path) if !self.no_cache => {
+                UserInfoCache::load(&path, chrono::Duration::days(self.user_cache_days))
+            }
+            _ => UserInfoCache::disabled(),
+        }
+    }
+
+    fn response_cache(&self) -> ResponseCache {
+        match ResponseCache::default_path() {
+            Some(path) if !self.no_cache => ResponseCache::load(&path),
+            _ => ResponseCache::disabled(),
+        }
+    }
+}
+
+/// The salt for `--anonymize`: `--anonymize-salt`, or a random one printed so the same
+/// pseudonyms can be made again.
+fn anonymize_salt(salt: Option<String>) -> Result<String> {
+    if let Some(salt) = salt {
+        return Ok(salt);
+    }
+    let salt = Pseudonyms::random_salt()?;
+    eprintln!(
+        "Anonymizing with a random salt; pass --anonymize-salt {} to get the same pseudonyms again",
+        salt
+    );
+    Ok(salt)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut cli = Cli::parse();
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+    init_logging(cli.verbose, cli.log_format);
+    if matches!(cli.command, Commands::Watch { .. }) && cli.output.is_some() {
+        // The file would only be written once the command ends, and watch runs until stopped
+        anyhow::bail!("--output can't be used with watch; redirect its output instead");
+    }
+    let audited_run = cli
+        .audit_log
+        .clone()
+        .map(|path| AuditedRun::start(path, &mut cli));
+    // `--output` only replaces the file once the command has succeeded
+    let mut file = cli.output_file().map(AtomicOutput::create).transpose()?;
+    let mut stdout = io::stdout();
+    let mut out = DigestWriter::new(match &mut file {
+        Some(file) => file as &mut dyn Write,
+        None => &mut stdout,
+    });
+    let result = match run(cli, &mut out).await.and_then(|()| Ok(out.flush()?)) {
+        // Output read by something that stops early, like `head`, ends without an error
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    };
+    let digest = out.finish();
+    let result = result.and_then(|()| Ok(file.map(AtomicOutput::commit).transpose()?));
+    if let Some(audited_run) = audited_run {
+        audited_run.finish(if result.is_ok() { 0 } else { 1 }, digest)?;
+    }
+    result?;
+    Ok(())
+}
+
+/// Runs the command given on the command line, writing its output to `out`.
+async fn run(mut cli: Cli, out: &mut dyn Write) -> Result<()> {
+    // Named before remote repositories are replaced by their clones
+    let repo_names: Vec<String> = cli
+        .command
+        .directories_mut()
+        .into_iter()
+        .map(|(directory, _)| bound::repo_name(directory))
+        .collect();
+    let mut remote_clones = Vec::new();
+    for (directory, since) in cli.command.directories_mut() {
+        let location = directory.to_string_lossy().to_string();
+        if bound::is_remote_url(&location) {
+            let cache_dir = match &cli.remote_cache {
+                Some(cache_dir) => cache_dir.clone(),
+                None => dirs::cache_dir()
+                    .ok_or_else(|| anyhow::anyhow!("No cache directory, pass --remote-cache"))?
+                    .join("bound"),
+            };
+            status!("Fetching {}...", location);
+            let clone = bound::fetch_remote(&location, since, &cache_dir)?;
+            *directory = clone.clone();
+            remote_clones.push(clone);
+        }
+    }
+    let warnings = Warnings::new();
+    for (directory, since) in cli.command.directories_mut() {
+        let Some(since) = since else {
+            continue;
+        };
+        let mut boundary = bound::shallow_boundary(directory, since)?;
+        if let (Some(_), Some(depth)) = (boundary, cli.fetch_depth) {
+            status!("Deepening shallow clone {}...", directory.display());
+            bound::deepen_shallow_clone(directory, depth)?;
+            boundary = bound::shallow_boundary(directory, since)?;
+        }
+        if let Some(boundary) = boundary {
+            let message = format!(
+                "{} is a shallow clone whose history stops at {}, after --since {}",
+                directory.display(),
+                bound::format_date(boundary),
+                since
+            );
+            if !cli.allow_shallow {
+                anyhow::bail!(
+                    "{}; results would miss the older commits. Deepen it with `git fetch --unshallow` or --fetch-depth, or pass --allow-shallow to analyze it anyway",
+                    message
+                );
+            }
+            warnings.add(WarningCategory::ShallowClone, message);
+        }
+    }
+    let mut isolated = Vec::new();
+    if cli.isolate {
+        if cli.command.uses_worktree() {
+            anyhow::bail!(
+                "--isolate can't be used with commands that read or change the working tree"
+            );
+        }
+        for (directory, _) in cli.command.directories_mut() {
+            let repo = bound::IsolatedRepo::new(directory)?;
+            *directory = repo.path().to_path_buf();
+            isolated.push(repo);
+        }
+    }
+    let config = bound::load_config(&cli.config)?;
+    let mut renamed_owners = config.renamed_owners.clone();
+    if let Some(path) = &cli.renamed_owners {
+        renamed_owners.extend(bound::read_owner_renames_from_tsv(path)?);
+    }
+    let context = HistoryContext {
+        warnings,
+        owner_aliases: OwnerAliases::new(&config.owner_aliases),
+        renamed_owners: OwnerAliases::new(&renamed_owners),
+        name_normalization: config.name_normalization.clone(),
+        bot_authors: config.bot_authors.clone(),
+        membership_rules: cli
+            .membership_rules
+            .as_deref()
+            .map(MembershipRules::load)
+            .transpose()?,
+        pseudonyms: cli
+            .anonymize
+            .then(|| anonymize_salt(cli.anonymize_salt.clone()))
+            .transpose()?
+            .map(|salt| Pseudonyms::new(salt, config.name_normalization.clone())),
+    };
+    let metrics = config.derived_metrics()?;
+    let github_auth = GithubAuth {
+        token: cli.token.clone(),
+        app: cli.github_app_id.as_ref().map(|app_id| GithubAppAuth {
+            app_id: app_id.clone(),
+            installation_id: cli.github_app_installation_id.clone().unwrap_or_default(),
+            private_key_path: cli.github_app_key.clone().unwrap_or_default(),
+        }),
+    };
+    match &cli.command {
+        Commands::Dev(dev_command) => match dev_command {
+            DevCommands::GhGetToken => {
+                let token = bound::get_token()?;
+                writeln!(out, "Token: {}", token)?;
+            }
+            DevCommands::GhGetTeamSlugs { org } => {
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                let slugs = bound::get_github_team_slugs(&api, org).await?;
+                for slug in slugs {
+                    writeln!(out, "{}", slug)?;
+                }
+            }
+            DevCommands::GhGetTeamMembers { org, team } => {
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                let members = bound::get_github_team_members(&api, org, team).await?;
+                for member in members {
+                    writeln!(out, "{}", member)?;
+                }
+            }
+            DevCommands::GhGetUserNameEmail { logins } => {
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                for login in logins {
+                    match bound::get_user_info(&api, login).await? {
+                        Some((name, email)) => {
+                            if email.is_empty() {
+                                writeln!(out, "{} <not found>", name)?;
+                            } else {
+                                writeln!(out, "{} <{}>", name, email)?;
+                            }
+                        }
+                        None => writeln!(out, "{} <not found>", login)?,
+                    }
+                }
+            }
+            DevCommands::GhGetOrgLogins => {
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                let orgs = bound::get_github_org_logins(&api).await?;
+                for org in orgs {
+                    writeln!(out, "{}", org)?;
+                }
+                report_api_usage(&api, &cli.api_usage_json)?;
+            }
+            DevCommands::PrintCommits {
+                since,
+                until,
+                directory,
+                history,
+                tsv,
+                format,
+            } => {
+                reject_markdown(*format)?;
+                let tsv = is_tsv(*tsv, *format);
+                let commits = history.commits(
+                    git_log_commits(since, until, directory, &history.log_options(&context))?,
+                    directory,
+                    &context,
+                )?;
+                if *format == Some(OutputFormat::Ndjson) {
+                    ignore_broken_pipe(bound::write_ndjson_stream(out, commits).map(|_| ()))?;
+                } else if tsv {
+                    writeln!(
+                        out,
+                        "commit_id\tauthor_name\tauthor_email\tdate\tpath\tinsertions\tdeletions"
+                    )?;
+                    for commit in commits {
+                        let commit = commit?;
+                        for change in commit.file_changes {
+                            writeln!(
+                                out,
+                                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                                commit.id,
                                 commit.author_name,
                                 commit.author_email,
-                                commit.timestamp,
+                                commit.author_date.timestamp(),
                                 change.path,
                                 change.insertions,
                                 change.deletions
-                            );
+                            )?;
                         }
                     }
                 } else {
                     for commit in commits {
                         let commit = commit?;
-                        println!("Commit: {}", commit.id);
-                        println!("Author: {} <{}>", commit.author_name, commit.author_email);
-                        println!("Date: {}", commit.timestamp);
-                        println!("Changes:");
+                        writeln!(out, "Commit: {}", commit.id)?;
+                        writeln!(
+                            out,
+                            "Author: {} <{}>",
+                            commit.author_name, commit.author_email
+                        )?;
+                        writeln!(out, "Date: {}", commit.author_date.timestamp())?;
+                        writeln!(out, "Changes:")?;
                         for change in commit.file_changes {
-                            println!(
+                            writeln!(
+                                out,
                                 "  {}: +{} -{}",
                                 change.path, change.insertions, change.deletions
-                            );
+                            )?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+            }
+            DevCommands::GetCodeowners { commit, directory } => {
+                let codeowners = bound::get_codeowners_at_commit(commit, directory)?;
+                match codeowners {
+                    Some(content) => writeln!(out, "{}", content)?,
+                    None => eprintln!("No CODEOWNERS file found at this commit."),
+                }
+            }
+            DevCommands::GetAllCodeowners { directory } => {
+                let codeowners = bound::get_all_codeowners(directory)?;
+                for codeowner in codeowners {
+                    writeln!(out, "{}", codeowner)?;
+                }
+            }
+
+            DevCommands::PrintCommitsWithCodeowners {
+                since,
+                until,
+                directory,
+                history,
+                codeowners_path: memberships_path,
+                tsv,
+                format,
+            } => {
+                reject_markdown(*format)?;
+                let tsv = is_tsv(*tsv, *format);
+                let memberships = memberships_path
+                    .as_ref()
+                    .map(|path| context.read_memberships(path, [directory]))
+                    .transpose()?;
+
+                let commits = history.commits(
+                    bound::git_log_commits_with_codeowners(
+                        since,
+                        until,
+                        directory,
+                        &history.log_options(&context),
+                        memberships,
+                    )?,
+                    directory,
+                    &context,
+                )?;
+
+                if *format == Some(OutputFormat::Ndjson) {
+                    ignore_broken_pipe(bound::write_ndjson_stream(out, commits).map(|_| ()))?;
+                } else if tsv {
+                    writeln!(out, "commit_id\tauthor_name\tauthor_email\tdate\tpath\tinsertions\tdeletions\tauthor_is_codeowner\tcodeowners\ttags")?;
+                    for commit in commits {
+                        let commit = commit?;
+                        let tags = bound::format_tags(&bound::tag_commit(&commit));
+                        for change in commit.file_changes {
+                            writeln!(
+                                out,
+                                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                                commit.id,
+                                commit.author_name,
+                                commit.author_email,
+                                commit.timestamp,
+                                change.path,
+                                change.insertions,
+                                change.deletions,
+                                change.author_is_codeowner.map_or("", |b| if b {
+                                    "true"
+                                } else {
+                                    "false"
+                                }),
+                                change
+                                    .codeowners
+                                    .as_ref()
+                                    .map_or_else(|| "".to_string(), |owners| owners.join(", ")),
+                                tags
+                            )?;
+                        }
+                    }
+                } else {
+                    for commit in commits {
+                        let commit = commit?;
+                        let tags = bound::tag_commit(&commit);
+                        writeln!(out, "Commit: {}", commit.id)?;
+                        writeln!(
+                            out,
+                            "Author: {} <{}>",
+                            commit.author_name, commit.author_email
+                        )?;
+                        writeln!(out, "Date: {}", commit.timestamp)?;
+                        if !tags.is_empty() {
+                            writeln!(out, "Tags: {}", bound::format_tags(&tags))?;
+                        }
+                        writeln!(out, "Changes:")?;
+                        for change in commit.file_changes {
+                            writeln!(
+                                out,
+                                "  {}: +{} -{} (Codeowners: {} {})",
+                                change.path,
+                                change.insertions,
+                                change.deletions,
+                                change.author_is_codeowner.map_or("-", |b| if b {
+                                    "Y"
+                                } else {
+                                    "N"
+                                }),
+                                change
+                                    .codeowners
+                                    .as_ref()
+                                    .map_or_else(|| "None".to_string(), |owners| owners.join(", "))
+                            )?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+            }
+        },
+        Commands::Export(export_command) => match export_command {
+            ExportCommands::Sqlite {
+                since,
+                until,
+                directory,
+                history,
+                codeowners_path: memberships_path,
+                output,
+            } => {
+                let memberships = memberships_path
+                    .as_ref()
+                    .map(|path| context.read_memberships(path, [directory]))
+                    .transpose()?;
+
+                let commits = history.commits(
+                    bound::git_log_commits_with_codeowners(
+                        since,
+                        until,
+                        directory,
+                        &history.log_options(&context),
+                        memberships.clone(),
+                    )?,
+                    directory,
+                    &context,
+                )?;
+                let stats =
+                    bound::export_to_sqlite(commits, &memberships.unwrap_or_default(), output)?;
+                status!(
+                    "Exported {} commits, {} file changes and {} memberships to {}",
+                    stats.commits,
+                    stats.file_changes,
+                    stats.memberships,
+                    output.display()
+                );
+            }
+            ExportCommands::Heatmap {
+                since,
+                until,
+                directory,
+                history,
+                row_globs,
+                format,
+                output,
+            } => {
+                let commits = history.commits(
+                    bound::git_log_commits_with_codeowners(
+                        since,
+                        until,
+                        directory,
+                        &history.log_options(&context),
+                        None,
+                    )?,
+                    directory,
+                    &context,
+                )?;
+                let heatmap = bound::build_heatmap(commits, row_globs)?;
+                match output {
+                    Some(output) => {
+                        let mut file = AtomicOutput::create(output)?;
+                        heatmap.write(&mut file, *format)?;
+                        file.commit()?;
+                    }
+                    None => ignore_broken_pipe(heatmap.write(&mut *out, *format))?,
+                }
+            }
+        },
+        Commands::Notify(notify_command) => match notify_command {
+            NotifyCommands::Owners {
+                since,
+                until,
+                directory,
+                history,
+                codeowners_path,
+                from,
+                sendmail,
+                dry_run,
+            } => {
+                if config.contacts.is_empty() {
+                    anyhow::bail!("No owner contacts configured in {}", cli.config.display());
+                }
+                let memberships = context.read_memberships(codeowners_path, [directory])?;
+                let commits = history
+                    .commits(
+                        bound::git_log_commits_with_codeowners(
+                            since,
+                            until,
+                            directory,
+                            &history.log_options(&context),
+                            Some(memberships),
+                        )?,
+                        directory,
+                        &context,
+                    )?
+                    .collect::<io::Result<Vec<_>>>()?;
+                let rev = history.git_ref.as_deref().unwrap_or("HEAD");
+                let adjacent = bound::unowned_paths_by_adjacent_owner(&commits, rev, directory)?;
+                let analysis = bound::analyze_by_owner(
+                    commits.into_iter().map(Ok),
+                    &AnalysisOptions::default(),
+                )?;
+                let notifications = bound::build_owner_notifications(
+                    &analysis,
+                    &adjacent,
+                    &config.contacts,
+                    since,
+                    until,
+                );
+                for notification in &notifications {
+                    if *dry_run {
+                        writeln!(
+                            out,
+                            "{}",
+                            bound::format_notification_email(notification, from)
+                        )?;
+                    } else {
+                        bound::send_notification(notification, from, sendmail)?;
+                        status!(
+                            "Notified {} ({})",
+                            notification.owner,
+                            notification.recipients.join(", ")
+                        );
+                    }
+                }
+            }
+        },
+        Commands::Init {
+            org,
+            codeowners_path,
+            state_path,
+            team_size_history,
+            repo,
+            no_commit_lookup,
+            from_export,
+            with_history,
+            team_fetch,
+        } => {
+            let (records, memberships) = match from_export {
+                Some(path) => {
+                    let export = bound::OrgExport::load(path)?;
+                    let all_codeowners = bound::get_all_codeowners(&PathBuf::from("."))?;
+                    let records =
+                        export.team_member_records(org, &all_codeowners, &context.warnings);
+                    let memberships = records.iter().map(|r| r.to_membership()).collect();
+                    (records, memberships)
+                }
+                None => {
+                    let api = GithubApi::new_with_auth(&github_auth)
+                        .await?
+                        .with_response_cache(cli.response_cache());
+                    let mut user_cache = cli.user_info_cache();
+                    let mut provider = GithubOrgProvider::new(
+                        &api,
+                        org,
+                        &PathBuf::from("."),
+                        &mut user_cache,
+                        &context.warnings,
+                    )
+                    .with_commits_repo(commits_repo(repo, !*no_commit_lookup)?)
+                    .with_team_membership(team_fetch.team_membership)
+                    .with_concurrency(team_fetch.concurrency);
+                    let progress = team_fetch_progress();
+                    let records = provider.fetch().await?;
+                    progress.finish_with_message("All teams processed");
+                    report_org_fetch(provider.stats(), org);
+                    let memberships = if *with_history {
+                        let all_codeowners = bound::get_all_codeowners(&PathBuf::from("."))?;
+                        let events: Vec<_> = bound::get_team_membership_events(&api, org)
+                            .await?
+                            .into_iter()
+                            .filter(|event| all_codeowners.contains(&event.codeowner))
+                            .collect();
+                        // Former members are only known by login from the events
+                        let current: HashSet<&str> =
+                            records.iter().map(|r| r.login.as_str()).collect();
+                        let former: Vec<String> = events
+                            .iter()
+                            .map(|event| event.login.clone())
+                            .filter(|login| !current.contains(login.as_str()))
+                            .collect::<HashSet<_>>()
+                            .into_iter()
+                            .collect();
+                        let users = bound::get_users_info_cached(
+                            &api,
+                            &former,
+                            &mut user_cache,
+                            team_fetch.concurrency,
+                        )
+                        .await?;
+                        status!(
+                            "Dated memberships from {} team membership events in the audit log.",
+                            events.len()
+                        );
+                        bound::dated_team_memberships(
+                            &records,
+                            &events,
+                            &users,
+                            chrono::Utc::now().date_naive(),
+                        )
+                    } else {
+                        records.iter().map(|r| r.to_membership()).collect()
+                    };
+                    user_cache.save()?;
+                    api.save_response_cache()?;
+                    report_api_usage(&api, &cli.api_usage_json)?;
+                    (records, memberships)
+                }
+            };
+            bound::write_memberships_to_tsv(&memberships, codeowners_path)?;
+            bound::write_team_member_state(&records, state_path)?;
+            let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+            bound::append_team_size_history(
+                &bound::snapshot_team_sizes(&memberships, &today),
+                team_size_history,
+            )?;
+        }
+        Commands::Refresh {
+            org,
+            codeowners_path,
+            state_path,
+            team_size_history,
+            repo,
+            no_commit_lookup,
+            team_fetch,
+        } => {
+            let api = GithubApi::new_with_auth(&github_auth)
+                .await?
+                .with_response_cache(cli.response_cache());
+            let mut memberships = read_memberships_from_tsv(codeowners_path)?;
+            let previous = bound::read_team_member_state(state_path)?;
+            if previous.is_empty() {
+                status!(
+                    "No previous state at {}; members who left cannot be removed this time.",
+                    state_path.display()
+                );
+            }
+            let mut user_cache = cli.user_info_cache();
+            let mut provider = GithubOrgProvider::new(
+                &api,
+                org,
+                &PathBuf::from("."),
+                &mut user_cache,
+                &context.warnings,
+            )
+            .with_commits_repo(commits_repo(repo, !*no_commit_lookup)?)
+            .with_team_membership(team_fetch.team_membership)
+            .with_concurrency(team_fetch.concurrency);
+            let progress = team_fetch_progress();
+            let (records, stats) = provider
+                .refresh(&mut memberships, &previous, &context.name_normalization)
+                .await?;
+            progress.finish_with_message("All teams processed");
+            report_org_fetch(provider.stats(), org);
+            user_cache.save()?;
+            api.save_response_cache()?;
+            bound::write_memberships_to_tsv(&memberships, codeowners_path)?;
+            bound::write_team_member_state(&records, state_path)?;
+            let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+            bound::append_team_size_history(
+                &bound::snapshot_team_sizes(&memberships, &today),
+                team_size_history,
+            )?;
+            status!(
+                "Added {} and removed {} memberships in {}",
+                stats.added,
+                stats.removed,
+                codeowners_path.display()
+            );
+            report_api_usage(&api, &cli.api_usage_json)?;
+        }
+        Commands::Analyze {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            all,
+            owners,
+            contributors,
+            tsv,
+            adjusted,
+            weight,
+            owner_attribution,
+            top,
+            split_coauthors,
+        } => {
+            let options = AnalysisOptions {
+                adjusted: adjusted_metric(*adjusted, *weight, &config),
+                owner_attribution: *owner_attribution,
+                top_contributors: *top,
+            };
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let commits = history.commits(
+                with_co_authors(
+                    bound::warn_unmatched_authors(
+                        bound::git_log_commits_with_codeowners(
+                            since,
+                            until,
+                            directory,
+                            &history.log_options_with_co_authors(&context, *split_coauthors),
+                            Some(memberships.clone()),
+                        )?,
+                        &memberships,
+                        &context.name_normalization,
+                        &context.warnings,
+                    ),
+                    *split_coauthors,
+                    &memberships,
+                    &context,
+                ),
+                directory,
+                &context,
+            )?;
+            // Without a selection both analyses are run, sharing one history walk
+            let (owner_analysis, contributor_analysis) = if *all || *owners == *contributors {
+                let analysis = bound::analyze_all(commits, &options)?;
+                (Some(analysis.owners), Some(analysis.contributors))
+            } else if *owners {
+                (Some(bound::analyze_by_owner(commits, &options)?), None)
+            } else {
+                (
+                    None,
+                    Some(bound::analyze_by_contributor(commits, &options)?),
+                )
+            };
+
+            if let Some(analysis) = &owner_analysis {
+                if *tsv {
+                    print_analysis_summary_tsv(out, &analysis.summary, OutputGroup::default())?;
+                    print_owner_tsv_header(out, *adjusted, &[], &metrics)?;
+                    print_owner_tsv(out, analysis, *adjusted, OutputGroup::default(), &metrics)?;
+                } else {
+                    print_owner_analysis(out, analysis, *adjusted)?;
+                }
+            }
+            if owner_analysis.is_some() && contributor_analysis.is_some() {
+                writeln!(out)?;
+            }
+            if let Some(analysis) = &contributor_analysis {
+                if *tsv {
+                    if owner_analysis.is_none() {
+                        print_analysis_summary_tsv(out, &analysis.summary, OutputGroup::default())?;
+                    }
+                    print_contributor_tsv_header(out, *adjusted, &[], &metrics)?;
+                    print_contributor_tsv(
+                        out,
+                        analysis,
+                        &None,
+                        *adjusted,
+                        OutputGroup::default(),
+                        &metrics,
+                    )?;
+                } else {
+                    print_contributor_analysis(out, analysis, &None, *adjusted)?;
+                }
+            }
+        }
+        Commands::AnalyzeByOwner {
+            since,
+            until,
+            directory,
+            merge_owners,
+            history,
+            codeowners_path,
+            tsv,
+            format,
+            adjusted,
+            weight,
+            owner_attribution,
+            top,
+            group_by,
+            newcomers,
+            grouping,
+            components_path,
+            pull_requests,
+            split_coauthors,
+            by_language,
+            compare_with,
+        } => {
+            let options = AnalysisOptions {
+                adjusted: adjusted_metric(*adjusted, *weight, &config),
+                owner_attribution: *owner_attribution,
+                top_contributors: *top,
+            };
+            let tsv = is_tsv(*tsv, *format);
+            reject_language_table(*by_language, tsv, *format)?;
+            let memberships = context.read_memberships(codeowners_path, directory)?;
+            let components = component_map(*grouping, components_path)?;
+            let comparison_range = compare_with
+                .map(|window| window.range(since, until))
+                .transpose()?;
+            // Also covering the compared window
+            let numbers_since = comparison_range
+                .as_ref()
+                .map_or(since.as_str(), |(previous_since, _)| previous_since);
+            let pull_request_numbers = match pull_requests {
+                Some(source) => Some(
+                    pull_request_numbers(
+                        *source,
+                        directory,
+                        numbers_since,
+                        until,
+                        history,
+                        &github_auth,
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+            let read_commits = |since: &str, until: &str, directory: &PathBuf| {
+                let commits = history.commits(
+                    with_co_authors(
+                        bound::git_log_commits_with_codeowners(
+                            since,
+                            until,
+                            directory,
+                            &history.log_options_with_co_authors(&context, *split_coauthors),
+                            Some(memberships.clone()),
+                        )?,
+                        *split_coauthors,
+                        &memberships,
+                        &context,
+                    ),
+                    directory,
+                    &context,
+                )?;
+                Ok(with_languages(
+                    with_pull_requests(
+                        with_components(commits, &components),
+                        &pull_request_numbers,
+                    )?,
+                    *by_language,
+                    &config.languages,
+                ))
+            };
+            // Who changed each owner's files before the period, to tell newcomers apart
+            let mut priors = Vec::new();
+            if *newcomers {
+                let start = bound::format_date(chrono::DateTime::UNIX_EPOCH);
+                for directory in directory {
+                    priors.push(bound::owner_contributors(read_commits(
+                        &start, since, directory,
+                    )?)?);
+                }
+            }
+            let runs = repository_runs(directory, &repo_names, *merge_owners, |directory| {
+                read_commits(since, until, directory)
+            })?;
+            let mut previous_runs = match &comparison_range {
+                Some((previous_since, previous_until)) => {
+                    repository_runs(directory, &repo_names, *merge_owners, |directory| {
+                        read_commits(previous_since, previous_until, directory)
+                    })?
+                }
+                None => Vec::new(),
+            }
+            .into_iter();
+            let merged = runs.len() == 1;
+            let group_columns = OutputGroup::columns(
+                runs.iter().any(|(repo, _)| repo.is_some()),
+                group_by.is_some(),
+            );
+            if tsv && !group_columns.is_empty() {
+                if compare_with.is_some() {
+                    print_comparison_tsv_header(out, &group_columns, &["owner"])?;
+                } else {
+                    print_owner_tsv_header(out, *adjusted, &group_columns, &metrics)?;
+                }
+            }
+            for (index, (repo, commits)) in runs.into_iter().enumerate() {
+                let mut prior = if merged {
+                    priors.drain(..).reduce(|mut all, prior| {
+                        all.extend(prior);
+                        all
+                    })
+                } else {
+                    priors.get(index).cloned()
+                };
+                if let Some(group_by) = group_by {
+                    for (period, period_commits) in
+                        bound::group_commits_by_period(commits, *group_by)?
+                    {
+                        // Earlier periods count as prior history for later ones
+                        let period_contributors = prior.as_ref().map(|_| {
+                            let mut contributors = OwnerContributors::default();
+                            for commit in &period_commits {
+                                contributors.add_commit(commit);
+                            }
+                            contributors
+                        });
+                        let analysis = analyze_owners(
+                            period_commits.into_iter().map(Ok),
+                            &options,
+                            prior.as_ref(),
+                        )?;
+                        if let (Some(prior), Some(contributors)) = (&mut prior, period_contributors)
+                        {
+                            prior.extend(contributors);
                         }
-                        println!();
+                        let group = OutputGroup {
+                            repo: repo.as_deref(),
+                            period: Some(&period),
+                        };
+                        print_owner_report(
+                            out,
+                            &analysis,
+                            group,
+                            output_format(tsv, *format),
+                            *adjusted,
+                            &metrics,
+                        )?;
+                    }
+                } else {
+                    let analysis = analyze_owners(commits, &options, prior.as_ref())?;
+                    let group = OutputGroup {
+                        repo: repo.as_deref(),
+                        period: None,
+                    };
+                    if let (Some((previous_since, previous_until)), Some((_, previous_commits))) =
+                        (&comparison_range, previous_runs.next())
+                    {
+                        let previous = analyze_owners(previous_commits, &options, None)?;
+                        let comparison = bound::compare_owner_analyses(
+                            &previous,
+                            &analysis,
+                            previous_since,
+                            previous_until,
+                        );
+                        print_owner_comparison(out, &comparison, group, *format, tsv)?;
+                    } else {
+                        print_owner_report(
+                            out,
+                            &analysis,
+                            group,
+                            output_format(tsv, *format),
+                            *adjusted,
+                            &metrics,
+                        )?;
+                    }
+                }
+            }
+        }
+        Commands::AnalyzeByContributor {
+            since,
+            until,
+            directory,
+            merge_owners,
+            history,
+            codeowners_path,
+            owner,
+            tsv,
+            format,
+            adjusted,
+            weight,
+            owner_attribution,
+            group_by,
+            grouping,
+            components_path,
+            pull_requests,
+            split_coauthors,
+            by_language,
+            compare_with,
+        } => {
+            let options = AnalysisOptions {
+                adjusted: adjusted_metric(*adjusted, *weight, &config),
+                owner_attribution: *owner_attribution,
+                ..AnalysisOptions::default()
+            };
+            let tsv = is_tsv(*tsv, *format);
+            reject_language_table(*by_language, tsv, *format)?;
+            let memberships = context.read_memberships(codeowners_path, directory)?;
+
+            let filter_authors = owner.as_ref().map(|owner| {
+                (
+                    AuthorMembership::with_name_normalization(
+                        &memberships,
+                        &context.name_normalization,
+                    ),
+                    owner.clone(),
+                )
+            });
+
+            let components = component_map(*grouping, components_path)?;
+            let comparison_range = compare_with
+                .map(|window| window.range(since, until))
+                .transpose()?;
+            // Also covering the compared window
+            let numbers_since = comparison_range
+                .as_ref()
+                .map_or(since.as_str(), |(previous_since, _)| previous_since);
+            let pull_request_numbers = match pull_requests {
+                Some(source) => Some(
+                    pull_request_numbers(
+                        *source,
+                        directory,
+                        numbers_since,
+                        until,
+                        history,
+                        &github_auth,
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+            let read_commits = |since: &str, until: &str, directory: &PathBuf| {
+                let commits = history.commits(
+                    with_co_authors(
+                        bound::git_log_commits_with_codeowners(
+                            since,
+                            until,
+                            directory,
+                            &history.log_options_with_co_authors(&context, *split_coauthors),
+                            Some(memberships.clone()),
+                        )?,
+                        *split_coauthors,
+                        &memberships,
+                        &context,
+                    ),
+                    directory,
+                    &context,
+                )?;
+                Ok(with_languages(
+                    with_pull_requests(
+                        with_components(commits, &components),
+                        &pull_request_numbers,
+                    )?,
+                    *by_language,
+                    &config.languages,
+                ))
+            };
+            let runs = repository_runs(directory, &repo_names, *merge_owners, |directory| {
+                read_commits(since, until, directory)
+            })?;
+            let mut previous_runs = match &comparison_range {
+                Some((previous_since, previous_until)) => {
+                    repository_runs(directory, &repo_names, *merge_owners, |directory| {
+                        read_commits(previous_since, previous_until, directory)
+                    })?
+                }
+                None => Vec::new(),
+            }
+            .into_iter();
+            let group_columns = OutputGroup::columns(
+                runs.iter().any(|(repo, _)| repo.is_some()),
+                group_by.is_some(),
+            );
+            if tsv && !group_columns.is_empty() {
+                if compare_with.is_some() {
+                    print_comparison_tsv_header(
+                        out,
+                        &group_columns,
+                        &["author_name", "author_email", "owner"],
+                    )?;
+                } else {
+                    print_contributor_tsv_header(out, *adjusted, &group_columns, &metrics)?;
+                }
+            }
+            for (repo, commits) in runs {
+                if let Some(group_by) = group_by {
+                    for (period, period_commits) in
+                        bound::group_commits_by_period(commits, *group_by)?
+                    {
+                        let analysis = bound::analyze_by_contributor(
+                            period_commits.into_iter().map(Ok),
+                            &options,
+                        )?;
+                        let group = OutputGroup {
+                            repo: repo.as_deref(),
+                            period: Some(&period),
+                        };
+                        print_contributor_report(
+                            out,
+                            &analysis,
+                            &filter_authors,
+                            group,
+                            output_format(tsv, *format),
+                            *adjusted,
+                            &metrics,
+                        )?;
+                    }
+                } else {
+                    let analysis = bound::analyze_by_contributor(commits, &options)?;
+                    let group = OutputGroup {
+                        repo: repo.as_deref(),
+                        period: None,
+                    };
+                    if let (Some((previous_since, previous_until)), Some((_, previous_commits))) =
+                        (&comparison_range, previous_runs.next())
+                    {
+                        let previous = bound::analyze_by_contributor(previous_commits, &options)?;
+                        let comparison = bound::compare_contributor_analyses(
+                            &previous,
+                            &analysis,
+                            previous_since,
+                            previous_until,
+                        );
+                        print_contributor_comparison(
+                            out,
+                            &comparison,
+                            &filter_authors,
+                            group,
+                            *format,
+                            tsv,
+                        )?;
+                    } else {
+                        print_contributor_report(
+                            out,
+                            &analysis,
+                            &filter_authors,
+                            group,
+                            output_format(tsv, *format),
+                            *adjusted,
+                            &metrics,
+                        )?;
                     }
                 }
             }
-            DevCommands::GetCodeowners { commit, directory } => {
-                let codeowners = bound::get_codeowners_at_commit(commit, directory)?;
-                match codeowners {
-                    Some(content) => println!("{}", content),
-                    None => eprintln!("No CODEOWNERS file found at this commit."),
+        }
+        Commands::AnalyzeByProject {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            tsv,
+            format,
+        } => {
+            let tsv = is_tsv(*tsv, *format);
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let index = bound::ProjectIndex::detect(history.tip(), directory)?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = bound::analyze_by_project(commits, index)?;
+            match format {
+                Some(OutputFormat::Ndjson) => {
+                    print_ndjson(out, analysis.projects.iter(), OutputGroup::default())?
+                }
+                Some(OutputFormat::Markdown) => print_project_markdown(out, &analysis)?,
+                _ if tsv => print_project_tsv(out, &analysis)?,
+                _ => print_project_analysis(out, &analysis)?,
+            }
+        }
+        Commands::Digest {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            history_since,
+            large_commit_threshold,
+            format,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let prior_commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    history_since,
+                    since,
+                    directory,
+                    &history.log_options(&context),
+                    None,
+                )?,
+                directory,
+                &context,
+            )?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let digests = bound::build_owner_digests(
+                commits,
+                prior_commits,
+                *large_commit_threshold,
+                directory,
+            )?;
+            print_owner_digests(out, &digests, since, until, *format)?;
+        }
+        Commands::Check {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            block,
+            worktree,
+            staged,
+            format,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let uncommitted = *worktree || *staged;
+            let commits: Box<dyn Iterator<Item = io::Result<CommitInfoWithCodeowner>>> =
+                if uncommitted {
+                    Box::new(std::iter::once(bound::worktree_commit_with_codeowners(
+                        directory,
+                        *staged,
+                        Some(memberships),
+                        &context.name_normalization,
+                    )))
+                } else {
+                    Box::new(bound::git_log_commits_with_codeowners(
+                        since,
+                        until,
+                        directory,
+                        &history.log_options(&context),
+                        Some(memberships),
+                    )?)
+                };
+            let codeowners = if uncommitted {
+                bound::find_worktree_codeowners(directory, *staged)?
+            } else {
+                bound::find_codeowners_at_commit(history.tip(), directory)?
+            };
+            let (codeowners_location, codeowners) = codeowners.unwrap_or_default();
+            for shadowed in bound::find_shadowed_rules(&codeowners) {
+                match format {
+                    CheckFormat::Text => eprintln!(
+                        "Warning: CODEOWNERS line {} ({}) never applies, as line {} ({}) overrides it",
+                        shadowed.rule.line,
+                        shadowed.rule.pattern,
+                        shadowed.shadowed_by.line,
+                        shadowed.shadowed_by.pattern
+                    ),
+                    CheckFormat::GithubAnnotations => writeln!(out,
+                        "{}",
+                        bound::github_annotation(
+                            "error",
+                            Some(codeowners_location),
+                            Some(shadowed.rule.line),
+                            "Shadowed CODEOWNERS rule",
+                            &format!(
+                                "{} never applies, as line {} ({}) overrides it",
+                                shadowed.rule.pattern,
+                                shadowed.shadowed_by.line,
+                                shadowed.shadowed_by.pattern
+                            ),
+                        )
+                    )?,
+                }
+            }
+            let commits = history.commits(commits, directory, &context)?;
+            let violations = bound::find_ownership_violations(commits)?;
+            for violation in &violations {
+                let location = if uncommitted {
+                    "uncommitted changes".to_string()
+                } else {
+                    violation.commit_id.clone()
+                };
+                match format {
+                    CheckFormat::Text => writeln!(
+                        out,
+                        "Warning: {} <{}> {} {} owned by {} in {}",
+                        violation.author_name,
+                        violation.author_email,
+                        violation.kind,
+                        violation.path,
+                        violation.codeowners.join(", "),
+                        location
+                    )?,
+                    CheckFormat::GithubAnnotations => writeln!(
+                        out,
+                        "{}",
+                        bound::github_annotation(
+                            if *block { "error" } else { "warning" },
+                            Some(&violation.path),
+                            None,
+                            "Change by a non-owner",
+                            &format!(
+                                "{} <{}> {} this file, owned by {}, in {}",
+                                violation.author_name,
+                                violation.author_email,
+                                violation.kind,
+                                violation.codeowners.join(", "),
+                                location
+                            ),
+                        )
+                    )?,
                 }
             }
-            DevCommands::GetAllCodeowners { directory } => {
-                let codeowners = bound::get_all_codeowners(directory)?;
-                for codeowner in codeowners {
-                    println!("{}", codeowner);
+            if *block && !violations.is_empty() {
+                anyhow::bail!(
+                    "{} changes to files owned by teams the author is not a member of",
+                    violations.len()
+                );
+            }
+        }
+        Commands::Gate {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            max_unowned_changes,
+            min_owner_review,
+            repo,
+            format,
+        } => {
+            let thresholds = GateThresholds {
+                max_unowned_changes: *max_unowned_changes,
+                min_owner_review: *min_owner_review,
+            };
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let reviews = if min_owner_review.is_some() {
+                let repo = match repo {
+                    Some(repo) => repo.clone(),
+                    None => bound::get_origin_github_repo(directory)?.ok_or_else(|| {
+                        anyhow::anyhow!("Could not detect the GitHub repository, pass --repo")
+                    })?,
+                };
+                let membership_index = AuthorMembership::with_name_normalization(
+                    &memberships,
+                    &context.name_normalization,
+                );
+                let owners = bound::get_owners_at_commit(history.tip(), directory)?;
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                let mut user_cache = cli.user_info_cache();
+                let (pull_requests, identities) =
+                    fetch_pull_request_reviews(&api, &repo, since, until, &mut user_cache).await?;
+                let identities = context.identities(identities);
+                user_cache.save()?;
+                report_api_usage(&api, &cli.api_usage_json)?;
+                bound::analyze_reviews(&pull_requests, &owners, &membership_index, &identities)
+            } else {
+                Vec::new()
+            };
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = analyze_owners(commits, &AnalysisOptions::default(), None)?;
+            let checks = bound::evaluate_gate(&thresholds, &analysis.summary, &reviews);
+            match format {
+                CheckFormat::Text => print_gate_checks(out, &checks, since, until)?,
+                CheckFormat::GithubAnnotations => {
+                    let codeowners_location =
+                        bound::find_codeowners_at_commit(history.tip(), directory)?
+                            .map(|(location, _)| location);
+                    print_gate_annotations(out, &checks, codeowners_location)?;
                 }
             }
-
-            DevCommands::PrintCommitsWithCodeowners {
-                since,
-                until,
+            let failed = checks.iter().filter(|check| !check.passed()).count();
+            if failed > 0 {
+                anyhow::bail!(
+                    "{} of {} ownership thresholds not met",
+                    failed,
+                    checks.len()
+                );
+            }
+        }
+        Commands::InstallHooks {
+            directory,
+            codeowners_path,
+            mode,
+            force,
+        } => {
+            let bound_path = std::env::current_exe()?;
+            let hook_path = bound::install_pre_push_hook(
                 directory,
-                codeowners_path: memberships_path,
-                tsv,
-            } => {
-                let memberships = memberships_path
-                    .as_ref()
-                    .map(read_memberships_from_tsv)
-                    .transpose()?;
+                &bound_path,
+                codeowners_path,
+                *mode,
+                *force,
+            )?;
+            status!(
+                "Installed pre-push hook at {} ({})",
+                hook_path.display(),
+                mode
+            );
+        }
+        Commands::AnalyzeReviews {
+            since,
+            until,
+            directory,
+            codeowners_path,
+            repo,
+            tsv,
+        } => {
+            let repo = match repo {
+                Some(repo) => repo.clone(),
+                None => bound::get_origin_github_repo(directory)?.ok_or_else(|| {
+                    anyhow::anyhow!("Could not detect the GitHub repository, pass --repo")
+                })?,
+            };
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let owners = bound::get_owners_at_commit("HEAD", directory)?;
 
-                let commits =
-                    bound::git_log_commits_with_codeowners(since, until, directory, memberships)?;
+            let api = GithubApi::new_with_auth(&github_auth).await?;
+            let mut user_cache = cli.user_info_cache();
+            let (pull_requests, identities) =
+                fetch_pull_request_reviews(&api, &repo, since, until, &mut user_cache).await?;
+            let identities = context.identities(identities);
+            user_cache.save()?;
+            report_api_usage(&api, &cli.api_usage_json)?;
+            let analysis =
+                bound::analyze_reviews(&pull_requests, &owners, &membership_index, &identities);
 
+            if *tsv {
+                writeln!(out, "owner\tpull_requests\tapproved_by_codeowner\tapproved_by_others_only\tmerged_without_approval\tcodeowner_approval_rate")?;
+            }
+            for info in analysis {
                 if *tsv {
-                    println!("commit_id\tauthor_name\tauthor_email\tdate\tpath\tinsertions\tdeletions\tauthor_is_codeowner\tcodeowners");
-                    for commit in commits {
-                        let commit = commit?;
-                        for change in commit.file_changes {
-                            println!(
-                                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-                                commit.id,
-                                commit.author_name,
-                                commit.author_email,
-                                commit.timestamp,
-                                change.path,
-                                change.insertions,
-                                change.deletions,
-                                change.author_is_codeowner.map_or("", |b| if b {
-                                    "true"
-                                } else {
-                                    "false"
-                                }),
-                                change
-                                    .codeowners
-                                    .as_ref()
-                                    .map_or_else(|| "".to_string(), |owners| owners.join(", "))
-                            );
-                        }
-                    }
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t{}\t{}\t{:.4}",
+                        info.owner,
+                        info.pull_requests,
+                        info.approved_by_codeowner,
+                        info.approved_by_others_only,
+                        info.merged_without_approval,
+                        info.codeowner_approval_rate()
+                    )?;
                 } else {
-                    for commit in commits {
-                        let commit = commit?;
-                        println!("Commit: {}", commit.id);
-                        println!("Author: {} <{}>", commit.author_name, commit.author_email);
-                        println!("Date: {}", commit.timestamp);
-                        println!("Changes:");
-                        for change in commit.file_changes {
-                            println!(
-                                "  {}: +{} -{} (Codeowners: {} {})",
-                                change.path,
-                                change.insertions,
-                                change.deletions,
-                                change.author_is_codeowner.map_or("-", |b| if b {
-                                    "Y"
-                                } else {
-                                    "N"
-                                }),
-                                change
-                                    .codeowners
-                                    .as_ref()
-                                    .map_or_else(|| "None".to_string(), |owners| owners.join(", "))
-                            );
-                        }
-                        println!();
-                    }
+                    writeln!(out, "Owner: {}", info.owner)?;
+                    writeln!(out, "  Pull Requests: {}", info.pull_requests)?;
+                    writeln!(
+                        out,
+                        "  Approved by Codeowner: {} ({:.1}%)",
+                        info.approved_by_codeowner,
+                        info.codeowner_approval_rate() * 100.0
+                    )?;
+                    writeln!(
+                        out,
+                        "  Approved by Others Only: {}",
+                        info.approved_by_others_only
+                    )?;
+                    writeln!(
+                        out,
+                        "  Merged Without Approval: {}",
+                        info.merged_without_approval
+                    )?;
+                    writeln!(out)?;
                 }
             }
-        },
-        Commands::Init {
-            org,
+        }
+        Commands::AnalyzeReviewLoad {
+            since,
+            until,
+            directory,
             codeowners_path,
+            repo,
+            tsv,
         } => {
-            let api = GithubApi::new()?;
-            let memberships = get_all_org_members(&api, org).await?;
-            bound::write_memberships_to_tsv(&memberships, codeowners_path)?;
+            let repo = match repo {
+                Some(repo) => repo.clone(),
+                None => bound::get_origin_github_repo(directory)?.ok_or_else(|| {
+                    anyhow::anyhow!("Could not detect the GitHub repository, pass --repo")
+                })?,
+            };
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let owners = bound::get_owners_at_commit("HEAD", directory)?;
+
+            let api = GithubApi::new_with_auth(&github_auth).await?;
+            let mut user_cache = cli.user_info_cache();
+            let (pull_requests, identities) =
+                fetch_pull_request_reviews(&api, &repo, since, until, &mut user_cache).await?;
+            let identities = context.identities(identities);
+            user_cache.save()?;
+            report_api_usage(&api, &cli.api_usage_json)?;
+            let analysis =
+                bound::analyze_review_load(&pull_requests, &owners, &membership_index, &identities);
+
+            if *tsv {
+                writeln!(
+                    out,
+                    "owner\tpull_requests\tapproved\tapproval_rate\tmedian_hours_to_approve"
+                )?;
+            }
+            for info in analysis {
+                if *tsv {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t{:.4}\t{}",
+                        info.owner,
+                        info.pull_requests,
+                        info.approved,
+                        info.approval_rate(),
+                        info.median_hours_to_approve
+                            .map(|hours| format!("{:.2}", hours))
+                            .unwrap_or_default()
+                    )?;
+                } else {
+                    writeln!(out, "Owner: {}", info.owner)?;
+                    writeln!(out, "  Review Requests: {}", info.pull_requests)?;
+                    writeln!(
+                        out,
+                        "  Approved: {} ({:.1}%)",
+                        info.approved,
+                        info.approval_rate() * 100.0
+                    )?;
+                    if let Some(hours) = info.median_hours_to_approve {
+                        writeln!(out, "  Median Time to Approve: {:.1} hours", hours)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
         }
-        Commands::AnalyzeByOwner {
+        Commands::Scorecard {
             since,
             until,
             directory,
+            history,
             codeowners_path,
-            adjusted,
+            reviews,
+            repo,
+            tsv,
+            format,
         } => {
-            let memberships = read_memberships_from_tsv(codeowners_path)?;
-            let commits =
-                bound::git_log_commits_with_codeowners(since, until, directory, Some(memberships))?;
-            let analysis = bound::analyze_by_owner(commits, *adjusted)?;
-            for owner_info in analysis {
-                println!("Owner: {}", owner_info.owner);
-                println!(
-                    "  Team Changes: {} (+{}, -{})",
-                    owner_info.total_insertions_by_team + owner_info.total_deletions_by_team,
-                    owner_info.total_insertions_by_team,
-                    owner_info.total_deletions_by_team
-                );
-                println!("  Team Commits: {:.2}", owner_info.total_commits_by_team);
-                if *adjusted {
-                    println!(
-                        "  Adjusted Team Changes: {} (Commits: {:.2})",
-                        owner_info.adjusted_changes_by_team, owner_info.adjusted_commits_by_team
-                    );
-                }
-                println!(
-                    "  Others Changes: {} (+{}, -{})",
-                    owner_info.total_insertions_by_others + owner_info.total_deletions_by_others,
-                    owner_info.total_insertions_by_others,
-                    owner_info.total_deletions_by_others
+            let tsv = is_tsv(*tsv, *format);
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let reviews = if *reviews {
+                let repo = match repo {
+                    Some(repo) => repo.clone(),
+                    None => bound::get_origin_github_repo(directory)?.ok_or_else(|| {
+                        anyhow::anyhow!("Could not detect the GitHub repository, pass --repo")
+                    })?,
+                };
+                let membership_index = AuthorMembership::with_name_normalization(
+                    &memberships,
+                    &context.name_normalization,
                 );
-                println!(
-                    "  Others Commits: {:.2}",
-                    owner_info.total_commits_by_others
-                );
-                if *adjusted {
-                    println!(
-                        "  Adjusted Others Changes: {} (Commits: {:.2})",
-                        owner_info.adjusted_changes_by_others,
-                        owner_info.adjusted_commits_by_others
-                    );
-                }
-                println!("  Top Outside Contributors by Changes:");
-                for contributor in &owner_info.top_outside_contributors_by_changes {
-                    println!(
-                        "    {} <{}>: {}",
-                        contributor.author_name, contributor.author_email, contributor.metric_value
-                    );
-                }
-                println!("  Top Outside Contributors by Commits:");
-                for contributor in &owner_info.top_outside_contributors_by_commits {
-                    println!(
-                        "    {} <{}>: {}",
-                        contributor.author_name, contributor.author_email, contributor.metric_value
-                    );
-                }
-                println!("  Top Team Contributors by Changes:");
-                for contributor in &owner_info.top_team_contributors_by_changes {
-                    println!(
-                        "    {} <{}>: {}",
-                        contributor.author_name, contributor.author_email, contributor.metric_value
-                    );
-                }
-                println!("  Top Team Contributors by Commits:");
-                for contributor in &owner_info.top_team_contributors_by_commits {
-                    println!(
-                        "    {} <{}>: {}",
-                        contributor.author_name, contributor.author_email, contributor.metric_value
-                    );
-                }
-                println!();
+                let owners = bound::get_owners_at_commit(history.tip(), directory)?;
+                let api = GithubApi::new_with_auth(&github_auth).await?;
+                let mut user_cache = cli.user_info_cache();
+                let (pull_requests, identities) =
+                    fetch_pull_request_reviews(&api, &repo, since, until, &mut user_cache).await?;
+                let identities = context.identities(identities);
+                user_cache.save()?;
+                report_api_usage(&api, &cli.api_usage_json)?;
+                Some(alias_review_owners(
+                    bound::analyze_reviews(&pull_requests, &owners, &membership_index, &identities),
+                    &context.owner_aliases,
+                ))
+            } else {
+                None
+            };
+            let owned_files =
+                bound::owned_files_by_owner(history.tip(), directory, &context.owner_aliases)?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            // An --until in the future would otherwise count days that haven't happened yet
+            let as_of = chrono::DateTime::parse_from_rfc3339(until)?
+                .timestamp()
+                .min(chrono::Utc::now().timestamp());
+            let scorecards = bound::build_scorecards(
+                commits,
+                &owned_files,
+                reviews.as_deref(),
+                &config.scorecard,
+                as_of,
+            )?;
+            match format {
+                Some(OutputFormat::Ndjson) => {
+                    print_ndjson(out, scorecards.iter(), OutputGroup::default())?
+                }
+                Some(OutputFormat::Markdown) => print_scorecard_markdown(out, &scorecards)?,
+                _ if tsv => print_scorecard_tsv(out, &scorecards)?,
+                _ => print_scorecards(out, &scorecards)?,
             }
         }
-        Commands::AnalyzeByContributor {
+        Commands::AnalyzeTeamSize {
             since,
             until,
             directory,
+            history,
             codeowners_path,
-            owner,
+            team_size_history,
+            group_by,
             tsv,
-            adjusted,
         } => {
-            let memberships = read_memberships_from_tsv(codeowners_path)?;
-
-            let filter_authors = if let Some(owner) = owner {
-                Some(
-                    memberships
-                        .iter()
-                        .filter(|m| &m.codeowner == owner)
-                        .map(|m| (m.author_email.clone(), m.author_name.clone()))
-                        .collect::<HashSet<_>>(),
-                )
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let team_sizes = bound::read_team_size_history(team_size_history)?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let periods = bound::group_commits_by_period(commits, *group_by)?;
+            let trends = bound::analyze_team_size_history(periods, &team_sizes, directory)?;
+            if *tsv {
+                print_team_size_tsv(out, &trends)?;
             } else {
-                None
+                print_team_size_trends(out, &trends)?;
+            }
+        }
+        Commands::PreviewCodeowners {
+            patch,
+            since,
+            until,
+            directory,
+            history,
+            tsv,
+        } => {
+            let rev = history.git_ref.as_deref().unwrap_or("HEAD");
+            let current = bound::get_codeowners_at_commit(rev, directory)?.unwrap_or_default();
+            let proposed =
+                bound::apply_codeowners_patch(&current, &std::fs::read_to_string(patch)?)?;
+            let files = bound::git_tree_files(rev, directory)?;
+            let commits = history.commits(
+                git_log_commits(since, until, directory, &history.log_options(&context))?,
+                directory,
+                &context,
+            )?;
+            let preview = bound::preview_codeowners_change(
+                &current,
+                &proposed,
+                &files,
+                commits,
+                &context.owner_aliases,
+            )?;
+            print_codeowners_preview(out, &preview, *tsv)?;
+        }
+        Commands::Simulate {
+            codeowners_file,
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            tsv,
+            format,
+            adjusted,
+            weight,
+            top,
+        } => {
+            let options = AnalysisOptions {
+                adjusted: adjusted_metric(*adjusted, *weight, &config),
+                owner_attribution: None,
+                top_contributors: *top,
             };
-
-            let commits =
-                bound::git_log_commits_with_codeowners(since, until, directory, Some(memberships))?;
-            let analysis = bound::analyze_by_contributor(commits, *adjusted)?;
+            let tsv = is_tsv(*tsv, *format);
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            // Every commit is owned by the proposed file, whatever CODEOWNERS said at the time
+            let log_options = GitLogOptions {
+                fixed_codeowners: Some(std::fs::read_to_string(codeowners_file)?),
+                ..history.log_options(&context)
+            };
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &log_options,
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = analyze_owners(commits, &options, None)?;
+            print_owner_report(
+                out,
+                &analysis,
+                OutputGroup::default(),
+                output_format(tsv, *format),
+                *adjusted,
+                &metrics,
+            )?;
+        }
+        Commands::Summary {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            tsv,
+            top,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = analyze_owners(commits, &AnalysisOptions::default(), None)?;
+            let summary = bound::summarize_repository(analysis, history.tip(), directory, *top)?;
+            print_repository_summary(out, &summary, since, until, *tsv)?;
+        }
+        Commands::Watch {
+            since,
+            directory,
+            history,
+            codeowners_path,
+            interval,
+            fetch,
+            tsv,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let until = parse_until("now")?;
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    &until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships.clone()),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = analyze_owners(commits, &AnalysisOptions::default(), None)?;
+            let summary = bound::summarize_repository(analysis, history.tip(), directory, 5)?;
+            print_repository_summary(out, &summary, since, &until, *tsv)?;
             if *tsv {
-                if *adjusted {
-                    println!("author_name\tauthor_email\towner\tcommits\tchanges\tadjusted_commits\tadjusted_changes");
-                } else {
-                    println!("author_name\tauthor_email\towner\tcommits\tchanges");
-                }
-                for contributor_info in analysis {
-                    if let Some(filter_authors) = &filter_authors {
-                        if !filter_authors.contains(&(
-                            Some(contributor_info.author_email.clone()),
-                            Some(contributor_info.author_name.clone()),
-                        )) {
-                            continue;
-                        }
-                    }
+                writeln!(
+                    out,
+                    "checked_at\tfrom\tto\tcommits\towner\tteam_changes\toutside_changes"
+                )?;
+            }
 
-                    for contribution in &contributor_info.contributions {
-                        if *adjusted {
-                            println!(
-                                "{}\t{}\t{}\t{}\t{}\t{:.2}\t{}",
-                                contributor_info.author_name,
-                                contributor_info.author_email,
-                                contribution.owner,
-                                contribution.total_commits,
-                                contribution.total_insertions + contribution.total_deletions,
-                                contribution.adjusted_commits,
-                                contribution.adjusted_changes
-                            );
-                        } else {
-                            println!(
-                                "{}\t{}\t{}\t{}\t{}",
-                                contributor_info.author_name,
-                                contributor_info.author_email,
-                                contribution.owner,
-                                contribution.total_commits,
-                                contribution.total_insertions + contribution.total_deletions
-                            );
-                        }
+            let mut watched = bound::resolve_watched_commit(history.tip(), directory)?;
+            status!(
+                "Watching {} for new commits every {}",
+                history.tip(),
+                interval
+            );
+            loop {
+                tokio::time::sleep(interval.duration()).await;
+                if *fetch {
+                    // A failed fetch is retried at the next check rather than ending the watch
+                    if let Err(e) = bound::fetch_watched_repository(directory) {
+                        eprintln!("Warning: could not fetch {}: {}", directory.display(), e);
+                        continue;
                     }
                 }
+                let tip = bound::resolve_watched_commit(history.tip(), directory)?;
+                if tip == watched {
+                    continue;
+                }
+                let options = GitLogOptions {
+                    rev: Some(format!("{}..{}", watched, tip)),
+                    ..history.log_options(&context)
+                };
+                let commits = history.commits(
+                    bound::git_log_commits_with_codeowners(
+                        since,
+                        &parse_until("now")?,
+                        directory,
+                        &options,
+                        Some(memberships.clone()),
+                    )?,
+                    directory,
+                    &context,
+                )?;
+                let analysis = analyze_owners(commits, &AnalysisOptions::default(), None)?;
+                print_watch_delta(out, &bound::watch_delta(&watched, &tip, analysis), *tsv)?;
+                watched = tip;
+            }
+        }
+        Commands::BlameOwners {
+            path,
+            git_ref,
+            directory,
+            codeowners_path,
+            tsv,
+            top,
+        } => {
+            let memberships = AuthorMembership::with_name_normalization(
+                &read_memberships_from_tsv(codeowners_path)?,
+                &context.name_normalization,
+            );
+            let mut blame = bound::blame_ownership(path, git_ref, directory, &memberships)?;
+            if let Some(pseudonyms) = &context.pseudonyms {
+                for author in &mut blame.authors {
+                    author.author_name = pseudonyms.name(&author.author_name);
+                    author.author_email = pseudonyms.email(&author.author_email);
+                }
+            }
+            print_blame_ownership(out, &blame, *tsv, *top)?;
+        }
+        Commands::OwnersTree {
+            depth,
+            git_ref,
+            directory,
+            tsv,
+        } => {
+            let tree = bound::owners_tree_at(git_ref, directory, &context.owner_aliases, *depth)?;
+            if *tsv {
+                writeln!(out, "path\tfiles\towners\tunowned_files")?;
+                print_owners_tree_tsv(out, &tree)?;
             } else {
-                for contributor_info in analysis {
-                    if let Some(filter_authors) = &filter_authors {
-                        if !filter_authors.contains(&(
-                            Some(contributor_info.author_email.clone()),
-                            Some(contributor_info.author_name.clone()),
-                        )) {
-                            continue;
-                        }
-                    }
-
-                    println!(
-                        "Contributor: {} <{}>",
-                        contributor_info.author_name, contributor_info.author_email
-                    );
-                    for contribution in &contributor_info.contributions {
-                        println!("  Owner: {}", contribution.owner);
-                        println!(
-                            "    Changes: {} (+{}, -{})",
-                            contribution.total_insertions + contribution.total_deletions,
-                            contribution.total_insertions,
-                            contribution.total_deletions
-                        );
-                        println!("    Commits: {}", contribution.total_commits);
-                        if *adjusted {
-                            println!("    Adjusted Changes: {}", contribution.adjusted_changes);
-                            println!("    Adjusted Commits: {:.2}", contribution.adjusted_commits);
-                        }
-                    }
-                    println!();
+                writeln!(out, "{}", owners_tree_label(&tree))?;
+                print_owners_tree(out, &tree.children, "")?;
+            }
+        }
+        Commands::CodeownersDiff {
+            from,
+            to,
+            directory,
+            tsv,
+        } => {
+            let to = bound::resolve_revision_or_date(to, "HEAD", directory)?;
+            let from = bound::resolve_revision_or_date(from, &to, directory)?;
+            let diff =
+                bound::diff_codeowners_between(&from, &to, directory, &context.owner_aliases)?;
+            print_codeowners_diff(out, &diff, *tsv)?;
+        }
+        Commands::AnalyzeCoverage {
+            since,
+            until,
+            directory,
+            history,
+            group_by,
+            tsv,
+        } => {
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    None,
+                )?,
+                directory,
+                &context,
+            )?;
+            let periods = bound::group_commits_by_period(commits, *group_by)?;
+            let coverage = bound::analyze_coverage(periods, directory)?;
+            print_coverage(out, &coverage, *tsv)?;
+        }
+        Commands::AnalyzeOwnershipDistribution {
+            since,
+            until,
+            directory,
+            history,
+            tsv,
+        } => {
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    None,
+                )?,
+                directory,
+                &context,
+            )?;
+            let buckets = bound::analyze_ownership_distribution(commits)?;
+            let total_changes: usize = buckets
+                .iter()
+                .map(|b| b.total_insertions + b.total_deletions)
+                .sum();
+            if *tsv {
+                writeln!(
+                    out,
+                    "owners\tfiles\tfile_changes\tchanges\tinsertions\tdeletions\tchanges_share"
+                )?;
+            }
+            for bucket in buckets {
+                let changes = bucket.total_insertions + bucket.total_deletions;
+                let share = if total_changes > 0 {
+                    changes as f64 / total_changes as f64
+                } else {
+                    0.0
+                };
+                if *tsv {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t{}\t{}\t{}\t{:.4}",
+                        bucket.label,
+                        bucket.files,
+                        bucket.file_changes,
+                        changes,
+                        bucket.total_insertions,
+                        bucket.total_deletions,
+                        share
+                    )?;
+                } else {
+                    writeln!(out, "Owners: {}", bucket.label)?;
+                    writeln!(out, "  Files: {}", bucket.files)?;
+                    writeln!(out, "  File Changes: {}", bucket.file_changes)?;
+                    writeln!(
+                        out,
+                        "  Changes: {} (+{}, -{}) ({:.1}%)",
+                        changes,
+                        bucket.total_insertions,
+                        bucket.total_deletions,
+                        share * 100.0
+                    )?;
+                    writeln!(out)?;
                 }
             }
         }
+        Commands::AnalyzeOwnerFiles {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            owner,
+            limit,
+            tsv,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let analysis = bound::analyze_owner_files(commits, owner, &membership_index)?;
+            print_owner_files(out, &analysis, *limit, *tsv)?;
+        }
+        Commands::AnalyzeStaleOwnership {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            max_team_changes,
+            tsv,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    None,
+                )?,
+                directory,
+                &context,
+            )?;
+            let codeowners =
+                bound::get_codeowners_at_commit(history.tip(), directory)?.unwrap_or_default();
+            let stale = bound::analyze_stale_ownership(
+                commits,
+                &codeowners,
+                &membership_index,
+                &context.owner_aliases,
+                *max_team_changes,
+            )?;
+            print_stale_ownership(out, &stale, *tsv)?;
+        }
+        Commands::SuggestOwners {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            min_share,
+            tsv,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    None,
+                )?,
+                directory,
+                &context,
+            )?;
+            let owners = bound::get_owners_at_commit(history.tip(), directory)?;
+            let files = bound::git_tree_files(history.tip(), directory)?;
+            let mut suggestions = bound::suggest_owners(
+                commits,
+                &owners,
+                &files,
+                &membership_index,
+                &context.owner_aliases,
+            )?;
+            suggestions.retain(|suggestion| suggestion.share() >= *min_share);
+            print_owner_suggestions(out, &suggestions, *tsv)?;
+        }
+        Commands::SecurityReport {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            tsv,
+        } => {
+            if config.security_paths.is_empty() {
+                anyhow::bail!("No security_paths configured in {}", cli.config.display());
+            }
+            let sensitive = PathFilter::new(&config.security_paths, &[])?;
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &history.log_options(&context),
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            let changes = bound::find_sensitive_changes(commits, &sensitive, &membership_index)?;
+            print_sensitive_changes(out, &changes, *tsv)?;
+        }
+        Commands::AnnotateLog {
+            since,
+            until,
+            directory,
+            history,
+            codeowners_path,
+            max_count,
+        } => {
+            let memberships = context.read_memberships(codeowners_path, [directory])?;
+            let membership_index = AuthorMembership::with_name_normalization(
+                &memberships,
+                &context.name_normalization,
+            );
+            // Subjects are needed for the `--oneline` form
+            let options = GitLogOptions {
+                include_message: true,
+                ..history.log_options(&context)
+            };
+            let commits = history.commits(
+                bound::git_log_commits_with_codeowners(
+                    since,
+                    until,
+                    directory,
+                    &options,
+                    Some(memberships),
+                )?,
+                directory,
+                &context,
+            )?;
+            for commit in commits.take(max_count.unwrap_or(usize::MAX)) {
+                let commit = commit?;
+                let annotations = bound::annotate_commit(&commit, &membership_index);
+                writeln!(
+                    out,
+                    "{}",
+                    bound::format_annotated_commit(&commit, &annotations)
+                )?;
+            }
+        }
+    }
+
+    // The isolated copy borrows objects from a remote clone, so it goes first
+    drop(isolated);
+    if cli.cleanup_remote {
+        for clone in remote_clones {
+            std::fs::remove_dir_all(clone)?;
+        }
     }
+    print_warnings_summary(&context.warnings, &cli.warnings_json)?;
 
     Ok(())
 }
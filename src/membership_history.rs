@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, NaiveDate};
+
+use crate::{AuthorCodeownerMemberships, TeamMemberRecord, TeamMembershipEvent};
+
+type MemberKey = (String, String);
+
+fn member_key(codeowner: &str, login: &str) -> MemberKey {
+    (codeowner.to_lowercase(), login.to_lowercase())
+}
+
+/// The periods a member was in a team, as `(valid_from, valid_to)`, from the team's
+/// `events` for them, oldest first. Membership before the first event is told from
+/// whether it adds or removes them, and membership after the last from whether they're a
+/// member `today`, so events missing from the log don't leave a current member out.
+fn membership_periods(
+    events: &[&TeamMembershipEvent],
+    is_member: bool,
+    today: NaiveDate,
+) -> Vec<(Option<NaiveDate>, Option<NaiveDate>)> {
+    let mut periods = Vec::new();
+    // The start of the period the member is in, if any
+    let mut start: Option<Option<NaiveDate>> = None;
+    for event in events {
+        let day = DateTime::from_timestamp(event.timestamp, 0).map(|time| time.date_naive());
+        if event.added {
+            start.get_or_insert(day);
+        } else if let Some(from) = start.take() {
+            periods.push((from, day));
+        } else if periods.is_empty() {
+            // Removed without being added since the log began
+            periods.push((None, day));
+        }
+    }
+    match (start, is_member) {
+        (Some(from), true) => periods.push((from, None)),
+        (Some(from), false) => periods.push((from, Some(today))),
+        (None, true) => {
+            let rejoined = periods.last().and_then(|(_, to)| *to);
+            periods.push((rejoined, None));
+        }
+        (None, false) => {}
+    }
+    periods
+}
+
+/// Dated memberships from the current team members in `records` and the `events` that
+/// added and removed members over time, as read from the audit log. Former members are
+/// only found in the events, so their names and emails are taken from `users`, by login;
+/// those not in it are left out. Events only apply to the team they were recorded on,
+/// not the teams it is nested under.
+pub fn dated_team_memberships(
+    records: &[TeamMemberRecord],
+    events: &[TeamMembershipEvent],
+    users: &HashMap<String, (String, String)>,
+    today: NaiveDate,
+) -> Vec<AuthorCodeownerMemberships> {
+    let mut events_by_member: BTreeMap<MemberKey, Vec<&TeamMembershipEvent>> = BTreeMap::new();
+    for event in events {
+        events_by_member
+            .entry(member_key(&event.codeowner, &event.login))
+            .or_default()
+            .push(event);
+    }
+
+    let mut memberships = Vec::new();
+    let mut dated = |membership: AuthorCodeownerMemberships, periods| {
+        for (valid_from, valid_to) in periods {
+            memberships.push(AuthorCodeownerMemberships {
+                valid_from,
+                valid_to,
+                ..membership.clone()
+            });
+        }
+    };
+    for record in records {
+        let events = events_by_member
+            .remove(&member_key(&record.codeowner, &record.login))
+            .unwrap_or_default();
+        dated(
+            record.to_membership(),
+            membership_periods(&events, true, today),
+        );
+    }
+    for (_, events) in events_by_member {
+        let Some(event) = events.first() else {
+            continue;
+        };
+        let Some((name, email)) = users.get(&event.login) else {
+            continue;
+        };
+        if name.is_empty() && email.is_empty() {
+            continue;
+        }
+        let record = TeamMemberRecord {
+            codeowner: event.codeowner.clone(),
+            login: event.login.clone(),
+            author_email: email.clone(),
+            author_name: name.clone(),
+        };
+        dated(
+            record.to_membership(),
+            membership_periods(&events, false, today),
+        );
+    }
+    memberships
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    get_all_codeowners, get_commit_author_emails, get_github_team_members_nested, get_github_teams,
+    get_noreply_emails, get_users_info_cached, merge_team_member_changes,
+    read_memberships_from_tsv, AuthorCodeownerMemberships, GHCliError, GithubApi,
+    NameNormalization, RefreshStats, TeamMemberRecord, TeamMembership, UserInfoCache,
+    WarningCategory, Warnings,
+};
+
+/// A source of the members of each codeowner, such as the GitHub organization or a
+/// company directory. `init` and `refresh` are built on it, so memberships can come from
+/// LDAP, Okta or an HR system by implementing it.
+#[async_trait(?Send)]
+pub trait MembershipProvider {
+    /// The current members of each codeowner.
+    async fn fetch(&mut self) -> Result<Vec<TeamMemberRecord>, io::Error>;
+
+    /// Applies the changes since `previous` was fetched to `memberships`, as
+    /// [`merge_team_member_changes`] does, and returns the members now along with what
+    /// changed.
+    async fn refresh(
+        &mut self,
+        memberships: &mut Vec<AuthorCodeownerMemberships>,
+        previous: &[TeamMemberRecord],
+        names: &NameNormalization,
+    ) -> Result<(Vec<TeamMemberRecord>, RefreshStats), io::Error> {
+        let current = self.fetch().await?;
+        let stats = merge_team_member_changes(memberships, previous, &current, names);
+        Ok((current, stats))
+    }
+}
+
+/// Memberships maintained in a file in the format of `codeowners.tsv`, such as one exported
+/// from another system.
+pub struct TsvMembershipProvider {
+    path: PathBuf,
+}
+
+impl TsvMembershipProvider {
+    pub fn new(path: &Path) -> Self {
+        TsvMembershipProvider {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl MembershipProvider for TsvMembershipProvider {
+    /// A record for each email and name a member is listed under. The file has no logins,
+    /// so members are identified by their main email, or name without one.
+    async fn fetch(&mut self) -> Result<Vec<TeamMemberRecord>, io::Error> {
+        let mut records = Vec::new();
+        for membership in read_memberships_from_tsv(&self.path)? {
+            let name = membership.author_name.clone().unwrap_or_default();
+            let login = membership
+                .author_email
+                .clone()
+                .unwrap_or_else(|| name.clone());
+            for email in membership.emails() {
+                records.push(TeamMemberRecord {
+                    codeowner: membership.codeowner.clone(),
+                    login: login.clone(),
+                    author_email: email.clone(),
+                    author_name: name.clone(),
+                });
+            }
+            for alias in membership
+                .names()
+                .filter(|alias| membership.emails().next().is_none() || **alias != name)
+            {
+                records.push(TeamMemberRecord {
+                    codeowner: membership.codeowner.clone(),
+                    login: login.clone(),
+                    author_email: String::new(),
+                    author_name: alias.clone(),
+                });
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// How many teams and emails the last fetch from a GitHub organization found.
+#[derive(Clone, Debug, Default)]
+pub struct GithubOrgStats {
+    pub teams: usize,
+    /// Of the teams, those listed in CODEOWNERS; only their members are fetched.
+    pub codeowning_teams: usize,
+    /// Emails of members without a public one, found as noreply addresses in the history.
+    pub emails_from_history: usize,
+    /// Emails of members without a public one, found through the commits API.
+    pub emails_from_commits: usize,
+}
+
+/// The members of every codeowning team in a GitHub organization, as fetched by `init`.
+pub struct GithubOrgProvider<'a> {
+    api: &'a GithubApi,
+    org: String,
+    /// The repository whose CODEOWNERS and history are read.
+    directory: PathBuf,
+    user_cache: &'a mut UserInfoCache,
+    /// Name and email of users already known, by login, looked up before asking GitHub.
+    known: HashMap<String, (String, String)>,
+    /// Repository whose commits API is asked for the email of members without a public
+    /// one, if any.
+    commits_repo: Option<String>,
+    team_membership: TeamMembership,
+    concurrency: usize,
+    warnings: Warnings,
+    stats: GithubOrgStats,
+}
+
+fn github_error(e: GHCliError) -> io::Error {
+    match e {
+        GHCliError::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+impl<'a> GithubOrgProvider<'a> {
+    pub fn new(
+        api: &'a GithubApi,
+        org: &str,
+        directory: &Path,
+        user_cache: &'a mut UserInfoCache,
+        warnings: &Warnings,
+    ) -> Self {
+        GithubOrgProvider {
+            api,
+            org: org.to_string(),
+            directory: directory.to_path_buf(),
+            user_cache,
+            known: HashMap::new(),
+            commits_repo: None,
+            team_membership: TeamMembership::default(),
+            concurrency: 8,
+            warnings: warnings.clone(),
+            stats: GithubOrgStats::default(),
+        }
+    }
+
+    /// Looks up the commit email of members without a public one in `repo` (`owner/name`).
+    pub fn with_commits_repo(mut self, repo: Option<String>) -> Self {
+        self.commits_repo = repo;
+        self
+    }
+
+    pub fn with_team_membership(mut self, membership: TeamMembership) -> Self {
+        self.team_membership = membership;
+        self
+    }
+
+    /// Requests up to `concurrency` teams or users at a time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn stats(&self) -> &GithubOrgStats {
+        &self.stats
+    }
+
+    /// The commit email of `login`, who has no public one, or an empty string.
+    async fn resolve_email(&mut self, noreply: &HashMap<String, String>, login: &str) -> String {
+        if let Some(email) = noreply.get(&login.to_lowercase()) {
+            self.stats.emails_from_history += 1;
+            return email.clone();
+        }
+        let Some(repo) = &self.commits_repo else {
+            return String::new();
+        };
+        match get_commit_author_emails(self.api, repo, login).await {
+            Ok(emails) => match emails.into_iter().next() {
+                Some(email) => {
+                    self.stats.emails_from_commits += 1;
+                    email
+                }
+                None => String::new(),
+            },
+            Err(e) => {
+                // Without access to the commits, asking for every other member fails too
+                self.warnings.add(
+                    WarningCategory::LowConfidenceMatch,
+                    format!("Could not look up commit emails in {}: {}", repo, e),
+                );
+                self.commits_repo = None;
+                String::new()
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl MembershipProvider for GithubOrgProvider<'_> {
+    async fn fetch(&mut self) -> Result<Vec<TeamMemberRecord>, io::Error> {
+        let api = self.api;
+        let org = self.org.clone();
+        let org = org.as_str();
+        let all_teams = get_github_teams(api, org).await.map_err(github_error)?;
+        let all_codeowners = get_all_codeowners(&self.directory)?;
+
+        // Filter teams to only include those that are codeowners
+        let teams: Vec<String> = all_teams
+            .iter()
+            .map(|team| team.slug.clone())
+            .filter(|team| all_codeowners.contains(&format!("@{}/{}", org, team)))
+            .collect();
+        self.stats = GithubOrgStats {
+            teams: all_teams.len(),
+            codeowning_teams: teams.len(),
+            ..GithubOrgStats::default()
+        };
+        tracing::info!(org, teams = teams.len(), "Fetching codeowning team members");
+
+        let mut all_members = HashSet::new();
+        let mut team_members = HashMap::new();
+        let team_membership = self.team_membership;
+        let mut fetched = stream::iter(teams)
+            .map(|team| {
+                let all_teams = &all_teams;
+                async move {
+                    let members =
+                        get_github_team_members_nested(api, org, all_teams, &team, team_membership)
+                            .await;
+                    (team, members)
+                }
+            })
+            .buffer_unordered(self.concurrency);
+        while let Some((team, members)) = fetched.next().await {
+            let members = members.map_err(github_error)?;
+            all_members.extend(members.iter().cloned());
+            team_members.insert(format!("@{}/{}", org, team), members);
+        }
+        drop(fetched);
+
+        // Individual users listed as owners are a team of one
+        for owner in &all_codeowners {
+            if let Some(login) = owner.strip_prefix('@').filter(|login| !login.contains('/')) {
+                all_members.insert(login.to_string());
+                team_members.insert(owner.clone(), vec![login.to_string()]);
+            }
+        }
+
+        let unknown: Vec<String> = all_members
+            .iter()
+            .filter(|member| !self.known.contains_key(*member))
+            .cloned()
+            .collect();
+        let users = get_users_info_cached(api, &unknown, self.user_cache, self.concurrency)
+            .await
+            .map_err(github_error)?;
+        let noreply = get_noreply_emails(&self.directory).map_err(github_error)?;
+
+        // Owners given as email addresses are matched against commit emails directly
+        let mut records: Vec<TeamMemberRecord> = all_codeowners
+            .iter()
+            .filter(|owner| !owner.starts_with('@'))
+            .map(|email| TeamMemberRecord {
+                codeowner: email.clone(),
+                login: email.clone(),
+                author_email: email.clone(),
+                author_name: String::new(),
+            })
+            .collect();
+        for (codeowner, members) in team_members {
+            for member in members {
+                let Some((name, mut email)) = self
+                    .known
+                    .get(&member)
+                    .or_else(|| users.get(&member))
+                    .cloned()
+                else {
+                    continue;
+                };
+                if email.is_empty() {
+                    email = self.resolve_email(&noreply, &member).await;
+                }
+                if email.is_empty() {
+                    self.warnings.add(
+                        WarningCategory::LowConfidenceMatch,
+                        format!(
+                            "{} in {} has no public email, matched by name '{}'",
+                            member, codeowner, name
+                        ),
+                    );
+                }
+                records.push(TeamMemberRecord {
+                    codeowner: codeowner.clone(),
+                    login: member,
+                    author_email: email,
+                    author_name: name,
+                });
+            }
+        }
+        Ok(records)
+    }
+
+    /// Takes the name and email of members known from `previous` rather than asking
+    /// GitHub for them again.
+    async fn refresh(
+        &mut self,
+        memberships: &mut Vec<AuthorCodeownerMemberships>,
+        previous: &[TeamMemberRecord],
+        names: &NameNormalization,
+    ) -> Result<(Vec<TeamMemberRecord>, RefreshStats), io::Error> {
+        self.known = previous
+            .iter()
+            .map(|record| {
+                (
+                    record.login.clone(),
+                    (record.author_name.clone(), record.author_email.clone()),
+                )
+            })
+            .collect();
+        let current = self.fetch().await?;
+        let stats = merge_team_member_changes(memberships, previous, &current, names);
+        Ok((current, stats))
+    }
+}
@@ -0,0 +1,177 @@
+use std::{collections::BTreeSet, fs, io, path::Path};
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+use crate::AuthorCodeownerMemberships;
+
+#[derive(Deserialize)]
+struct RuleEntry {
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    codeowner: String,
+}
+
+#[derive(Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleEntry>,
+}
+
+struct MembershipRule {
+    email: Option<Regex>,
+    name: Option<Regex>,
+    codeowner: String,
+}
+
+impl MembershipRule {
+    fn matches(&self, name: &str, email: &str) -> bool {
+        self.email
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(email))
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(name))
+    }
+}
+
+/// Memberships inferred from authors' emails or names, for organizations whose teams
+/// follow from email domains or naming conventions rather than a list of members.
+#[derive(Default)]
+pub struct MembershipRules {
+    rules: Vec<MembershipRule>,
+}
+
+fn compile(pattern: &str) -> Result<Regex, io::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid membership rule pattern '{}': {}", pattern, e),
+            )
+        })
+}
+
+impl MembershipRules {
+    /// Parses rules mapping patterns to the codeowner authors matching them belong to:
+    ///
+    /// ```toml
+    /// [[rule]]
+    /// email = '@payments\.example\.com$'
+    /// codeowner = "@org/payments"
+    ///
+    /// [[rule]]
+    /// name = '^infra-'
+    /// codeowner = "@org/infra"
+    /// ```
+    ///
+    /// Patterns are case-insensitive regular expressions matched anywhere in the value. A
+    /// rule with both an `email` and a `name` pattern only matches authors matching both,
+    /// and an author matching several rules belongs to each of their codeowners.
+    pub fn parse(content: &str) -> Result<Self, io::Error> {
+        let file: RulesFile = toml::from_str(content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid membership rules: {}", e),
+            )
+        })?;
+        let mut rules = Vec::new();
+        for entry in file.rules {
+            if entry.email.is_none() && entry.name.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Invalid membership rule for {}: expected an email or name pattern",
+                        entry.codeowner
+                    ),
+                ));
+            }
+            rules.push(MembershipRule {
+                email: entry.email.as_deref().map(compile).transpose()?,
+                name: entry.name.as_deref().map(compile).transpose()?,
+                codeowner: entry.codeowner,
+            });
+        }
+        Ok(MembershipRules { rules })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The codeowners the rules place an author in.
+    pub fn codeowners(&self, name: &str, email: &str) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(name, email))
+            .map(|rule| rule.codeowner.as_str())
+            .collect()
+    }
+
+    /// A membership for each of `authors` in each codeowner a rule places them in, keyed by
+    /// the author's email, or by name for authors without one.
+    pub fn memberships(
+        &self,
+        authors: impl IntoIterator<Item = (String, String)>,
+    ) -> Vec<AuthorCodeownerMemberships> {
+        let mut memberships = Vec::new();
+        for (name, email) in authors {
+            for codeowner in self.codeowners(&name, &email) {
+                let (author_email, author_name) = if email.is_empty() {
+                    (None, Some(name.clone()))
+                } else {
+                    (Some(email.clone()), None)
+                };
+                memberships.push(AuthorCodeownerMemberships {
+                    author_email,
+                    author_name,
+                    codeowner: codeowner.to_string(),
+                    additional_emails: Vec::new(),
+                    aliases: Vec::new(),
+                    valid_from: None,
+                    valid_to: None,
+                });
+            }
+        }
+        memberships
+    }
+}
+
+/// Everyone who authored or co-authored a commit reachable from any ref in the repository
+/// at `cwd`, as `(name, email)`, for inferring their memberships up front.
+pub fn git_authors(cwd: &Path) -> Result<BTreeSet<(String, String)>, io::Error> {
+    let output = crate::platform::command("git")
+        .args([
+            "log",
+            "--all",
+            "--format=%an%x1f%ae%x1f%(trailers:key=Co-authored-by,valueonly,separator=%x1f)",
+        ])
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let mut authors = BTreeSet::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split('\x1f');
+        let (Some(name), Some(email)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        authors.insert((name.to_string(), email.to_string()));
+        for co_author in fields {
+            let Some((name, rest)) = co_author.split_once('<') else {
+                continue;
+            };
+            let email = rest.split_once('>').map_or(rest, |(email, _)| email);
+            authors.insert((name.trim().to_string(), email.trim().to_string()));
+        }
+    }
+    Ok(authors)
+}
@@ -0,0 +1,177 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MetricError {
+    #[error("Invalid expression for metric '{name}': {message}")]
+    Parse { name: String, message: String },
+    #[error("Unknown field '{field}' in metric '{name}'")]
+    UnknownField { name: String, field: String },
+}
+
+enum Expr {
+    Number(f64),
+    Field(String),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            let value = number
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' => Token::Op(c),
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                _ => return Err(format!("unexpected character '{}'", c)),
+            });
+            chars.next();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = *op;
+            self.next();
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.term()?));
+        }
+        Ok(expr)
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = *op;
+            self.next();
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.factor()?));
+        }
+        Ok(expr)
+    }
+
+    // factor := number | field | '-' factor | '(' expression ')'
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(*value)),
+            Some(Token::Ident(name)) => Ok(Expr::Field(name.clone())),
+            Some(Token::Op('-')) => Ok(Expr::Negate(Box::new(self.factor()?))),
+            Some(Token::LParen) => {
+                let expr = self.expression()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("expected ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// A named metric computed from the built-in numeric fields of a report row, e.g.
+/// `outside_ratio = others_changes / (team_changes + others_changes)`.
+pub struct DerivedMetric {
+    pub name: String,
+    expression: Expr,
+}
+
+impl DerivedMetric {
+    pub fn parse(name: &str, expression: &str) -> Result<Self, MetricError> {
+        let parse_error = |message: String| MetricError::Parse {
+            name: name.to_string(),
+            message,
+        };
+        let mut parser = Parser {
+            tokens: tokenize(expression).map_err(parse_error)?,
+            position: 0,
+        };
+        let parsed = parser.expression().map_err(parse_error)?;
+        if let Some(token) = parser.peek() {
+            return Err(parse_error(format!("unexpected {:?}", token)));
+        }
+        Ok(DerivedMetric {
+            name: name.to_string(),
+            expression: parsed,
+        })
+    }
+
+    /// Evaluates the metric against `fields`. Division by zero yields `0.0` so ratios over
+    /// empty rows stay printable.
+    pub fn evaluate(&self, fields: &[(&str, f64)]) -> Result<f64, MetricError> {
+        self.evaluate_expr(&self.expression, fields)
+    }
+
+    fn evaluate_expr(&self, expr: &Expr, fields: &[(&str, f64)]) -> Result<f64, MetricError> {
+        Ok(match expr {
+            Expr::Number(value) => *value,
+            Expr::Field(field) => fields
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| MetricError::UnknownField {
+                    name: self.name.clone(),
+                    field: field.clone(),
+                })?,
+            Expr::Negate(inner) => -self.evaluate_expr(inner, fields)?,
+            Expr::Binary(left, op, right) => {
+                let left = self.evaluate_expr(left, fields)?;
+                let right = self.evaluate_expr(right, fields)?;
+                match op {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ if right == 0.0 => 0.0,
+                    _ => left / right,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// How author names are normalized before being compared, so the same person matches
+/// whichever way their name was written. Configured in the `[name_normalization]` section
+/// of `bound.toml`; by default names are only compared case-insensitively.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct NameNormalization {
+    /// Compose characters to Unicode NFC, so precomposed and decomposed accents match.
+    pub nfc: bool,
+    /// Compare names case-insensitively.
+    pub case_fold: bool,
+    /// Read `Last, First` as `First Last`.
+    pub reorder_comma: bool,
+    /// Drop accents and other combining marks, so `José` matches `Jose`.
+    pub strip_diacritics: bool,
+}
+
+impl Default for NameNormalization {
+    fn default() -> Self {
+        Self {
+            nfc: false,
+            case_fold: true,
+            reorder_comma: false,
+            strip_diacritics: false,
+        }
+    }
+}
+
+impl NameNormalization {
+    pub fn normalize(&self, name: &str) -> String {
+        let mut name = name.trim().to_string();
+        if self.reorder_comma {
+            if let Some((last, first)) = name.split_once(',') {
+                if !first.contains(',') {
+                    name = format!("{} {}", first.trim(), last.trim());
+                }
+            }
+        }
+        if self.strip_diacritics {
+            name = name
+                .nfd()
+                .filter(|c| !is_combining_mark(*c))
+                .nfc()
+                .collect();
+        } else if self.nfc {
+            name = name.nfc().collect();
+        }
+        if self.case_fold {
+            name = name.to_lowercase();
+        }
+        name.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuthorCodeownerMemberships, AuthorMembership};
+
+    fn all() -> NameNormalization {
+        NameNormalization {
+            nfc: true,
+            case_fold: true,
+            reorder_comma: true,
+            strip_diacritics: true,
+        }
+    }
+
+    #[test]
+    fn names_are_only_case_folded_by_default() {
+        let names = NameNormalization::default();
+        assert_eq!(names.normalize("  Ada   Lovelace "), "ada lovelace");
+        assert_eq!(names.normalize("Lovelace, Ada"), "lovelace, ada");
+        assert_eq!(names.normalize("José"), "josé");
+    }
+
+    #[test]
+    fn commas_reorder_last_and_first_names() {
+        assert_eq!(all().normalize("Lovelace, Ada"), "ada lovelace");
+        // More than one comma is not a `Last, First` name
+        assert_eq!(
+            all().normalize("Ada, Countess, Lovelace"),
+            "ada, countess, lovelace"
+        );
+    }
+
+    #[test]
+    fn accents_compose_or_are_stripped() {
+        let decomposed = "Jose\u{301}";
+        let nfc = NameNormalization {
+            nfc: true,
+            ..NameNormalization::default()
+        };
+        assert_eq!(nfc.normalize(decomposed), "josé");
+        assert_eq!(
+            NameNormalization::default().normalize(decomposed),
+            "jose\u{301}"
+        );
+        assert_eq!(all().normalize(decomposed), "jose");
+        assert_eq!(all().normalize("Zoë Ångström"), "zoe angstrom");
+    }
+
+    #[test]
+    fn memberships_match_normalized_names() {
+        let memberships = [AuthorCodeownerMemberships {
+            author_email: None,
+            author_name: Some("Lovelace, Ada".to_string()),
+            codeowner: "@org/a".to_string(),
+            additional_emails: Vec::new(),
+            aliases: vec!["Jose\u{301}".to_string()],
+            valid_from: None,
+            valid_to: None,
+        }];
+        let membership = AuthorMembership::with_name_normalization(&memberships, &all());
+        assert!(membership.is_codeowner("ADA LOVELACE", "ada@example.com", "@org/a"));
+        assert!(membership.is_codeowner("José", "", "@org/a"));
+        let membership = AuthorMembership::new(&memberships);
+        assert!(!membership.is_codeowner("Ada Lovelace", "ada@example.com", "@org/a"));
+    }
+}
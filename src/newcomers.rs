@@ -0,0 +1,78 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+use serde::Serialize;
+
+use crate::CommitInfoWithCodeowner;
+
+/// Who has changed each owner's files, by `(name, email)`.
+#[derive(Clone, Default)]
+pub struct OwnerContributors {
+    contributors: HashMap<String, HashSet<(String, String)>>,
+}
+
+impl OwnerContributors {
+    /// Records the author of `commit` as a contributor to every owner of the files it
+    /// changed.
+    pub fn add_commit(&mut self, commit: &CommitInfoWithCodeowner) {
+        for change in &commit.file_changes {
+            for owner in change.codeowners.iter().flatten() {
+                self.contributors
+                    .entry(owner.clone())
+                    .or_default()
+                    .insert((commit.author_name.clone(), commit.author_email.clone()));
+            }
+        }
+    }
+
+    pub fn extend(&mut self, other: OwnerContributors) {
+        for (owner, contributors) in other.contributors {
+            self.contributors
+                .entry(owner)
+                .or_default()
+                .extend(contributors);
+        }
+    }
+
+    pub fn contains(&self, owner: &str, author_name: &str, author_email: &str) -> bool {
+        self.contributors.get(owner).is_some_and(|contributors| {
+            contributors.contains(&(author_name.to_string(), author_email.to_string()))
+        })
+    }
+}
+
+/// The contributors to each owner in `commits`, typically the history before an analyzed
+/// period.
+pub fn owner_contributors(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+) -> Result<OwnerContributors, io::Error> {
+    let mut contributors = OwnerContributors::default();
+    for commit_result in commits {
+        contributors.add_commit(&commit_result?);
+    }
+    Ok(contributors)
+}
+
+/// How an owner's contributors split between newcomers, who had never changed the owner's
+/// files before, and those who had.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct NewcomerBreakdown {
+    pub first_time_contributors: usize,
+    pub repeat_contributors: usize,
+    pub changes_by_first_time_contributors: usize,
+    pub changes_by_repeat_contributors: usize,
+}
+
+impl NewcomerBreakdown {
+    /// Fraction of the changes made by first-time contributors.
+    pub fn first_time_share(&self) -> f64 {
+        let total = self.changes_by_first_time_contributors + self.changes_by_repeat_contributors;
+        if total > 0 {
+            self.changes_by_first_time_contributors as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{get_owners_at_commit, git_tree_files, CommitInfoWithCodeowner, OwnerAnalysis};
+
+const MAX_ADJACENT_PATHS: usize = 10;
+
+/// One owner team's slice of a report, addressed to the team's configured contacts.
+pub struct OwnerNotification {
+    pub owner: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+fn parent_directory(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Groups the unowned files changed in `commits` by the owners of the other files in the same
+/// directory at `commit_id`, as `(path, changes)` sorted by most changes first.
+pub fn unowned_paths_by_adjacent_owner(
+    commits: &[CommitInfoWithCodeowner],
+    commit_id: &str,
+    cwd: &PathBuf,
+) -> Result<HashMap<String, Vec<(String, usize)>>, io::Error> {
+    let mut unowned_changes: HashMap<&str, usize> = HashMap::new();
+    for commit in commits {
+        for change in &commit.file_changes {
+            if change
+                .codeowners
+                .as_ref()
+                .is_none_or(|owners| owners.is_empty())
+            {
+                *unowned_changes.entry(&change.path).or_default() +=
+                    (change.insertions + change.deletions) as usize;
+            }
+        }
+    }
+    if unowned_changes.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let owners = get_owners_at_commit(commit_id, cwd)?;
+    let mut directory_owners: HashMap<String, HashSet<String>> = HashMap::new();
+    for path in git_tree_files(commit_id, cwd)? {
+        if let Some(file_owners) = owners.of(&path) {
+            directory_owners
+                .entry(parent_directory(&path).to_string())
+                .or_default()
+                .extend(file_owners.iter().map(|owner| owner.to_string()));
+        }
+    }
+
+    let mut adjacent: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+    for (path, changes) in unowned_changes {
+        for owner in directory_owners
+            .get(parent_directory(path))
+            .into_iter()
+            .flatten()
+        {
+            adjacent
+                .entry(owner.clone())
+                .or_default()
+                .push((path.to_string(), changes));
+        }
+    }
+    for paths in adjacent.values_mut() {
+        paths.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        paths.truncate(MAX_ADJACENT_PATHS);
+    }
+    Ok(adjacent)
+}
+
+/// Builds a notification for each owner in `contacts` that had activity in the report
+/// window; owners without contacts or activity are left out.
+pub fn build_owner_notifications(
+    analysis: &OwnerAnalysis,
+    adjacent_unowned: &HashMap<String, Vec<(String, usize)>>,
+    contacts: &BTreeMap<String, Vec<String>>,
+    since: &str,
+    until: &str,
+) -> Vec<OwnerNotification> {
+    let mut notifications = Vec::new();
+
+    for (owner, recipients) in contacts {
+        if recipients.is_empty() {
+            continue;
+        }
+        let owner_info = analysis
+            .owners
+            .iter()
+            .find(|info| info.owner.eq_ignore_ascii_case(owner));
+        let adjacent = adjacent_unowned
+            .iter()
+            .find(|(adjacent_owner, _)| adjacent_owner.eq_ignore_ascii_case(owner))
+            .map(|(_, paths)| paths.as_slice())
+            .unwrap_or_default();
+        if owner_info.is_none() && adjacent.is_empty() {
+            continue;
+        }
+
+        let mut body = format!("Ownership report for {} ({} to {})\n", owner, since, until);
+        if let Some(info) = owner_info {
+            body.push_str(&format!(
+                "\nChanges by team: {} in {} commits\nChanges by others: {} in {} commits\n",
+                info.total_insertions_by_team + info.total_deletions_by_team,
+                info.total_commits_by_team,
+                info.total_insertions_by_others + info.total_deletions_by_others,
+                info.total_commits_by_others,
+            ));
+            if !info.top_outside_contributors_by_changes.is_empty() {
+                body.push_str("\nTop outside contributors:\n");
+                for contributor in &info.top_outside_contributors_by_changes {
+                    body.push_str(&format!(
+                        "  {} <{}>: {} changes\n",
+                        contributor.author_name, contributor.author_email, contributor.metric_value
+                    ));
+                }
+            }
+        }
+        if !adjacent.is_empty() {
+            body.push_str("\nUnowned paths next to your code:\n");
+            for (path, changes) in adjacent {
+                body.push_str(&format!("  {}: {} changes\n", path, changes));
+            }
+        }
+
+        notifications.push(OwnerNotification {
+            owner: owner.clone(),
+            recipients: recipients.clone(),
+            subject: format!("[bound] Ownership report for {}", owner),
+            body,
+        });
+    }
+
+    notifications
+}
+
+/// Formats `notification` as an RFC 5322 message from `from`.
+pub fn format_notification_email(notification: &OwnerNotification, from: &str) -> String {
+    format!(
+        "From: {}\nTo: {}\nSubject: {}\nContent-Type: text/plain; charset=utf-8\n\n{}",
+        from,
+        notification.recipients.join(", "),
+        notification.subject,
+        notification.body
+    )
+}
+
+/// Sends `notification` by piping it to a sendmail-compatible command.
+pub fn send_notification(
+    notification: &OwnerNotification,
+    from: &str,
+    sendmail: &Path,
+) -> Result<(), io::Error> {
+    let mut child = Command::new(sendmail)
+        .arg("-t")
+        .stdin(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| io::Error::other("Could not capture stdin"))?
+        .write_all(format_notification_email(notification, from).as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{} exited with {} while notifying {}",
+            sendmail.display(),
+            status,
+            notification.owner
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::{TeamMemberRecord, WarningCategory, Warnings};
+
+/// A team member as an export lists them: a bare login, or an object with GitHub's field
+/// names (`login`, `name`, `email`) or SCIM's (`userName`, `displayName`, `emails`).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExportMember {
+    Login(String),
+    User {
+        #[serde(alias = "userName")]
+        login: String,
+        #[serde(default, alias = "displayName")]
+        name: Option<String>,
+        #[serde(default)]
+        email: Option<String>,
+        #[serde(default)]
+        emails: Vec<ScimEmail>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ScimEmail {
+    value: String,
+    #[serde(default)]
+    primary: bool,
+}
+
+#[derive(Deserialize)]
+struct ExportTeam {
+    #[serde(alias = "displayName")]
+    slug: String,
+    #[serde(default)]
+    members: Vec<ExportMember>,
+}
+
+#[derive(Deserialize)]
+struct ExportFile {
+    teams: Vec<ExportTeam>,
+    /// Details of members the teams only list by login.
+    #[serde(default)]
+    users: Vec<ExportMember>,
+}
+
+/// A member of an organization as read from an export, with an empty name or email when
+/// the export has none.
+#[derive(Clone, Debug, Default)]
+pub struct ExportUser {
+    pub login: String,
+    pub name: String,
+    pub email: String,
+}
+
+/// Teams and their members read from a file rather than the GitHub API.
+pub struct OrgExport {
+    /// Members of each team by login, keyed by team slug.
+    pub teams: HashMap<String, Vec<String>>,
+    pub users: HashMap<String, ExportUser>,
+}
+
+impl ExportMember {
+    fn into_user(self) -> ExportUser {
+        match self {
+            ExportMember::Login(login) => ExportUser {
+                login,
+                ..ExportUser::default()
+            },
+            ExportMember::User {
+                login,
+                name,
+                email,
+                emails,
+            } => {
+                let email = email.filter(|email| !email.is_empty()).or_else(|| {
+                    let primary = emails.iter().find(|email| email.primary);
+                    primary.or(emails.first()).map(|email| email.value.clone())
+                });
+                ExportUser {
+                    login,
+                    name: name.unwrap_or_default(),
+                    email: email.unwrap_or_default(),
+                }
+            }
+        }
+    }
+}
+
+/// Keeps what each source knows about a user, preferring values already known.
+fn merge_user(users: &mut HashMap<String, ExportUser>, user: ExportUser) {
+    let known = users
+        .entry(user.login.to_lowercase())
+        .or_insert_with(|| ExportUser {
+            login: user.login.clone(),
+            ..ExportUser::default()
+        });
+    if known.name.is_empty() {
+        known.name = user.name;
+    }
+    if known.email.is_empty() {
+        known.email = user.email;
+    }
+}
+
+impl OrgExport {
+    /// Parses a JSON export of an organization's teams:
+    ///
+    /// ```json
+    /// {
+    ///   "teams": [
+    ///     {"slug": "web", "members": [{"login": "alice", "name": "Alice", "email": "alice@example.com"}, "bob"]}
+    ///   ],
+    ///   "users": [{"login": "bob", "name": "Bob", "email": "bob@example.com"}]
+    /// }
+    /// ```
+    ///
+    /// Members are logins or user objects, and `users` optionally fills in the details of
+    /// those given by login. Teams and users may also use the field names of SCIM exports:
+    /// `displayName` for the team slug and the user's name, `userName` for the login, and
+    /// `emails`, of which the primary is used.
+    pub fn parse(content: &str) -> Result<Self, io::Error> {
+        let export: ExportFile = serde_json::from_str(content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid org export: {}", e),
+            )
+        })?;
+        let mut users = HashMap::new();
+        let mut teams = HashMap::new();
+        for team in export.teams {
+            let mut logins = Vec::new();
+            for member in team.members {
+                let user = member.into_user();
+                logins.push(user.login.clone());
+                merge_user(&mut users, user);
+            }
+            teams.insert(team.slug, logins);
+        }
+        for user in export.users {
+            merge_user(&mut users, user.into_user());
+        }
+        Ok(OrgExport { teams, users })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn user(&self, login: &str) -> ExportUser {
+        self.users
+            .get(&login.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| ExportUser {
+                login: login.to_string(),
+                ..ExportUser::default()
+            })
+    }
+
+    /// The team memberships of `org` among `codeowners`, as `init` fetches them from the
+    /// API: members of each codeowning team, individual users listed as owners, and
+    /// owners given as email addresses. Members with neither a name nor an email are left
+    /// out, and those without an email are matched by name, with a warning.
+    pub fn team_member_records(
+        &self,
+        org: &str,
+        codeowners: &HashSet<String>,
+        warnings: &Warnings,
+    ) -> Vec<TeamMemberRecord> {
+        let mut team_members: Vec<(String, Vec<String>)> = self
+            .teams
+            .iter()
+            .map(|(slug, members)| (format!("@{}/{}", org, slug), members.clone()))
+            .filter(|(codeowner, _)| codeowners.contains(codeowner))
+            .collect();
+        // Individual users listed as owners are a team of one
+        for owner in codeowners {
+            if let Some(login) = owner.strip_prefix('@').filter(|login| !login.contains('/')) {
+                team_members.push((owner.clone(), vec![login.to_string()]));
+            }
+        }
+        team_members.sort();
+
+        let mut records: Vec<TeamMemberRecord> = codeowners
+            .iter()
+            .filter(|owner| !owner.starts_with('@'))
+            .map(|email| TeamMemberRecord {
+                codeowner: email.clone(),
+                login: email.clone(),
+                author_email: email.clone(),
+                author_name: String::new(),
+            })
+            .collect();
+        for (codeowner, members) in team_members {
+            for member in members {
+                let user = self.user(&member);
+                if user.name.is_empty() && user.email.is_empty() {
+                    warnings.add(
+                        WarningCategory::MalformedEntry,
+                        format!(
+                            "{} in {} has no name or email in the export",
+                            member, codeowner
+                        ),
+                    );
+                    continue;
+                }
+                if user.email.is_empty() {
+                    warnings.add(
+                        WarningCategory::LowConfidenceMatch,
+                        format!(
+                            "{} in {} has no email in the export, matched by name '{}'",
+                            member, codeowner, user.name
+                        ),
+                    );
+                }
+                records.push(TeamMemberRecord {
+                    codeowner: codeowner.clone(),
+                    login: user.login,
+                    author_email: user.email,
+                    author_name: user.name,
+                });
+            }
+        }
+        records
+    }
+}
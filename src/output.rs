@@ -0,0 +1,211 @@
+use std::{
+    fmt,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use flate2::{write::GzEncoder, Compression};
+use serde::Serialize;
+use tempfile::NamedTempFile;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Tsv,
+    Ndjson,
+    Markdown,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(format!(
+                "Invalid format '{}', expected 'text', 'tsv', 'ndjson' or 'markdown'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Tsv => write!(f, "tsv"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
+            OutputFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+/// A record tagged with the period it was computed for.
+#[derive(Serialize)]
+pub struct PeriodRecord<'a, T> {
+    pub period: &'a str,
+    #[serde(flatten)]
+    pub record: &'a T,
+}
+
+/// A record tagged with the repository it was computed for, when several are analyzed.
+#[derive(Serialize)]
+pub struct RepoRecord<'a, T> {
+    pub repo: &'a str,
+    #[serde(flatten)]
+    pub record: &'a T,
+}
+
+/// Writes `record` as one line of JSON and flushes, so a reader such as `jq` sees each
+/// record as soon as it is produced.
+pub fn write_ndjson<W: Write + ?Sized, T: Serialize>(writer: &mut W, record: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Writes every record from `records` as it arrives, without collecting them first.
+/// Returns the number of records written.
+pub fn write_ndjson_stream<W, T, I>(writer: &mut W, records: I) -> io::Result<usize>
+where
+    W: Write + ?Sized,
+    T: Serialize,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    let mut count = 0;
+    for record in records {
+        write_ndjson(writer, &record?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Escapes `cell` for a Markdown table: a `|` would end the cell and `<email>` would be
+/// read as HTML.
+fn escape_markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', " ")
+}
+
+/// Writes a GitHub-flavored Markdown table with a column per entry of `header`.
+pub fn write_markdown_table<W: Write + ?Sized>(
+    writer: &mut W,
+    header: &[&str],
+    rows: &[Vec<String>],
+) -> io::Result<()> {
+    writeln!(writer, "| {} |", header.join(" | "))?;
+    writeln!(writer, "|{}", " --- |".repeat(header.len()))?;
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown_cell(cell)).collect();
+        writeln!(writer, "| {} |", cells.join(" | "))?;
+    }
+    Ok(())
+}
+
+/// Escapes `value` for a GitHub Actions workflow command, where properties such as the
+/// file also can't hold `:` or `,`.
+fn escape_workflow_command(value: &str, property: bool) -> String {
+    let escaped = value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+    if property {
+        escaped.replace(':', "%3A").replace(',', "%2C")
+    } else {
+        escaped
+    }
+}
+
+/// A GitHub Actions workflow command such as `::error file=CODEOWNERS,line=3::message`,
+/// which shows `message` as an annotation at that line of a pull request. `level` is
+/// `error`, `warning` or `notice`.
+pub fn github_annotation(
+    level: &str,
+    file: Option<&str>,
+    line: Option<usize>,
+    title: &str,
+    message: &str,
+) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_workflow_command(file, true)));
+    }
+    if let Some(line) = line {
+        properties.push(format!("line={}", line));
+    }
+    properties.push(format!("title={}", escape_workflow_command(title, true)));
+    format!(
+        "::{} {}::{}",
+        level,
+        properties.join(","),
+        escape_workflow_command(message, false)
+    )
+}
+
+enum OutputSink {
+    Plain(BufWriter<NamedTempFile>),
+    Gzip(GzEncoder<BufWriter<NamedTempFile>>),
+}
+
+/// A file written in full or not at all: output goes to a temporary file beside `path`,
+/// which only replaces `path` on [`AtomicOutput::commit`], so a run that fails or is
+/// killed part way leaves no truncated file behind. A `path` ending in `.gz` is gzip
+/// compressed.
+pub struct AtomicOutput {
+    path: PathBuf,
+    sink: OutputSink,
+}
+
+impl AtomicOutput {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let file = BufWriter::new(NamedTempFile::new_in(dir)?);
+        let sink = if path.extension().is_some_and(|extension| extension == "gz") {
+            OutputSink::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            OutputSink::Plain(file)
+        };
+        Ok(AtomicOutput {
+            path: path.to_path_buf(),
+            sink,
+        })
+    }
+
+    /// Finishes writing and moves the file into place.
+    pub fn commit(self) -> io::Result<()> {
+        let file = match self.sink {
+            OutputSink::Plain(file) => file,
+            OutputSink::Gzip(encoder) => encoder.finish()?,
+        };
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        file.as_file().sync_all()?;
+        file.persist(&self.path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
+
+impl Write for AtomicOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.sink {
+            OutputSink::Plain(file) => file.write(buf),
+            OutputSink::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            OutputSink::Plain(file) => file.flush(),
+            OutputSink::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -1,92 +1,348 @@
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     io::{self, Cursor},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
 };
 
-use crate::{read_file_at_commit, CommitInfo};
+use chrono::{NaiveDate, NaiveTime};
+use serde::Serialize;
 
-const CODEOWNERS_LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+use crate::{
+    commit::git_path_revisions, git_worktree_changes, read_file_at_commit, ChangeKind, CommitInfo,
+    CommitMessage, FileSize, GitLogOptions, NameNormalization, OwnerAliases, ProgressSink,
+};
+
+pub(crate) const CODEOWNERS_LOCATIONS: [&str; 3] =
+    [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
 
 pub fn get_codeowners_at_commit(
     commit_id: &str,
     cwd: &PathBuf,
 ) -> Result<Option<String>, io::Error> {
+    Ok(find_codeowners_at_commit(commit_id, cwd)?.map(|(_, content)| content))
+}
+
+/// The path of the CODEOWNERS file in effect at `commit_id`, along with its content.
+pub fn find_codeowners_at_commit(
+    commit_id: &str,
+    cwd: &PathBuf,
+) -> Result<Option<(&'static str, String)>, io::Error> {
     for location in CODEOWNERS_LOCATIONS.iter() {
         if let Some(content) = read_file_at_commit(commit_id, location, cwd)? {
-            return Ok(Some(content));
+            tracing::debug!(commit = commit_id, location, "Read CODEOWNERS");
+            return Ok(Some((location, content)));
         }
     }
+    tracing::debug!(commit = commit_id, "No CODEOWNERS at commit");
     Ok(None)
 }
 
+#[derive(Serialize)]
 pub struct CommitInfoWithCodeowner {
     pub id: String,
     pub author_name: String,
     pub author_email: String,
     pub timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<CommitMessage>,
     pub file_changes: Vec<FileChangeWithCodeowner>,
 }
 
+impl CommitInfoWithCodeowner {
+    /// `(name, email)` of the author followed by any co-authors credited in the message.
+    pub fn contributors(&self) -> Vec<(String, String)> {
+        let mut contributors = vec![(self.author_name.clone(), self.author_email.clone())];
+        if let Some(message) = &self.message {
+            for co_author in message.co_authors() {
+                if !contributors.contains(&co_author) {
+                    contributors.push(co_author);
+                }
+            }
+        }
+        contributors
+    }
+}
+
+#[derive(Serialize)]
 pub struct FileChangeWithCodeowner {
     pub insertions: i32,
     pub deletions: i32,
     pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub kind: ChangeKind,
+    /// Only set once the commits are classified by [`crate::classify_languages`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub binary: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_before: Option<FileSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_after: Option<FileSize>,
     pub codeowners: Option<Vec<String>>,
     pub author_is_codeowner: Option<bool>,
 }
 
+#[derive(Clone)]
 pub struct AuthorCodeownerMemberships {
     pub author_email: Option<String>,
     pub author_name: Option<String>,
     pub codeowner: String,
+    /// Other emails the same person commits under, such as a personal address.
+    pub additional_emails: Vec<String>,
+    /// Other names the same person commits under.
+    pub aliases: Vec<String>,
+    /// The first day the person was in the codeowner, when they haven't always been.
+    pub valid_from: Option<NaiveDate>,
+    /// The last day the person was in the codeowner, when they've left it.
+    pub valid_to: Option<NaiveDate>,
+}
+
+impl AuthorCodeownerMemberships {
+    pub fn emails(&self) -> impl Iterator<Item = &String> {
+        self.author_email.iter().chain(&self.additional_emails)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.author_name.iter().chain(&self.aliases)
+    }
+
+    /// Whether the person was in the codeowner on `date`.
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        self.valid_from.is_none_or(|from| date >= from) && self.valid_to.is_none_or(|to| date <= to)
+    }
+
+    fn term(&self) -> CodeownerTerm {
+        let start_of = |date: NaiveDate| date.and_time(NaiveTime::MIN).and_utc().timestamp();
+        CodeownerTerm {
+            codeowner: self.codeowner.to_lowercase(),
+            from: self.valid_from.map(start_of),
+            until: self.valid_to.and_then(|date| date.succ_opt()).map(start_of),
+        }
+    }
+}
+
+/// A codeowner someone is a member of, from `from` until just before `until`, as Unix
+/// timestamps.
+struct CodeownerTerm {
+    codeowner: String,
+    from: Option<i64>,
+    until: Option<i64>,
+}
+
+impl CodeownerTerm {
+    fn includes(&self, timestamp: i64) -> bool {
+        self.from.is_none_or(|from| timestamp >= from)
+            && self.until.is_none_or(|until| timestamp < until)
+    }
 }
 
-struct AuthorMembership {
-    email_to_codeowner: HashMap<String, HashSet<String>>,
-    name_to_codeowner: HashMap<String, HashSet<String>>,
+/// Index from author emails (case-insensitive) and normalized names to the codeowners they
+/// belong to, and when.
+pub struct AuthorMembership {
+    email_to_codeowner: HashMap<String, Vec<CodeownerTerm>>,
+    name_to_codeowner: HashMap<String, Vec<CodeownerTerm>>,
+    names: NameNormalization,
 }
 
 impl AuthorMembership {
-    fn new(memberships: &[AuthorCodeownerMemberships]) -> Self {
+    pub fn new(memberships: &[AuthorCodeownerMemberships]) -> Self {
+        Self::with_name_normalization(memberships, &NameNormalization::default())
+    }
+
+    pub fn with_name_normalization(
+        memberships: &[AuthorCodeownerMemberships],
+        names: &NameNormalization,
+    ) -> Self {
         let mut email_to_codeowner = HashMap::new();
         let mut name_to_codeowner = HashMap::new();
 
         for membership in memberships {
-            if let Some(email) = &membership.author_email {
+            for email in membership.emails() {
                 email_to_codeowner
                     .entry(email.to_lowercase())
-                    .or_insert_with(HashSet::new)
-                    .insert(membership.codeowner.to_lowercase());
+                    .or_insert_with(Vec::new)
+                    .push(membership.term());
             }
-            if let Some(name) = &membership.author_name {
+            for name in membership.names() {
                 name_to_codeowner
-                    .entry(name.to_lowercase())
-                    .or_insert_with(HashSet::new)
-                    .insert(membership.codeowner.to_lowercase());
+                    .entry(names.normalize(name))
+                    .or_insert_with(Vec::new)
+                    .push(membership.term());
             }
         }
 
         Self {
             email_to_codeowner,
             name_to_codeowner,
+            names: names.clone(),
         }
     }
 
-    fn get_codeowners_for_author(&self, author_name: &str, author_email: &str) -> HashSet<String> {
-        let mut codeowners = HashSet::new();
-        if let Some(email_codeowners) = self.email_to_codeowner.get(&author_email.to_lowercase()) {
-            codeowners.extend(email_codeowners.iter().cloned());
+    /// Every codeowner the author has been a member of, at any time.
+    pub fn get_codeowners_for_author(
+        &self,
+        author_name: &str,
+        author_email: &str,
+    ) -> HashSet<String> {
+        self.get_codeowners_for_author_at(author_name, author_email, None)
+    }
+
+    /// The codeowners the author was a member of at `timestamp`, or at any time without
+    /// one.
+    pub fn get_codeowners_for_author_at(
+        &self,
+        author_name: &str,
+        author_email: &str,
+        timestamp: Option<i64>,
+    ) -> HashSet<String> {
+        let email_terms = self.email_to_codeowner.get(&author_email.to_lowercase());
+        let name_terms = self
+            .name_to_codeowner
+            .get(&self.names.normalize(author_name));
+        email_terms
+            .into_iter()
+            .chain(name_terms)
+            .flatten()
+            .filter(|term| timestamp.is_none_or(|timestamp| term.includes(timestamp)))
+            .map(|term| term.codeowner.clone())
+            .collect()
+    }
+
+    pub fn is_codeowner(&self, author_name: &str, author_email: &str, codeowner: &str) -> bool {
+        self.is_codeowner_at(author_name, author_email, codeowner, None)
+    }
+
+    /// Whether the author was in `codeowner` at `timestamp`, or at any time without one.
+    pub fn is_codeowner_at(
+        &self,
+        author_name: &str,
+        author_email: &str,
+        codeowner: &str,
+        timestamp: Option<i64>,
+    ) -> bool {
+        self.get_codeowners_for_author_at(author_name, author_email, timestamp)
+            .contains(&codeowner.to_lowercase())
+    }
+}
+
+/// A branch, possibly of another repository, that CODEOWNERS is maintained on instead of
+/// alongside the code.
+#[derive(Clone, Debug)]
+pub struct CodeownersSource {
+    /// The repository to read from; the analyzed one when unset.
+    pub repo: Option<PathBuf>,
+    pub rev: String,
+}
+
+/// Which CODEOWNERS file each commit is owned according to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CodeownersMode {
+    /// The file as it was when the commit was made.
+    #[default]
+    Historical,
+    /// Today's file for every commit, for history from before CODEOWNERS was introduced
+    /// or reorganized.
+    Current,
+    /// The file at a revision for every commit.
+    At(String),
+}
+
+impl FromStr for CodeownersMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rev) = s.strip_prefix("at:") {
+            if rev.is_empty() {
+                return Err(
+                    "Invalid CODEOWNERS mode 'at:', expected a revision after 'at:'".into(),
+                );
+            }
+            return Ok(CodeownersMode::At(rev.to_string()));
         }
-        if let Some(name_codeowners) = self.name_to_codeowner.get(&author_name.to_lowercase()) {
-            codeowners.extend(name_codeowners.iter().cloned());
+        match s.to_lowercase().as_str() {
+            "historical" => Ok(CodeownersMode::Historical),
+            "current" => Ok(CodeownersMode::Current),
+            _ => Err(format!(
+                "Invalid CODEOWNERS mode '{}', expected 'historical', 'current' or 'at:<rev>'",
+                s
+            )),
         }
-        codeowners
     }
+}
 
-    fn is_codeowner(&self, author_name: &str, author_email: &str, codeowner: &str) -> bool {
-        self.get_codeowners_for_author(author_name, author_email)
-            .contains(&codeowner.to_lowercase())
+impl fmt::Display for CodeownersMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeownersMode::Historical => write!(f, "historical"),
+            CodeownersMode::Current => write!(f, "current"),
+            CodeownersMode::At(rev) => write!(f, "at:{}", rev),
+        }
+    }
+}
+
+/// The CODEOWNERS content `options` apply to every commit, if any: the fixed content, or
+/// the file `options.codeowners_mode` names, read from the CODEOWNERS source when there is
+/// one. `current` is its tip, or HEAD of `cwd`.
+fn fixed_codeowners(options: &GitLogOptions, cwd: &Path) -> Result<Option<String>, io::Error> {
+    if let Some(content) = &options.fixed_codeowners {
+        return Ok(Some(content.clone()));
+    }
+    let source = options.codeowners_source.as_ref();
+    let rev = match &options.codeowners_mode {
+        CodeownersMode::Historical => return Ok(None),
+        CodeownersMode::Current => source.map_or("HEAD", |source| source.rev.as_str()),
+        CodeownersMode::At(rev) => rev.as_str(),
+    };
+    let repo = source
+        .and_then(|source| source.repo.clone())
+        .unwrap_or_else(|| cwd.to_path_buf());
+    match get_codeowners_at_commit(rev, &repo)? {
+        Some(content) => Ok(Some(content)),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No CODEOWNERS file at {}", rev),
+        )),
+    }
+}
+
+/// The revisions of an external CODEOWNERS source, to find which one was in effect when
+/// each analyzed commit landed.
+struct CodeownersTimeline {
+    repo: PathBuf,
+    /// `(committer timestamp, commit id)` of each change to CODEOWNERS, oldest first.
+    revisions: Vec<(i64, String)>,
+}
+
+impl CodeownersTimeline {
+    fn load(source: &CodeownersSource, cwd: &Path) -> Result<Self, io::Error> {
+        let repo = source.repo.clone().unwrap_or_else(|| cwd.to_path_buf());
+        let revisions = git_path_revisions(&source.rev, &CODEOWNERS_LOCATIONS, &repo)?;
+        tracing::info!(
+            rev = %source.rev,
+            revisions = revisions.len(),
+            "Loaded CODEOWNERS timeline"
+        );
+        Ok(CodeownersTimeline { repo, revisions })
+    }
+
+    /// The last revision at or before `timestamp`, if CODEOWNERS existed by then.
+    fn revision_at(&self, timestamp: i64) -> Option<&str> {
+        let index = self
+            .revisions
+            .partition_point(|(revision_timestamp, _)| *revision_timestamp <= timestamp);
+        index
+            .checked_sub(1)
+            .map(|index| self.revisions[index].1.as_str())
+    }
+
+    /// The revision in effect when `commit` was committed, so a commit authored before a
+    /// CODEOWNERS change but landed after it, as a rebased pull request, gets the new rules.
+    fn revision_for(&self, commit: &CommitInfo) -> Option<&str> {
+        self.revision_at(commit.committer_date.timestamp())
     }
 }
 
@@ -97,14 +353,42 @@ where
     commit_iter: I,
     cwd: PathBuf,
     memberships: Option<AuthorMembership>,
-    cached_owners: Option<codeowners::Owners>,
+    renamed_owners: OwnerAliases,
+    timeline: Option<CodeownersTimeline>,
+    /// Whether `cached_owners` applies to every commit, from
+    /// [`GitLogOptions::fixed_codeowners`].
+    fixed_owners: bool,
+    /// The revision `cached_owners` was read from, when using a timeline.
+    cached_revision: Option<String>,
+    cached_owners: Option<Arc<codeowners::Owners>>,
+    parsed: ParsedCodeowners,
+    progress: Option<Arc<dyn ProgressSink>>,
+    processed: usize,
+}
+
+/// Parsed CODEOWNERS files by the SHA-256 of their content, so a history that goes back
+/// and forth between versions parses each only once.
+#[derive(Default)]
+struct ParsedCodeowners {
+    owners: HashMap<Vec<u8>, Arc<codeowners::Owners>>,
+}
+
+impl ParsedCodeowners {
+    fn parse(&mut self, content: &str) -> Arc<codeowners::Owners> {
+        let digest = ring::digest::digest(&ring::digest::SHA256, content.as_bytes());
+        self.owners
+            .entry(digest.as_ref().to_vec())
+            .or_insert_with(|| Arc::new(codeowners::from_reader(Cursor::new(content))))
+            .clone()
+    }
 }
 
 fn codeowners_changed(commit: &CommitInfo) -> bool {
     commit
         .file_changes
         .iter()
-        .any(|change| CODEOWNERS_LOCATIONS.contains(&change.path.as_str()))
+        .flat_map(|change| std::iter::once(&change.path).chain(&change.old_path))
+        .any(|path| CODEOWNERS_LOCATIONS.contains(&path.as_str()))
 }
 
 impl<I> Iterator for CommitWithCodeownersIterator<I>
@@ -114,60 +398,119 @@ where
     type Item = Result<CommitInfoWithCodeowner, io::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let commit = match self.commit_iter.next()? {
-            Ok(commit) => commit,
-            Err(e) => return Some(Err(e)),
+        let commit = match self.commit_iter.next() {
+            Some(Ok(commit)) => commit,
+            Some(Err(e)) => return Some(Err(e)),
+            None => {
+                if let Some(progress) = &self.progress {
+                    progress.commits_finished(self.processed);
+                }
+                return None;
+            }
         };
+        self.processed += 1;
+        if let Some(progress) = &self.progress {
+            progress.commits_processed(self.processed);
+        }
 
-        if self.cached_owners.is_none() || codeowners_changed(&commit) {
-            match get_owners_at_commit(&commit.id, &self.cwd) {
-                Ok(owners) => self.cached_owners = Some(owners),
+        if let Some(timeline) = &self.timeline {
+            let revision = timeline.revision_for(&commit);
+            if self.cached_owners.is_none() || self.cached_revision.as_deref() != revision {
+                let content = match revision {
+                    Some(revision) => match get_codeowners_at_commit(revision, &timeline.repo) {
+                        Ok(content) => content,
+                        Err(e) => return Some(Err(e)),
+                    },
+                    None => None,
+                };
+                self.cached_owners = Some(self.parsed.parse(&content.unwrap_or_default()));
+                self.cached_revision = revision.map(str::to_string);
+            }
+        } else if !self.fixed_owners
+            && (self.cached_owners.is_none() || codeowners_changed(&commit))
+        {
+            match get_codeowners_at_commit(&commit.id, &self.cwd) {
+                Ok(content) => {
+                    self.cached_owners = Some(self.parsed.parse(&content.unwrap_or_default()))
+                }
                 Err(e) => return Some(Err(e)),
             }
         }
 
         let owners = self.cached_owners.as_ref().unwrap();
 
-        Some(Ok(CommitInfoWithCodeowner {
-            id: commit.id,
-            author_name: commit.author_name.clone(),
-            author_email: commit.author_email.clone(),
-            timestamp: commit.timestamp,
-            file_changes: commit
-                .file_changes
-                .into_iter()
-                .map(|change| {
-                    let file_owners = owners.of(&change.path).map(|owners| {
-                        owners
-                            .iter()
-                            .map(|o| o.to_string())
-                            .collect::<Vec<String>>()
-                    });
-
-                    let author_name = &commit.author_name;
-                    let author_email = &commit.author_email;
-
-                    FileChangeWithCodeowner {
-                        insertions: change.insertions,
-                        deletions: change.deletions,
-                        codeowners: file_owners.clone(),
-                        author_is_codeowner: self.memberships.as_ref().map(|memberships| {
-                            is_author_codeowner(
-                                memberships,
-                                &file_owners.clone().unwrap_or_default(),
-                                author_name,
-                                author_email,
-                            )
-                        }),
-                        path: change.path,
+        Some(Ok(with_codeowners(
+            commit,
+            owners,
+            self.memberships.as_ref(),
+            &self.renamed_owners,
+        )))
+    }
+}
+
+fn with_codeowners(
+    commit: CommitInfo,
+    owners: &codeowners::Owners,
+    memberships: Option<&AuthorMembership>,
+    renamed_owners: &OwnerAliases,
+) -> CommitInfoWithCodeowner {
+    CommitInfoWithCodeowner {
+        id: commit.id,
+        author_name: commit.author_name.clone(),
+        author_email: commit.author_email.clone(),
+        timestamp: commit.author_date.timestamp(),
+        message: commit.message,
+        file_changes: commit
+            .file_changes
+            .into_iter()
+            .map(|change| {
+                // Renamed owners take their current name before membership is checked, as
+                // the memberships only know teams by their current name
+                let file_owners = owners.of(&change.path).map(|owners| {
+                    let mut file_owners: Vec<String> = Vec::with_capacity(owners.len());
+                    for owner in owners {
+                        let owner = renamed_owners.resolve(&owner.to_string()).to_string();
+                        if !file_owners.contains(&owner) {
+                            file_owners.push(owner);
+                        }
                     }
-                })
-                .collect(),
-        }))
+                    file_owners
+                });
+
+                let author_name = &commit.author_name;
+                let author_email = &commit.author_email;
+                let timestamp = commit.author_date.timestamp();
+
+                FileChangeWithCodeowner {
+                    insertions: change.insertions,
+                    deletions: change.deletions,
+                    binary: change.binary,
+                    size_before: change.size_before,
+                    size_after: change.size_after,
+                    codeowners: file_owners.clone(),
+                    author_is_codeowner: memberships.map(|memberships| {
+                        is_author_codeowner(
+                            memberships,
+                            &file_owners.clone().unwrap_or_default(),
+                            author_name,
+                            author_email,
+                            Some(timestamp),
+                        )
+                    }),
+                    path: change.path,
+                    old_path: change.old_path,
+                    kind: change.kind,
+                    language: change.language,
+                }
+            })
+            .collect(),
     }
 }
 
-fn get_owners_at_commit(commit_id: &str, cwd: &PathBuf) -> Result<codeowners::Owners, io::Error> {
+pub fn get_owners_at_commit(
+    commit_id: &str,
+    cwd: &PathBuf,
+) -> Result<codeowners::Owners, io::Error> {
     let codeowners_str = get_codeowners_at_commit(commit_id, cwd)?;
 
     let reader = match codeowners_str {
@@ -178,70 +521,249 @@ fn get_owners_at_commit(commit_id: &str, cwd: &PathBuf) -> Result<codeowners::Ow
     Ok(codeowners::from_reader(reader))
 }
 
-fn is_author_codeowner(
+/// Whether the author was in one of `owners` at `timestamp`, or at any time without one.
+pub(crate) fn is_author_codeowner(
     memberships: &AuthorMembership,
     owners: &[String],
     commit_author_name: &str,
     commit_author_email: &str,
+    timestamp: Option<i64>,
 ) -> bool {
-    owners
-        .iter()
-        .any(|owner| memberships.is_codeowner(commit_author_name, commit_author_email, owner))
+    owners.iter().any(|owner| {
+        // Owners listed by email are their own member
+        owner.eq_ignore_ascii_case(commit_author_email)
+            || memberships.is_codeowner_at(
+                commit_author_name,
+                commit_author_email,
+                owner,
+                timestamp,
+            )
+    })
 }
 
 pub fn git_log_commits_with_codeowners(
     since: &str,
     until: &str,
     cwd: &PathBuf,
+    options: &GitLogOptions,
     memberships: Option<Vec<AuthorCodeownerMemberships>>,
 ) -> Result<impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>, io::Error> {
-    let commit_iter = crate::git_log_commits(since, until, cwd)?;
+    let commit_iter = crate::git_log_commits(since, until, cwd, options)?;
 
-    let author_membership = memberships.map(|m| AuthorMembership::new(&m));
+    let author_membership = memberships
+        .map(|m| AuthorMembership::with_name_normalization(&m, &options.name_normalization));
+
+    let fixed_owners = fixed_codeowners(options, cwd)?
+        .map(|content| Arc::new(codeowners::from_reader(Cursor::new(content))));
+    let timeline = match (&fixed_owners, &options.codeowners_source) {
+        (None, Some(source)) => Some(CodeownersTimeline::load(source, cwd)?),
+        _ => None,
+    };
 
     Ok(CommitWithCodeownersIterator {
         commit_iter,
         memberships: author_membership,
+        renamed_owners: options.renamed_owners.clone(),
+        timeline,
+        fixed_owners: fixed_owners.is_some(),
+        cached_revision: None,
         cwd: cwd.clone(),
-        cached_owners: None,
+        cached_owners: fixed_owners,
+        parsed: ParsedCodeowners::default(),
+        progress: options.progress.clone(),
+        processed: 0,
     })
 }
 
+/// The CODEOWNERS file in the index when `staged_only` is set and in the working tree
+/// otherwise.
+pub fn get_worktree_codeowners(
+    cwd: &PathBuf,
+    staged_only: bool,
+) -> Result<Option<String>, io::Error> {
+    Ok(find_worktree_codeowners(cwd, staged_only)?.map(|(_, content)| content))
+}
+
+/// The path of the CODEOWNERS file in the index or working tree, as with
+/// [`get_worktree_codeowners`], along with its content.
+pub fn find_worktree_codeowners(
+    cwd: &PathBuf,
+    staged_only: bool,
+) -> Result<Option<(&'static str, String)>, io::Error> {
+    for location in CODEOWNERS_LOCATIONS.iter() {
+        let content = if staged_only {
+            // An empty revision reads the file from the index
+            read_file_at_commit("", location, cwd)?
+        } else {
+            match std::fs::read_to_string(cwd.join(location)) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e),
+            }
+        };
+        if let Some(content) = content {
+            return Ok(Some((location, content)));
+        }
+    }
+    Ok(None)
+}
+
+/// Uncommitted changes as a single pseudo-commit (see [`crate::git_worktree_changes`]),
+/// owned according to the CODEOWNERS file in the index when `staged_only` is set and in
+/// the working tree otherwise.
+pub fn worktree_commit_with_codeowners(
+    cwd: &PathBuf,
+    staged_only: bool,
+    memberships: Option<Vec<AuthorCodeownerMemberships>>,
+    names: &NameNormalization,
+) -> Result<CommitInfoWithCodeowner, io::Error> {
+    let commit = git_worktree_changes(cwd, staged_only)?;
+    let codeowners_str = get_worktree_codeowners(cwd, staged_only)?;
+    let owners = codeowners::from_reader(Cursor::new(codeowners_str.unwrap_or_default()));
+
+    let author_membership =
+        memberships.map(|m| AuthorMembership::with_name_normalization(&m, names));
+    // The working tree's CODEOWNERS already uses current owner names
+    Ok(with_codeowners(
+        commit,
+        &owners,
+        author_membership.as_ref(),
+        &OwnerAliases::default(),
+    ))
+}
+
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 
+const MEMBERSHIP_COLUMNS: [&str; 7] = [
+    "author_email",
+    "author_name",
+    "codeowner",
+    "additional_emails",
+    "aliases",
+    "valid_from",
+    "valid_to",
+];
+/// Columns of the formats from before memberships were dated, and before additional
+/// emails and aliases, still accepted when reading.
+const UNDATED_MEMBERSHIP_COLUMNS: usize = 5;
+const LEGACY_MEMBERSHIP_COLUMNS: usize = 3;
+const LIST_SEPARATOR: &str = ",";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn parse_list(field: &str) -> Vec<String> {
+    field
+        .split(LIST_SEPARATOR)
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn parse_date(field: &str) -> io::Result<Option<NaiveDate>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(field, DATE_FORMAT)
+        .map(Some)
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid date '{}', expected YYYY-MM-DD: {}", field, e),
+            )
+        })
+}
+
+fn format_date(date: Option<NaiveDate>) -> String {
+    date.map(|date| date.format(DATE_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+/// Writes memberships in the format read by [`read_memberships_from_tsv`]. The
+/// `valid_from` and `valid_to` columns are only written when a membership is dated.
 pub fn write_memberships_to_tsv(
     memberships: &[AuthorCodeownerMemberships],
     path: &PathBuf,
 ) -> io::Result<()> {
+    let dated = memberships
+        .iter()
+        .any(|membership| membership.valid_from.is_some() || membership.valid_to.is_some());
+    let columns = if dated {
+        MEMBERSHIP_COLUMNS.len()
+    } else {
+        UNDATED_MEMBERSHIP_COLUMNS
+    };
     let mut file = File::create(path)?;
-    writeln!(file, "author_email\tauthor_name\tcodeowner")?;
+    writeln!(file, "{}", MEMBERSHIP_COLUMNS[..columns].join("\t"))?;
     for membership in memberships {
-        writeln!(
+        write!(
             file,
-            "{}\t{}\t{}",
+            "{}\t{}\t{}\t{}\t{}",
             membership.author_email.as_deref().unwrap_or(""),
             membership.author_name.as_deref().unwrap_or(""),
-            membership.codeowner
+            membership.codeowner,
+            membership.additional_emails.join(LIST_SEPARATOR),
+            membership.aliases.join(LIST_SEPARATOR)
         )?;
+        if dated {
+            write!(
+                file,
+                "\t{}\t{}",
+                format_date(membership.valid_from),
+                format_date(membership.valid_to)
+            )?;
+        }
+        writeln!(file)?;
     }
     Ok(())
 }
 
+/// Reads memberships written by [`write_memberships_to_tsv`]. Files without the
+/// `valid_from` and `valid_to` columns, and in the original three-column format, are read
+/// as well.
+///
+/// `path` may also be a directory of dated snapshots, such as `codeowners-2023-01.tsv` and
+/// `codeowners-2023-07-15.tsv`: each snapshot's memberships apply from its date until the
+/// day before the next one's, and the first and last snapshots extend to the beginning
+/// and end of history. Dates a membership sets itself take precedence.
 pub fn read_memberships_from_tsv(path: &PathBuf) -> io::Result<Vec<AuthorCodeownerMemberships>> {
+    if path.is_dir() {
+        return read_membership_snapshots(path);
+    }
     let file = File::open(path)?;
     let reader = BufReader::new(file);
     let mut memberships = Vec::new();
 
     let mut lines = reader.lines();
 
-    // Skip the first line
-    lines.next();
+    // The header determines which format the rest of the file is in
+    let columns = match lines.next().transpose()? {
+        Some(header) => header.split('\t').count(),
+        None => return Ok(memberships),
+    };
+    if ![
+        LEGACY_MEMBERSHIP_COLUMNS,
+        UNDATED_MEMBERSHIP_COLUMNS,
+        MEMBERSHIP_COLUMNS.len(),
+    ]
+    .contains(&columns)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Invalid header: expected {}, {} or {} columns, found {}",
+                LEGACY_MEMBERSHIP_COLUMNS,
+                UNDATED_MEMBERSHIP_COLUMNS,
+                MEMBERSHIP_COLUMNS.len(),
+                columns
+            ),
+        ));
+    }
 
     for line in lines {
         let line = line?;
         let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() != 3 {
+        if parts.len() != columns {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Invalid line: {}", line),
@@ -259,37 +781,274 @@ pub fn read_memberships_from_tsv(path: &PathBuf) -> io::Result<Vec<AuthorCodeown
                 Some(parts[1].to_string())
             },
             codeowner: parts[2].to_string(),
+            additional_emails: parts
+                .get(3)
+                .map(|field| parse_list(field))
+                .unwrap_or_default(),
+            aliases: parts
+                .get(4)
+                .map(|field| parse_list(field))
+                .unwrap_or_default(),
+            valid_from: parse_date(parts.get(5).unwrap_or(&""))?,
+            valid_to: parse_date(parts.get(6).unwrap_or(&""))?,
         });
     }
 
     Ok(memberships)
 }
 
+/// The date a snapshot's file name ends in, as `-YYYY-MM-DD.tsv` or `-YYYY-MM.tsv` for the
+/// first of the month.
+fn snapshot_date(path: &Path) -> Option<NaiveDate> {
+    if path.extension()? != "tsv" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let parse = |date: &str| NaiveDate::parse_from_str(date, DATE_FORMAT).ok();
+    let day = stem
+        .len()
+        .checked_sub(10)
+        .and_then(|start| stem.get(start..))
+        .and_then(parse);
+    day.or_else(|| {
+        let month = stem.get(stem.len().checked_sub(7)?..)?;
+        parse(&format!("{}-01", month))
+    })
+}
+
+fn read_membership_snapshots(dir: &Path) -> io::Result<Vec<AuthorCodeownerMemberships>> {
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(date) = snapshot_date(&path) {
+            snapshots.push((date, path));
+        }
+    }
+    if snapshots.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No membership snapshots named like codeowners-YYYY-MM.tsv in {}",
+                dir.display()
+            ),
+        ));
+    }
+    snapshots.sort();
+
+    let mut memberships = Vec::new();
+    for (index, (date, path)) in snapshots.iter().enumerate() {
+        let valid_from = (index > 0).then_some(*date);
+        let valid_to = snapshots
+            .get(index + 1)
+            .and_then(|(next, _)| next.pred_opt());
+        for mut membership in read_memberships_from_tsv(path)? {
+            membership.valid_from = membership.valid_from.or(valid_from);
+            membership.valid_to = membership.valid_to.or(valid_to);
+            memberships.push(membership);
+        }
+    }
+    Ok(memberships)
+}
+
 pub fn get_all_codeowners(cwd: &PathBuf) -> Result<HashSet<String>, io::Error> {
     let mut all_codeowners = HashSet::new();
 
     for location in CODEOWNERS_LOCATIONS.iter() {
         let versions = crate::git_file_versions(location, cwd)?;
 
-        for version in versions {
-            if let Ok(content) = version {
-                for line in content.lines() {
-                    let line = line.trim();
-                    if line.is_empty() || line.starts_with('#') {
-                        continue;
-                    }
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() > 1 {
-                        for part in &parts[1..] {
-                            if part.starts_with('@') {
-                                all_codeowners.insert(part.to_string());
-                            }
-                        }
-                    }
+        for content in versions.flatten() {
+            for line in content.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
                 }
+                // Owners are teams (`@org/team`), users (`@user`) or email addresses
+                let owners = line
+                    .split_whitespace()
+                    .skip(1)
+                    .take_while(|part| !part.starts_with('#'))
+                    .filter(|part| part.contains('@'));
+                all_codeowners.extend(owners.map(|owner| owner.to_string()));
             }
         }
     }
 
     Ok(all_codeowners)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_tsv(content: &str) -> io::Result<Vec<AuthorCodeownerMemberships>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("codeowners.tsv");
+        std::fs::write(&path, content)?;
+        read_memberships_from_tsv(&path)
+    }
+
+    #[test]
+    fn three_column_memberships_are_read() {
+        let memberships = read_tsv(
+            "author_email\tauthor_name\tcodeowner\n\
+             ada@example.com\tAda\t@org/a\n\
+             \tGrace\t@org/b\n",
+        )
+        .unwrap();
+        assert_eq!(memberships.len(), 2);
+        assert_eq!(
+            memberships[0].author_email.as_deref(),
+            Some("ada@example.com")
+        );
+        assert_eq!(memberships[0].codeowner, "@org/a");
+        assert!(memberships[0].additional_emails.is_empty());
+        assert_eq!(memberships[1].author_email, None);
+        assert_eq!(memberships[1].author_name.as_deref(), Some("Grace"));
+    }
+
+    #[test]
+    fn five_column_memberships_list_emails_and_aliases() {
+        let memberships = read_tsv(
+            "author_email\tauthor_name\tcodeowner\tadditional_emails\taliases\n\
+             ada@example.com\tAda\t@org/a\tada@home.example, ada@old.example\tA. Lovelace\n\
+             grace@example.com\tGrace\t@org/b\t\t\n",
+        )
+        .unwrap();
+        assert_eq!(
+            memberships[0].emails().collect::<Vec<_>>(),
+            ["ada@example.com", "ada@home.example", "ada@old.example"]
+        );
+        assert_eq!(
+            memberships[0].names().collect::<Vec<_>>(),
+            ["Ada", "A. Lovelace"]
+        );
+        assert!(memberships[1].additional_emails.is_empty());
+        assert!(memberships[1].aliases.is_empty());
+    }
+
+    #[test]
+    fn lines_must_match_the_header() {
+        for content in [
+            "author_email\tauthor_name\tcodeowner\tadditional_emails\taliases\n\
+             ada@example.com\tAda\t@org/a\n",
+            "author_email\tauthor_name\n",
+        ] {
+            let Err(error) = read_tsv(content) else {
+                panic!("expected {:?} to be rejected", content);
+            };
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn seven_column_memberships_are_dated() {
+        let memberships = read_tsv(
+            "author_email\tauthor_name\tcodeowner\tadditional_emails\taliases\tvalid_from\tvalid_to\n\
+             ada@example.com\tAda\t@org/a\t\t\t2023-01-01\t2023-06-30\n\
+             grace@example.com\tGrace\t@org/b\t\t\t\t\n",
+        )
+        .unwrap();
+        assert_eq!(memberships[0].valid_from, Some(date("2023-01-01")));
+        assert_eq!(memberships[0].valid_to, Some(date("2023-06-30")));
+        assert!(!memberships[0].is_valid_on(date("2022-12-31")));
+        assert!(memberships[0].is_valid_on(date("2023-01-01")));
+        assert!(memberships[0].is_valid_on(date("2023-06-30")));
+        assert!(!memberships[0].is_valid_on(date("2023-07-01")));
+        assert!(memberships[1].is_valid_on(date("1970-01-01")));
+
+        let Err(error) = read_tsv(
+            "author_email\tauthor_name\tcodeowner\tadditional_emails\taliases\tvalid_from\tvalid_to\n\
+             ada@example.com\tAda\t@org/a\t\t\t2023-13-01\t\n",
+        ) else {
+            panic!("expected the invalid date to be rejected");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn memberships_round_trip_through_tsv() {
+        let membership = AuthorCodeownerMemberships {
+            author_email: Some("ada@example.com".to_string()),
+            author_name: None,
+            codeowner: "@org/a".to_string(),
+            additional_emails: vec!["ada@home.example".to_string()],
+            aliases: vec!["Ada".to_string(), "A. Lovelace".to_string()],
+            valid_from: None,
+            valid_to: None,
+        };
+        let dated = AuthorCodeownerMemberships {
+            valid_from: Some(date("2023-01-01")),
+            ..membership.clone()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("codeowners.tsv");
+        for (memberships, columns) in [(vec![membership.clone()], 5), (vec![membership, dated], 7)]
+        {
+            write_memberships_to_tsv(&memberships, &path).unwrap();
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(content.lines().next().unwrap().split('\t').count(), columns);
+            let read = read_memberships_from_tsv(&path).unwrap();
+            assert_eq!(read.len(), memberships.len());
+            for (read, written) in read.iter().zip(&memberships) {
+                assert_eq!(read.author_email, written.author_email);
+                assert_eq!(read.author_name, written.author_name);
+                assert_eq!(read.codeowner, written.codeowner);
+                assert_eq!(read.additional_emails, written.additional_emails);
+                assert_eq!(read.aliases, written.aliases);
+                assert_eq!(read.valid_from, written.valid_from);
+                assert_eq!(read.valid_to, written.valid_to);
+            }
+        }
+    }
+
+    #[test]
+    fn snapshots_date_memberships_until_the_next_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = "author_email\tauthor_name\tcodeowner\n";
+        for (name, owner) in [
+            ("codeowners-2023-01.tsv", "@org/a"),
+            ("codeowners-2023-07-15.tsv", "@org/b"),
+            ("notes.txt", "@org/c"),
+        ] {
+            let content = format!("{}ada@example.com\tAda\t{}\n", header, owner);
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        let memberships = read_memberships_from_tsv(&dir.path().to_path_buf()).unwrap();
+        assert_eq!(memberships.len(), 2);
+        assert_eq!(memberships[0].codeowner, "@org/a");
+        assert_eq!(memberships[0].valid_from, None);
+        assert_eq!(memberships[0].valid_to, Some(date("2023-07-14")));
+        assert_eq!(memberships[1].codeowner, "@org/b");
+        assert_eq!(memberships[1].valid_from, Some(date("2023-07-15")));
+        assert_eq!(memberships[1].valid_to, None);
+    }
+
+    #[test]
+    fn codeowners_revisions_are_found_by_commit_date() {
+        let timeline = CodeownersTimeline {
+            repo: PathBuf::new(),
+            revisions: vec![(100, "old".to_string()), (200, "new".to_string())],
+        };
+        let commit = |authored: i64, committed: i64| CommitInfo {
+            id: "abc".to_string(),
+            author_date: chrono::DateTime::from_timestamp(authored, 0).unwrap(),
+            committer_date: chrono::DateTime::from_timestamp(committed, 0).unwrap(),
+            author_name: "A".to_string(),
+            author_email: "a@example.com".to_string(),
+            committer_name: "A".to_string(),
+            committer_email: "a@example.com".to_string(),
+            parents: Vec::new(),
+            message: None,
+            file_changes: Vec::new(),
+        };
+        assert_eq!(timeline.revision_for(&commit(50, 50)), None);
+        assert_eq!(timeline.revision_for(&commit(100, 150)), Some("old"));
+        // Authored before the change, landed after it
+        assert_eq!(timeline.revision_for(&commit(150, 250)), Some("new"));
+        assert_eq!(timeline.revision_for(&commit(250, 250)), Some("new"));
+    }
+}
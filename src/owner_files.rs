@@ -0,0 +1,173 @@
+use std::{collections::HashMap, io};
+
+use serde::Serialize;
+
+use crate::{AuthorMembership, CommitInfoWithCodeowner};
+
+#[derive(Serialize)]
+pub struct PathContributorInfo {
+    pub author_name: String,
+    pub author_email: String,
+    pub changes: usize,
+    pub commits: usize,
+    /// Whether the author is a member of the owner.
+    pub is_team_member: bool,
+}
+
+/// Changes to a file, or to every file under a directory, owned by a single owner.
+#[derive(Serialize)]
+pub struct OwnerPathInfo {
+    pub path: String,
+    pub total_insertions: usize,
+    pub total_deletions: usize,
+    pub total_commits: usize,
+    /// Sorted by changes, most first.
+    pub contributors: Vec<PathContributorInfo>,
+}
+
+impl OwnerPathInfo {
+    pub fn total_changes(&self) -> usize {
+        self.total_insertions + self.total_deletions
+    }
+}
+
+pub struct OwnerFilesAnalysis {
+    pub owner: String,
+    /// Sorted by changes, most first.
+    pub files: Vec<OwnerPathInfo>,
+    /// Parent directories of the files, sorted by changes, most first.
+    pub directories: Vec<OwnerPathInfo>,
+}
+
+#[derive(Default)]
+struct PathStats {
+    insertions: usize,
+    deletions: usize,
+    commits: Vec<String>,
+    contributors: HashMap<(String, String), (usize, usize, bool)>,
+}
+
+impl PathStats {
+    fn add(
+        &mut self,
+        commit: &CommitInfoWithCodeowner,
+        insertions: usize,
+        deletions: usize,
+        is_team_member: bool,
+    ) {
+        self.insertions += insertions;
+        self.deletions += deletions;
+        // A directory sees a commit once per file it changed in it
+        let new_commit = self.commits.last() != Some(&commit.id);
+        if new_commit {
+            self.commits.push(commit.id.clone());
+        }
+        let (changes, commits, _) = self
+            .contributors
+            .entry((commit.author_name.clone(), commit.author_email.clone()))
+            .or_insert((0, 0, is_team_member));
+        *changes += insertions + deletions;
+        if new_commit {
+            *commits += 1;
+        }
+    }
+
+    fn finish(self, path: String) -> OwnerPathInfo {
+        let mut contributors: Vec<_> = self
+            .contributors
+            .into_iter()
+            .map(
+                |((author_name, author_email), (changes, commits, is_team_member))| {
+                    PathContributorInfo {
+                        author_name,
+                        author_email,
+                        changes,
+                        commits,
+                        is_team_member,
+                    }
+                },
+            )
+            .collect();
+        contributors.sort_by(|a, b| {
+            b.changes
+                .cmp(&a.changes)
+                .then_with(|| a.author_name.cmp(&b.author_name))
+        });
+        OwnerPathInfo {
+            path,
+            total_insertions: self.insertions,
+            total_deletions: self.deletions,
+            total_commits: self.commits.len(),
+            contributors,
+        }
+    }
+}
+
+fn sorted_by_changes(stats: HashMap<String, PathStats>) -> Vec<OwnerPathInfo> {
+    let mut paths: Vec<_> = stats
+        .into_iter()
+        .map(|(path, stats)| stats.finish(path))
+        .collect();
+    paths.sort_by(|a, b| {
+        b.total_changes()
+            .cmp(&a.total_changes())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    paths
+}
+
+/// Breaks the changes to files owned by `owner` down by file and by directory, with who
+/// made them and whether they are in `owner` according to `memberships`.
+pub fn analyze_owner_files(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    owner: &str,
+    memberships: &AuthorMembership,
+) -> Result<OwnerFilesAnalysis, io::Error> {
+    let mut files: HashMap<String, PathStats> = HashMap::new();
+    let mut directories: HashMap<String, PathStats> = HashMap::new();
+
+    for commit_result in commits {
+        let commit = commit_result?;
+        // Owners listed by email are their own member
+        let is_team_member = owner.eq_ignore_ascii_case(&commit.author_email)
+            || memberships.is_codeowner_at(
+                &commit.author_name,
+                &commit.author_email,
+                owner,
+                Some(commit.timestamp),
+            );
+        for change in &commit.file_changes {
+            let owned = change
+                .codeowners
+                .as_ref()
+                .is_some_and(|owners| owners.iter().any(|o| o.eq_ignore_ascii_case(owner)));
+            if !owned {
+                continue;
+            }
+            let insertions = change.insertions as usize;
+            let deletions = change.deletions as usize;
+            files.entry(change.path.clone()).or_default().add(
+                &commit,
+                insertions,
+                deletions,
+                is_team_member,
+            );
+            let directory = change
+                .path
+                .rsplit_once('/')
+                .map_or(".", |(directory, _)| directory);
+            directories.entry(directory.to_string()).or_default().add(
+                &commit,
+                insertions,
+                deletions,
+                is_team_member,
+            );
+        }
+    }
+
+    Ok(OwnerFilesAnalysis {
+        owner: owner.to_string(),
+        files: sorted_by_changes(files),
+        directories: sorted_by_changes(directories),
+    })
+}
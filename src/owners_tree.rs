@@ -0,0 +1,131 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::PathBuf,
+};
+
+use serde::Serialize;
+
+use crate::{get_owners_at_commit, git_tree_files, preview::owners_of, OwnerAliases};
+
+/// A directory with the ownership of the files below it.
+#[derive(Serialize)]
+pub struct OwnersTreeNode {
+    /// Directory relative to the repository root, empty for the root itself.
+    pub path: String,
+    /// Files anywhere below the directory.
+    pub files: usize,
+    /// The owners of every file below, when all of them have the same ones; empty when
+    /// they are all unowned.
+    pub uniform_owners: Option<Vec<String>>,
+    /// Files below by owner, counting a file once for each of its owners.
+    pub files_by_owner: BTreeMap<String, usize>,
+    pub unowned_files: usize,
+    /// Subdirectories, sorted by name. Directories with uniform ownership, and those at
+    /// the depth limit, are collapsed and have none.
+    pub children: Vec<OwnersTreeNode>,
+}
+
+#[derive(Default)]
+struct Directory {
+    /// The owners of each file directly in the directory.
+    files: Vec<BTreeSet<String>>,
+    subdirectories: BTreeMap<String, Directory>,
+}
+
+fn summarize(
+    directory: Directory,
+    path: String,
+    depth: usize,
+    max_depth: Option<usize>,
+) -> OwnersTreeNode {
+    let mut node = OwnersTreeNode {
+        path,
+        files: 0,
+        uniform_owners: None,
+        files_by_owner: BTreeMap::new(),
+        unowned_files: 0,
+        children: Vec::new(),
+    };
+    // The distinct sets of owners below; more than one means mixed ownership
+    let mut owner_sets: Vec<BTreeSet<String>> = Vec::new();
+    let mut mixed = false;
+    let mut note_owners = |owners: BTreeSet<String>, mixed: &mut bool| {
+        if !owner_sets.contains(&owners) {
+            owner_sets.push(owners);
+            *mixed |= owner_sets.len() > 1;
+        }
+    };
+
+    for owners in directory.files {
+        node.files += 1;
+        node.unowned_files += owners.is_empty() as usize;
+        for owner in &owners {
+            *node.files_by_owner.entry(owner.clone()).or_default() += 1;
+        }
+        note_owners(owners, &mut mixed);
+    }
+    for (name, subdirectory) in directory.subdirectories {
+        let child_path = if node.path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", node.path, name)
+        };
+        let child = summarize(subdirectory, child_path, depth + 1, max_depth);
+        node.files += child.files;
+        node.unowned_files += child.unowned_files;
+        for (owner, files) in &child.files_by_owner {
+            *node.files_by_owner.entry(owner.clone()).or_default() += files;
+        }
+        match &child.uniform_owners {
+            Some(owners) => note_owners(owners.iter().cloned().collect(), &mut mixed),
+            None => mixed = true,
+        }
+        node.children.push(child);
+    }
+
+    if !mixed {
+        node.uniform_owners = Some(owner_sets.pop().unwrap_or_default().into_iter().collect());
+    }
+    if !mixed || max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        node.children.clear();
+    }
+    node
+}
+
+/// Groups `files` into a directory tree annotated with the owners `owners` assigns, with
+/// owners resolved through `aliases`. Subtrees where every file has the same owners are
+/// collapsed, as are directories `max_depth` levels below the root.
+pub fn build_owners_tree(
+    files: &[String],
+    owners: &codeowners::Owners,
+    aliases: &OwnerAliases,
+    max_depth: Option<usize>,
+) -> OwnersTreeNode {
+    let mut root = Directory::default();
+    for path in files {
+        let mut directory = &mut root;
+        let mut parts: Vec<&str> = path.split('/').collect();
+        parts.pop();
+        for part in parts {
+            directory = directory
+                .subdirectories
+                .entry(part.to_string())
+                .or_default();
+        }
+        directory.files.push(owners_of(owners, path, aliases));
+    }
+    summarize(root, String::new(), 0, max_depth)
+}
+
+/// The ownership tree of the files in the tree at `rev`, owned by its CODEOWNERS file.
+pub fn owners_tree_at(
+    rev: &str,
+    cwd: &PathBuf,
+    aliases: &OwnerAliases,
+    max_depth: Option<usize>,
+) -> Result<OwnersTreeNode, io::Error> {
+    let owners = get_owners_at_commit(rev, cwd)?;
+    let files = git_tree_files(rev, cwd)?;
+    Ok(build_owners_tree(&files, &owners, aliases, max_depth))
+}
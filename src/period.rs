@@ -0,0 +1,140 @@
+use std::{collections::BTreeMap, fmt, io, str::FromStr};
+
+use chrono::{DateTime, Datelike};
+
+use crate::CommitInfoWithCodeowner;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodGrouping {
+    Month,
+    Quarter,
+}
+
+impl FromStr for PeriodGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "month" => Ok(PeriodGrouping::Month),
+            "quarter" => Ok(PeriodGrouping::Quarter),
+            _ => Err(format!(
+                "Invalid period '{}', expected 'month' or 'quarter'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PeriodGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeriodGrouping::Month => write!(f, "month"),
+            PeriodGrouping::Quarter => write!(f, "quarter"),
+        }
+    }
+}
+
+/// Returns the label of the period containing `timestamp` (UTC), e.g. `2024-03` or `2024-Q1`.
+pub fn period_key(timestamp: i64, grouping: PeriodGrouping) -> String {
+    let date = DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+    match grouping {
+        PeriodGrouping::Month => format!("{:04}-{:02}", date.year(), date.month()),
+        PeriodGrouping::Quarter => format!("{:04}-Q{}", date.year(), (date.month() - 1) / 3 + 1),
+    }
+}
+
+/// Buckets commits by period so each period can be analyzed separately from a single
+/// history walk. Periods are returned in chronological order.
+pub fn group_commits_by_period(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    grouping: PeriodGrouping,
+) -> Result<Vec<(String, Vec<CommitInfoWithCodeowner>)>, io::Error> {
+    let mut periods: BTreeMap<String, Vec<CommitInfoWithCodeowner>> = BTreeMap::new();
+
+    for commit_result in commits {
+        let commit = commit_result?;
+        periods
+            .entry(period_key(commit.timestamp, grouping))
+            .or_default()
+            .push(commit);
+    }
+
+    Ok(periods.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const END_OF_2023: i64 = 1704067199;
+    const MID_JANUARY_2024: i64 = 1705276800;
+    const END_OF_MARCH_2024: i64 = 1711929599;
+    const START_OF_APRIL_2024: i64 = 1711929600;
+
+    fn commit(id: &str, timestamp: i64) -> CommitInfoWithCodeowner {
+        CommitInfoWithCodeowner {
+            id: id.to_string(),
+            author_name: "Ada".to_string(),
+            author_email: "ada@example.com".to_string(),
+            timestamp,
+            message: None,
+            file_changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn period_keys_label_months_and_quarters() {
+        assert_eq!(period_key(END_OF_2023, PeriodGrouping::Month), "2023-12");
+        assert_eq!(period_key(END_OF_2023, PeriodGrouping::Quarter), "2023-Q4");
+        assert_eq!(
+            period_key(END_OF_MARCH_2024, PeriodGrouping::Month),
+            "2024-03"
+        );
+        assert_eq!(
+            period_key(END_OF_MARCH_2024, PeriodGrouping::Quarter),
+            "2024-Q1"
+        );
+        assert_eq!(
+            period_key(START_OF_APRIL_2024, PeriodGrouping::Quarter),
+            "2024-Q2"
+        );
+    }
+
+    #[test]
+    fn commits_are_bucketed_in_chronological_order() {
+        // git log lists the newest commits first
+        let commits = [
+            commit("d", START_OF_APRIL_2024),
+            commit("c", END_OF_MARCH_2024),
+            commit("b", MID_JANUARY_2024),
+            commit("a", END_OF_2023),
+        ];
+        let periods =
+            group_commits_by_period(commits.into_iter().map(Ok), PeriodGrouping::Quarter).unwrap();
+        let ids: Vec<(&str, Vec<&str>)> = periods
+            .iter()
+            .map(|(period, commits)| {
+                (
+                    period.as_str(),
+                    commits.iter().map(|commit| commit.id.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ("2023-Q4", vec!["a"]),
+                ("2024-Q1", vec!["c", "b"]),
+                ("2024-Q2", vec!["d"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn groupings_parse_case_insensitively() {
+        assert_eq!("Month".parse(), Ok(PeriodGrouping::Month));
+        assert_eq!("quarter".parse(), Ok(PeriodGrouping::Quarter));
+        assert!("week".parse::<PeriodGrouping>().is_err());
+        assert_eq!(PeriodGrouping::Quarter.to_string(), "quarter");
+    }
+}
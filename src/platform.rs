@@ -0,0 +1,65 @@
+use std::{
+    borrow::Cow,
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Where Windows installers put a program when it isn't on `PATH`, under `%ProgramFiles%`.
+fn windows_install_locations(program: &str) -> &'static [&'static str] {
+    match program {
+        "git" => &["Git\\cmd\\git.exe", "Git\\bin\\git.exe"],
+        "gh" => &["GitHub CLI\\gh.exe"],
+        _ => &[],
+    }
+}
+
+/// Finds `program` the way a Windows shell would: on `PATH` with each of the `PATHEXT`
+/// extensions (a bare `git` resolves to `git.exe`, or to `git.cmd` from some installers),
+/// then where its installer usually puts it.
+fn find_windows_program(program: &str) -> Option<PathBuf> {
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let directories = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let on_path = directories.iter().find_map(|directory| {
+        extensions
+            .split(';')
+            .filter(|extension| !extension.is_empty())
+            .map(|extension| directory.join(format!("{}{}", program, extension.to_lowercase())))
+            .find(|candidate| candidate.is_file())
+    });
+    on_path.or_else(|| {
+        ["ProgramFiles", "ProgramW6432", "ProgramFiles(x86)"]
+            .iter()
+            .filter_map(env::var_os)
+            .flat_map(|root| {
+                windows_install_locations(program)
+                    .iter()
+                    .map(move |location| Path::new(&root).join(location))
+            })
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// A command running `program` (`git`, `gh`), located explicitly on Windows, where
+/// `Command` on its own only finds `.exe` files on `PATH`.
+pub(crate) fn command(program: &str) -> Command {
+    if cfg!(windows) {
+        if let Some(path) = find_windows_program(program) {
+            return Command::new(path);
+        }
+    }
+    Command::new(program)
+}
+
+/// Converts `path` to the `/`-separated form git reports and CODEOWNERS patterns use. On
+/// Windows, where paths and globs may be written with `\`, those become `/`; elsewhere `\`
+/// can be part of a file name and is left alone.
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    if cfg!(windows) && path.contains('\\') {
+        Cow::Owned(path.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, Cursor},
+};
+
+use thiserror::Error;
+
+use crate::{CommitInfo, OwnerAliases};
+
+#[derive(Error, Debug)]
+pub enum PatchError {
+    #[error("The patch has no hunks for a CODEOWNERS file")]
+    NoHunks,
+    #[error("Invalid hunk header '{0}'")]
+    InvalidHunkHeader(String),
+    #[error("The patch does not apply: line {line} of CODEOWNERS does not match '{expected}'")]
+    Mismatch { line: usize, expected: String },
+}
+
+struct Hunk {
+    old_start: usize,
+    old_count: usize,
+    new_count: usize,
+    lines: Vec<String>,
+}
+
+/// Parses `-12,3` or `+12` from a hunk header into a start and a count, requiring the
+/// range to start with `sign`.
+fn parse_range(range: &str, sign: char) -> Option<(usize, usize)> {
+    let range = range.strip_prefix(sign)?;
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Result<Hunk, PatchError> {
+    let invalid = || PatchError::InvalidHunkHeader(line.to_string());
+    let mut parts = line.split_whitespace().skip(1);
+    let (old_start, old_count) = parts
+        .next()
+        .and_then(|range| parse_range(range, '-'))
+        .ok_or_else(invalid)?;
+    let (_, new_count) = parts
+        .next()
+        .and_then(|range| parse_range(range, '+'))
+        .ok_or_else(invalid)?;
+    Ok(Hunk {
+        old_start,
+        old_count,
+        new_count,
+        lines: Vec::new(),
+    })
+}
+
+/// Collects the hunks of the CODEOWNERS file in a unified diff. A diff without file headers
+/// is taken to be for CODEOWNERS as a whole.
+fn parse_codeowners_hunks(patch: &str) -> Result<Vec<Hunk>, PatchError> {
+    let has_headers = patch.lines().any(|line| line.starts_with("+++ "));
+    let mut in_codeowners = !has_headers;
+    let mut hunks = Vec::new();
+    let mut lines = patch.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            in_codeowners = path.trim_end().ends_with("CODEOWNERS");
+        } else if line.starts_with("@@ ") {
+            let mut hunk = parse_hunk_header(line)?;
+            let (mut old, mut new) = (0, 0);
+            while old < hunk.old_count || new < hunk.new_count {
+                let Some(line) = lines.next() else {
+                    break;
+                };
+                match line.chars().next() {
+                    Some('-') => old += 1,
+                    Some('+') => new += 1,
+                    Some('\\') => continue,
+                    _ => {
+                        old += 1;
+                        new += 1;
+                    }
+                }
+                hunk.lines.push(line.to_string());
+            }
+            if in_codeowners {
+                hunks.push(hunk);
+            }
+        }
+    }
+
+    if hunks.is_empty() {
+        return Err(PatchError::NoHunks);
+    }
+    Ok(hunks)
+}
+
+/// Applies the CODEOWNERS part of a unified diff to `original`, requiring every context and
+/// removed line to match exactly.
+pub fn apply_codeowners_patch(original: &str, patch: &str) -> Result<String, PatchError> {
+    let original: Vec<&str> = original.lines().collect();
+    let mut patched: Vec<&str> = Vec::new();
+    let mut position = 0;
+
+    let hunks = parse_codeowners_hunks(patch)?;
+    for hunk in &hunks {
+        // A hunk that only adds lines starts after its old start rather than at it
+        let start = if hunk.old_count == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        if start < position || start > original.len() {
+            return Err(PatchError::Mismatch {
+                line: start + 1,
+                expected: hunk.lines.first().cloned().unwrap_or_default(),
+            });
+        }
+        patched.extend(&original[position..start]);
+        position = start;
+
+        for line in &hunk.lines {
+            let marker = line.chars().next();
+            let content = marker.map_or("", |marker| &line[marker.len_utf8()..]);
+            if marker == Some('+') {
+                patched.push(content);
+                continue;
+            }
+            if original.get(position) != Some(&content) {
+                return Err(PatchError::Mismatch {
+                    line: position + 1,
+                    expected: content.to_string(),
+                });
+            }
+            if marker != Some('-') {
+                patched.push(content);
+            }
+            position += 1;
+        }
+    }
+    patched.extend(&original[position..]);
+
+    let mut result = patched.join("\n");
+    result.push('\n');
+    Ok(result)
+}
+
+/// How a CODEOWNERS change moves ownership to or from one owner.
+#[derive(Default)]
+pub struct OwnershipImpact {
+    pub owner: String,
+    pub files_gained: usize,
+    pub files_lost: usize,
+    /// Changed lines in the history whose files the owner would gain.
+    pub changes_gained: usize,
+    pub changes_lost: usize,
+}
+
+pub struct CodeownersPreview {
+    pub impacts: Vec<OwnershipImpact>,
+    pub files: usize,
+    /// Files whose set of owners would change.
+    pub files_reassigned: usize,
+    pub unowned_files_before: usize,
+    pub unowned_files_after: usize,
+    pub changes: usize,
+    pub changes_reassigned: usize,
+}
+
+pub(crate) fn owners_of(
+    owners: &codeowners::Owners,
+    path: &str,
+    aliases: &OwnerAliases,
+) -> BTreeSet<String> {
+    owners
+        .of(path)
+        .into_iter()
+        .flatten()
+        .map(|owner| aliases.resolve(&owner.to_string()).to_string())
+        .collect()
+}
+
+fn impact<'a>(
+    impacts: &'a mut BTreeMap<String, OwnershipImpact>,
+    owner: &str,
+) -> &'a mut OwnershipImpact {
+    impacts
+        .entry(owner.to_string())
+        .or_insert_with(|| OwnershipImpact {
+            owner: owner.to_string(),
+            ..Default::default()
+        })
+}
+
+/// Compares the owners `current` and `proposed` CODEOWNERS contents assign to each of
+/// `files` and to the files changed by `commits`, tallying what every owner would gain
+/// and lose.
+pub fn preview_codeowners_change<I>(
+    current: &str,
+    proposed: &str,
+    files: &[String],
+    commits: I,
+    aliases: &OwnerAliases,
+) -> Result<CodeownersPreview, io::Error>
+where
+    I: Iterator<Item = Result<CommitInfo, io::Error>>,
+{
+    let current = codeowners::from_reader(Cursor::new(current.to_string()));
+    let proposed = codeowners::from_reader(Cursor::new(proposed.to_string()));
+    let mut impacts: BTreeMap<String, OwnershipImpact> = BTreeMap::new();
+
+    let mut preview = CodeownersPreview {
+        impacts: Vec::new(),
+        files: files.len(),
+        files_reassigned: 0,
+        unowned_files_before: 0,
+        unowned_files_after: 0,
+        changes: 0,
+        changes_reassigned: 0,
+    };
+
+    for path in files {
+        let before = owners_of(&current, path, aliases);
+        let after = owners_of(&proposed, path, aliases);
+        preview.unowned_files_before += before.is_empty() as usize;
+        preview.unowned_files_after += after.is_empty() as usize;
+        if before == after {
+            continue;
+        }
+        preview.files_reassigned += 1;
+        for owner in after.difference(&before) {
+            impact(&mut impacts, owner).files_gained += 1;
+        }
+        for owner in before.difference(&after) {
+            impact(&mut impacts, owner).files_lost += 1;
+        }
+    }
+
+    for commit in commits {
+        for change in commit?.file_changes {
+            let lines = (change.insertions + change.deletions) as usize;
+            preview.changes += lines;
+            let before = owners_of(&current, &change.path, aliases);
+            let after = owners_of(&proposed, &change.path, aliases);
+            if before == after {
+                continue;
+            }
+            preview.changes_reassigned += lines;
+            for owner in after.difference(&before) {
+                impact(&mut impacts, owner).changes_gained += lines;
+            }
+            for owner in before.difference(&after) {
+                impact(&mut impacts, owner).changes_lost += lines;
+            }
+        }
+    }
+
+    preview.impacts = impacts.into_values().collect();
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(line: &str) -> Option<(usize, usize, usize)> {
+        parse_hunk_header(line)
+            .ok()
+            .map(|hunk| (hunk.old_start, hunk.old_count, hunk.new_count))
+    }
+
+    #[test]
+    fn hunk_headers_parse_starts_and_counts() {
+        assert_eq!(header("@@ -12,3 +12,4 @@ section"), Some((12, 3, 4)));
+        assert_eq!(header("@@ -5 +5 @@"), Some((5, 1, 1)));
+        assert_eq!(header("@@ -0,0 +1,2 @@"), Some((0, 0, 2)));
+    }
+
+    #[test]
+    fn malformed_hunk_headers_are_errors() {
+        for line in [
+            "@@",
+            "@@ - +1 @@",
+            "@@ 12,3 +12 @@",
+            "@@ +12,3 -12 @@",
+            "@@ é +1 @@",
+            "@@ -1,x +1 @@",
+        ] {
+            assert!(
+                matches!(
+                    parse_hunk_header(line),
+                    Err(PatchError::InvalidHunkHeader(_))
+                ),
+                "{}",
+                line
+            );
+        }
+    }
+
+    const CODEOWNERS: &str = "* @org/all\n/src/ @org/src\n/docs/ @org/docs\n";
+
+    #[test]
+    fn patches_replace_and_add_lines() {
+        let patch = "\
+--- a/.github/CODEOWNERS
++++ b/.github/CODEOWNERS
+@@ -2,2 +2,3 @@
+-/src/ @org/src
++/src/ @org/core
+ /docs/ @org/docs
++/web/ @org/web
+";
+        assert_eq!(
+            apply_codeowners_patch(CODEOWNERS, patch).unwrap(),
+            "* @org/all\n/src/ @org/core\n/docs/ @org/docs\n/web/ @org/web\n"
+        );
+    }
+
+    #[test]
+    fn patches_without_file_headers_apply_to_codeowners() {
+        let patch = "@@ -0,0 +1 @@\n+# Owners\n";
+        assert_eq!(
+            apply_codeowners_patch(CODEOWNERS, patch).unwrap(),
+            format!("# Owners\n{}", CODEOWNERS)
+        );
+    }
+
+    #[test]
+    fn hunks_for_other_files_are_ignored() {
+        let patch = "\
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-old
++new
+";
+        assert!(matches!(
+            apply_codeowners_patch(CODEOWNERS, patch),
+            Err(PatchError::NoHunks)
+        ));
+    }
+
+    #[test]
+    fn patches_that_do_not_match_are_errors() {
+        let patch = "@@ -2 +2 @@\n-/lib/ @org/lib\n+/lib/ @org/core\n";
+        assert!(matches!(
+            apply_codeowners_patch(CODEOWNERS, patch),
+            Err(PatchError::Mismatch { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,16 @@
+/// Receives progress from long-running work, such as reading history or paging through
+/// the GitHub API, so it can be reported while it runs. Every callback does nothing by
+/// default; implement only the ones of interest.
+///
+/// Callbacks may come from whichever thread does the work and should return quickly.
+pub trait ProgressSink: Send + Sync {
+    /// `count` commits have been processed so far.
+    fn commits_processed(&self, _count: usize) {}
+
+    /// All commits have been processed, `count` in total.
+    fn commits_finished(&self, _count: usize) {}
+
+    /// `count` responses have been received from the API so far, one for each page of
+    /// paginated results.
+    fn api_page_fetched(&self, _count: usize) {}
+}
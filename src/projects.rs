@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use serde::Serialize;
+
+use crate::{
+    git_tree_files, read_file_at_commit, AnalysisSummary, CommitInfoWithCodeowner,
+    ContributorToOwnerInfo, ProgressSink,
+};
+
+/// The build system whose manifest defines a project.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    Cargo,
+    Npm,
+    Bazel,
+}
+
+impl fmt::Display for ProjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectKind::Cargo => write!(f, "cargo"),
+            ProjectKind::Npm => write!(f, "npm"),
+            ProjectKind::Bazel => write!(f, "bazel"),
+        }
+    }
+}
+
+/// A directory holding a package manifest, grouping every file under it that isn't in a
+/// nested project.
+#[derive(Clone, Debug, Serialize)]
+pub struct Project {
+    /// The package name from the manifest, or the directory when it has none.
+    pub name: String,
+    /// Directory relative to the repository root, empty for the root itself.
+    pub path: String,
+    pub kind: ProjectKind,
+}
+
+impl Project {
+    fn contains(&self, path: &str) -> bool {
+        self.path.is_empty()
+            || path
+                .strip_prefix(&self.path)
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// Reads the project a manifest defines, if any. Cargo workspace roots and npm
+/// workspace roots only group other projects, so they are not projects themselves.
+fn parse_manifest(file_name: &str, directory: &str, content: &str) -> Option<Project> {
+    let project = |name: Option<&str>, kind| Project {
+        name: name.map_or_else(|| directory_name(directory), str::to_string),
+        path: directory.to_string(),
+        kind,
+    };
+    match file_name {
+        "Cargo.toml" => {
+            let manifest: toml::Value = toml::from_str(content).ok()?;
+            let name = manifest.get("package")?.get("name")?.as_str()?;
+            Some(project(Some(name), ProjectKind::Cargo))
+        }
+        "package.json" => {
+            let manifest: serde_json::Value = serde_json::from_str(content).ok()?;
+            let name = manifest.get("name").and_then(|name| name.as_str());
+            if name.is_none() && manifest.get("workspaces").is_some() {
+                return None;
+            }
+            Some(project(name, ProjectKind::Npm))
+        }
+        _ => None,
+    }
+}
+
+fn directory_name(directory: &str) -> String {
+    if directory.is_empty() {
+        ".".to_string()
+    } else {
+        directory.to_string()
+    }
+}
+
+/// The projects of a tree, to look up which one a path belongs to.
+pub struct ProjectIndex {
+    /// Deepest directories first, so the first match is the innermost project.
+    projects: Vec<Project>,
+}
+
+impl ProjectIndex {
+    /// Finds the Cargo packages, npm packages and Bazel packages in the tree at `rev`.
+    /// Vendored `node_modules` are ignored.
+    pub fn detect(rev: &str, cwd: &PathBuf) -> Result<Self, io::Error> {
+        let mut projects = Vec::new();
+        let mut seen = HashSet::new();
+        for path in git_tree_files(rev, cwd)? {
+            if path.split('/').any(|part| part == "node_modules") {
+                continue;
+            }
+            let (directory, file_name) = path.rsplit_once('/').unwrap_or(("", &path));
+            // A directory can be several kinds of package; the first manifest wins
+            if seen.contains(directory) {
+                continue;
+            }
+            let project = match file_name {
+                "BUILD" | "BUILD.bazel" => Some(Project {
+                    name: format!("//{}", directory),
+                    path: directory.to_string(),
+                    kind: ProjectKind::Bazel,
+                }),
+                "Cargo.toml" | "package.json" => read_file_at_commit(rev, &path, cwd)?
+                    .and_then(|content| parse_manifest(file_name, directory, &content)),
+                _ => None,
+            };
+            if let Some(project) = project {
+                seen.insert(directory.to_string());
+                projects.push(project);
+            }
+        }
+        Ok(Self::new(projects))
+    }
+
+    pub fn new(mut projects: Vec<Project>) -> Self {
+        // A nested project's directory is always longer than its parent's
+        projects.sort_by_key(|project| std::cmp::Reverse(project.path.len()));
+        ProjectIndex { projects }
+    }
+
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+
+    /// The innermost project containing `path`.
+    pub fn project_for(&self, path: &str) -> Option<&Project> {
+        self.projects.iter().find(|project| project.contains(path))
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProjectOwnerInfo {
+    pub owner: String,
+    pub changes: usize,
+}
+
+#[derive(Serialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub path: String,
+    pub kind: ProjectKind,
+    pub total_insertions: usize,
+    pub total_deletions: usize,
+    pub total_commits: usize,
+    /// Changes by an owner of the changed file.
+    pub changes_by_team: usize,
+    pub changes_by_others: usize,
+    pub unowned_changes: usize,
+    /// Owners of the changed files, by changes, most first.
+    pub owners: Vec<ProjectOwnerInfo>,
+    pub top_contributors_by_changes: Vec<ContributorToOwnerInfo>,
+}
+
+impl ProjectInfo {
+    pub fn total_changes(&self) -> usize {
+        self.total_insertions + self.total_deletions
+    }
+
+    /// Fraction of changes made by an owner of the changed file.
+    pub fn team_share(&self) -> f64 {
+        let total_changes = self.total_changes();
+        if total_changes > 0 {
+            self.changes_by_team as f64 / total_changes as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+pub struct ProjectAnalysis {
+    pub summary: AnalysisSummary,
+    /// Sorted by changes, most first.
+    pub projects: Vec<ProjectInfo>,
+    /// Changes to files outside every project.
+    pub changes_outside_projects: usize,
+}
+
+#[derive(Default)]
+struct ProjectStats {
+    insertions: usize,
+    deletions: usize,
+    commits: usize,
+    changes_by_team: usize,
+    changes_by_others: usize,
+    unowned_changes: usize,
+    owners: HashMap<String, usize>,
+    contributors: HashMap<(String, String), usize>,
+}
+
+/// Incremental form of [`analyze_by_project`], fed one commit at a time.
+pub struct ProjectAnalyzer {
+    index: ProjectIndex,
+    summary: AnalysisSummary,
+    projects: HashMap<String, ProjectStats>,
+    changes_outside_projects: usize,
+    progress: Option<Arc<dyn ProgressSink>>,
+}
+
+impl ProjectAnalyzer {
+    pub fn new(index: ProjectIndex) -> Self {
+        ProjectAnalyzer {
+            index,
+            summary: AnalysisSummary::default(),
+            projects: HashMap::new(),
+            changes_outside_projects: 0,
+            progress: None,
+        }
+    }
+
+    /// Reports each commit added to `progress`, and the total once finished.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn add_commit(&mut self, commit: &CommitInfoWithCodeowner) {
+        self.summary.add_commit(commit);
+        if let Some(progress) = &self.progress {
+            progress.commits_processed(self.summary.total_commits);
+        }
+        let mut touched = HashSet::new();
+        for change in &commit.file_changes {
+            let changes = (change.insertions + change.deletions) as usize;
+            let Some(project) = self.index.project_for(&change.path) else {
+                self.changes_outside_projects += changes;
+                continue;
+            };
+            let stats = self.projects.entry(project.path.clone()).or_default();
+            if touched.insert(project.path.clone()) {
+                stats.commits += 1;
+            }
+            stats.insertions += change.insertions as usize;
+            stats.deletions += change.deletions as usize;
+            let owners = change.codeowners.as_deref().unwrap_or_default();
+            if owners.is_empty() {
+                stats.unowned_changes += changes;
+            } else if change.author_is_codeowner.unwrap_or(false) {
+                stats.changes_by_team += changes;
+            } else {
+                stats.changes_by_others += changes;
+            }
+            for owner in owners {
+                *stats.owners.entry(owner.clone()).or_default() += changes;
+            }
+            *stats
+                .contributors
+                .entry((commit.author_name.clone(), commit.author_email.clone()))
+                .or_default() += changes;
+        }
+    }
+
+    pub fn finish(mut self) -> ProjectAnalysis {
+        if let Some(progress) = &self.progress {
+            progress.commits_finished(self.summary.total_commits);
+        }
+        let mut projects: Vec<ProjectInfo> = self
+            .index
+            .projects
+            .iter()
+            .filter_map(|project| {
+                let stats = self.projects.remove(&project.path)?;
+                let mut owners: Vec<_> = stats
+                    .owners
+                    .into_iter()
+                    .map(|(owner, changes)| ProjectOwnerInfo { owner, changes })
+                    .collect();
+                owners.sort_by(|a, b| b.changes.cmp(&a.changes).then(a.owner.cmp(&b.owner)));
+                let mut contributors: Vec<_> = stats.contributors.into_iter().collect();
+                contributors.sort_by(|(a, changes_a), (b, changes_b)| {
+                    changes_b.cmp(changes_a).then(a.cmp(b))
+                });
+                Some(ProjectInfo {
+                    name: project.name.clone(),
+                    path: project.path.clone(),
+                    kind: project.kind,
+                    total_insertions: stats.insertions,
+                    total_deletions: stats.deletions,
+                    total_commits: stats.commits,
+                    changes_by_team: stats.changes_by_team,
+                    changes_by_others: stats.changes_by_others,
+                    unowned_changes: stats.unowned_changes,
+                    owners,
+                    top_contributors_by_changes: contributors
+                        .into_iter()
+                        .take(10)
+                        .map(
+                            |((author_name, author_email), changes)| ContributorToOwnerInfo {
+                                author_name,
+                                author_email,
+                                metric_value: changes,
+                            },
+                        )
+                        .collect(),
+                })
+            })
+            .collect();
+        projects.sort_by(|a, b| {
+            b.total_changes()
+                .cmp(&a.total_changes())
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        ProjectAnalysis {
+            summary: self.summary,
+            projects,
+            changes_outside_projects: self.changes_outside_projects,
+        }
+    }
+}
+
+/// Breaks changes down by the project, rather than the owner, of the changed files.
+pub fn analyze_by_project(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    index: ProjectIndex,
+) -> Result<ProjectAnalysis, io::Error> {
+    let mut analyzer = ProjectAnalyzer::new(index);
+    for commit_result in commits {
+        analyzer.add_commit(&commit_result?);
+    }
+    Ok(analyzer.finish())
+}
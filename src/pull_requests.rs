@@ -0,0 +1,148 @@
+use std::{collections::HashMap, fmt, io, path::Path, str::FromStr};
+
+use crate::{isolate::git_output, CommitInfoWithCodeowner, FileChangeWithCodeowner};
+
+/// Where the pull request each commit came from is learned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PullRequestSource {
+    /// Merge commit subjects and `(#1234)` squash suffixes in the history.
+    Git,
+    /// The history as with `Git`, plus the merge commits GitHub recorded for each pull
+    /// request, which also covers rebase merges.
+    Github,
+}
+
+impl FromStr for PullRequestSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "git" => Ok(PullRequestSource::Git),
+            "github" => Ok(PullRequestSource::Github),
+            _ => Err(format!(
+                "Invalid pull request source '{}', expected 'git' or 'github'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PullRequestSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PullRequestSource::Git => write!(f, "git"),
+            PullRequestSource::Github => write!(f, "github"),
+        }
+    }
+}
+
+/// The pull request number in a merge commit subject, as GitHub writes them.
+fn merged_pull_request(subject: &str) -> Option<u64> {
+    let rest = subject.strip_prefix("Merge pull request #")?;
+    let number = rest.split_whitespace().next()?;
+    number.parse().ok()
+}
+
+/// The pull request number a squash merge appends to the subject, as in `Fix (#1234)`.
+fn squashed_pull_request(subject: &str) -> Option<u64> {
+    let (_, number) = subject.trim_end().strip_suffix(')')?.rsplit_once("(#")?;
+    number.parse().ok()
+}
+
+/// Maps the commits on `rev` (HEAD when unset) that came from a pull request merged since
+/// `since` to its number. Commits squashed into one get it from their subject; for merge
+/// commits, so do the merge and every commit it brought in.
+pub fn pull_request_numbers(
+    since: &str,
+    rev: Option<&str>,
+    cwd: &Path,
+) -> Result<HashMap<String, u64>, io::Error> {
+    let log = git_output(
+        &[
+            "log",
+            &format!("--since={}", since),
+            "--format=%H%x09%P%x09%s",
+            rev.unwrap_or("HEAD"),
+            "--",
+        ],
+        cwd,
+    )?;
+    let mut numbers = HashMap::new();
+    for line in log.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(id), Some(parents), Some(subject)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let parents: Vec<&str> = parents.split_whitespace().collect();
+        if let (Some(number), [first, second]) = (merged_pull_request(subject), &parents[..]) {
+            numbers.entry(id.to_string()).or_insert(number);
+            let branch = git_output(&["rev-list", &format!("{}..{}", first, second)], cwd)?;
+            for commit in branch.lines() {
+                numbers.entry(commit.to_string()).or_insert(number);
+            }
+        } else if let Some(number) = squashed_pull_request(subject) {
+            numbers.entry(id.to_string()).or_insert(number);
+        }
+    }
+    Ok(numbers)
+}
+
+fn merge_file_changes(
+    into: &mut Vec<FileChangeWithCodeowner>,
+    changes: Vec<FileChangeWithCodeowner>,
+) {
+    for change in changes {
+        match into
+            .iter_mut()
+            .find(|existing| existing.path == change.path)
+        {
+            // `into` holds the later change, whose owners and size after are kept
+            Some(existing) => {
+                existing.insertions += change.insertions;
+                existing.deletions += change.deletions;
+                existing.binary |= change.binary;
+                existing.old_path = change.old_path.or(existing.old_path.take());
+                existing.size_before = change.size_before;
+            }
+            None => into.push(change),
+        }
+    }
+}
+
+/// Combines the commits each author made to the same pull request, as numbered by
+/// `numbers`, into one, so analyses count pull requests rather than commits. The combined
+/// commit takes the id, time and owners of the author's latest commit in the pull request,
+/// and sums the changes to each file. Commits not from a pull request are kept as they are.
+///
+/// `commits` are expected newest first, as `git log` lists them, and so are the results.
+pub fn group_commits_by_pull_request(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    numbers: &HashMap<String, u64>,
+) -> Result<Vec<CommitInfoWithCodeowner>, io::Error> {
+    let mut grouped: Vec<CommitInfoWithCodeowner> = Vec::new();
+    let mut positions: HashMap<(u64, String, String), usize> = HashMap::new();
+    for commit_result in commits {
+        let commit = commit_result?;
+        let Some(number) = numbers.get(&commit.id) else {
+            grouped.push(commit);
+            continue;
+        };
+        let key = (
+            *number,
+            commit.author_name.clone(),
+            commit.author_email.clone(),
+        );
+        match positions.get(&key) {
+            Some(&position) => {
+                merge_file_changes(&mut grouped[position].file_changes, commit.file_changes)
+            }
+            None => {
+                positions.insert(key, grouped.len());
+                grouped.push(commit);
+            }
+        }
+    }
+    Ok(grouped)
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use crate::{AuthorCodeownerMemberships, AuthorMembership, NameNormalization};
+
+/// A GitHub login's membership in a codeowner team as last fetched, kept next to the
+/// memberships file so a refresh can tell which rows it generated.
+#[derive(Clone)]
+pub struct TeamMemberRecord {
+    pub codeowner: String,
+    pub login: String,
+    pub author_email: String,
+    pub author_name: String,
+}
+
+impl TeamMemberRecord {
+    pub fn to_membership(&self) -> AuthorCodeownerMemberships {
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        AuthorCodeownerMemberships {
+            author_email: non_empty(&self.author_email),
+            author_name: non_empty(&self.author_name),
+            codeowner: self.codeowner.clone(),
+            additional_emails: Vec::new(),
+            aliases: Vec::new(),
+            valid_from: None,
+            valid_to: None,
+        }
+    }
+
+    fn key(&self) -> (String, String) {
+        (self.codeowner.to_lowercase(), self.login.to_lowercase())
+    }
+
+    fn matches(&self, membership: &AuthorCodeownerMemberships) -> bool {
+        let generated = self.to_membership();
+        membership.codeowner.eq_ignore_ascii_case(&self.codeowner)
+            && membership.author_email == generated.author_email
+            && membership.author_name == generated.author_name
+    }
+}
+
+pub fn write_team_member_state(records: &[TeamMemberRecord], path: &PathBuf) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "codeowner\tlogin\tauthor_email\tauthor_name")?;
+    for record in records {
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{}",
+            record.codeowner, record.login, record.author_email, record.author_name
+        )?;
+    }
+    Ok(())
+}
+
+/// Reads the state written by [`write_team_member_state`]; a missing file means no state.
+pub fn read_team_member_state(path: &PathBuf) -> io::Result<Vec<TeamMemberRecord>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+
+    // Skip the header
+    for line in BufReader::new(file).lines().skip(1) {
+        let line = line?;
+        let parts: Vec<&str> = line.split('\t').collect();
+        if parts.len() != 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            ));
+        }
+        records.push(TeamMemberRecord {
+            codeowner: parts[0].to_string(),
+            login: parts[1].to_string(),
+            author_email: parts[2].to_string(),
+            author_name: parts[3].to_string(),
+        });
+    }
+
+    Ok(records)
+}
+
+pub struct RefreshStats {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Applies the difference between the `previous` and `current` team members to
+/// `memberships`. Only rows generated for members who left are removed, so rows added or
+/// edited by hand are kept; new members are not added again if a row already covers them.
+pub fn merge_team_member_changes(
+    memberships: &mut Vec<AuthorCodeownerMemberships>,
+    previous: &[TeamMemberRecord],
+    current: &[TeamMemberRecord],
+    names: &NameNormalization,
+) -> RefreshStats {
+    let previous_keys: HashSet<_> = previous.iter().map(TeamMemberRecord::key).collect();
+    let current_keys: HashSet<_> = current.iter().map(TeamMemberRecord::key).collect();
+    let mut stats = RefreshStats {
+        added: 0,
+        removed: 0,
+    };
+
+    for record in previous
+        .iter()
+        .filter(|record| !current_keys.contains(&record.key()))
+    {
+        let count = memberships.len();
+        memberships.retain(|membership| !record.matches(membership));
+        stats.removed += count - memberships.len();
+    }
+
+    let existing = AuthorMembership::with_name_normalization(memberships, names);
+    for record in current
+        .iter()
+        .filter(|record| !previous_keys.contains(&record.key()))
+    {
+        if !existing.is_codeowner(&record.author_name, &record.author_email, &record.codeowner) {
+            memberships.push(record.to_membership());
+            stats.added += 1;
+        }
+    }
+
+    stats
+}
@@ -0,0 +1,136 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{isolate::git_output, resolve_date, DateBound};
+
+/// Whether `location` names a remote repository rather than a local directory.
+pub fn is_remote_url(location: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "file://", "git@"]
+        .iter()
+        .any(|prefix| location.starts_with(prefix))
+}
+
+/// Directory under `cache_dir` that a clone of `url` is kept in.
+pub fn remote_cache_path(url: &str, cache_dir: &Path) -> PathBuf {
+    let name: String = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .split("://")
+        .last()
+        .unwrap_or(url)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    cache_dir.join(name)
+}
+
+/// Short name for the repository at `location`, a local directory or a remote URL, used to
+/// tell repositories apart when several are analyzed together.
+pub fn repo_name(location: &Path) -> String {
+    let text = location.to_string_lossy();
+    if is_remote_url(&text) {
+        let trimmed = text.trim_end_matches('/').trim_end_matches(".git");
+        return trimmed
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or(trimmed)
+            .to_string();
+    }
+    location
+        .canonicalize()
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| text.to_string())
+}
+
+pub(crate) fn run_git(args: &[&str], cwd: &Path) -> Result<(), io::Error> {
+    let output = crate::platform::command("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Clones `url` into the cache, or updates an earlier clone, and returns its path.
+///
+/// With `since`, only history from that date on is fetched. The shallow boundary is then
+/// deepened by one commit so the oldest commits in range still diff against their parents
+/// instead of appearing to add every file.
+pub fn fetch_remote(
+    url: &str,
+    since: Option<&str>,
+    cache_dir: &Path,
+) -> Result<PathBuf, io::Error> {
+    let path = remote_cache_path(url, cache_dir);
+    let shallow_since = since.map(|since| format!("--shallow-since={}", since));
+
+    if path.join(".git").exists() {
+        let mut args = vec!["fetch", "--quiet", "origin"];
+        args.extend(shallow_since.as_deref());
+        run_git(&args, &path)?;
+        // The clone has no checkout, so only the branch needs to move
+        run_git(&["reset", "--quiet", "--soft", "FETCH_HEAD"], &path)?;
+    } else {
+        std::fs::create_dir_all(cache_dir)?;
+        let path_str = path.to_string_lossy();
+        let mut args = vec!["clone", "--quiet", "--no-checkout"];
+        args.extend(shallow_since.as_deref());
+        args.extend([url, path_str.as_ref()]);
+        run_git(&args, cache_dir)?;
+    }
+
+    if since.is_some() {
+        run_git(&["fetch", "--quiet", "--deepen=1", "origin"], &path)?;
+    }
+    Ok(path)
+}
+
+/// When the repository at `cwd` is a shallow clone whose history stops after `since`,
+/// the date of the newest commit it stops at. Commits before it are missing, and the
+/// boundary commits themselves look as if they added every file they have, so analyses of
+/// the range would be silently wrong. Clones made by [`fetch_remote`] stop before `since`.
+pub fn shallow_boundary(cwd: &Path, since: &str) -> Result<Option<DateTime<Utc>>, io::Error> {
+    if git_output(&["rev-parse", "--is-shallow-repository"], cwd)? != "true" {
+        return Ok(None);
+    }
+    let shallow_file = cwd.join(git_output(&["rev-parse", "--git-path", "shallow"], cwd)?);
+    let boundary: Vec<String> = std::fs::read_to_string(shallow_file)?
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let mut args = vec!["show", "--no-patch", "--format=%ct"];
+    args.extend(boundary.iter().map(String::as_str));
+    let newest = git_output(&args, cwd)?
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .max()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0));
+    let since = resolve_date(since, DateBound::Since, Utc::now())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    Ok(newest.filter(|newest| *newest >= since))
+}
+
+/// Fetches more history into a shallow clone: the last `depth` commits of each branch, or
+/// all of it when `depth` is 0.
+pub fn deepen_shallow_clone(cwd: &Path, depth: u32) -> Result<(), io::Error> {
+    let depth_arg = if depth == 0 {
+        "--unshallow".to_string()
+    } else {
+        format!("--depth={}", depth)
+    };
+    run_git(&["fetch", "--quiet", &depth_arg], cwd)
+}
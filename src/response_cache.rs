@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A page of a GitHub API listing as last received, with the ETag to ask whether it has
+/// changed since.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    pub etag: String,
+    pub body: serde_json::Value,
+    /// The URL of the following page, from the `Link` header.
+    pub next: Option<String>,
+}
+
+/// GitHub API listings kept on disk between runs. Pages are asked for with their ETag,
+/// and GitHub answers those that haven't changed with a `304 Not Modified`, which doesn't
+/// count against the rate limit.
+pub struct ResponseCache {
+    /// Where the cache is saved; nothing is read or saved without one.
+    path: Option<PathBuf>,
+    responses: HashMap<String, CachedResponse>,
+}
+
+impl ResponseCache {
+    /// `responses.json` in the user's cache directory, e.g. `~/.cache/bound/responses.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("bound").join("responses.json"))
+    }
+
+    /// Loads the cache at `path`. A missing or unreadable cache starts out empty.
+    pub fn load(path: &Path) -> Self {
+        let responses = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        ResponseCache {
+            path: Some(path.to_path_buf()),
+            responses,
+        }
+    }
+
+    /// A cache that never has anything, for `--no-cache`.
+    pub fn disabled() -> Self {
+        ResponseCache {
+            path: None,
+            responses: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Option<&CachedResponse> {
+        self.responses.get(url)
+    }
+
+    pub(crate) fn insert(&mut self, url: &str, response: CachedResponse) {
+        if self.path.is_none() {
+            return;
+        }
+        self.responses.insert(url.to_string(), response);
+    }
+
+    /// Writes the cache back, replacing the file in one step so a concurrent run never
+    /// reads half of it.
+    pub fn save(&self) -> Result<(), io::Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let dir = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer(&mut file, &self.responses)?;
+        file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::DateTime;
+use serde::Serialize;
+
+use crate::{analyze_reviews::approver_is_codeowner, AuthorMembership, PullRequestReviewData};
+
+/// How much reviewing an owner's files asks of the owner.
+#[derive(Serialize)]
+pub struct ReviewLoadInfo {
+    pub owner: String,
+    /// Pull requests changing the owner's files, which CODEOWNERS requests its review on.
+    pub pull_requests: usize,
+    /// Of those, the ones a member of the owner approved.
+    pub approved: usize,
+    /// Median hours from opening a pull request to the owner's first approval, over the
+    /// approved ones.
+    pub median_hours_to_approve: Option<f64>,
+}
+
+impl ReviewLoadInfo {
+    /// Fraction of the requested pull requests the owner approved.
+    pub fn approval_rate(&self) -> f64 {
+        if self.pull_requests > 0 {
+            self.approved as f64 / self.pull_requests as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+fn hours_between(from: &str, to: &str) -> Option<f64> {
+    let from = DateTime::parse_from_rfc3339(from).ok()?;
+    let to = DateTime::parse_from_rfc3339(to).ok()?;
+    Some((to - from).num_seconds().max(0) as f64 / 3600.0)
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    })
+}
+
+/// Counts the pull requests each owner was asked to review, being an owner of a file one
+/// changed, and how long the owner took to approve them. Approvals count for an owner as in
+/// [`crate::analyze_reviews`]; `identities` maps logins to `(name, email)`. Sorted by pull
+/// requests, most first.
+pub fn analyze_review_load(
+    pull_requests: &[PullRequestReviewData],
+    owners: &codeowners::Owners,
+    memberships: &AuthorMembership,
+    identities: &HashMap<String, (String, String)>,
+) -> Vec<ReviewLoadInfo> {
+    let mut results: BTreeMap<String, (ReviewLoadInfo, Vec<f64>)> = BTreeMap::new();
+
+    for pull_request in pull_requests {
+        let pr_owners: HashSet<String> = pull_request
+            .files
+            .iter()
+            .filter_map(|file| owners.of(file))
+            .flatten()
+            .map(|owner| owner.to_string())
+            .collect();
+
+        for owner in pr_owners {
+            let (info, hours) = results.entry(owner.clone()).or_insert_with(|| {
+                (
+                    ReviewLoadInfo {
+                        owner: owner.clone(),
+                        pull_requests: 0,
+                        approved: 0,
+                        median_hours_to_approve: None,
+                    },
+                    Vec::new(),
+                )
+            });
+            info.pull_requests += 1;
+            let first_approval = pull_request
+                .first_approved_at
+                .iter()
+                .filter(|(login, _)| approver_is_codeowner(login, &owner, memberships, identities))
+                .map(|(_, approved_at)| approved_at)
+                .min();
+            if let Some(approved_at) = first_approval {
+                info.approved += 1;
+                hours.extend(hours_between(&pull_request.created_at, approved_at));
+            }
+        }
+    }
+
+    let mut results: Vec<ReviewLoadInfo> = results
+        .into_values()
+        .map(|(mut info, hours)| {
+            info.median_hours_to_approve = median(hours);
+            info
+        })
+        .collect();
+    results.sort_by_key(|info| std::cmp::Reverse(info.pull_requests));
+    results
+}
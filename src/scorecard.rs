@@ -0,0 +1,297 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    get_owners_at_commit, git_tree_files, CommitInfoWithCodeowner, ContributorToOwnerInfo,
+    OwnerAliases, OwnerReviewInfo,
+};
+
+/// The measures an owner is scored on, each turned into a score from 0 (worst) to 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScorecardMetric {
+    /// Share of the owner's files that its members changed in the period.
+    Coverage,
+    /// How many members it takes to account for half of the team's changes.
+    BusFactor,
+    /// Share of changes to the owner's files made by people outside it; lower is better.
+    OutsideRatio,
+    /// Share of the owner's pull requests approved by one of its members.
+    ReviewCoverage,
+    /// Days since a member last changed the owner's files, or `stale_after_days` when none
+    /// did in the period; lower is better.
+    Staleness,
+}
+
+impl ScorecardMetric {
+    pub const ALL: [ScorecardMetric; 5] = [
+        ScorecardMetric::Coverage,
+        ScorecardMetric::BusFactor,
+        ScorecardMetric::OutsideRatio,
+        ScorecardMetric::ReviewCoverage,
+        ScorecardMetric::Staleness,
+    ];
+}
+
+impl fmt::Display for ScorecardMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScorecardMetric::Coverage => write!(f, "coverage"),
+            ScorecardMetric::BusFactor => write!(f, "bus_factor"),
+            ScorecardMetric::OutsideRatio => write!(f, "outside_ratio"),
+            ScorecardMetric::ReviewCoverage => write!(f, "review_coverage"),
+            ScorecardMetric::Staleness => write!(f, "staleness"),
+        }
+    }
+}
+
+/// The `[scorecard]` section of `bound.toml`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ScorecardConfig {
+    /// Relative weight of each metric; unlisted metrics weigh 1. Metrics without data,
+    /// such as review coverage when reviews aren't fetched, are left out and the rest
+    /// reweighted.
+    pub weights: BTreeMap<ScorecardMetric, f64>,
+    /// Lowest score for each grade, as `A = 0.9`. Scores below every threshold get
+    /// `fallback_grade`.
+    pub grades: BTreeMap<String, f64>,
+    pub fallback_grade: String,
+    /// Members accounting for half the changes at which the bus factor scores full marks.
+    pub bus_factor_target: usize,
+    /// Days without a change by a member after which staleness scores 0.
+    pub stale_after_days: f64,
+}
+
+impl Default for ScorecardConfig {
+    fn default() -> Self {
+        ScorecardConfig {
+            weights: BTreeMap::new(),
+            grades: [("A", 0.9), ("B", 0.8), ("C", 0.7), ("D", 0.6)]
+                .into_iter()
+                .map(|(grade, threshold)| (grade.to_string(), threshold))
+                .collect(),
+            fallback_grade: "F".to_string(),
+            bus_factor_target: 3,
+            stale_after_days: 90.0,
+        }
+    }
+}
+
+impl ScorecardConfig {
+    /// The grade with the highest threshold that `score` reaches.
+    pub fn grade(&self, score: f64) -> String {
+        self.grades
+            .iter()
+            .filter(|(_, threshold)| score >= **threshold)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or_else(|| self.fallback_grade.clone(), |(grade, _)| grade.clone())
+    }
+}
+
+#[derive(Serialize)]
+pub struct ScoreComponent {
+    pub metric: ScorecardMetric,
+    /// The measured value, in the metric's own unit (a ratio, people or days).
+    pub value: f64,
+    pub score: f64,
+    pub weight: f64,
+}
+
+/// An owner's score with the data behind it.
+#[derive(Serialize)]
+pub struct OwnerScorecard {
+    pub owner: String,
+    pub score: f64,
+    pub grade: String,
+    pub components: Vec<ScoreComponent>,
+    pub owned_files: usize,
+    /// Owned files changed by a member in the period.
+    pub team_changed_files: usize,
+    pub team_changes: usize,
+    pub others_changes: usize,
+    /// Unix timestamp of the latest change by a member, if any in the period.
+    pub last_team_change: Option<i64>,
+    pub top_team_contributors_by_changes: Vec<ContributorToOwnerInfo>,
+}
+
+/// The files each owner owns in the tree at `rev`, with owners resolved through `aliases`.
+pub fn owned_files_by_owner(
+    rev: &str,
+    cwd: &PathBuf,
+    aliases: &OwnerAliases,
+) -> Result<HashMap<String, HashSet<String>>, io::Error> {
+    let owners = get_owners_at_commit(rev, cwd)?;
+    let mut files: HashMap<String, HashSet<String>> = HashMap::new();
+    for path in git_tree_files(rev, cwd)? {
+        for owner in owners.of(&path).into_iter().flatten() {
+            files
+                .entry(aliases.resolve(&owner.to_string()).to_string())
+                .or_default()
+                .insert(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+#[derive(Default)]
+struct OwnerActivity {
+    team_changed_files: HashSet<String>,
+    team_changes: usize,
+    others_changes: usize,
+    last_team_change: Option<i64>,
+    team_contributors: HashMap<(String, String), usize>,
+}
+
+/// Fewest members whose changes add up to at least half of all of them.
+fn bus_factor(contributors: &HashMap<(String, String), usize>) -> usize {
+    let total: usize = contributors.values().sum();
+    let mut changes: Vec<usize> = contributors.values().copied().collect();
+    changes.sort_unstable_by(|a, b| b.cmp(a));
+    let mut covered = 0;
+    for (count, contributor_changes) in changes.into_iter().enumerate() {
+        covered += contributor_changes;
+        if covered * 2 >= total {
+            return count + 1;
+        }
+    }
+    0
+}
+
+/// Scores every owner in `owned_files` on the changes in `commits` and, when given,
+/// `reviews`. Staleness is measured up to `as_of`, a Unix timestamp. Sorted by score,
+/// lowest first, so owners needing attention come first.
+pub fn build_scorecards(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    owned_files: &HashMap<String, HashSet<String>>,
+    reviews: Option<&[OwnerReviewInfo]>,
+    config: &ScorecardConfig,
+    as_of: i64,
+) -> Result<Vec<OwnerScorecard>, io::Error> {
+    let mut activity: HashMap<String, OwnerActivity> = HashMap::new();
+    for commit_result in commits {
+        let commit = commit_result?;
+        for change in &commit.file_changes {
+            let changes = (change.insertions + change.deletions) as usize;
+            let is_team = change.author_is_codeowner.unwrap_or(false);
+            for owner in change.codeowners.iter().flatten() {
+                let owner_activity = activity.entry(owner.clone()).or_default();
+                if !is_team {
+                    owner_activity.others_changes += changes;
+                    continue;
+                }
+                owner_activity.team_changes += changes;
+                owner_activity
+                    .team_changed_files
+                    .insert(change.path.clone());
+                owner_activity.last_team_change =
+                    owner_activity.last_team_change.max(Some(commit.timestamp));
+                *owner_activity
+                    .team_contributors
+                    .entry((commit.author_name.clone(), commit.author_email.clone()))
+                    .or_default() += changes;
+            }
+        }
+    }
+    let reviews: Option<HashMap<&str, &OwnerReviewInfo>> = reviews.map(|reviews| {
+        reviews
+            .iter()
+            .map(|info| (info.owner.as_str(), info))
+            .collect()
+    });
+
+    let stale_after_days = config.stale_after_days.max(1.0);
+
+    let mut scorecards: Vec<OwnerScorecard> = owned_files
+        .iter()
+        .map(|(owner, files)| {
+            let owner_activity = activity.remove(owner).unwrap_or_default();
+            let team_changed_files = owner_activity
+                .team_changed_files
+                .iter()
+                .filter(|path| files.contains(*path))
+                .count();
+
+            let mut measured = Vec::new();
+            let coverage = team_changed_files as f64 / files.len().max(1) as f64;
+            measured.push((ScorecardMetric::Coverage, coverage, coverage));
+            let people = bus_factor(&owner_activity.team_contributors);
+            measured.push((
+                ScorecardMetric::BusFactor,
+                people as f64,
+                (people as f64 / config.bus_factor_target.max(1) as f64).min(1.0),
+            ));
+            let total_changes = owner_activity.team_changes + owner_activity.others_changes;
+            if total_changes > 0 {
+                let outside = owner_activity.others_changes as f64 / total_changes as f64;
+                measured.push((ScorecardMetric::OutsideRatio, outside, 1.0 - outside));
+            }
+            if let Some(info) = reviews
+                .as_ref()
+                .and_then(|reviews| reviews.get(owner.as_str()))
+            {
+                let rate = info.codeowner_approval_rate();
+                measured.push((ScorecardMetric::ReviewCoverage, rate, rate));
+            }
+            let stale_days = owner_activity
+                .last_team_change
+                .map_or(stale_after_days, |last| {
+                    (as_of - last).max(0) as f64 / 86_400.0
+                });
+            measured.push((
+                ScorecardMetric::Staleness,
+                stale_days,
+                1.0 - (stale_days / stale_after_days).min(1.0),
+            ));
+
+            let components: Vec<ScoreComponent> = measured
+                .into_iter()
+                .map(|(metric, value, score)| ScoreComponent {
+                    metric,
+                    value,
+                    score,
+                    weight: config.weights.get(&metric).copied().unwrap_or(1.0),
+                })
+                .collect();
+            let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+            let score = if total_weight > 0.0 {
+                components.iter().map(|c| c.score * c.weight).sum::<f64>() / total_weight
+            } else {
+                0.0
+            };
+
+            let mut contributors: Vec<_> = owner_activity.team_contributors.into_iter().collect();
+            contributors
+                .sort_by(|(a, changes_a), (b, changes_b)| changes_b.cmp(changes_a).then(a.cmp(b)));
+            OwnerScorecard {
+                owner: owner.clone(),
+                score,
+                grade: config.grade(score),
+                components,
+                owned_files: files.len(),
+                team_changed_files,
+                team_changes: owner_activity.team_changes,
+                others_changes: owner_activity.others_changes,
+                last_team_change: owner_activity.last_team_change,
+                top_team_contributors_by_changes: contributors
+                    .into_iter()
+                    .take(5)
+                    .map(
+                        |((author_name, author_email), changes)| ContributorToOwnerInfo {
+                            author_name,
+                            author_email,
+                            metric_value: changes,
+                        },
+                    )
+                    .collect(),
+            }
+        })
+        .collect();
+    scorecards.sort_by(|a, b| a.score.total_cmp(&b.score).then(a.owner.cmp(&b.owner)));
+    Ok(scorecards)
+}
@@ -0,0 +1,57 @@
+use std::io;
+
+use crate::{AuthorMembership, CommitInfoWithCodeowner, PathFilter};
+
+/// A change to a security-sensitive path by someone who does not own it.
+pub struct SensitiveChange {
+    pub commit_id: String,
+    pub timestamp: i64,
+    pub author_name: String,
+    pub author_email: String,
+    pub path: String,
+    pub insertions: i32,
+    pub deletions: i32,
+    pub codeowners: Vec<String>,
+    /// Whether the author belongs to any codeowner in the memberships file, which tells
+    /// colleagues outside the owning team apart from people outside the organization.
+    pub org_member: bool,
+}
+
+/// Finds changes to paths matching `sensitive` whose author is not one of the file's
+/// owners, including changes to sensitive files that have no owner at all.
+pub fn find_sensitive_changes(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    sensitive: &PathFilter,
+    memberships: &AuthorMembership,
+) -> Result<Vec<SensitiveChange>, io::Error> {
+    let mut changes = Vec::new();
+
+    for commit_result in commits {
+        let commit = commit_result?;
+        let org_member = !memberships
+            .get_codeowners_for_author_at(
+                &commit.author_name,
+                &commit.author_email,
+                Some(commit.timestamp),
+            )
+            .is_empty();
+        for change in commit.file_changes {
+            if !sensitive.matches(&change.path) || change.author_is_codeowner == Some(true) {
+                continue;
+            }
+            changes.push(SensitiveChange {
+                commit_id: commit.id.clone(),
+                timestamp: commit.timestamp,
+                author_name: commit.author_name.clone(),
+                author_email: commit.author_email.clone(),
+                path: change.path,
+                insertions: change.insertions,
+                deletions: change.deletions,
+                codeowners: change.codeowners.unwrap_or_default(),
+                org_member,
+            });
+        }
+    }
+
+    Ok(changes)
+}
@@ -0,0 +1,125 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Stdio},
+};
+
+use serde::Serialize;
+
+use crate::CommitInfo;
+
+/// Size of a file at one side of a change.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct FileSize {
+    pub bytes: u64,
+    /// Line count; 0 for binary files, as in `--numstat`.
+    pub lines: usize,
+}
+
+impl FileSize {
+    fn of(content: &[u8]) -> Self {
+        let lines = if content.contains(&0) {
+            0
+        } else {
+            content
+                .split(|byte| *byte == b'\n')
+                .filter(|line| !line.is_empty())
+                .count()
+        };
+        FileSize {
+            bytes: content.len() as u64,
+            lines,
+        }
+    }
+}
+
+/// Reads blobs through a single long-running `git cat-file --batch`, rather than one git
+/// process per file.
+pub(crate) struct BlobReader {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    pub(crate) fn new(cwd: &PathBuf) -> Result<Self, io::Error> {
+        let mut child = crate::platform::command("git")
+            .args(["cat-file", "--batch"])
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("Could not capture stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("Could not capture stdout"))?;
+        Ok(BlobReader {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    /// Content of `path` at `rev`, or `None` when no file exists there. An empty `rev`
+    /// reads the file from the index.
+    pub(crate) fn content(&mut self, rev: &str, path: &str) -> Result<Option<Vec<u8>>, io::Error> {
+        writeln!(self.stdin, "{}:{}", rev, path)?;
+        self.stdin.flush()?;
+
+        // Either "<oid> <type> <size>" followed by the content, or "<object> missing"
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+            return Ok(None);
+        }
+        let mut fields = header.rsplit(' ');
+        let size: usize = fields.next().unwrap_or_default().parse().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid blob header '{}': {}", header, e),
+            )
+        })?;
+        let kind = fields.next().unwrap_or_default();
+        // The content is followed by a newline
+        let mut content = vec![0; size + 1];
+        self.stdout.read_exact(&mut content)?;
+        content.truncate(size);
+        // A directory, or a submodule's commit, isn't a file
+        Ok((kind == "blob").then_some(content))
+    }
+
+    /// Size of `path` at `rev`, or `None` when it does not exist there.
+    fn size(&mut self, rev: &str, path: &str) -> Result<Option<FileSize>, io::Error> {
+        Ok(self
+            .content(rev, path)?
+            .map(|content| FileSize::of(&content)))
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Fills in the size of each changed file before (in the first parent) and after the
+/// commit. Sizes are left unset for files that don't exist on that side.
+pub(crate) fn fill_file_sizes(
+    commit: &mut CommitInfo,
+    blobs: &mut BlobReader,
+) -> Result<(), io::Error> {
+    for change in &mut commit.file_changes {
+        change.size_after = blobs.size(&commit.id, &change.path)?;
+        if let Some(parent) = commit.parents.first() {
+            let old_path = change.old_path.as_ref().unwrap_or(&change.path);
+            change.size_before = blobs.size(parent, old_path)?;
+        }
+    }
+    Ok(())
+}
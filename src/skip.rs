@@ -0,0 +1,18 @@
+use std::io;
+
+use crate::{WarningCategory, Warnings};
+
+/// Drops failed items from `iter`, recording each one as a malformed entry in `warnings`.
+pub fn skip_errors<T, I>(iter: I, warnings: &Warnings) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    let warnings = warnings.clone();
+    iter.filter(move |item| match item {
+        Ok(_) => true,
+        Err(e) => {
+            warnings.add(WarningCategory::MalformedEntry, e.to_string());
+            false
+        }
+    })
+}
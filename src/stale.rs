@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    io::{self, Cursor},
+};
+
+use serde::Serialize;
+
+use crate::{
+    owner::is_author_codeowner, parse_codeowners_rules, AuthorMembership, CommitInfoWithCodeowner,
+    ContributorToOwnerInfo, OwnerAliases,
+};
+
+/// A CODEOWNERS rule whose owners have stopped changing the files it covers while others
+/// still do.
+#[derive(Serialize)]
+pub struct StaleOwnershipInfo {
+    pub line: usize,
+    pub pattern: String,
+    pub owners: Vec<String>,
+    /// Changed lines by members of the owners in files this rule decides the owners of.
+    pub team_changes: usize,
+    pub outside_changes: usize,
+    /// Unix timestamp of the latest change by a member in the period, if any.
+    pub last_team_change: Option<i64>,
+    /// Those outside the owners who changed the files, by changes, most first.
+    pub top_outside_contributors: Vec<ContributorToOwnerInfo>,
+}
+
+#[derive(Default)]
+struct RuleActivity {
+    team_changes: usize,
+    outside_changes: usize,
+    last_team_change: Option<i64>,
+    outside_contributors: HashMap<(String, String), usize>,
+}
+
+/// Finds the rules of `codeowners` (CODEOWNERS content) whose owners made at most
+/// `max_team_changes` changed lines in `commits` to the files the rule applies to, while
+/// people outside them made more. A file belongs to the last rule matching it, as in
+/// CODEOWNERS itself, and membership comes from `memberships` with owners resolved through
+/// `aliases`. Sorted by outside changes, most first.
+pub fn analyze_stale_ownership(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    codeowners: &str,
+    memberships: &AuthorMembership,
+    aliases: &OwnerAliases,
+    max_team_changes: usize,
+) -> Result<Vec<StaleOwnershipInfo>, io::Error> {
+    let rules = parse_codeowners_rules(codeowners);
+    // Each pattern on its own, with a stand-in owner so rules without owners match too
+    let matchers: Vec<codeowners::Owners> = rules
+        .iter()
+        .map(|rule| codeowners::from_reader(Cursor::new(format!("{} @bound", rule.pattern))))
+        .collect();
+    let rule_owners: Vec<Vec<String>> = rules
+        .iter()
+        .map(|rule| {
+            rule.owners
+                .iter()
+                .map(|owner| aliases.resolve(owner).to_string())
+                .collect()
+        })
+        .collect();
+
+    let mut activity: HashMap<usize, RuleActivity> = HashMap::new();
+    for commit_result in commits {
+        let commit = commit_result?;
+        for change in &commit.file_changes {
+            let Some(index) = matchers
+                .iter()
+                .rposition(|matcher| matcher.of(&change.path).is_some())
+            else {
+                continue;
+            };
+            if rule_owners[index].is_empty() {
+                continue;
+            }
+            let changes = (change.insertions + change.deletions) as usize;
+            let rule_activity = activity.entry(index).or_default();
+            if is_author_codeowner(
+                memberships,
+                &rule_owners[index],
+                &commit.author_name,
+                &commit.author_email,
+                Some(commit.timestamp),
+            ) {
+                rule_activity.team_changes += changes;
+                rule_activity.last_team_change =
+                    rule_activity.last_team_change.max(Some(commit.timestamp));
+            } else {
+                rule_activity.outside_changes += changes;
+                *rule_activity
+                    .outside_contributors
+                    .entry((commit.author_name.clone(), commit.author_email.clone()))
+                    .or_default() += changes;
+            }
+        }
+    }
+
+    let mut stale: Vec<StaleOwnershipInfo> = activity
+        .into_iter()
+        .filter(|(_, activity)| {
+            activity.team_changes <= max_team_changes
+                && activity.outside_changes > activity.team_changes
+        })
+        .map(|(index, activity)| {
+            let mut contributors: Vec<_> = activity.outside_contributors.into_iter().collect();
+            contributors
+                .sort_by(|(a, changes_a), (b, changes_b)| changes_b.cmp(changes_a).then(a.cmp(b)));
+            StaleOwnershipInfo {
+                line: rules[index].line,
+                pattern: rules[index].pattern.clone(),
+                owners: rule_owners[index].clone(),
+                team_changes: activity.team_changes,
+                outside_changes: activity.outside_changes,
+                last_team_change: activity.last_team_change,
+                top_outside_contributors: contributors
+                    .into_iter()
+                    .take(5)
+                    .map(
+                        |((author_name, author_email), changes)| ContributorToOwnerInfo {
+                            author_name,
+                            author_email,
+                            metric_value: changes,
+                        },
+                    )
+                    .collect(),
+            }
+        })
+        .collect();
+    stale.sort_by(|a, b| {
+        b.outside_changes
+            .cmp(&a.outside_changes)
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    Ok(stale)
+}
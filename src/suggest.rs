@@ -0,0 +1,140 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+use serde::Serialize;
+
+use crate::{AuthorMembership, CommitInfoWithCodeowner, OwnerAliases};
+
+/// A CODEOWNERS rule proposed for unowned files, giving them to the owner whose members
+/// changed them the most.
+#[derive(Serialize)]
+pub struct OwnerSuggestion {
+    /// A directory (`/tools/`) when nothing under it is owned yet, otherwise a single file.
+    pub pattern: String,
+    pub owner: String,
+    /// Changed lines under the pattern by members of the owner.
+    pub owner_changes: usize,
+    /// Changed lines under the pattern by anyone.
+    pub total_changes: usize,
+    /// Unowned files under the pattern that were changed.
+    pub files: usize,
+}
+
+impl OwnerSuggestion {
+    /// Fraction of the changes made by members of the suggested owner.
+    pub fn share(&self) -> f64 {
+        if self.total_changes > 0 {
+            self.owner_changes as f64 / self.total_changes as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// The rule as a line to add at the end of CODEOWNERS.
+    pub fn codeowners_line(&self) -> String {
+        format!("{} {}", self.pattern.replace(' ', "\\ "), self.owner)
+    }
+}
+
+#[derive(Default)]
+struct PatternTally {
+    changes_by_owner: HashMap<String, usize>,
+    total_changes: usize,
+    files: HashSet<String>,
+}
+
+fn parent_directory(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(directory, _)| directory)
+}
+
+/// Suggests owners for the files among `files` that `owners` leaves unowned, from who
+/// changed them in `commits` and the owners `memberships` puts those authors in (resolved
+/// through `aliases`). Changes by authors without an owner count towards the total only.
+///
+/// The outermost directories with no owned files anywhere under them are suggested as a
+/// whole, so the rules can be appended to CODEOWNERS without taking over files that already
+/// have owners. Sorted by total changes, most first; files nobody with an owner changed get
+/// no suggestion.
+pub fn suggest_owners(
+    commits: impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+    owners: &codeowners::Owners,
+    files: &[String],
+    memberships: &AuthorMembership,
+    aliases: &OwnerAliases,
+) -> Result<Vec<OwnerSuggestion>, io::Error> {
+    let mut owned_directories: HashSet<&str> = HashSet::new();
+    let mut unowned_files: HashSet<&str> = HashSet::new();
+    for path in files {
+        if owners.of(path).is_some_and(|owners| !owners.is_empty()) {
+            let mut directory = parent_directory(path);
+            while !directory.is_empty() && owned_directories.insert(directory) {
+                directory = parent_directory(directory);
+            }
+        } else {
+            unowned_files.insert(path);
+        }
+    }
+
+    let mut tallies: HashMap<String, PatternTally> = HashMap::new();
+    for commit_result in commits {
+        let commit = commit_result?;
+        let author_owners: HashSet<String> = memberships
+            .get_codeowners_for_author_at(
+                &commit.author_name,
+                &commit.author_email,
+                Some(commit.timestamp),
+            )
+            .iter()
+            .map(|owner| aliases.resolve(owner).to_string())
+            .collect();
+        for change in &commit.file_changes {
+            if !unowned_files.contains(change.path.as_str()) {
+                continue;
+            }
+            // The outermost directory with nothing owned under it, if any
+            let mut directory = parent_directory(&change.path);
+            let mut unowned_directory = None;
+            while !directory.is_empty() && !owned_directories.contains(directory) {
+                unowned_directory = Some(directory);
+                directory = parent_directory(directory);
+            }
+            let pattern = match unowned_directory {
+                Some(directory) => format!("/{}/", directory),
+                None => format!("/{}", change.path),
+            };
+            let changes = (change.insertions + change.deletions) as usize;
+            let tally = tallies.entry(pattern).or_default();
+            tally.total_changes += changes;
+            tally.files.insert(change.path.clone());
+            for owner in &author_owners {
+                *tally.changes_by_owner.entry(owner.clone()).or_default() += changes;
+            }
+        }
+    }
+
+    let mut suggestions: Vec<OwnerSuggestion> = tallies
+        .into_iter()
+        .filter_map(|(pattern, tally)| {
+            let (owner, owner_changes) = tally
+                .changes_by_owner
+                .into_iter()
+                .filter(|(_, changes)| *changes > 0)
+                .max_by(|(owner_a, a), (owner_b, b)| a.cmp(b).then(owner_b.cmp(owner_a)))?;
+            Some(OwnerSuggestion {
+                pattern,
+                owner,
+                owner_changes,
+                total_changes: tally.total_changes,
+                files: tally.files.len(),
+            })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.total_changes
+            .cmp(&a.total_changes)
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+    Ok(suggestions)
+}
@@ -0,0 +1,115 @@
+use std::{io, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{get_owners_at_commit, git_tree_files, AnalysisSummary, OwnerAnalysis};
+
+/// How much of the changes to an owner's files came from outside the owning team.
+#[derive(Serialize)]
+pub struct OutsideContributionShare {
+    pub owner: String,
+    pub team_changes: usize,
+    pub outside_changes: usize,
+}
+
+impl OutsideContributionShare {
+    /// The fraction of changes made by people outside the owner; 0 without changes.
+    pub fn outside_share(&self) -> f64 {
+        let total = self.team_changes + self.outside_changes;
+        if total > 0 {
+            self.outside_changes as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The headline numbers for a range of history: how much changed, how much of it the
+/// owning teams made, and how much of the tree has no owner.
+pub struct RepositorySummary {
+    pub summary: AnalysisSummary,
+    /// Owners whose files were changed.
+    pub owners: usize,
+    /// Owners whose files were changed most from outside, by share of the changes.
+    pub top_outside_owners: Vec<OutsideContributionShare>,
+    /// Files in the tree at the end of the range.
+    pub files: usize,
+    /// Of those, the files matched by no CODEOWNERS rule.
+    pub unowned_files: usize,
+}
+
+impl RepositorySummary {
+    /// The fraction of changed lines made by members of the files' owners.
+    pub fn team_share(&self) -> f64 {
+        let total = self.summary.total_changes();
+        if total > 0 {
+            self.summary.changes_by_team as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// The fraction of changed lines in files without an owner.
+    pub fn unowned_share(&self) -> f64 {
+        let total = self.summary.total_changes();
+        if total > 0 {
+            self.summary.unowned_changes as f64 / total as f64
+        } else {
+            0.0
+        }
+    }
+
+    pub fn unowned_file_share(&self) -> f64 {
+        if self.files > 0 {
+            self.unowned_files as f64 / self.files as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Summarizes `analysis` of the history up to `rev`, with the `top` owners by outside
+/// contribution and the unowned files in the tree at `rev`.
+pub fn summarize_repository(
+    analysis: OwnerAnalysis,
+    rev: &str,
+    cwd: &PathBuf,
+    top: usize,
+) -> Result<RepositorySummary, io::Error> {
+    let mut shares: Vec<OutsideContributionShare> = analysis
+        .owners
+        .iter()
+        .map(|owner| OutsideContributionShare {
+            owner: owner.owner.clone(),
+            team_changes: owner.total_insertions_by_team + owner.total_deletions_by_team,
+            outside_changes: owner.total_insertions_by_others + owner.total_deletions_by_others,
+        })
+        .collect();
+    let owners = shares.len();
+    shares.sort_by(|a, b| {
+        b.outside_share()
+            .total_cmp(&a.outside_share())
+            .then_with(|| b.outside_changes.cmp(&a.outside_changes))
+            .then_with(|| a.owner.cmp(&b.owner))
+    });
+    shares.truncate(top);
+
+    let owners_at_rev = get_owners_at_commit(rev, cwd)?;
+    let tree = git_tree_files(rev, cwd)?;
+    let unowned_files = tree
+        .iter()
+        .filter(|path| {
+            owners_at_rev
+                .of(path)
+                .is_none_or(|owners| owners.is_empty())
+        })
+        .count();
+
+    Ok(RepositorySummary {
+        summary: analysis.summary,
+        owners,
+        top_outside_owners: shares,
+        files: tree.len(),
+        unowned_files,
+    })
+}
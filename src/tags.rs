@@ -0,0 +1,267 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::{ChangeKind, FileChangePaths, PathChange};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommitTag {
+    VendorBump,
+    MassRename,
+    FormatOnly,
+}
+
+impl FromStr for CommitTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "vendor-bump" => Ok(CommitTag::VendorBump),
+            "mass-rename" => Ok(CommitTag::MassRename),
+            "format-only" => Ok(CommitTag::FormatOnly),
+            _ => Err(format!(
+                "Invalid tag '{}', expected 'vendor-bump', 'mass-rename' or 'format-only'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CommitTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommitTag::VendorBump => write!(f, "vendor-bump"),
+            CommitTag::MassRename => write!(f, "mass-rename"),
+            CommitTag::FormatOnly => write!(f, "format-only"),
+        }
+    }
+}
+
+const VENDOR_DIRECTORIES: [&str; 4] = ["vendor", "third_party", "third-party", "node_modules"];
+
+const LOCKFILES: [&str; 10] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "Gemfile.lock",
+    "poetry.lock",
+    "composer.lock",
+    "Pipfile.lock",
+    "flake.lock",
+];
+
+const MASS_RENAME_MIN_FILES: usize = 5;
+const FORMAT_ONLY_MIN_FILES: usize = 3;
+/// Minimum ratio of the smaller to the larger of insertions/deletions for a file to
+/// count as reformatted rather than edited.
+const FORMAT_ONLY_SYMMETRY: f64 = 0.9;
+
+fn is_vendored_or_lockfile(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    LOCKFILES.contains(&file_name)
+        || path
+            .split('/')
+            .any(|component| VENDOR_DIRECTORIES.contains(&component))
+}
+
+fn is_rename(change: &PathChange) -> bool {
+    change.kind == ChangeKind::Renamed
+}
+
+/// Tags a commit using only its file changes: commits that solely touch vendored code or
+/// lockfiles are `vendor-bump`, commits that mostly rename files are `mass-rename`, and
+/// commits where every file has near-identical insertions and deletions are `format-only`.
+pub fn tag_commit<T: FileChangePaths>(commit: &T) -> Vec<CommitTag> {
+    let changes = commit.path_changes();
+    let mut tags = Vec::new();
+    if changes.is_empty() {
+        return tags;
+    }
+
+    if changes
+        .iter()
+        .all(|change| is_vendored_or_lockfile(change.path))
+    {
+        tags.push(CommitTag::VendorBump);
+    }
+
+    let renames = changes.iter().filter(|change| is_rename(change)).count();
+    if renames >= MASS_RENAME_MIN_FILES && renames * 5 >= changes.len() * 4 {
+        tags.push(CommitTag::MassRename);
+    }
+
+    let symmetric = |insertions: i32, deletions: i32| {
+        let (low, high) = (insertions.min(deletions), insertions.max(deletions));
+        high > 0 && low as f64 / high as f64 >= FORMAT_ONLY_SYMMETRY
+    };
+    if changes.len() >= FORMAT_ONLY_MIN_FILES
+        && changes
+            .iter()
+            .all(|change| !is_rename(change) && symmetric(change.insertions, change.deletions))
+    {
+        tags.push(CommitTag::FormatOnly);
+    }
+
+    tags
+}
+
+pub fn format_tags(tags: &[CommitTag]) -> String {
+    tags.iter()
+        .map(|tag| tag.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Drops commits carrying any of the `excluded` tags.
+pub fn exclude_tagged_commits<T, I>(
+    commits: I,
+    excluded: Vec<CommitTag>,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    commits.filter(move |commit_result| match commit_result {
+        Ok(commit) => !tag_commit(commit).iter().any(|tag| excluded.contains(tag)),
+        Err(_) => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::{CommitInfo, FileChange};
+
+    fn commit(changes: &[(&str, ChangeKind, i32, i32)]) -> CommitInfo {
+        CommitInfo {
+            id: "abc".to_string(),
+            author_date: DateTime::default(),
+            committer_date: DateTime::default(),
+            author_name: "A".to_string(),
+            author_email: "a@example.com".to_string(),
+            committer_name: "A".to_string(),
+            committer_email: "a@example.com".to_string(),
+            parents: Vec::new(),
+            message: None,
+            file_changes: changes
+                .iter()
+                .map(|&(path, kind, insertions, deletions)| FileChange {
+                    insertions,
+                    deletions,
+                    path: path.to_string(),
+                    old_path: (kind == ChangeKind::Renamed).then(|| format!("old/{}", path)),
+                    kind,
+                    language: None,
+                    binary: false,
+                    size_before: None,
+                    size_after: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn renames(count: usize) -> Vec<(String, ChangeKind, i32, i32)> {
+        (0..count)
+            .map(|i| (format!("src/file{}.rs", i), ChangeKind::Renamed, 0, 0))
+            .collect()
+    }
+
+    fn tags_of(changes: &[(String, ChangeKind, i32, i32)]) -> Vec<CommitTag> {
+        let changes: Vec<_> = changes
+            .iter()
+            .map(|(path, kind, insertions, deletions)| {
+                (path.as_str(), *kind, *insertions, *deletions)
+            })
+            .collect();
+        tag_commit(&commit(&changes))
+    }
+
+    #[test]
+    fn tags_parse_and_display() {
+        for tag in [
+            CommitTag::VendorBump,
+            CommitTag::MassRename,
+            CommitTag::FormatOnly,
+        ] {
+            assert_eq!(tag.to_string().parse::<CommitTag>(), Ok(tag));
+        }
+        assert!("refactor".parse::<CommitTag>().is_err());
+    }
+
+    #[test]
+    fn commits_without_changes_are_untagged() {
+        assert!(tag_commit(&commit(&[])).is_empty());
+    }
+
+    #[test]
+    fn vendor_bump_needs_every_file_vendored() {
+        let vendored = commit(&[
+            ("vendor/lib/a.go", ChangeKind::Modified, 10, 2),
+            ("web/node_modules/x/index.js", ChangeKind::Added, 5, 0),
+            ("Cargo.lock", ChangeKind::Modified, 3, 3),
+        ]);
+        assert_eq!(tag_commit(&vendored), vec![CommitTag::VendorBump]);
+
+        let mixed = commit(&[
+            ("vendor/lib/a.go", ChangeKind::Modified, 10, 2),
+            ("src/main.rs", ChangeKind::Modified, 1, 0),
+        ]);
+        assert!(tag_commit(&mixed).is_empty());
+    }
+
+    #[test]
+    fn mass_rename_needs_five_renames_making_up_four_fifths() {
+        assert!(tags_of(&renames(4)).is_empty());
+        assert_eq!(tags_of(&renames(5)), vec![CommitTag::MassRename]);
+
+        let mut mostly = renames(5);
+        mostly.push(("src/lib.rs".to_string(), ChangeKind::Modified, 3, 1));
+        assert_eq!(tags_of(&mostly), vec![CommitTag::MassRename]);
+
+        mostly.push(("src/main.rs".to_string(), ChangeKind::Modified, 3, 1));
+        assert!(tags_of(&mostly).is_empty());
+    }
+
+    #[test]
+    fn paths_containing_an_arrow_are_not_renames() {
+        let changes: Vec<_> = (0..5)
+            .map(|i| (format!("docs/a => b {}.md", i), ChangeKind::Modified, 2, 0))
+            .collect();
+        assert!(tags_of(&changes).is_empty());
+    }
+
+    #[test]
+    fn format_only_needs_three_symmetric_files() {
+        let reformatted = commit(&[
+            ("src/a.rs", ChangeKind::Modified, 10, 10),
+            ("src/b.rs", ChangeKind::Modified, 9, 10),
+            ("src/c.rs", ChangeKind::Modified, 40, 38),
+        ]);
+        assert_eq!(tag_commit(&reformatted), vec![CommitTag::FormatOnly]);
+
+        let two_files = commit(&[
+            ("src/a.rs", ChangeKind::Modified, 10, 10),
+            ("src/b.rs", ChangeKind::Modified, 10, 10),
+        ]);
+        assert!(tag_commit(&two_files).is_empty());
+
+        let edited = commit(&[
+            ("src/a.rs", ChangeKind::Modified, 10, 10),
+            ("src/b.rs", ChangeKind::Modified, 10, 8),
+            ("src/c.rs", ChangeKind::Modified, 10, 10),
+        ]);
+        assert!(tag_commit(&edited).is_empty());
+    }
+
+    #[test]
+    fn format_only_leaves_out_renames() {
+        let renamed = commit(&[
+            ("src/a.rs", ChangeKind::Modified, 10, 10),
+            ("src/b.rs", ChangeKind::Renamed, 10, 10),
+            ("src/c.rs", ChangeKind::Modified, 10, 10),
+        ]);
+        assert!(tag_commit(&renamed).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, NaiveDate};
+
+use crate::{
+    get_owners_at_commit, git_tree_files, AuthorCodeownerMemberships, CommitInfoWithCodeowner,
+};
+
+/// Number of members a codeowner had on a given date (`YYYY-MM-DD`).
+#[derive(Clone)]
+pub struct TeamSizeSnapshot {
+    pub date: String,
+    pub codeowner: String,
+    pub members: usize,
+}
+
+/// Counts the distinct people in each codeowner, identified by email or, failing that, name.
+pub fn snapshot_team_sizes(
+    memberships: &[AuthorCodeownerMemberships],
+    date: &str,
+) -> Vec<TeamSizeSnapshot> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let mut members: BTreeMap<&str, HashSet<String>> = BTreeMap::new();
+    for membership in memberships {
+        if day.is_some_and(|day| !membership.is_valid_on(day)) {
+            continue;
+        }
+        let Some(identity) = membership
+            .author_email
+            .as_ref()
+            .or(membership.author_name.as_ref())
+        else {
+            continue;
+        };
+        members
+            .entry(&membership.codeowner)
+            .or_default()
+            .insert(identity.to_lowercase());
+    }
+
+    members
+        .into_iter()
+        .map(|(codeowner, members)| TeamSizeSnapshot {
+            date: date.to_string(),
+            codeowner: codeowner.to_string(),
+            members: members.len(),
+        })
+        .collect()
+}
+
+/// Appends `snapshots` to the history at `path`, creating it with a header if needed.
+pub fn append_team_size_history(snapshots: &[TeamSizeSnapshot], path: &PathBuf) -> io::Result<()> {
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if is_new {
+        writeln!(file, "date\tcodeowner\tmembers")?;
+    }
+    for snapshot in snapshots {
+        writeln!(
+            file,
+            "{}\t{}\t{}",
+            snapshot.date, snapshot.codeowner, snapshot.members
+        )?;
+    }
+    Ok(())
+}
+
+pub fn read_team_size_history(path: &PathBuf) -> io::Result<Vec<TeamSizeSnapshot>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut snapshots = Vec::new();
+
+    // Skip the header
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        let parts: Vec<&str> = line.split('\t').collect();
+        let members = match parts.as_slice() {
+            [_, _, members] => members.parse().ok(),
+            _ => None,
+        };
+        let Some(members) = members else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            ));
+        };
+        snapshots.push(TeamSizeSnapshot {
+            date: parts[0].to_string(),
+            codeowner: parts[1].to_string(),
+            members,
+        });
+    }
+
+    Ok(snapshots)
+}
+
+/// Size of `codeowner` as of `date`: the latest snapshot on or before it, or the earliest
+/// snapshot when the history starts later, on the assumption the team was unchanged.
+pub fn team_size_on(history: &[TeamSizeSnapshot], codeowner: &str, date: &str) -> Option<usize> {
+    let snapshots = history
+        .iter()
+        .filter(|snapshot| snapshot.codeowner.eq_ignore_ascii_case(codeowner));
+    snapshots
+        .clone()
+        .filter(|snapshot| snapshot.date.as_str() <= date)
+        .max_by(|a, b| a.date.cmp(&b.date))
+        .or_else(|| snapshots.min_by(|a, b| a.date.cmp(&b.date)))
+        .map(|snapshot| snapshot.members)
+}
+
+pub struct TeamSizeTrend {
+    pub period: String,
+    pub owner: String,
+    pub team_size: Option<usize>,
+    pub changes_by_team: usize,
+    pub changes_by_others: usize,
+    /// Files owned at the newest commit of the period.
+    pub owned_files: usize,
+}
+
+impl TeamSizeTrend {
+    pub fn changes_per_member(&self) -> Option<f64> {
+        self.team_size
+            .filter(|size| *size > 0)
+            .map(|size| self.changes_by_team as f64 / size as f64)
+    }
+}
+
+/// Joins each period's contributions with the team sizes in effect at the period's newest
+/// commit and the number of files each owner owned then.
+pub fn analyze_team_size_history(
+    periods: Vec<(String, Vec<CommitInfoWithCodeowner>)>,
+    history: &[TeamSizeSnapshot],
+    cwd: &PathBuf,
+) -> Result<Vec<TeamSizeTrend>, io::Error> {
+    let mut trends = Vec::new();
+
+    for (period, commits) in periods {
+        let Some(newest) = commits.iter().max_by_key(|commit| commit.timestamp) else {
+            continue;
+        };
+        let date = DateTime::from_timestamp(newest.timestamp, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let owners = get_owners_at_commit(&newest.id, cwd)?;
+        let mut owned_files: HashMap<String, usize> = HashMap::new();
+        for path in git_tree_files(&newest.id, cwd)? {
+            for owner in owners.of(&path).into_iter().flatten() {
+                *owned_files.entry(owner.to_string()).or_default() += 1;
+            }
+        }
+
+        let mut changes: BTreeMap<String, (usize, usize)> = owned_files
+            .keys()
+            .map(|owner| (owner.clone(), (0, 0)))
+            .collect();
+        for commit in &commits {
+            for change in &commit.file_changes {
+                let lines = (change.insertions + change.deletions) as usize;
+                for owner in change.codeowners.iter().flatten() {
+                    let entry = changes.entry(owner.clone()).or_default();
+                    if change.author_is_codeowner.unwrap_or(false) {
+                        entry.0 += lines;
+                    } else {
+                        entry.1 += lines;
+                    }
+                }
+            }
+        }
+
+        for (owner, (changes_by_team, changes_by_others)) in changes {
+            trends.push(TeamSizeTrend {
+                period: period.clone(),
+                team_size: team_size_on(history, &owner, &date),
+                owned_files: owned_files.get(&owner).copied().unwrap_or(0),
+                owner,
+                changes_by_team,
+                changes_by_others,
+            });
+        }
+    }
+
+    Ok(trends)
+}
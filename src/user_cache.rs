@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedUser {
+    name: String,
+    email: String,
+    /// Unix timestamp of when the profile was fetched.
+    fetched_at: i64,
+}
+
+/// GitHub profiles (name and public email by login) kept on disk between runs, so
+/// `init` and `refresh` only ask the API about users not looked up recently.
+pub struct UserInfoCache {
+    /// Where the cache is saved; nothing is read or saved without one.
+    path: Option<PathBuf>,
+    ttl: Duration,
+    users: HashMap<String, CachedUser>,
+}
+
+impl UserInfoCache {
+    /// `users.json` in the user's cache directory, e.g. `~/.cache/bound/users.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("bound").join("users.json"))
+    }
+
+    /// Loads the cache at `path`, where entries older than `ttl` count as missing. A
+    /// missing or unreadable cache starts out empty.
+    pub fn load(path: &Path, ttl: Duration) -> Self {
+        let users = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        UserInfoCache {
+            path: Some(path.to_path_buf()),
+            ttl,
+            users,
+        }
+    }
+
+    /// A cache that never has anything, for `--no-cache`.
+    pub fn disabled() -> Self {
+        UserInfoCache {
+            path: None,
+            ttl: Duration::zero(),
+            users: HashMap::new(),
+        }
+    }
+
+    /// `(name, email)` of `login`, if fetched within the TTL.
+    pub fn get(&self, login: &str) -> Option<(String, String)> {
+        let user = self.users.get(login)?;
+        let age = Utc::now().timestamp() - user.fetched_at;
+        (age <= self.ttl.num_seconds()).then(|| (user.name.clone(), user.email.clone()))
+    }
+
+    pub fn insert(&mut self, login: &str, (name, email): (String, String)) {
+        if self.path.is_none() {
+            return;
+        }
+        self.users.insert(
+            login.to_string(),
+            CachedUser {
+                name,
+                email,
+                fetched_at: Utc::now().timestamp(),
+            },
+        );
+    }
+
+    /// Writes the cache back, replacing the file in one step so a concurrent run never
+    /// reads half of it. Expired entries are dropped.
+    pub fn save(&mut self) -> Result<(), io::Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let oldest = Utc::now().timestamp() - self.ttl.num_seconds();
+        self.users.retain(|_, user| user.fetched_at >= oldest);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+        let mut file = tempfile::NamedTempFile::new_in(dir)?;
+        serde_json::to_writer(&mut file, &self.users)?;
+        file.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+}
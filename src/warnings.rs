@@ -0,0 +1,170 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+    rc::Rc,
+};
+
+use serde::Serialize;
+
+use crate::{
+    get_codeowners_at_commit, AuthorCodeownerMemberships, AuthorMembership,
+    CommitInfoWithCodeowner, FileChangePaths, NameNormalization,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCategory {
+    MalformedEntry,
+    InvalidCodeowners,
+    BinaryFile,
+    UnmatchedAuthor,
+    LowConfidenceMatch,
+    ShallowClone,
+    LargeCommit,
+}
+
+impl fmt::Display for WarningCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningCategory::MalformedEntry => write!(f, "Skipped malformed entries"),
+            WarningCategory::InvalidCodeowners => write!(f, "Invalid CODEOWNERS lines"),
+            WarningCategory::BinaryFile => write!(f, "Binary files counted as 0 lines"),
+            WarningCategory::UnmatchedAuthor => write!(f, "Authors not in the memberships file"),
+            WarningCategory::LowConfidenceMatch => write!(f, "Members matched by name only"),
+            WarningCategory::ShallowClone => write!(f, "Shallow clones missing history"),
+            WarningCategory::LargeCommit => write!(f, "Commits over --max-commit-changes"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct WarningSummary {
+    pub category: WarningCategory,
+    pub count: usize,
+    pub messages: Vec<String>,
+}
+
+/// Non-fatal issues collected over a run, shared between the iterators that find them and
+/// the summary printed once the run is over.
+#[derive(Clone, Default)]
+pub struct Warnings(Rc<RefCell<BTreeMap<WarningCategory, Vec<String>>>>);
+
+impl Warnings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, category: WarningCategory, message: impl Into<String>) {
+        self.0
+            .borrow_mut()
+            .entry(category)
+            .or_default()
+            .push(message.into());
+    }
+
+    pub fn count(&self, category: WarningCategory) -> usize {
+        self.0.borrow().get(&category).map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    pub fn summary(&self) -> Vec<WarningSummary> {
+        self.0
+            .borrow()
+            .iter()
+            .map(|(category, messages)| WarningSummary {
+                category: *category,
+                count: messages.len(),
+                messages: messages.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Lines of a CODEOWNERS file with an owner that is neither `@user`, `@org/team` nor an
+/// email address, as `(line number, line)`.
+pub fn invalid_codeowners_lines(content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let rule = line.split('#').next().unwrap_or_default();
+            rule.split_whitespace()
+                .skip(1)
+                .any(|owner| !owner.starts_with('@') && !owner.contains('@'))
+        })
+        .map(|(index, line)| (index + 1, line.to_string()))
+        .collect()
+}
+
+/// Records the invalid lines of the CODEOWNERS file at `commit_id`.
+pub fn warn_invalid_codeowners(
+    commit_id: &str,
+    cwd: &PathBuf,
+    warnings: &Warnings,
+) -> Result<(), io::Error> {
+    let content = get_codeowners_at_commit(commit_id, cwd)?.unwrap_or_default();
+    for (number, line) in invalid_codeowners_lines(&content) {
+        warnings.add(
+            WarningCategory::InvalidCodeowners,
+            format!("line {}: {}", number, line),
+        );
+    }
+    Ok(())
+}
+
+/// Records each binary file the first time it is changed, since its changes count as no
+/// lines at all.
+pub fn warn_binary_files<T, I>(
+    commits: I,
+    warnings: &Warnings,
+) -> impl Iterator<Item = Result<T, io::Error>>
+where
+    T: FileChangePaths,
+    I: Iterator<Item = Result<T, io::Error>>,
+{
+    let warnings = warnings.clone();
+    let mut seen = HashSet::new();
+    commits.inspect(move |commit| {
+        if let Ok(commit) = commit {
+            for path in commit.binary_paths() {
+                if seen.insert(path.to_string()) {
+                    warnings.add(WarningCategory::BinaryFile, path);
+                }
+            }
+        }
+    })
+}
+
+/// Records each commit author, once, who matches no row of `memberships`.
+pub fn warn_unmatched_authors<I>(
+    commits: I,
+    memberships: &[AuthorCodeownerMemberships],
+    names: &NameNormalization,
+    warnings: &Warnings,
+) -> impl Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>
+where
+    I: Iterator<Item = Result<CommitInfoWithCodeowner, io::Error>>,
+{
+    let membership_index = AuthorMembership::with_name_normalization(memberships, names);
+    let warnings = warnings.clone();
+    let mut seen = HashSet::new();
+    commits.inspect(move |commit| {
+        if let Ok(commit) = commit {
+            if seen.insert(commit.author_email.to_lowercase())
+                && membership_index
+                    .get_codeowners_for_author(&commit.author_name, &commit.author_email)
+                    .is_empty()
+            {
+                warnings.add(
+                    WarningCategory::UnmatchedAuthor,
+                    format!("{} <{}>", commit.author_name, commit.author_email),
+                );
+            }
+        }
+    })
+}
@@ -0,0 +1,104 @@
+use std::{fmt, io, path::Path, str::FromStr, time::Duration};
+
+use crate::{
+    isolate::git_output, remote::run_git, AnalysisSummary, OutsideContributionShare, OwnerAnalysis,
+};
+
+/// How often `bound watch` looks for new commits, given as `30s`, `10m`, `2h` or a number
+/// of seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchInterval(Duration);
+
+impl WatchInterval {
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl FromStr for WatchInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+            Some(index) => s.split_at(index),
+            None => (s, "s"),
+        };
+        let multiplier = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            _ => 0,
+        };
+        match number.parse::<u64>() {
+            Ok(count) if count > 0 && multiplier > 0 => {
+                Ok(WatchInterval(Duration::from_secs(count * multiplier)))
+            }
+            _ => Err(format!(
+                "Invalid interval '{}', expected a duration such as '30s', '10m' or '2h'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WatchInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.as_secs();
+        if seconds.is_multiple_of(3600) {
+            write!(f, "{}h", seconds / 3600)
+        } else if seconds.is_multiple_of(60) {
+            write!(f, "{}m", seconds / 60)
+        } else {
+            write!(f, "{}s", seconds)
+        }
+    }
+}
+
+/// The commit `rev` points to in the repository at `cwd`.
+pub fn resolve_watched_commit(rev: &str, cwd: &Path) -> Result<String, io::Error> {
+    git_output(
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", rev)],
+        cwd,
+    )
+}
+
+/// Fetches from every remote of the repository at `cwd`, so a watched remote-tracking
+/// ref such as `origin/main` moves as commits are pushed.
+pub fn fetch_watched_repository(cwd: &Path) -> Result<(), io::Error> {
+    run_git(&["fetch", "--quiet", "--all"], cwd)
+}
+
+/// What the commits that landed between two checks changed.
+pub struct WatchDelta {
+    pub from: String,
+    pub to: String,
+    pub summary: AnalysisSummary,
+    /// The owners whose files the new commits changed, most changed first.
+    pub owners: Vec<OutsideContributionShare>,
+}
+
+/// The delta report for `analysis` of the commits in `from..to`.
+pub fn watch_delta(from: &str, to: &str, analysis: OwnerAnalysis) -> WatchDelta {
+    let mut owners: Vec<OutsideContributionShare> = analysis
+        .owners
+        .iter()
+        .map(|owner| OutsideContributionShare {
+            owner: owner.owner.clone(),
+            team_changes: owner.total_insertions_by_team + owner.total_deletions_by_team,
+            outside_changes: owner.total_insertions_by_others + owner.total_deletions_by_others,
+        })
+        .filter(|share| share.team_changes + share.outside_changes > 0)
+        .collect();
+    owners.sort_by(|a, b| {
+        (b.team_changes + b.outside_changes)
+            .cmp(&(a.team_changes + a.outside_changes))
+            .then_with(|| a.owner.cmp(&b.owner))
+    });
+    WatchDelta {
+        from: from.to_string(),
+        to: to.to_string(),
+        summary: analysis.summary,
+        owners,
+    }
+}